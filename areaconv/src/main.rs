@@ -0,0 +1,69 @@
+use std::process::exit;
+
+use mudlib::{convert_area, diagnostics_report, AreaFormat};
+
+const USAGE: &str = "Usage: areaconv [--dawn] <area file> [output file]
+
+Loads a Dawn of Time, ROM 2.4, Merc or DemiMUD area file, and writes it in
+DemiMUD's own TOML format, or in the Dawn of Time format with --dawn, to the
+output file or to the standard output.
+
+Exits with 1 if the area has errors, and isn't converted.";
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    let mut format = AreaFormat::Native;
+    if let Some(index) = args.iter().position(|arg| arg == "--dawn") {
+        args.remove(index);
+        format = AreaFormat::Dawn;
+    }
+
+    let (input, output) = match &args[..] {
+        [flag] if flag == "--help" || flag == "-h" => {
+            println!("{}", USAGE);
+            return;
+        }
+        [input] if !input.starts_with('-') => (input, None),
+        [input, output] if !input.starts_with('-') => (input, Some(output)),
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+
+    // Keeps the file's own newlines, unlike `Files::read_file`, so that
+    // descriptions are written as multi-line strings
+    let contents = match std::fs::read(input) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(error) => {
+            eprintln!("{}: error: Could not read file: {}", input, error);
+            exit(1);
+        }
+    };
+
+    let mut diagnostics = Vec::new();
+    let converted = convert_area(&contents, input, format, &mut diagnostics);
+    if let Err(error) = &converted {
+        diagnostics.push(error.clone());
+    }
+
+    if let Some(report) = diagnostics_report(&diagnostics) {
+        eprint!("{}", report);
+    }
+
+    let converted = match converted {
+        Ok(converted) => converted,
+        Err(_) => exit(1),
+    };
+
+    match output {
+        Some(output) => {
+            if let Err(error) = std::fs::write(output, converted) {
+                eprintln!("{}: error: Could not write file: {}", output, error);
+                exit(1);
+            }
+        }
+        None => print!("{}", converted),
+    }
+}
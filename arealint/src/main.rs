@@ -0,0 +1,46 @@
+use std::process::exit;
+
+use mudlib::{diagnostics_report, lint_areas, RealFiles, Severity};
+
+const USAGE: &str = "Usage: arealint [--deny-warnings] [area directory]
+
+Loads every area listed in <area directory>/arealist.txt (data/area by default)
+and checks that they are consistent, without starting a game.
+
+Exits with 1 if there are errors, or also on warnings with --deny-warnings.";
+
+fn main() {
+    let mut deny_warnings = false;
+    let mut path = None;
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--deny-warnings" | "-D" => deny_warnings = true,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg),
+            _ => {
+                eprintln!("{}", USAGE);
+                exit(2);
+            }
+        }
+    }
+
+    let path = path.unwrap_or_else(|| "data/area".to_string());
+    let diagnostics = lint_areas(&RealFiles, path.trim_end_matches('/'));
+
+    match diagnostics_report(&diagnostics) {
+        Some(report) => print!("{}", report),
+        None => println!("No problems found."),
+    }
+
+    let failed = diagnostics
+        .iter()
+        .any(|diagnostic| deny_warnings || diagnostic.severity == Severity::Error);
+
+    if failed {
+        exit(1);
+    }
+}
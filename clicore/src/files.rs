@@ -11,8 +11,15 @@ impl Files for StaticFiles {
             "clicore/notice.txt" => include_bytes!("../notice.txt"),
 
             "data/socials.txt" => include_bytes!("../../data/basic_socials.txt"),
+            "data/quests.toml" => include_bytes!("../../data/basic_quests.toml"),
+            "data/immortals.txt" => b"You",
+            "data/scripts/scriptlist.txt" => b"$",
             "data/area/arealist.txt" => b"basic.are",
             "data/area/basic.are" => include_bytes!("../../data/basic_area.txt"),
+            // Nothing is saved in the CLI, so every player is a new one
+            path if path.starts_with("data/players/") => {
+                return Err(std::io::ErrorKind::NotFound.into())
+            }
             _ => panic!("Unknown file {}", path),
         };
 
@@ -29,6 +36,9 @@ impl Files for StaticFiles {
             "clicore/notice.txt" => include_bytes!("../notice.txt"),
 
             "data/socials.txt" => include_bytes!("../../data/socials.txt"),
+            "data/quests.toml" => include_bytes!("../../data/basic_quests.toml"),
+            "data/immortals.txt" => b"You",
+            "data/scripts/scriptlist.txt" => b"$",
             "data/area/arealist.txt" => include_bytes!("../../data/area/arealist.txt"),
 
             "data/area/aarislan.are" => include_bytes!("../../data/area/aarislan.are"),
@@ -77,6 +87,10 @@ impl Files for StaticFiles {
             "data/area/wild_1.are" => include_bytes!("../../data/area/wild_1.are"),
             "data/area/wild_2.are" => include_bytes!("../../data/area/wild_2.are"),
 
+            // Nothing is saved in the CLI, so every player is a new one
+            path if path.starts_with("data/players/") => {
+                return Err(std::io::ErrorKind::NotFound.into())
+            }
             _ => panic!("Unknown file {}", path),
         };
         Ok(contents.to_vec())
@@ -1,6 +1,6 @@
 use std::io::{stdout, Write};
 
-use mudlib::{colorize, Files, WorldState};
+use mudlib::{colorize, Editor, EditorStatus, Files, WorldState};
 
 mod files;
 
@@ -9,7 +9,7 @@ struct Game {
 }
 
 impl Game {
-    fn new(files: &dyn Files) -> Self {
+    fn new(files: Box<dyn Files>) -> Self {
         Self {
             world: WorldState::from_files(files),
         }
@@ -21,16 +21,20 @@ impl Game {
             .expect("Player was created at the start of main().")
     }
 
-    fn send_echoes(&mut self) -> Result<(), std::io::Error> {
+    fn send_echoes(&mut self, editing: bool) -> Result<(), std::io::Error> {
         let press_enter = self.world.pending_room_events("You");
         let echoes = self.echoes();
 
         echo(&colorize(echoes));
         let mut stdout = stdout();
-        if press_enter {
-            stdout.write_all(b"\x1b[1;30mpress enter")?;
+        if editing {
+            stdout.write_all(b"\x1b[1;37m] \x1b[0m")?;
+        } else {
+            if press_enter {
+                stdout.write_all(b"\x1b[1;30mpress enter")?;
+            }
+            stdout.write_all(b"\x1b[1;37m> \x1b[0m")?;
         }
-        stdout.write_all(b"\x1b[1;37m> \x1b[0m")?;
         stdout.flush()?;
         echoes.clear();
 
@@ -79,21 +83,41 @@ fn main() {
     // Print info about DemiMUD and help pages
     echo(&colorize(&files.read_file("clicore/notice.txt").unwrap()));
 
-    let mut game = Game::new(&files);
+    let mut game = Game::new(Box::new(files::StaticFiles));
+    if let Some(report) = game.world.load_report() {
+        echo(&report);
+    }
     game.world.add_player("You");
-    game.send_echoes().unwrap();
+    game.send_echoes(false).unwrap();
+
+    // Lines go to the editor instead of being commands while one is open
+    let mut editor: Option<Editor> = None;
 
     loop {
         let mut line = String::new();
         std::io::stdin().read_line(&mut line).unwrap();
+
+        if let Some(current_editor) = &mut editor {
+            let mut output = String::new();
+            let status = current_editor.input(&line, &mut output);
+            game.echoes().push_str(&output);
+
+            if status != EditorStatus::Editing {
+                let finished_editor = editor.take().expect("Checked above");
+                if status == EditorStatus::Saved {
+                    game.world.finish_editor("You", finished_editor);
+                }
+            }
+
+            game.send_echoes(editor.is_some()).unwrap();
+            continue;
+        }
+
         let words = line.split_whitespace().collect::<Vec<_>>();
 
         match words[..] {
-            ["who"] => {
-                echo("Just you. It's a CLI after all.\r\n");
-            }
             ["restart"] => {
-                game = Game::new(&files);
+                game = Game::new(Box::new(files::StaticFiles));
                 game.world.add_player("You");
                 echo("World reloaded.\r\n");
             }
@@ -104,11 +128,12 @@ fn main() {
             [] => game.wait_for_events(),
             ref words => {
                 game.world.process_player_command("You", words);
+                editor = game.world.take_editor("You");
             }
         }
 
         game.world.update_world();
 
-        game.send_echoes().unwrap();
+        game.send_echoes(editor.is_some()).unwrap();
     }
 }
@@ -34,7 +34,7 @@
 //! The stage can then be used to get message targets for `.myself()`,
 //! `.target()`, and other `.others()`, which return objects that implement the
 //! `Write` trait. Writing to these objects will send that message to all
-//! players that match.
+//! players that match. Sleeping players only get messages about themselves.
 //!
 //! An `echo!()` macro is provided, which is similar to the standard `write!()`
 //! but directly panics on errors instead of returning a Result. The acting
@@ -42,31 +42,55 @@
 //! object, which makes writing mostly infallible.
 //!
 //! The variables are:
-//! * $n - the short description, e.g. "an apple"
+//! * $n - the short description, e.g. "an apple", or "someone" for players
+//!   that can't see it
 //! * $m - the objective pronoun, e.g. "him"
 //! * $s - the possessive pronoun, e.g. "his"
 //! * $e - the subjective pronoun, e.g. "he"
+//! * $v - put the verb that follows in plural if the actor is plural, e.g.
+//!   "$e $vflexes" is "they flex"; it's only needed after $e, since names
+//!   and short descriptions take singular verbs even for those who go by
+//!   "they"
 //! * $^ - capitalize the first letter of the next variable
 //!
-//! Example: "$^$n licks $mself with $s tongue." would be translated to "An
-//! apple licks itself with its tongue."
+//! Variables in uppercase, e.g. $N and $V, are the same but for the target.
+//!
+//! Example: "$^$n licks $mself, and $e $vlooks pleased." would be translated
+//! to "An apple licks itself, and it looks pleased."
 
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter, Result, Write};
 
 use crate::{
+    channels::ChannelHistory,
+    editor::Editor,
     entity::{EntityId, EntityInfo},
-    world::Gender,
+    mobprogs::MobProgTraces,
+    who::ConnectionInfo,
 };
 
 pub(crate) struct Players {
     pub(crate) player_echoes: BTreeMap<String, PlayerEcho>,
+    pub(crate) mobprog_traces: MobProgTraces,
+    pub(crate) channel_history: ChannelHistory,
 }
 
 #[derive(Default)]
 pub(crate) struct PlayerEcho {
     pub echo_buffer: String,
     current_target_type: Option<TargetType>,
+
+    /// Where the player is connected from, if the network layer said so
+    pub connection: Option<ConnectionInfo>,
+    pub last_command_tick: u64,
+
+    /// Editor opened by the last command, for the front end to take
+    pub editor: Option<Editor>,
+
+    /// Whether the next line answers the question about pronouns that new
+    /// characters are asked
+    pub choosing_pronouns: bool,
 }
 
 impl Players {
@@ -99,26 +123,71 @@ pub(crate) trait Actor {
     fn entity_id(&self) -> EntityId;
     fn is_player(&self, player_name: &str) -> bool;
     fn colocated_with_player(&self, player_name: &str) -> bool;
+    fn visible_to_player(&self, player_name: &str) -> bool;
+    fn player_is_awake(&self, player_name: &str) -> bool;
 
     fn short_description(&self, f: &mut Formatter, capitalized: bool) -> Result;
-    fn pronouns(&self, capitalized: bool) -> (&str, &str, &str);
+    fn pronouns(&self) -> (&str, &str, &str);
+    fn is_plural(&self) -> bool;
 
     fn subjective_pronoun(&self, f: &mut Formatter, capitalized: bool) -> Result {
-        self.pronouns(capitalized).0.fmt(f)
+        write_capitalized(self.pronouns().0, f, capitalized)
     }
 
     fn objective_pronoun(&self, f: &mut Formatter, capitalized: bool) -> Result {
-        self.pronouns(capitalized).1.fmt(f)
+        write_capitalized(self.pronouns().1, f, capitalized)
     }
 
     fn possessive_pronoun(&self, f: &mut Formatter, capitalized: bool) -> Result {
-        self.pronouns(capitalized).2.fmt(f)
+        write_capitalized(self.pronouns().2, f, capitalized)
     }
 }
 
+fn write_capitalized(text: &str, f: &mut Formatter, capitalized: bool) -> Result {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first_character) if capitalized => {
+            first_character.to_uppercase().fmt(f)?;
+            chars.as_str().fmt(f)
+        }
+        _ => text.fmt(f),
+    }
+}
+
+/// Turn a verb that agrees with "he", "she" or "it" into one that agrees with
+/// "they", e.g. "flexes" into "flex".
+fn plural_verb(verb: &str) -> Cow<'_, str> {
+    let plural = match verb {
+        "is" => "are",
+        "was" => "were",
+        "has" => "have",
+        "does" => "do",
+        "goes" => "go",
+        "isn't" => "aren't",
+        "wasn't" => "weren't",
+        "hasn't" => "haven't",
+        "doesn't" => "don't",
+        _ => match verb.strip_suffix("ies") {
+            // "tries" becomes "try", but "dies" becomes "die"
+            Some(stem) if stem.len() > 1 => return Cow::Owned(format!("{}y", stem)),
+            _ => {
+                let ending = ["sses", "shes", "ches", "xes", "zzes"]
+                    .iter()
+                    .find(|ending| verb.ends_with(*ending));
+                match ending {
+                    Some(_) => &verb[..verb.len() - 2],
+                    None => verb.strip_suffix('s').unwrap_or(verb),
+                }
+            }
+        },
+    };
+
+    Cow::Borrowed(plural)
+}
+
 impl<'e> Actor for EntityInfo<'e> {
     fn entity_id(&self) -> EntityId {
-        EntityInfo::entity_id(&self)
+        EntityInfo::entity_id(self)
     }
 
     fn is_player(&self, player_name: &str) -> bool {
@@ -129,35 +198,24 @@ impl<'e> Actor for EntityInfo<'e> {
         self.colocated_with_player(player_name)
     }
 
-    fn short_description(&self, f: &mut Formatter, capitalized: bool) -> Result {
-        if !capitalized {
-            self.component_info().short_description().fmt(f)
-        } else {
-            let mut short_description = self.component_info().short_description();
+    fn visible_to_player(&self, player_name: &str) -> bool {
+        self.visible_to_player(player_name)
+    }
 
-            if let Some(first_character) = short_description.chars().next() {
-                first_character.to_uppercase().fmt(f)?;
-                short_description = &short_description[first_character.len_utf8()..];
-            }
+    fn player_is_awake(&self, player_name: &str) -> bool {
+        self.player_is_awake(player_name)
+    }
 
-            short_description.fmt(f)
-        }
+    fn short_description(&self, f: &mut Formatter, capitalized: bool) -> Result {
+        write_capitalized(self.component_info().short_description(), f, capitalized)
     }
 
-    fn pronouns(&self, capitalized: bool) -> (&str, &str, &str) {
-        if !capitalized {
-            match self.component_info().gender() {
-                Gender::Male => ("he", "him", "his"),
-                Gender::Female => ("she", "her", "her"),
-                Gender::Neutral => ("it", "it", "its"),
-            }
-        } else {
-            match self.component_info().gender() {
-                Gender::Male => ("He", "Him", "His"),
-                Gender::Female => ("She", "Her", "Her"),
-                Gender::Neutral => ("It", "It", "Its"),
-            }
-        }
+    fn pronouns(&self) -> (&str, &str, &str) {
+        self.component_info().gender().pronouns()
+    }
+
+    fn is_plural(&self) -> bool {
+        self.component_info().gender().is_plural()
     }
 }
 
@@ -187,6 +245,9 @@ impl<'p, 'e> ActingStage<'p, 'e, ()> {
         for (player_name, player_echo) in players.player_echoes.iter_mut() {
             player_echo.current_target_type = if current.is_player(player_name) {
                 Some(TargetType::Myself)
+            } else if !current.player_is_awake(player_name) {
+                // Sleepers only hear about what they do themselves
+                None
             } else if target.map(|target| target.is_player(player_name)) == Some(true) {
                 Some(TargetType::Target)
             } else if current.colocated_with_player(player_name) {
@@ -278,14 +339,15 @@ pub(crate) struct ActTarget<'a, 'p: 'a, 'e: 'a, ActType = ()> {
 
 impl Write for ActTarget<'_, '_, '_, ()> {
     fn write_str(&mut self, message: &str) -> Result {
-        let message = ReplaceActVariables {
-            current: self.stage.current_actor,
-            target: self.stage.target_actor,
-            message,
-        };
-
-        for player_echo in self.stage.players.player_echoes.values_mut() {
+        for (player_name, player_echo) in self.stage.players.player_echoes.iter_mut() {
             if player_echo.current_target_type.as_ref() == Some(&self.target_type) {
+                let message = ReplaceActVariables {
+                    current: self.stage.current_actor,
+                    target: self.stage.target_actor,
+                    observer: Some(player_name),
+                    message,
+                };
+
                 write!(player_echo.echo_buffer, "{}", message)?;
             }
         }
@@ -296,9 +358,11 @@ impl Write for ActTarget<'_, '_, '_, ()> {
 
 impl Write for ActTarget<'_, '_, '_, Acts> {
     fn write_str(&mut self, message: &str) -> Result {
-        let message = ReplaceActVariables {
+        // Triggers see everything
+        let stored_message = ReplaceActVariables {
             current: self.stage.current_actor,
             target: self.stage.target_actor,
+            observer: None,
             message,
         };
 
@@ -307,10 +371,17 @@ impl Write for ActTarget<'_, '_, '_, Acts> {
             TargetType::Target => &mut self.stage.acts.target,
             TargetType::Others => &mut self.stage.acts.others,
         };
-        write!(stored_acts, "{}", message)?;
+        write!(stored_acts, "{}", stored_message)?;
 
-        for player_echo in self.stage.players.player_echoes.values_mut() {
+        for (player_name, player_echo) in self.stage.players.player_echoes.iter_mut() {
             if player_echo.current_target_type.as_ref() == Some(&self.target_type) {
+                let message = ReplaceActVariables {
+                    current: self.stage.current_actor,
+                    target: self.stage.target_actor,
+                    observer: Some(player_name),
+                    message,
+                };
+
                 write!(player_echo.echo_buffer, "{}", message)?;
             }
         }
@@ -348,9 +419,44 @@ impl Display for EscapeVariables<'_> {
 struct ReplaceActVariables<'e, 'm> {
     current: &'e dyn Actor,
     target: Option<&'e dyn Actor>,
+    /// The player that will read the message, if it's for a player
+    observer: Option<&'m str>,
     message: &'m str,
 }
 
+impl ReplaceActVariables<'_, '_> {
+    fn short_description(&self, actor: &dyn Actor, f: &mut Formatter, capitalized: bool) -> Result {
+        match self.observer {
+            Some(observer) if !actor.visible_to_player(observer) => {
+                if capitalized { "Someone" } else { "someone" }.fmt(f)
+            }
+            _ => actor.short_description(f, capitalized),
+        }
+    }
+
+    /// Write the verb at the start of `message` so that it agrees with the
+    /// actor, returning the rest of the message.
+    fn verb<'m>(
+        &self,
+        actor: &dyn Actor,
+        f: &mut Formatter,
+        message: &'m str,
+    ) -> std::result::Result<&'m str, std::fmt::Error> {
+        let end = message
+            .find(|c: char| !c.is_alphabetic() && c != '\'')
+            .unwrap_or(message.len());
+        let (verb, rest) = message.split_at(end);
+
+        if actor.is_plural() {
+            plural_verb(verb).fmt(f)?;
+        } else {
+            verb.fmt(f)?;
+        }
+
+        Ok(rest)
+    }
+}
+
 impl Display for ReplaceActVariables<'_, '_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         let mut message = self.message;
@@ -376,7 +482,7 @@ impl Display for ReplaceActVariables<'_, '_> {
                     '$'.fmt(f)?;
                 }
                 Some('n') => {
-                    self.current.short_description(f, capitalized)?;
+                    self.short_description(self.current, f, capitalized)?;
                 }
                 Some('e') => {
                     self.current.subjective_pronoun(f, capitalized)?;
@@ -387,9 +493,20 @@ impl Display for ReplaceActVariables<'_, '_> {
                 Some('s') => {
                     self.current.possessive_pronoun(f, capitalized)?;
                 }
+                Some('v') => {
+                    message = self.verb(self.current, f, &message[2..])?;
+                    capitalized = false;
+                    continue;
+                }
+                Some('V') if self.target.is_some() => {
+                    let target = self.target.expect("Checked above");
+                    message = self.verb(target, f, &message[2..])?;
+                    capitalized = false;
+                    continue;
+                }
                 Some('N') if self.target.is_some() => {
                     let target = self.target.expect("Checked above");
-                    target.short_description(f, capitalized)?;
+                    self.short_description(target, f, capitalized)?;
                 }
                 Some('E') if self.target.is_some() => {
                     let target = self.target.expect("Checked above");
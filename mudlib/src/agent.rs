@@ -1,22 +1,29 @@
 use crate::{
     acting::{InfoTarget, Players},
-    components::{Components, EntityType, GeneralData, InternComponent, Silver},
     echo,
     entity::{EntityId, EntityWorld},
+    file_parser::Diagnostic,
     find_entities::MatchError,
     import::VnumTemplates,
     mobprogs::Action,
+    quests::Quests,
+    scripts::Scripts,
     socials::Socials,
     state::Area,
-    world::{opposite_direction, Gender, Vnum},
-    WorldState,
+    world::{opposite_direction, World},
+    Files, WorldState,
 };
 
 pub(crate) struct EntityAgent<'e, 'p> {
     pub entity_world: &'e mut EntityWorld,
     pub socials: &'e Socials,
-    pub vnum_templates: &'e VnumTemplates,
+    pub world: &'e mut World,
+    pub vnum_templates: &'e mut VnumTemplates,
     pub areas: &'e Vec<Area>,
+    pub diagnostics: &'e [Diagnostic],
+    pub quests: &'e Quests,
+    pub scripts: &'e mut Scripts,
+    pub files: &'e dyn Files,
     pub players: &'p mut Players,
 
     pub entity_id: EntityId,
@@ -27,8 +34,13 @@ impl EntityAgent<'_, '_> {
         EntityAgent {
             entity_world: &mut world_state.entity_world,
             socials: &world_state.socials,
-            vnum_templates: &world_state.vnum_templates,
+            world: &mut world_state.world,
+            vnum_templates: &mut world_state.vnum_templates,
             areas: &world_state.areas,
+            diagnostics: &world_state.diagnostics,
+            quests: &world_state.quests,
+            scripts: &mut world_state.scripts,
+            files: &*world_state.files,
             players: &mut world_state.players,
 
             entity_id,
@@ -39,8 +51,13 @@ impl EntityAgent<'_, '_> {
         EntityAgent {
             entity_world: self.entity_world,
             socials: self.socials,
+            world: self.world,
             vnum_templates: self.vnum_templates,
             areas: self.areas,
+            diagnostics: self.diagnostics,
+            quests: self.quests,
+            scripts: self.scripts,
+            files: self.files,
             players: self.players,
 
             entity_id,
@@ -54,6 +71,10 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         self.players.info(&myself)
     }
 
+    pub fn is_immortal(&self) -> bool {
+        self.entity_world.entity_info(self.entity_id).is_immortal()
+    }
+
     pub fn echo_error(&mut self, error: MatchError) {
         let myself = self.entity_world.entity_info(self.entity_id);
         match error {
@@ -79,15 +100,12 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         let room = self.entity_world.entity_info(from_room_id);
 
         for follower in room.contained_entities() {
-            let main_keyword = myself.main_keyword();
-            if let Some(following) = &follower.components().general.following {
-                if following == main_keyword {
-                    followers.push(follower.entity_id());
+            if follower.components().general.following == Some(myself.permanent_entity_id()) {
+                followers.push(follower.entity_id());
 
-                    let mut act = self.players.act_with(&follower, &myself);
-                    echo!(act.myself(), "You follow $N to the {}.\r\n", direction);
-                    echo!(act.others(), "$^$n follows $N to the {}.\r\n", direction);
-                }
+                let mut act = self.players.act_with(&follower, &myself);
+                echo!(act.myself(), "You follow $N to the {}.\r\n", direction);
+                echo!(act.others(), "$^$n follows $N to the {}.\r\n", direction);
             }
         }
 
@@ -113,127 +131,3 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         }
     }
 }
-
-// Silver handling
-impl<'e, 'p> EntityAgent<'e, 'p> {
-    pub fn add_silver(&mut self, amount: usize, to_entity: EntityId) {
-        let entity = self.entity_world.entity_info(to_entity);
-
-        // See if the silver coins can be joined with an existing pile in the inventory
-        let silver_pile = entity
-            .objects()
-            .find(|object| object.components().silver.is_some())
-            .map(|object| object.entity_id());
-
-        match silver_pile {
-            Some(silver_pile_id) => {
-                // Add to an existing pile
-                let mut silver_pile = self.entity_world.entity_info_mut(silver_pile_id);
-
-                let silver = silver_pile
-                    .components()
-                    .silver
-                    .as_mut()
-                    .expect("Filtered above to one that has the component");
-
-                silver.amount += amount;
-                let new_amount = silver.amount;
-
-                let (mut silver_pile, interner) = self
-                    .entity_world
-                    .entity_info_mut_with_interner(silver_pile_id);
-
-                let short_description = format!("{} silver coins", new_amount);
-                silver_pile.set_short_description(interner, &short_description);
-            }
-            None => {
-                // Otherwise create a new pile
-                let keyword = "silver coins";
-                let short_description = format!("{} silver coins", amount);
-
-                let title = "Swimming in silver coins.";
-                let internal = "You are inside a pile of silver coins.";
-                let external = "A pile of silver coins.";
-                let lateral = "A pile of silver coins is on the ground here.";
-
-                let act_info = self.entity_world.interner.act_info(
-                    keyword,
-                    &short_description,
-                    Gender::Neutral,
-                );
-                let descriptions = self
-                    .entity_world
-                    .interner
-                    .descriptions(title, internal, external, lateral);
-
-                self.entity_world.insert_entity(
-                    to_entity,
-                    Components {
-                        act_info,
-                        descriptions,
-                        general: GeneralData {
-                            vnum: Vnum(0),
-                            area: "silver".to_string(),
-                            sector: None,
-                            entity_type: EntityType::Object,
-                            equipped: None,
-                            command_queue: Vec::new(),
-                            following: None,
-                        },
-                        mobile: None,
-                        object: None,
-                        door: None,
-                        mobprog: None,
-                        silver: Some(Silver { amount }),
-                    },
-                );
-            }
-        };
-    }
-
-    pub fn remove_silver(&mut self, amount: usize, to_entity: EntityId) -> bool {
-        let entity = self.entity_world.entity_info(to_entity);
-
-        // See if the silver coins can be joined with an existing pile in the inventory
-        let silver_pile = entity
-            .objects()
-            .find(|object| object.components().silver.is_some())
-            .map(|object| object.entity_id());
-
-        match silver_pile {
-            Some(silver_pile_id) => {
-                let new_amount = {
-                    let mut silver_pile = self.entity_world.entity_info_mut(silver_pile_id);
-                    let silver = silver_pile
-                        .components()
-                        .silver
-                        .as_mut()
-                        .expect("Filtered above to one that has the component");
-
-                    if silver.amount >= amount {
-                        silver.amount -= amount;
-                        Some(silver.amount)
-                    } else {
-                        None
-                    }
-                };
-
-                if let Some(new_amount) = new_amount {
-                    if new_amount != 0 {
-                        let (mut silver_pile, interner) = self
-                            .entity_world
-                            .entity_info_mut_with_interner(silver_pile_id);
-
-                        let short_description = format!("{} silver coins", new_amount);
-                        silver_pile.set_short_description(interner, &short_description);
-                    } else {
-                        let mut agent = self.switch_agent(silver_pile_id);
-                        agent.do_die();
-                    }
-                }
-                new_amount.is_some()
-            }
-            None => false,
-        }
-    }
-}
@@ -0,0 +1,357 @@
+//! How players look to others: their pronouns, description, title and short
+//! description, which they can change with `pronouns`, `description`, `title`
+//! and `short`.
+//!
+//! The player component keeps what the player chose, and the descriptions
+//! that everything else uses are rebuilt from it with `player_descriptions()`
+//! whenever it changes, or when it's restored from the player file.
+
+use inflector::Inflector;
+use string_interner::StringInterner;
+
+use crate::{
+    agent::EntityAgent,
+    colors::{strip_colors, EscapeColors},
+    components::{ActInfo, Descriptions, InternComponent, Player},
+    echo,
+    editor::EditorTarget,
+    world::{Gender, Pronouns},
+};
+
+const MAX_TITLE_LENGTH: usize = 45;
+const MAX_SHORT_DESCRIPTION_LENGTH: usize = 60;
+const MAX_DESCRIPTION_LENGTH: usize = 1500;
+const MAX_PRONOUN_LENGTH: usize = 12;
+
+/// Build a player's short description and descriptions from their name and
+/// the player component.
+pub(crate) fn player_descriptions(
+    interner: &mut StringInterner,
+    name: &str,
+    player: &Player,
+) -> (ActInfo, Descriptions) {
+    let keyword = name.to_lowercase();
+    let proper_name = name.to_title_case();
+    let short_description = player
+        .short_description
+        .clone()
+        .unwrap_or_else(|| proper_name.clone());
+
+    let title = format!("Inside {}.", proper_name);
+    let internal = format!(
+        "You are inside {}, the player. How did you get in here?",
+        proper_name
+    );
+    let external = player
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("You see nothing special about {}.", proper_name));
+
+    let mut chars = short_description.chars();
+    let capitalized = match chars.next() {
+        Some(first) => format!("{}{}", first.to_uppercase(), chars.as_str()),
+        None => String::new(),
+    };
+    let lateral = if player.title.is_empty() {
+        format!("{} is here.", capitalized)
+    } else {
+        format!("{} {} is here.", capitalized, player.title)
+    };
+
+    (
+        interner.act_info(&keyword, &short_description, player.gender.clone()),
+        interner.descriptions(&title, &internal, &external, &lateral),
+    )
+}
+
+/// Parse pronouns from the usual sets, e.g. "she" or "she/her", or a custom
+/// set as "subjective/objective/possessive".
+fn parse_pronouns(pronouns: &str) -> Option<Gender> {
+    let lowercase = pronouns.to_lowercase();
+    let parts: Vec<&str> = lowercase.split('/').collect();
+
+    let usual = vec![
+        Gender::Male,
+        Gender::Female,
+        Gender::Plural,
+        Gender::Neutral,
+    ]
+    .into_iter()
+    .find(|gender| {
+        let (subjective, objective, possessive) = gender.pronouns();
+        parts[..] == [subjective]
+            || parts[..] == [subjective, objective]
+            || parts[..] == [subjective, objective, possessive]
+    });
+
+    match (usual, &parts[..]) {
+        (Some(gender), _) => Some(gender),
+        (None, &[subjective, objective, possessive])
+            if parts.iter().all(|part| {
+                !part.is_empty()
+                    && part.len() <= MAX_PRONOUN_LENGTH
+                    && part.chars().all(|c| c.is_ascii_alphabetic())
+            }) =>
+        {
+            Some(Gender::Custom(Pronouns {
+                subjective: subjective.to_string(),
+                objective: objective.to_string(),
+                possessive: possessive.to_string(),
+            }))
+        }
+        _ => None,
+    }
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    /// Rebuild the player's descriptions after their player component changed.
+    pub(crate) fn refresh_player_descriptions(&mut self) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let name = match myself.player_name() {
+            Some(name) => name.to_string(),
+            None => return,
+        };
+        let player = match &myself.components().player {
+            Some(player) => player.clone(),
+            None => return,
+        };
+
+        let (act_info, descriptions) =
+            player_descriptions(&mut self.entity_world.interner, &name, &player);
+
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        let components = myself.components();
+        components.act_info = act_info;
+        components.descriptions = descriptions;
+    }
+
+    /// Change the player component, then rebuild the descriptions from it.
+    /// Returns false if the agent isn't a player.
+    fn change_player(&mut self, change: impl FnOnce(&mut Player)) -> bool {
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        match &mut myself.components().player {
+            Some(player) => change(player),
+            None => {
+                echo!(self.info(), "Only players can do that.\r\n");
+                return false;
+            }
+        }
+
+        self.refresh_player_descriptions();
+        true
+    }
+
+    /// Choose pronouns from the usual sets, e.g. "she" or "she/her", or a
+    /// custom set as "subjective/objective/possessive", e.g. "xe/xem/xyr".
+    pub fn do_pronouns(&mut self, pronouns: &str) {
+        let gender = match parse_pronouns(pronouns) {
+            Some(gender) => gender,
+            None => {
+                echo!(
+                    self.info(),
+                    "Syntax: `Wpronouns he|she|they|it`^, or `Wpronouns <subjective>/<objective>/<possessive>`^ for your own, e.g. `Wpronouns xe/xem/xyr`^\r\n"
+                );
+                return;
+            }
+        };
+
+        self.set_pronouns(gender);
+    }
+
+    fn set_pronouns(&mut self, gender: Gender) {
+        if !self.change_player(|player| player.gender = gender) {
+            return;
+        }
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let mut act = self.players.act_alone(&myself);
+        echo!(
+            act.myself(),
+            "Others will now say that $e $vis here, and talk about $m and $s things.\r\n"
+        );
+    }
+
+    /// Ask a new character which pronouns to use; their next line is the
+    /// answer.
+    pub(crate) fn ask_pronouns(&mut self) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let player = match myself.player_name() {
+            Some(player) => player,
+            None => return,
+        };
+
+        if let Some(player_echo) = self.players.player_echoes.get_mut(player) {
+            player_echo.choosing_pronouns = true;
+        }
+
+        echo!(
+            self.info(),
+            "Welcome to the realm! Which pronouns should others use for you? Type `Whe`^, \
+            `Wshe`^, `Wthey`^ or `Wit`^, make your own with e.g. `Wxe/xem/xyr`^, or just press \
+            enter to be \"they\" for now.\r\n"
+        );
+    }
+
+    /// Take the player's line as the answer to `ask_pronouns()`, if it was
+    /// asked. Returns false if the line should run as a command instead.
+    pub(crate) fn answer_pronouns(&mut self, words: &[&str]) -> bool {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let player_echo = match myself.player_name() {
+            Some(player) => self.players.player_echoes.get_mut(player),
+            None => None,
+        };
+        match player_echo {
+            Some(player_echo) if player_echo.choosing_pronouns => {
+                player_echo.choosing_pronouns = false
+            }
+            _ => return false,
+        }
+
+        let gender = match words {
+            [pronouns] => parse_pronouns(pronouns),
+            _ => None,
+        };
+
+        match gender {
+            Some(gender) => {
+                self.set_pronouns(gender);
+                true
+            }
+            None => {
+                echo!(
+                    self.info(),
+                    "Others will call you \"they\" until you choose your pronouns with \
+                    `Wpronouns`^.\r\n"
+                );
+                words.is_empty()
+            }
+        }
+    }
+
+    pub fn do_title(&mut self, title: &str) {
+        let title = strip_colors(title);
+        if title.chars().count() > MAX_TITLE_LENGTH {
+            echo!(
+                self.info(),
+                "Titles can't be longer than {} characters.\r\n",
+                MAX_TITLE_LENGTH
+            );
+            return;
+        }
+
+        let title = title.into_owned();
+        if self.change_player(|player| player.title = title) {
+            echo!(self.info(), "Title set.\r\n");
+        }
+    }
+
+    /// Set the short description, which must have the player's name in it so
+    /// that others know who they are. Using just the name goes back to the
+    /// default.
+    pub fn do_short(&mut self, short_description: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let name = myself.player_name().unwrap_or_default().to_string();
+        let short_description = strip_colors(short_description);
+
+        if short_description.chars().count() > MAX_SHORT_DESCRIPTION_LENGTH {
+            echo!(
+                self.info(),
+                "Short descriptions can't be longer than {} characters.\r\n",
+                MAX_SHORT_DESCRIPTION_LENGTH
+            );
+            return;
+        }
+
+        let has_name = short_description
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| word.eq_ignore_ascii_case(&name));
+        if !has_name {
+            echo!(
+                self.info(),
+                "Your short description has to include your name, {}.\r\n",
+                name.to_title_case()
+            );
+            return;
+        }
+
+        let short_description = if short_description.eq_ignore_ascii_case(&name) {
+            None
+        } else {
+            Some(short_description.to_string())
+        };
+        if !self.change_player(|player| player.short_description = short_description) {
+            return;
+        }
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let mut act = self.players.act_alone(&myself);
+        echo!(act.myself(), "You are now known as $n.\r\n");
+    }
+
+    /// Set the description in one line, or open the editor on it if there's
+    /// no text.
+    pub fn do_description(&mut self, description: Option<&str>) {
+        match description {
+            Some(description) => self.set_player_description(format!("{}\r\n", description)),
+            None => {
+                let myself = self.entity_world.entity_info(self.entity_id);
+                let description = match &myself.components().player {
+                    Some(player) => player.description.clone().unwrap_or_default(),
+                    None => return,
+                };
+                self.start_editor(EditorTarget::Player, "your description", &description);
+            }
+        }
+    }
+
+    /// Set the description that others see when looking at the player; an
+    /// empty one goes back to the default.
+    pub(crate) fn set_player_description(&mut self, description: String) {
+        if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+            echo!(
+                self.info(),
+                "Descriptions can't be longer than {} characters.\r\n",
+                MAX_DESCRIPTION_LENGTH
+            );
+            return;
+        }
+
+        let description = if description.trim().is_empty() {
+            None
+        } else {
+            Some(description)
+        };
+        if self.change_player(|player| player.description = description) {
+            echo!(self.info(), "Description set.\r\n");
+        }
+    }
+
+    /// Show what others see of the player, and how to change it.
+    pub fn do_appearance(&mut self) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let title = match &myself.components().player {
+            Some(player) => player.title.clone(),
+            None => return,
+        };
+        let info = myself.component_info();
+        let short_description = info.short_description();
+        let lateral = info.lateral_description();
+        let (subjective, objective, possessive) = info.gender().pronouns();
+
+        let mut output = self.players.info(&myself);
+        echo!(
+            output,
+            "Pronouns: {}/{}/{}\r\n",
+            subjective,
+            objective,
+            possessive
+        );
+        echo!(output, "Title: {}\r\n", EscapeColors(&title));
+        echo!(output, "Short: {}\r\n", EscapeColors(short_description));
+        echo!(output, "In the room: {}\r\n", EscapeColors(lateral));
+        echo!(
+            output,
+            "Use `Wpronouns <pronouns>`^, `Wtitle <title>`^, `Wshort <description>`^ and `Wdescription [text]`^ to change them.\r\n"
+        );
+    }
+}
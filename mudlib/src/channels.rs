@@ -0,0 +1,387 @@
+//! Global channels, tells and replies between players.
+//!
+//! Unlike `say` and emotes, which only reach the current room, these reach
+//! players anywhere in the world. Everyone can talk on the `gossip`, `ooc` and
+//! `newbie` channels, each of which can be turned off, and keeps the last few
+//! messages said on it for the `history` command. A `tell` goes to a single
+//! player, who can answer with `reply`.
+//!
+//! Players on someone's `ignore` list can't reach them in any of these ways,
+//! and sleeping players don't hear anything until they wake up.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    agent::EntityAgent, colors::recolor, components::ChannelSettings, echo, entity::EntityInfo,
+};
+
+pub(crate) struct Channel {
+    pub name: &'static str,
+    pub title: &'static str,
+    pub color: &'static str,
+}
+
+static CHANNELS: &[Channel] = &[
+    Channel {
+        name: "gossip",
+        title: "Gossip",
+        color: "`Y",
+    },
+    Channel {
+        name: "ooc",
+        title: "OOC",
+        color: "`C",
+    },
+    Channel {
+        name: "newbie",
+        title: "Newbie",
+        color: "`G",
+    },
+];
+
+/// How many messages each channel remembers.
+const HISTORY_LENGTH: usize = 20;
+
+const TELL_COLOR: &str = "`R";
+
+pub(crate) fn find_channel(name: &str) -> Option<&'static Channel> {
+    CHANNELS.iter().find(|channel| channel.name == name)
+}
+
+/// The last messages said on each channel, along with who said them so that
+/// ignored players stay ignored.
+#[derive(Default)]
+pub(crate) struct ChannelHistory {
+    channels: HashMap<&'static str, VecDeque<(Option<String>, String)>>,
+}
+
+impl ChannelHistory {
+    fn add(&mut self, channel: &'static str, speaker: Option<String>, line: String) {
+        let history = self.channels.entry(channel).or_default();
+
+        if history.len() >= HISTORY_LENGTH {
+            history.pop_front();
+        }
+        history.push_back((speaker, line));
+    }
+}
+
+impl ChannelSettings {
+    fn is_ignoring(&self, player_name: Option<&str>) -> bool {
+        match player_name {
+            Some(player_name) => self
+                .ignoring
+                .iter()
+                .any(|ignored| ignored.eq_ignore_ascii_case(player_name)),
+            None => false,
+        }
+    }
+
+    fn is_listening(&self, channel: &Channel) -> bool {
+        !self.turned_off.iter().any(|name| name == channel.name)
+    }
+}
+
+impl<'e> EntityInfo<'e> {
+    pub fn channel_settings(&self) -> Option<&'e ChannelSettings> {
+        self.components()
+            .player
+            .as_ref()
+            .map(|player| &player.channels)
+    }
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    /// Find the name of a player in the game, ignoring case.
+    fn find_player_name(&self, name: &str) -> Option<String> {
+        self.players
+            .player_echoes
+            .keys()
+            .find(|player_name| player_name.eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
+    pub fn do_channel(&mut self, channel: &Channel, message: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        if let Some(settings) = myself.channel_settings() {
+            if !settings.is_listening(channel) {
+                echo!(
+                    self.info(),
+                    "You have the {} channel turned off.\r\n",
+                    channel.name
+                );
+                return;
+            }
+        }
+
+        let speaker = myself.player_name().map(String::from);
+        let short_description = myself.component_info().short_description();
+        let message = recolor(channel.color, message);
+
+        let line = format!(
+            "{}[{}] {}: {}`^\r\n",
+            channel.color, channel.title, short_description, message
+        );
+
+        let player_names: Vec<String> = self.players.player_echoes.keys().cloned().collect();
+        for player_name in player_names {
+            let player = match self.entity_world.player_entity_id(&player_name) {
+                Some(player_id) => self.entity_world.entity_info(player_id),
+                None => continue,
+            };
+
+            if player.entity_id() != self.entity_id {
+                let deaf = player.channel_settings().is_some_and(|settings| {
+                    !settings.is_listening(channel) || settings.is_ignoring(speaker.as_deref())
+                });
+
+                if deaf || !player.is_awake() {
+                    continue;
+                }
+            }
+
+            if let Some(player_echo) = self.players.player_echoes.get_mut(&player_name) {
+                player_echo.echo_buffer.push_str(&line);
+            }
+        }
+
+        self.players
+            .channel_history
+            .add(channel.name, speaker, line);
+    }
+
+    pub fn do_channel_toggle(&mut self, channel: &Channel) {
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        let settings = match &mut myself.components().player {
+            Some(player) => &mut player.channels,
+            None => return,
+        };
+
+        let listening = settings.is_listening(channel);
+        if listening {
+            settings.turned_off.push(channel.name.to_string());
+        } else {
+            settings.turned_off.retain(|name| name != channel.name);
+        }
+
+        if listening {
+            echo!(
+                self.info(),
+                "You turn off the {} channel.\r\n",
+                channel.name
+            );
+        } else {
+            echo!(self.info(), "You turn on the {} channel.\r\n", channel.name);
+        }
+    }
+
+    pub fn do_channels(&mut self) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let settings = myself.channel_settings();
+
+        let mut info = self.players.info(&myself);
+        echo!(info, "Channels:\r\n");
+        for channel in CHANNELS {
+            let state = match settings {
+                Some(settings) if !settings.is_listening(channel) => "`soff`^",
+                _ => "`Won`^",
+            };
+            echo!(
+                info,
+                "  {}{:<8}`^ {}\r\n",
+                channel.color,
+                channel.name,
+                state
+            );
+        }
+        echo!(
+            info,
+            "Use '`W<channel> <message>`^' to talk, or just '`W<channel>`^' to turn it on or off.\r\n"
+        );
+    }
+
+    pub fn do_history(&mut self, channel_name: &str) {
+        let channel = match find_channel(channel_name) {
+            Some(channel) => channel,
+            None => {
+                echo!(
+                    self.info(),
+                    "There is no channel named '{}'.\r\n",
+                    channel_name
+                );
+                return;
+            }
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let settings = myself.channel_settings();
+        let history = self.players.channel_history.channels.get(channel.name);
+
+        let lines: Vec<String> = history
+            .into_iter()
+            .flatten()
+            .filter(|(speaker, _line)| {
+                !settings.is_some_and(|settings| settings.is_ignoring(speaker.as_deref()))
+            })
+            .map(|(_speaker, line)| line.clone())
+            .collect();
+
+        let mut info = self.players.info(&myself);
+        if lines.is_empty() {
+            echo!(
+                info,
+                "Nothing was said on the {} channel yet.\r\n",
+                channel.name
+            );
+        }
+        for line in lines {
+            echo!(info, "{}", line);
+        }
+    }
+
+    pub fn do_tell(&mut self, target: &str, message: &str) {
+        let target_name = match self.find_player_name(target) {
+            Some(target_name) => target_name,
+            None => {
+                echo!(
+                    self.info(),
+                    "There is no player named '{}' here.\r\n",
+                    target
+                );
+                return;
+            }
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = match self.entity_world.player_entity_id(&target_name) {
+            Some(target_id) => self.entity_world.entity_info(target_id),
+            None => {
+                echo!(
+                    self.info(),
+                    "{} doesn't seem to have a body.\r\n",
+                    target_name
+                );
+                return;
+            }
+        };
+
+        if target.entity_id() == self.entity_id {
+            echo!(self.info(), "You talk to yourself for a while.\r\n");
+            return;
+        }
+
+        let speaker = myself.player_name();
+        if target
+            .channel_settings()
+            .is_some_and(|settings| settings.is_ignoring(speaker))
+        {
+            echo!(self.info(), "{} is ignoring you.\r\n", target_name);
+            return;
+        }
+
+        if !target.is_awake() {
+            echo!(
+                self.info(),
+                "{} is asleep and can't hear you.\r\n",
+                target_name
+            );
+            return;
+        }
+
+        let message = recolor(TELL_COLOR, message);
+        let short_description = myself.component_info().short_description();
+        let target_short_description = target.component_info().short_description();
+        let speaker = speaker.map(String::from);
+        let target_id = target.entity_id();
+
+        if let Some(player_echo) = self.players.player_echoes.get_mut(&target_name) {
+            echo!(
+                player_echo.echo_buffer,
+                "{}{} tells you, '{}'`^\r\n",
+                TELL_COLOR,
+                short_description,
+                message
+            );
+        }
+        echo!(
+            self.players.info(&myself),
+            "{}You tell {}, '{}'`^\r\n",
+            TELL_COLOR,
+            target_short_description,
+            message
+        );
+
+        let mut target = self.entity_world.entity_info_mut(target_id);
+        if let Some(player) = &mut target.components().player {
+            player.channels.reply_to = speaker;
+        }
+    }
+
+    pub fn do_reply(&mut self, message: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let reply_to = myself
+            .channel_settings()
+            .and_then(|settings| settings.reply_to.clone());
+
+        match reply_to {
+            Some(reply_to) => self.do_tell(&reply_to, message),
+            None => echo!(self.info(), "Nobody has told you anything yet.\r\n"),
+        }
+    }
+
+    pub fn do_ignore(&mut self, target: Option<&str>) {
+        let target = match target {
+            Some(target) => target,
+            None => {
+                let myself = self.entity_world.entity_info(self.entity_id);
+                let ignoring = myself
+                    .channel_settings()
+                    .map(|settings| settings.ignoring.join(", "))
+                    .unwrap_or_default();
+
+                if ignoring.is_empty() {
+                    echo!(self.info(), "You are not ignoring anyone.\r\n");
+                } else {
+                    echo!(self.info(), "You are ignoring: {}.\r\n", ignoring);
+                }
+                return;
+            }
+        };
+
+        if !target.chars().all(|c| c.is_ascii_alphabetic()) || target.is_empty() {
+            echo!(self.info(), "That's not a player's name.\r\n");
+            return;
+        }
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        if myself
+            .player_name()
+            .is_some_and(|name| name.eq_ignore_ascii_case(target))
+        {
+            echo!(self.info(), "You can't ignore yourself.\r\n");
+            return;
+        }
+
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        let settings = match &mut myself.components().player {
+            Some(player) => &mut player.channels,
+            None => return,
+        };
+
+        let ignoring = settings.is_ignoring(Some(target));
+        if ignoring {
+            settings
+                .ignoring
+                .retain(|ignored| !ignored.eq_ignore_ascii_case(target));
+        } else {
+            settings.ignoring.push(target.to_string());
+        }
+
+        if ignoring {
+            echo!(self.info(), "You stop ignoring {}.\r\n", target);
+        } else {
+            echo!(self.info(), "You now ignore {}.\r\n", target);
+        }
+    }
+}
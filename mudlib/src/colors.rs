@@ -1,9 +1,13 @@
 //! Turn codes like "`w" into "\e[37m".
 //!
 //! This allows writing strings like "Hello `Rworld`^", where "world" will be
-//! colored bright red.
+//! colored bright red. A literal backtick can be written as "``".
 
-use std::{borrow::Cow, collections::HashMap};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::{Display, Formatter, Result},
+};
 
 use lazy_static::lazy_static;
 
@@ -27,6 +31,7 @@ static COLOR_CODES: &[(char, &str)] = &[
     ('^', "\x1b[0m"),
     ('1', "\r\n"),
     ('N', "Demi MUD"),
+    ('`', "`"),
 ];
 
 lazy_static! {
@@ -80,3 +85,43 @@ pub fn recolor<'a>(color: &str, text: &'a str) -> Cow<'a, str> {
         Cow::Borrowed(text)
     }
 }
+
+/// Remove the color codes from text, keeping literal backticks as "``", so
+/// that what players type can't color or break up the lines it ends up in.
+pub(crate) fn strip_colors(text: &str) -> Cow<'_, str> {
+    if !text.contains('`') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut stripped = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '`' => {
+                if let Some('`') = chars.next() {
+                    stripped.push_str("``");
+                }
+            }
+            c => stripped.push(c),
+        }
+    }
+
+    Cow::Owned(stripped)
+}
+
+/// Display text as-is, without interpreting its color codes.
+pub(crate) struct EscapeColors<'t>(pub &'t str);
+
+impl Display for EscapeColors<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        for (index, part) in self.0.split('`').enumerate() {
+            if index != 0 {
+                "``".fmt(f)?;
+            }
+            part.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,23 +1,42 @@
 use crate::{
     acting::EscapeVariables,
     agent::EntityAgent,
-    colors::recolor,
-    components::{Door, Mobile, Object},
+    channels::find_channel,
+    colors::{recolor, EscapeColors},
+    components::{Coins, Position},
     echo,
-    entity::Found,
+    entity::{EntityId, EntityInfo, Found},
+    file_parser::Severity,
     files::fix_newlines,
-    find_entities::EntityIterator,
+    find_entities::{join_target_words, EntityIterator, Target},
+    items::describe_item,
     mapper::make_map,
     mobprogs::Action,
     state::WorldState,
-    world::{common_direction, long_direction, opposite_direction, Shop},
+    world::{common_direction, long_direction, opposite_direction},
 };
 
 pub(crate) fn process_agent_command(agent: &mut EntityAgent, words: &[&str]) -> bool {
+    if let Some(words) = join_target_words(words) {
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        return process_agent_command(agent, &words);
+    }
+
     match *words {
         ["panic"] => {
             panic!("Oh no! I panicked!");
         }
+        ["mq", ticks, ref command @ ..] => {
+            agent.do_queue(ticks, command.join(" "));
+        }
+        [mq, ref command @ ..] if mq.starts_with("mq") && mq[2..].parse::<u32>().is_ok() => {
+            agent.do_queue(&mq[2..], command.join(" "));
+        }
+        ["mob", ref command @ ..] => {
+            agent.do_mob(command);
+        }
+        ref command if agent.is_immortal() && process_immortal_command(agent, command) => (),
+        [command, ..] if !agent.check_position(command) => (),
         ["help"] => {
             agent.do_help(None);
         }
@@ -30,29 +49,86 @@ pub(crate) fn process_agent_command(agent: &mut EntityAgent, words: &[&str]) ->
         ["areas"] => {
             agent.do_areas();
         }
+        ["who"] => {
+            agent.do_who();
+        }
+        ["whois", name] => {
+            agent.do_whois(name);
+        }
+        ["finger", name] => {
+            agent.do_finger(name);
+        }
+        ["whois", ..] | ["finger", ..] => {
+            echo!(agent.info(), "Syntax: `Wwhois <player>`^\r\n");
+        }
         ["buy", item] => {
             agent.do_buy(item);
         }
+        ["deposit", "all"] => {
+            agent.do_deposit(None);
+        }
+        ["deposit", amount, denomination] => {
+            agent.do_deposit(Some((amount, denomination)));
+        }
+        ["withdraw", amount, denomination] => {
+            agent.do_withdraw(amount, denomination);
+        }
+        ["deposit", ..] | ["withdraw", ..] => {
+            echo!(
+                agent.info(),
+                "Syntax: `Wdeposit <amount> <gold|silver|copper>`^, `Wdeposit all`^ or `Wwithdraw <amount> <gold|silver|copper>`^\r\n"
+            );
+        }
+        ["balance"] => {
+            agent.do_balance();
+        }
         ["sell", item] => {
             agent.do_sell(item);
         }
+        ["value", item] => {
+            agent.do_value(item);
+        }
+        ["appraise", item] => {
+            agent.do_appraise(item);
+        }
         ["eat", item] => {
             agent.do_eat(item, false);
         }
         ["eat", item, "forcefully"] => {
             agent.do_eat(item, true);
         }
-        ["mq", ticks, ref command @ ..] => {
-            agent.do_queue(ticks, command.join(" "));
+        ["map"] => {
+            agent.do_map();
         }
-        [mq, ref command @ ..] if mq.starts_with("mq") && mq[2..].parse::<u32>().is_ok() => {
-            agent.do_queue(&mq[2..], command.join(" "));
+        ["drink"] => {
+            agent.do_drink(None);
         }
-        ["mob", ref command @ ..] => {
-            agent.do_mob(command);
+        ["drink", item] | ["drink", "from", item] => {
+            agent.do_drink(Some(item));
         }
-        ["map"] => {
-            agent.do_map();
+        ["fill", item] => {
+            agent.do_fill(item, None);
+        }
+        ["fill", item, fountain] | ["fill", item, "from", fountain] => {
+            agent.do_fill(item, Some(fountain));
+        }
+        ["pour", item, target] | ["pour", item, "into", target] => {
+            agent.do_pour(item, target);
+        }
+        ["pour", ..] => {
+            echo!(
+                agent.info(),
+                "Syntax: '`Wpour <container> out`^' or '`Wpour <container> <container>`^'\r\n"
+            );
+        }
+        ["light", item] => {
+            agent.do_light(item);
+        }
+        ["extinguish", item] => {
+            agent.do_extinguish(item);
+        }
+        ["enter", target] => {
+            agent.do_enter(target);
         }
         ["look"] | ["l"] | ["examine"] => {
             agent.do_look();
@@ -78,7 +154,7 @@ pub(crate) fn process_agent_command(agent: &mut EntityAgent, words: &[&str]) ->
         [target, ref message @ ..] if target.starts_with("'>") => {
             agent.do_say_to(&target[2..], &message.join(" "));
         }
-        [ref message @ ..] if !message.is_empty() && message[0].starts_with('\'') => {
+        ref message if !message.is_empty() && message[0].starts_with('\'') => {
             agent.do_say(&message.join(" ")[1..]);
         }
         ["rsay", ref message @ ..] if false => {
@@ -91,6 +167,39 @@ pub(crate) fn process_agent_command(agent: &mut EntityAgent, words: &[&str]) ->
         ["rsay", ref message @ ..] => {
             agent.do_say(&message.join(" "));
         }
+        [channel] if find_channel(channel).is_some() => {
+            agent.do_channel_toggle(find_channel(channel).unwrap());
+        }
+        [channel, ref message @ ..] if find_channel(channel).is_some() => {
+            agent.do_channel(find_channel(channel).unwrap(), &message.join(" "));
+        }
+        ["channels"] => {
+            agent.do_channels();
+        }
+        ["history", channel] => {
+            agent.do_history(channel);
+        }
+        ["history", ..] => {
+            echo!(agent.info(), "Syntax: `Whistory <channel>`^\r\n");
+        }
+        ["tell", target, ref message @ ..] if !message.is_empty() => {
+            agent.do_tell(target, &message.join(" "));
+        }
+        ["tell", ..] => {
+            echo!(agent.info(), "Syntax: `Wtell <player> <message>`^\r\n");
+        }
+        ["reply", ref message @ ..] if !message.is_empty() => {
+            agent.do_reply(&message.join(" "));
+        }
+        ["reply"] => {
+            echo!(agent.info(), "Syntax: `Wreply <message>`^\r\n");
+        }
+        ["ignore"] => {
+            agent.do_ignore(None);
+        }
+        ["ignore", target] => {
+            agent.do_ignore(Some(target));
+        }
         ["recall"] => {
             agent.do_recall(None);
         }
@@ -100,11 +209,41 @@ pub(crate) fn process_agent_command(agent: &mut EntityAgent, words: &[&str]) ->
         ["exits"] => {
             agent.do_exits();
         }
-        ["get", "all"] => {
-            agent.do_get_all(false);
+        ["hide"] => {
+            agent.do_hide();
+        }
+        ["sneak"] => {
+            agent.do_sneak();
+        }
+        ["visible"] => {
+            agent.do_visible();
+        }
+        ["sleep"] => {
+            agent.do_sleep();
         }
-        ["drop", "all"] => {
-            agent.do_drop_all(false);
+        ["rest"] => {
+            agent.do_rest();
+        }
+        ["sit"] => {
+            agent.do_sit();
+        }
+        ["sleep", target] | ["sleep", "at" | "on" | "in", target] => {
+            agent.do_use_furniture(Position::Sleeping, target);
+        }
+        ["rest", target] | ["rest", "at" | "on" | "in", target] => {
+            agent.do_use_furniture(Position::Resting, target);
+        }
+        ["sit", target] | ["sit", "at" | "on" | "in", target] => {
+            agent.do_use_furniture(Position::Sitting, target);
+        }
+        ["stand"] => {
+            agent.do_stand();
+        }
+        ["wake"] => {
+            agent.do_wake(None);
+        }
+        ["wake", target] => {
+            agent.do_wake(Some(target));
         }
         ["get"] => {
             agent.do_get(None, false);
@@ -145,9 +284,17 @@ pub(crate) fn process_agent_command(agent: &mut EntityAgent, words: &[&str]) ->
         ["drop", item] => {
             agent.do_drop(Some(item), false);
         }
+        ["drop", amount, denomination] if Coins::parse(amount, denomination).is_some() => {
+            agent.do_drop_coins(amount, denomination);
+        }
         ["drop", item, "forcefully"] => {
             agent.do_drop(Some(item), true);
         }
+        ["give", amount, denomination, target] | ["give", amount, denomination, "to", target]
+            if Coins::parse(amount, denomination).is_some() =>
+        {
+            agent.do_give_coins(amount, denomination, target);
+        }
         ["give", item, target] | ["give", item, "to", target] => {
             agent.do_give(item, target, false);
         }
@@ -157,6 +304,15 @@ pub(crate) fn process_agent_command(agent: &mut EntityAgent, words: &[&str]) ->
         ["give", ..] => {
             echo!(agent.info(), "Syntax: `Wgive <item> [to] <target>`^\r\n");
         }
+        ["wear", item] | ["wield", item] | ["hold", item] => {
+            agent.do_wear(item);
+        }
+        ["remove", item] => {
+            agent.do_remove(item);
+        }
+        ["use", item] => {
+            agent.do_use(item);
+        }
         ["open", target] => {
             agent.do_open(target);
         }
@@ -169,9 +325,48 @@ pub(crate) fn process_agent_command(agent: &mut EntityAgent, words: &[&str]) ->
         ["lock", target] => {
             agent.do_lock(target);
         }
+        ["pick", target] => {
+            agent.do_pick(target);
+        }
+        ["bash", target] => {
+            agent.do_bash(target);
+        }
         ["i"] | ["inv"] | ["inventory"] => {
             agent.do_inventory();
         }
+        ["save"] => {
+            agent.do_save();
+        }
+        ["title"] | ["short"] | ["pronouns"] => {
+            agent.do_appearance();
+        }
+        ["pronouns", pronouns] => {
+            agent.do_pronouns(pronouns);
+        }
+        ["pronouns", ..] => {
+            echo!(agent.info(), "Syntax: `Wpronouns <pronouns>`^\r\n");
+        }
+        ["title", ref title @ ..] => {
+            agent.do_title(&title.join(" "));
+        }
+        ["short", ref short_description @ ..] => {
+            agent.do_short(&short_description.join(" "));
+        }
+        ["description"] => {
+            agent.do_description(None);
+        }
+        ["description", ref description @ ..] => {
+            agent.do_description(Some(&description.join(" ")));
+        }
+        ["quest"] | ["quest", "list"] | ["quests"] => {
+            agent.do_quest_list();
+        }
+        ["quest", "info", name] => {
+            agent.do_quest_info(name);
+        }
+        ["quest", ..] => {
+            echo!(agent.info(), "Syntax: `Wquest [list|info <quest>]`^\r\n");
+        }
         ["list"] | ["wares"] => {
             agent.do_list();
         }
@@ -181,10 +376,28 @@ pub(crate) fn process_agent_command(agent: &mut EntityAgent, words: &[&str]) ->
         ["unfollow"] => {
             agent.do_unfollow();
         }
+        ["group"] => {
+            agent.do_group(None);
+        }
+        ["group", target] => {
+            agent.do_group(Some(target));
+        }
+        ["gtell", ref message @ ..] if !message.is_empty() => {
+            agent.do_gtell(&message.join(" "));
+        }
+        ["gtell", ..] => {
+            echo!(agent.info(), "Syntax: `Wgtell <message>`^\r\n");
+        }
+        ["split", amount] => {
+            agent.do_split(amount, "silver");
+        }
+        ["split", amount, denomination] => {
+            agent.do_split(amount, denomination);
+        }
         ["emote", ref message @ ..] => {
             agent.do_emote(&message.join(" "));
         }
-        [ref message @ ..] if !message.is_empty() && message[0].starts_with(',') => {
+        ref message if !message.is_empty() && message[0].starts_with(',') => {
             agent.do_emote(&message.join(" ")[1..]);
         }
         ["pmote", target, ref message @ ..] => {
@@ -209,6 +422,57 @@ pub(crate) fn process_agent_command(agent: &mut EntityAgent, words: &[&str]) ->
     true
 }
 
+/// Commands only available to immortals; returns false if none matched.
+fn process_immortal_command(agent: &mut EntityAgent, words: &[&str]) -> bool {
+    match *words {
+        ["mptrace"] => {
+            agent.do_mptrace(None);
+        }
+        ["mptrace", target] => {
+            agent.do_mptrace(Some(target));
+        }
+        ["mpstat", target] => {
+            agent.do_mpstat(target);
+        }
+        ["mpdump", p_vnum] => {
+            agent.do_mpdump(p_vnum);
+        }
+        ["scripts", ref words @ ..] => {
+            agent.do_scripts(words);
+        }
+        ["invis"] => {
+            agent.do_invis();
+        }
+        ["holylight"] => {
+            agent.do_holylight();
+        }
+        ["users"] | ["sockets"] => {
+            agent.do_users();
+        }
+        ["areas", "report"] => {
+            agent.do_areas_report();
+        }
+        ["redit", ref words @ ..] => {
+            agent.do_redit(words);
+        }
+        ["medit", ref words @ ..] => {
+            agent.do_medit(words);
+        }
+        ["oedit", ref words @ ..] => {
+            agent.do_oedit(words);
+        }
+        ["resetedit", ref words @ ..] => {
+            agent.do_resetedit(words);
+        }
+        ["asave", ref words @ ..] => {
+            agent.do_asave(words);
+        }
+        _ => return false,
+    };
+
+    true
+}
+
 pub(crate) fn process_player_command(world_state: &mut WorldState, player: &str, words: &[&str]) {
     let world = &mut world_state.entity_world;
     let player_id = world.player_entity_id(player);
@@ -225,15 +489,28 @@ pub(crate) fn process_player_command(world_state: &mut WorldState, player: &str,
         }
     };
 
+    if let Some(player_echo) = world_state.players.player_echoes.get_mut(player) {
+        player_echo.last_command_tick = world_state.entity_world.current_tick();
+    }
+
     let mut agent = EntityAgent {
         entity_world: &mut world_state.entity_world,
         socials: &world_state.socials,
-        vnum_templates: &world_state.vnum_templates,
+        world: &mut world_state.world,
+        vnum_templates: &mut world_state.vnum_templates,
         areas: &world_state.areas,
+        diagnostics: &world_state.diagnostics,
+        quests: &world_state.quests,
+        scripts: &mut world_state.scripts,
+        files: &*world_state.files,
         players: &mut world_state.players,
         entity_id: player_id,
     };
 
+    if agent.answer_pronouns(words) {
+        return;
+    }
+
     process_agent_command(&mut agent, words);
 }
 
@@ -253,6 +530,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             Some("cli") => include_str!("../help_cli.txt"),
             Some("demimud") => include_str!("../help_demimud.txt"),
             Some("credits") => include_str!("../help_credits.txt"),
+            Some("immortal") => include_str!("../help_immortal.txt"),
             None => include_str!("../help.txt"),
             _ => "Unknown help file. See '`Whelp`^' without an argument.\r\n",
         };
@@ -268,9 +546,11 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             echo!(act.others(), "$n is `RDEAD`^.\r\n");
 
             let holder = myself.room();
-            let mut act = self.players.act_with(&holder, &myself);
-            echo!(act.myself(), "$^$N is `RDEAD`^.\r\n");
-            echo!(act.others(), "$^$N is `RDEAD`^.\r\n");
+            if !holder.is_room() {
+                let mut act = self.players.act_with(&holder, &myself);
+                echo!(act.myself(), "$^$N is `RDEAD`^.\r\n");
+                echo!(act.others(), "$^$N is `RDEAD`^.\r\n");
+            }
         }
 
         let limbo = self
@@ -303,136 +583,45 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         }
     }
 
-    pub fn do_buy(&mut self, item_name: &str) {
+    /// Problems found in the data files when the world was loaded.
+    pub fn do_areas_report(&mut self) {
         let myself = self.entity_world.entity_info(self.entity_id);
+        let mut info = self.players.info(&myself);
 
-        let found = myself
-            .room()
-            .contained_entities()
-            .with_component::<Shop>()
-            .filter_or(
-                |e| *e != myself,
-                "The only shopkeeper here is yourself.\r\n",
-            )
-            .find_one_with_component_or("You don't see any shopkeepers here.");
-
-        let (entity, shop_info) = match found {
-            Ok(found) => found,
-            Err(error) => return self.echo_error(error),
-        };
-
-        let shopkeeper_id = entity.entity_id();
-
-        let found = entity
-            .objects()
-            .filter_by_keyword(item_name)
-            .with_component::<Object>()
-            .find_one_with_component_or("You don't see anything named like that to buy.");
-
-        let (item, object) = match found {
-            Ok(item) => item,
-            Err(error) => return self.echo_error(error),
-        };
-
-        let item_id = item.entity_id();
-        let cost = (object.cost * shop_info.profit_buy as i32 / 100) as usize;
+        if self.diagnostics.is_empty() {
+            echo!(info, "All data files loaded without problems.\r\n");
+            return;
+        }
 
-        if self.remove_silver(cost, self.entity_id) {
-            // Clone it so that the shopkeeper can keep selling it
-            let item = self.entity_world.entity_info(item_id);
-            let components = item.components().clone();
-            self.entity_world.insert_entity(self.entity_id, components);
+        for diagnostic in self.diagnostics {
+            let severity = match diagnostic.severity {
+                Severity::Warning => "`YWarning:`^",
+                Severity::Error => "`RError:`^",
+            };
 
-            let myself = self.entity_world.entity_info(self.entity_id);
-            let shopkeeper = self.entity_world.entity_info(shopkeeper_id);
-            let item = self.entity_world.entity_info(item_id);
-            let mut act = self.players.act_with(&myself, &shopkeeper);
-            echo!(
-                act.myself(),
-                "You buy {} from $N for {} silver.\r\n",
-                item,
-                cost
-            );
             echo!(
-                act.target(),
-                "$^$n buys {} from you for {} silver.\r\n",
-                item,
-                cost
-            );
-            echo!(act.others(), "$^$n buys {} from $N.\r\n", item);
-        } else {
-            echo!(
-                self.info(),
-                "You don't have the {} silver to pay for it!\r\n",
-                cost
+                info,
+                "{} `W{}`^ {}\r\n",
+                severity,
+                EscapeColors(&diagnostic.location()),
+                EscapeColors(&diagnostic.message)
             );
+            if !diagnostic.context.trim().is_empty() {
+                echo!(
+                    info,
+                    "    `D{}`^\r\n",
+                    EscapeColors(diagnostic.context.trim_end())
+                );
+            }
         }
     }
 
-    pub fn do_sell(&mut self, item_name: &str) {
-        let myself = self.entity_world.entity_info(self.entity_id);
-
-        let found = myself
-            .room()
-            .contained_entities()
-            .with_component::<Shop>()
-            .filter_or(
-                |e| *e != myself,
-                "The only shopkeeper here is yourself.\r\n",
-            )
-            .find_one_with_component_or("You don't see any shopkeepers here.");
-
-        let (entity, shop_info) = match found {
-            Ok(found) => found,
-            Err(error) => return self.echo_error(error),
-        };
-
-        let shopkeeper_id = entity.entity_id();
-
-        let found = myself
-            .objects()
-            .filter_by_keyword(item_name)
-            .with_component_or::<Object>("$^$N is not an object you can sell.")
-            .find_one_with_component_or("You don't own anything named like that to sell.");
-
-        let (item, object) = match found {
-            Ok(found) => found,
-            Err(error) => return self.echo_error(error),
-        };
-
-        // FIXME: fix integer types
-        let cost = (object.cost * shop_info.profit_sell as i32 / 100) as usize;
-        let item_id = item.entity_id();
-
-        self.add_silver(cost, self.entity_id);
-        let mut item_agent = self.switch_agent(item_id);
-        item_agent.do_die();
-
-        let myself = self.entity_world.entity_info(self.entity_id);
-        let shopkeeper = self.entity_world.entity_info(shopkeeper_id);
-        let item = self.entity_world.entity_info(item_id);
-        let mut act = self.players.act_with(&myself, &shopkeeper);
-        echo!(
-            act.myself(),
-            "You sell {} to $N for {} silver.\r\n",
-            item,
-            cost
-        );
-        echo!(
-            act.target(),
-            "$^$n sells {} to you for {} silver.\r\n",
-            item,
-            cost
-        );
-        echo!(act.others(), "$^$n sells {} to $N.\r\n", item);
-    }
-
     pub fn do_eat(&mut self, item_name: &str, forcefully: bool) {
         let myself = self.entity_world.entity_info(self.entity_id);
 
         let found = myself
             .contained_entities()
-            .filter_by_keyword(item_name)
+            .filter_by_keyword(&myself, item_name)
             .filter_or(
                 |food| {
                     forcefully
@@ -472,9 +661,15 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         }
 
         let food_id = food.entity_id();
+        let killed = food.is_mobile() || food.is_player();
 
         let mut agent = self.switch_agent(food_id);
         agent.do_die();
+
+        if killed {
+            self.check_triggers_self(Action::Kill { victim_id: food_id });
+            self.share_kill(food_id);
+        }
     }
 
     pub fn do_queue(&mut self, ticks: &str, command: String) {
@@ -496,10 +691,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
     }
 
     pub fn do_map(&mut self) {
-        let map = make_map(
-            &self.entity_world,
-            self.entity_world.room_of(self.entity_id),
-        );
+        let map = make_map(self.entity_world, self.entity_world.room_of(self.entity_id));
 
         echo!(self.info(), "{}", map);
     }
@@ -508,6 +700,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         let myself = self.entity_world.entity_info(self.entity_id);
         let room_id = self.entity_world.room_of(self.entity_id);
         let room = self.entity_world.entity_info(room_id);
+        let sight = myself.sight();
 
         let mut info = self.players.info(&myself);
 
@@ -515,10 +708,14 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         echo!(info, "`y{}`^\r\n", room.component_info().internal_title());
 
         // Description
-        let description = room.component_info().internal_description();
-        echo!(info, "{}", description);
-        if !description.ends_with('\r') && !description.ends_with('\n') {
-            echo!(info, "\r\n");
+        if sight.in_darkness() {
+            echo!(info, "It is pitch black...\r\n");
+        } else {
+            let description = room.component_info().internal_description();
+            echo!(info, "{}", description);
+            if !description.ends_with('\r') && !description.ends_with('\n') {
+                echo!(info, "\r\n");
+            }
         }
 
         // Exits
@@ -551,7 +748,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         }
 
         // Objects
-        for object in room.objects() {
+        for object in room.objects().filter(|object| sight.can_see(object)) {
             let container_state = match &object.components().door {
                 Some(door) if door.locked => " (locked)",
                 Some(door) if door.closed => " (closed)",
@@ -568,25 +765,27 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         }
 
         // Mobiles
-        for mobile in room.mobiles() {
-            echo!(
-                info,
-                "`m{}`^\r\n",
-                mobile.component_info().lateral_description()
-            );
+        for mobile in room.mobiles().filter(|mobile| sight.can_see(mobile)) {
+            let position = mobile.position_description();
+            let description = position
+                .as_deref()
+                .unwrap_or_else(|| mobile.component_info().lateral_description());
+
+            echo!(info, "`m{}`^\r\n", description);
         }
 
         // Players
         for player in room.players() {
-            if player.entity_id() == self.entity_id {
+            if player.entity_id() == self.entity_id || !sight.can_see(&player) {
                 continue;
             }
 
-            echo!(
-                info,
-                "`M{}`^\r\n",
-                player.component_info().lateral_description()
-            );
+            let position = player.position_description();
+            let description = position
+                .as_deref()
+                .unwrap_or_else(|| player.component_info().lateral_description());
+
+            echo!(info, "`M{}`^\r\n", description);
         }
     }
 
@@ -595,7 +794,6 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
 
         let found = myself
             .visible_entities(target)
-            .filter_or(|e| *e != myself, "It's you! But you're sadly just a player and players don't have descriptions yet.\r\n")
             .find_one_or("You don't see anything named like that here.\r\n");
 
         let target = match found {
@@ -616,11 +814,17 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
 
         let mut act = self.players.act_with(&myself, &target);
 
-        if !target.is_extra_description() {
+        if !target.is_extra_description() && target != myself {
             echo!(act.target(), "$^$n looks at you.\r\n");
             echo!(act.others(), "$^$n looks at $N.\r\n");
         }
 
+        if let Some(object) = &target.components().object {
+            for line in describe_item(object) {
+                echo!(act.myself(), "{}\r\n", line);
+            }
+        }
+
         if let Some(door) = &target.components().door {
             if door.closed {
                 echo!(act.myself(), "$^$E is closed.\r\n");
@@ -631,12 +835,16 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         }
 
         // Contents
+        let sight = myself.sight();
         let mut first = true;
         let mut column = 0;
-        for item in target.contained_entities() {
+        for item in target
+            .contained_entities()
+            .filter(|item| sight.can_see(item))
+        {
             if item.equipped().is_none() {
                 if first {
-                    echo!(act.myself(), "$^$E is holding:\r\n    ");
+                    echo!(act.myself(), "$^$E $Vis holding:\r\n    ");
                     first = false;
                     column = 4;
                 } else {
@@ -661,10 +869,13 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
 
         // Equipment
         let mut first = true;
-        for item in target.contained_entities() {
+        for item in target
+            .contained_entities()
+            .filter(|item| sight.can_see(item))
+        {
             if let Some(location) = item.equipped() {
                 if first {
-                    echo!(act.myself(), "$^$E is wearing:\r\n");
+                    echo!(act.myself(), "$^$E $Vis wearing:\r\n");
                     first = false;
                 }
                 echo!(
@@ -678,6 +889,11 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
     }
 
     pub fn do_force(&mut self, target_name: &str, words: &[&str]) {
+        // Forced mobiles pass `check_trusted()`, so players can't force
+        if !self.check_trusted() {
+            return;
+        }
+
         let myself = self.entity_world.entity_info(self.entity_id);
         let target = myself.find_entity(target_name, |entity| {
             // Prefer objects over their descriptions, and others over self
@@ -691,6 +907,15 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
                 echo!(act.others(), "He snaps his fingers at himself.\r\n");
                 myself
             }
+            // Immortal commands are checked on the one doing them, so only
+            // immortals may make immortals do anything
+            Found::Other(other) | Found::WrongOther(other)
+                if other.is_immortal() && !myself.is_immortal() =>
+            {
+                let mut act = self.players.act_with(&myself, &other);
+                echo!(act.myself(), "$^$N ignores you.\r\n");
+                return;
+            }
             Found::Other(other) | Found::WrongOther(other) => {
                 let mut act = self.players.act_with(&myself, &other);
                 echo!(act.myself(), "You snap your fingers at $N.\r\n");
@@ -768,7 +993,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
                 Some(&message[start + 1..message.len() - 1]),
             )
         } else {
-            (&message[..], None)
+            (message, None)
         };
 
         let message = message.trim();
@@ -821,7 +1046,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
                 if emote_suffix.is_some() { "." } else { "" },
             );
 
-            self.check_triggers_others(Action::Speech { message: &message })
+            self.check_triggers_others(Action::Speech { message })
         } else {
             echo!(self.info(), "You say nothing whatsoever.\r\n");
         }
@@ -841,7 +1066,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
                     act.myself(),
                     "You mutter something to yourself, but nobody hears it.\r\n"
                 );
-                echo!(act.others(), "$^$n mutters something to himself.\r\n");
+                echo!(act.others(), "$^$n mutters something to $mself.\r\n");
                 return;
             }
             Found::Other(other) | Found::WrongOther(other) => other,
@@ -975,19 +1200,9 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
                 return true;
             }
             Found::Other(exit) => exit,
-            Found::WrongSelf => {
-                echo!(
-                    self.info(),
-                    "That's you! And you're not an exit to go in.\r\n"
-                );
-                return true;
-            }
-            Found::WrongOther(target) => {
-                let mut act = self.players.act_with(&myself, &target);
-                echo!(act.myself(), "$^$n is not an exit you go in.\r\n");
-                return true;
-            }
-            Found::Nothing => {
+            // Keywords match by prefix, so "hug" finds a "huge ogre"; leave
+            // those to socials and other commands
+            Found::WrongSelf | Found::WrongOther(_) | Found::Nothing => {
                 if common_direction(direction) {
                     echo!(self.info(), "The way to the {} is blocked.\r\n", direction);
                     return true;
@@ -997,6 +1212,11 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             }
         };
 
+        if myself.position() < Position::Standing {
+            echo!(self.info(), "You need to stand up first.\r\n");
+            return true;
+        }
+
         if let Some(door) = &exit.components().door {
             if door.closed {
                 echo!(
@@ -1026,23 +1246,29 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         };
 
         let exit_id = exit.entity_id();
+        let sneaking = myself.components().visibility.sneaking;
 
         echo!(act.myself(), "You walk {}.\r\n", exit_keyword);
-        echo!(act.others(), "$^$n leaves {}.\r\n", exit_keyword);
+        if !sneaking {
+            echo!(act.others(), "$^$n leaves {}.\r\n", exit_keyword);
+        }
 
         self.check_triggers_others(Action::Exit { direction });
 
         self.entity_world.move_entity(self.entity_id, to_room_id);
+        self.reveal_hidden();
 
         // Reacquire everything, the acting stage is now changed.
         let myself = self.entity_world.entity_info(self.entity_id);
         let exit = self.entity_world.entity_info(exit_id);
         let mut act = self.players.act_alone(&myself);
-        echo!(
-            act.others(),
-            "$^$n arrives from the {}.\r\n",
-            opposite_direction(exit.main_keyword())
-        );
+        if !sneaking {
+            echo!(
+                act.others(),
+                "$^$n arrives from the {}.\r\n",
+                opposite_direction(exit.main_keyword())
+            );
+        }
 
         // Admire new surroundings.
         self.do_look();
@@ -1287,32 +1513,6 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         true
     }
 
-    pub fn do_get_all(&mut self, forcefully: bool) {
-        let myself = self.entity_world.entity_info(self.entity_id);
-        let objects: Vec<_> = myself
-            .room()
-            .objects()
-            .filter(|object| *object != myself)
-            .map(|object| object.main_keyword().to_string())
-            .collect();
-
-        for object in objects {
-            self.do_get(Some(&object), forcefully);
-        }
-    }
-
-    pub fn do_drop_all(&mut self, forcefully: bool) {
-        let myself = self.entity_world.entity_info(self.entity_id);
-        let objects: Vec<_> = myself
-            .objects()
-            .map(|object| object.main_keyword().to_string())
-            .collect();
-
-        for object in objects {
-            self.do_drop(Some(&object), forcefully);
-        }
-    }
-
     pub fn do_get(&mut self, object_name: Option<&str>, forcefully: bool) {
         let myself = self.entity_world.entity_info(self.entity_id);
 
@@ -1325,45 +1525,41 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         };
 
         let room_id = self.entity_world.room_of(self.entity_id);
-        let target = myself.find_entity(object_name, |object| {
+        let is_in_room = |object: &EntityInfo| {
             let object_room_id = self.entity_world.room_of(object.entity_id());
             (object.is_object() || forcefully) && object_room_id == room_id
-        });
+        };
 
-        match target {
-            Found::Myself | Found::WrongSelf => {
+        if Target::parse(object_name).is_multiple() {
+            let objects: Vec<_> = myself
+                .find_entities(object_name, is_in_room)
+                .iter()
+                .map(|object| object.entity_id())
+                .collect();
+
+            if objects.is_empty() {
                 echo!(
                     self.info(),
-                    "You try to get a hold of yourself. You think you succeeded.\r\n"
+                    "You don't see any objects named like that in the room.\r\n"
                 );
             }
-            Found::Other(other) => {
-                let mut act = self.players.act_with(&myself, &other).store_acts();
-                echo!(act.myself(), "You pick up $N.\r\n");
-                echo!(
-                    act.target(),
-                    "$^$n picks you up. You're now in $s inventory!\r\n"
-                );
-                echo!(act.others(), "$^$n gets $N.\r\n");
-                let acts1 = act.into_acts();
-
-                let myself_id = myself.entity_id();
-                let other_id = other.entity_id();
 
-                self.entity_world.move_entity(other_id, myself_id);
+            for object_id in objects {
+                self.get_object(object_id);
+            }
+            return;
+        }
 
-                let other = self.entity_world.entity_info(other_id);
-                let mut act = self.players.act_alone(&other).store_acts();
+        match myself.find_entity(object_name, is_in_room) {
+            Found::Myself | Found::WrongSelf => {
                 echo!(
-                    act.others(),
-                    "$^$n is tossed into here, and lands with a thud.\r\n"
+                    self.info(),
+                    "You try to get a hold of yourself. You think you succeeded.\r\n"
                 );
-                let acts2 = act.into_acts();
-
-                // Check triggers only have everyone saw the message, so that
-                // the events are seen in order.
-                self.check_act_triggers(acts1);
-                self.check_act_triggers(acts2);
+            }
+            Found::Other(other) => {
+                let other_id = other.entity_id();
+                self.get_object(other_id);
             }
             Found::Nothing | Found::WrongOther(_) => {
                 echo!(
@@ -1374,6 +1570,37 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         }
     }
 
+    fn get_object(&mut self, object_id: EntityId) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let other = self.entity_world.entity_info(object_id);
+
+        let mut act = self.players.act_with(&myself, &other).store_acts();
+        echo!(act.myself(), "You pick up $N.\r\n");
+        echo!(
+            act.target(),
+            "$^$n picks you up. You're now in $s inventory!\r\n"
+        );
+        echo!(act.others(), "$^$n gets $N.\r\n");
+        let acts1 = act.into_acts();
+
+        self.entity_world.move_entity(object_id, self.entity_id);
+
+        let other = self.entity_world.entity_info(object_id);
+        let mut act = self.players.act_alone(&other).store_acts();
+        echo!(
+            act.others(),
+            "$^$n is tossed into here, and lands with a thud.\r\n"
+        );
+        let acts2 = act.into_acts();
+
+        // Check triggers only have everyone saw the message, so that
+        // the events are seen in order.
+        self.check_act_triggers(acts1);
+        self.check_act_triggers(acts2);
+        self.check_triggers_target(Action::Get, object_id);
+        self.collect_coins(object_id);
+    }
+
     pub fn do_get_from(&mut self, object: &str, container: &str, forcefully: bool) {
         let myself = self.entity_world.entity_info(self.entity_id);
 
@@ -1416,21 +1643,40 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             }
         }
 
-        let object = container.find_entity(object, |object| {
-            (object.is_object() || forcefully) && object.room() == container
-        });
-        let object = match object {
-            Found::Nothing | Found::Myself | Found::WrongSelf | Found::WrongOther(_) => {
-                let mut act = self.players.act_with(&myself, &container);
-                echo!(
-                    act.myself(),
-                    "$^$N isn't holding anything named like that.\r\n"
-                );
-                return;
+        let is_inside =
+            |object: &EntityInfo| (object.is_object() || forcefully) && object.room() == container;
+
+        let objects = if Target::parse(object).is_multiple() {
+            container.find_entities(object, is_inside)
+        } else {
+            match container.find_entity(object, is_inside) {
+                Found::Other(other) => vec![other],
+                _ => Vec::new(),
             }
-            Found::Other(other) => other,
         };
 
+        if objects.is_empty() {
+            let mut act = self.players.act_with(&myself, &container);
+            echo!(
+                act.myself(),
+                "$^$N isn't holding anything named like that.\r\n"
+            );
+            return;
+        }
+
+        let container_id = container.entity_id();
+        let objects: Vec<_> = objects.iter().map(|object| object.entity_id()).collect();
+
+        for object_id in objects {
+            self.get_object_from(object_id, container_id);
+        }
+    }
+
+    fn get_object_from(&mut self, object_id: EntityId, container_id: EntityId) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let container = self.entity_world.entity_info(container_id);
+        let object = self.entity_world.entity_info(object_id);
+
         // Taker and container perspective
         let mut act = self.players.act_with(&myself, &container).store_acts();
         echo!(act.myself(), "You get {} from $N.\r\n", object);
@@ -1447,10 +1693,10 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             container
         );
 
-        let object_id = object.entity_id();
-
         self.entity_world.move_entity(object_id, self.entity_id);
         self.check_act_triggers(acts);
+        self.check_triggers_target(Action::Get, object_id);
+        self.merge_coins(object_id);
     }
 
     pub fn do_drop(&mut self, object_name: Option<&str>, forcefully: bool) {
@@ -1463,7 +1709,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
 
         let found = myself
             .contained_entities()
-            .filter_by_keyword(object_name)
+            .filter_by_keyword(&myself, object_name)
             .filter_or(|e| e.is_object() || forcefully, "$^$N is not an object.")
             .filter_or(|e| e.room() == myself, "You aren't holding $N.")
             .filter_or(
@@ -1471,13 +1717,22 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
                 "You attempt to let go of yourself, but somehow the rest of you just keeps on\r\n\
                 sticking to your hand.",
             )
-            .find_one_or("You aren't holding anything named like that.");
+            .find_targets_or(object_name, "You aren't holding anything named like that.");
 
-        let object = match found {
-            Ok(object) => object,
+        let objects: Vec<_> = match found {
+            Ok(objects) => objects.iter().map(|object| object.entity_id()).collect(),
             Err(err) => return self.echo_error(err),
         };
 
+        for object_id in objects {
+            self.drop_object(object_id);
+        }
+    }
+
+    pub fn drop_object(&mut self, object_id: EntityId) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let object = self.entity_world.entity_info(object_id);
+
         let mut act = self.players.act_with(&myself, &object).store_acts();
         echo!(act.myself(), "You drop $N.\r\n");
         echo!(act.target(), "$^$n drops you out of $m.\r\n");
@@ -1487,13 +1742,121 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         let mut act = self.players.act_alone(&object);
         echo!(act.others(), "$^$n is tossed out of here.\r\n");
 
-        let object_id = object.entity_id();
         let room_id = self.entity_world.room_of(myself.entity_id());
         self.entity_world.move_entity(object_id, room_id);
 
+        self.check_act_triggers(acts);
+        self.check_triggers_target(Action::Drop, object_id);
+        self.merge_coins(object_id);
+    }
+
+    pub fn do_wear(&mut self, object_name: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let found = myself
+            .contained_entities()
+            .filter_by_keyword(&myself, object_name)
+            .filter_or(|e| e.is_object(), "$^$N is not an object.")
+            .filter_or(|e| e.equipped().is_none(), "You are already wearing $N.")
+            .find_one_or("You aren't holding anything named like that.");
+
+        let object = match found {
+            Ok(object) => object,
+            Err(err) => return self.echo_error(err),
+        };
+
+        let location = object
+            .components()
+            .object
+            .as_ref()
+            .and_then(|object| object.wear_location.clone());
+
+        let location = match location {
+            Some(location) => location,
+            None => {
+                let mut act = self.players.act_with(&myself, &object);
+                echo!(act.myself(), "You can't wear $N.\r\n");
+                return;
+            }
+        };
+
+        let worn = myself
+            .objects()
+            .find(|item| item.equipped() == Some(location.as_str()));
+
+        if let Some(worn) = worn {
+            let mut act = self.players.act_with(&myself, &worn);
+            echo!(act.myself(), "You are already wearing $N there.\r\n");
+            return;
+        }
+
+        let mut act = self.players.act_with(&myself, &object).store_acts();
+        echo!(act.myself(), "You wear $N.\r\n");
+        echo!(act.target(), "$^$n wears you.\r\n");
+        echo!(act.others(), "$^$n wears $N.\r\n");
+        let acts = act.into_acts();
+
+        let object_id = object.entity_id();
+        let mut object = self.entity_world.entity_info_mut(object_id);
+        object.components().general.equipped = Some(location);
+
+        self.check_act_triggers(acts);
+        self.check_triggers_target(Action::Wear, object_id);
+    }
+
+    pub fn do_remove(&mut self, object_name: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let found = myself
+            .contained_entities()
+            .filter_by_keyword(&myself, object_name)
+            .filter_or(|e| e.equipped().is_some(), "You aren't wearing $N.")
+            .find_one_or("You aren't wearing anything named like that.");
+
+        let object = match found {
+            Ok(object) => object,
+            Err(err) => return self.echo_error(err),
+        };
+
+        let mut act = self.players.act_with(&myself, &object).store_acts();
+        echo!(act.myself(), "You stop using $N.\r\n");
+        echo!(act.target(), "$^$n stops using you.\r\n");
+        echo!(act.others(), "$^$n stops using $N.\r\n");
+        let acts = act.into_acts();
+
+        let object_id = object.entity_id();
+        let mut object = self.entity_world.entity_info_mut(object_id);
+        object.components().general.equipped = None;
+
         self.check_act_triggers(acts);
     }
 
+    pub fn do_use(&mut self, object_name: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let found = myself
+            .contained_entities()
+            .filter_by_keyword(&myself, object_name)
+            .filter_or(|e| e.is_object(), "$^$N is not an object.")
+            .find_one_or("You aren't holding anything named like that.");
+
+        let object = match found {
+            Ok(object) => object,
+            Err(err) => return self.echo_error(err),
+        };
+
+        let mut act = self.players.act_with(&myself, &object).store_acts();
+        echo!(act.myself(), "You use $N.\r\n");
+        echo!(act.target(), "$^$n uses you.\r\n");
+        echo!(act.others(), "$^$n uses $N.\r\n");
+        let acts = act.into_acts();
+
+        let object_id = object.entity_id();
+
+        self.check_act_triggers(acts);
+        self.check_triggers_target(Action::Use, object_id);
+    }
+
     pub fn do_give(&mut self, object: &str, target: &str, forcefully: bool) {
         let myself = self.entity_world.entity_info(self.entity_id);
 
@@ -1509,13 +1872,27 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
                 |e| *e != myself,
                 "But once you do, what will your consciousness be attached to",
             )
-            .find_one_or("You aren't holding anything named like that.");
+            .find_targets_or(object, "You aren't holding anything named like that.");
 
-        let object = match found {
-            Ok(object) => object,
+        let objects: Vec<_> = match found {
+            Ok(objects) => objects.iter().map(|object| object.entity_id()).collect(),
             Err(error) => return self.echo_error(error),
         };
 
+        let target_id = match self.find_receiver(target, forcefully) {
+            Some(target_id) => target_id,
+            None => return,
+        };
+
+        for object_id in objects {
+            self.give_object(object_id, target_id);
+        }
+    }
+
+    /// Find someone to give things to.
+    pub fn find_receiver(&mut self, target: &str, forcefully: bool) -> Option<EntityId> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
         let found = myself
             .visible_entities(target)
             .filter_or(
@@ -1533,10 +1910,19 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             )
             .find_one_or("You don't see anyone here named like that.");
 
-        let target = match found {
-            Ok(target) => target,
-            Err(error) => return self.echo_error(error),
-        };
+        match found {
+            Ok(target) => Some(target.entity_id()),
+            Err(error) => {
+                self.echo_error(error);
+                None
+            }
+        }
+    }
+
+    pub fn give_object(&mut self, object_id: EntityId, target_id: EntityId) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let object = self.entity_world.entity_info(object_id);
+        let target = self.entity_world.entity_info(target_id);
 
         // Giver and receiver perspectives
         let mut act = self.players.act_with(&myself, &target).store_acts();
@@ -1554,49 +1940,65 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             target
         );
 
-        let object_id = object.entity_id();
-        let target_id = target.entity_id();
+        let coins = object.components().coins;
 
         self.entity_world.move_entity(object_id, target_id);
         self.check_act_triggers(acts);
-        self.check_triggers_target(Action::Give { object_id }, target_id);
+        match coins {
+            Some(coins) => {
+                let value = coins.value().unwrap_or(usize::MAX);
+                self.check_triggers_target(Action::Bribe { value }, target_id);
+                self.merge_coins(object_id);
+            }
+            None => {
+                self.check_triggers_target(Action::Give { object_id }, target_id);
+                self.check_triggers_target(Action::Give { object_id }, object_id);
+            }
+        }
     }
 
     pub fn do_put_into(&mut self, object: &str, container: &str, forcefully: bool) {
         let myself = self.entity_world.entity_info(self.entity_id);
 
-        let object = myself.find_entity(object, |e| {
-            (forcefully || e.is_object()) && e.room() == myself
-        });
+        let is_held = |e: &EntityInfo| (forcefully || e.is_object()) && e.room() == myself;
 
-        let object = match object {
-            Found::Myself | Found::WrongSelf => {
-                echo!(
-                    self.info(),
-                    "But once you do, what will your consciousness be attached to?\r\n"
-                );
-                return;
-            }
-            Found::Other(other) => other,
-            Found::WrongOther(other) => {
-                if other.room() != myself {
-                    let mut act = self.players.act_with(&myself, &other);
-                    echo!(act.myself(), "But you aren't holding $N!\r\n");
-                    return;
-                } else if other.is_extra_description() {
-                    // These don't have a good short description to refer to.
+        let objects = if Target::parse(object).is_multiple() {
+            myself.find_entities(object, is_held)
+        } else {
+            match myself.find_entity(object, is_held) {
+                Found::Myself | Found::WrongSelf => {
                     echo!(
                         self.info(),
-                        "That's just a descriptive detail, you can't put that away.\r\n"
+                        "But once you do, what will your consciousness be attached to?\r\n"
                     );
                     return;
-                } else {
-                    let mut act = self.players.act_with(&myself, &other);
-                    echo!(act.myself(), "But you aren't holding $N!\r\n");
-                    return;
                 }
+                Found::Other(other) => vec![other],
+                Found::WrongOther(other) => {
+                    if other.room() != myself {
+                        let mut act = self.players.act_with(&myself, &other);
+                        echo!(act.myself(), "But you aren't holding $N!\r\n");
+                        return;
+                    } else if other.is_extra_description() {
+                        // These don't have a good short description to refer to.
+                        echo!(
+                            self.info(),
+                            "That's just a descriptive detail, you can't put that away.\r\n"
+                        );
+                        return;
+                    } else {
+                        let mut act = self.players.act_with(&myself, &other);
+                        echo!(act.myself(), "But you aren't holding $N!\r\n");
+                        return;
+                    }
+                }
+                Found::Nothing => Vec::new(),
             }
-            Found::Nothing => {
+        };
+
+        let object = match objects.first() {
+            Some(object) => object,
+            None => {
                 echo!(
                     self.info(),
                     "You aren't holding anything named like that.\r\n"
@@ -1667,6 +2069,29 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             }
         }
 
+        let container_id = container.entity_id();
+        let objects: Vec<_> = objects
+            .iter()
+            .filter(|object| **object != container)
+            .map(|object| object.entity_id())
+            .collect();
+
+        if objects.is_empty() {
+            let mut act = self.players.act_with(&myself, &container);
+            echo!(act.myself(), "You can't put $N into itself.\r\n");
+            return;
+        }
+
+        for object_id in objects {
+            self.put_object(object_id, container_id);
+        }
+    }
+
+    fn put_object(&mut self, object_id: EntityId, container_id: EntityId) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let object = self.entity_world.entity_info(object_id);
+        let container = self.entity_world.entity_info(container_id);
+
         // Giver and receiver perspectives
         let mut act = self.players.act_with(&myself, &container).store_acts();
         echo!(act.myself(), "You put {} into $N.\r\n", object);
@@ -1683,311 +2108,10 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             container
         );
 
-        let object_id = object.entity_id();
-        let target_id = container.entity_id();
-
-        self.entity_world.move_entity(object_id, target_id);
+        self.entity_world.move_entity(object_id, container_id);
         self.check_act_triggers(acts);
-        self.check_triggers_target(Action::Give { object_id }, target_id);
-    }
-
-    pub fn do_open(&mut self, target: &str) {
-        let myself = self.entity_world.entity_info(self.entity_id);
-        let target = long_direction(target);
-        let target = myself.find_entity(target, |entity| {
-            // Prefer closed doors
-            if let Some(ref door) = entity.components().door {
-                door.closed
-            } else {
-                false
-            }
-        });
-
-        let target = match target {
-            Found::Myself | Found::WrongSelf => {
-                // Not necessarily impossible, but doesn't seem useful.
-                echo!(
-                    self.info(),
-                    "You can't seem to open yourself, you'll need some assistance.\r\n"
-                );
-                return;
-            }
-            Found::Nothing => {
-                echo!(
-                    self.info(),
-                    "You don't see anything here by that name to open.\r\n"
-                );
-                return;
-            }
-            Found::Other(other) | Found::WrongOther(other) => other,
-        };
-
-        let target_id = target.entity_id();
-        let mut target = self.entity_world.entity_info_mut(target_id);
-
-        let door = match &mut target.components().door {
-            Some(door) if door.locked => Err("appears to be locked"),
-            Some(door) if !door.closed => Err("is already open"),
-            Some(door) => Ok(door),
-            None => Err("is not something you can open"),
-        };
-
-        let door = match door {
-            Ok(door) => door,
-            Err(err) => {
-                let myself = self.entity_world.entity_info(self.entity_id);
-                let target = self.entity_world.entity_info(target_id);
-                let mut act = self.players.act_with(&myself, &target);
-                echo!(act.myself(), "But $N {}!\r\n", err);
-                return;
-            }
-        };
-
-        door.closed = false;
-
-        let myself = self.entity_world.entity_info(self.entity_id);
-        let target = self.entity_world.entity_info(target_id);
-        let mut act = self.players.act_with(&myself, &target).store_acts();
-        echo!(act.myself(), "You open $N.\r\n");
-        echo!(act.target(), "$^$n opens you.\r\n");
-        echo!(act.others(), "$^$n opens $N.\r\n");
-        let acts1 = act.into_acts();
-        let mut acts2 = None;
-
-        if let Some(leads_to) = target.leads_to() {
-            let other_room = self.entity_world.entity_info(leads_to);
-
-            let mut other_exit_id = None;
-
-            for other_exit in other_room.exits() {
-                if other_exit.main_keyword() == opposite_direction(target.main_keyword()) {
-                    other_exit_id = Some(other_exit.entity_id());
-                    break;
-                }
-            }
-
-            if let Some(other_exit_id) = other_exit_id {
-                let mut other_exit = self.entity_world.entity_info_mut(other_exit_id);
-
-                if let Some(door) = &mut other_exit.components().door {
-                    if door.closed {
-                        door.closed = false;
-
-                        let other_exit = self.entity_world.entity_info(other_exit_id);
-                        let mut act = self.players.act_alone(&other_exit).store_acts();
-                        echo!(act.myself(), "You are opened from the other side.\r\n");
-                        echo!(act.others(), "$^$n is opened from the other side.\r\n");
-                        acts2 = Some(act.into_acts());
-                    }
-                }
-            }
-        }
-
-        self.check_act_triggers(acts1);
-        if let Some(acts2) = acts2 {
-            self.check_act_triggers(acts2);
-        }
-    }
-
-    pub fn do_close(&mut self, target: &str) {
-        let myself = self.entity_world.entity_info(self.entity_id);
-        let target = long_direction(target);
-        let target = myself.find_entity(target, |entity| {
-            // Prefer opened doors
-            if let Some(ref door) = entity.components().door {
-                !door.closed
-            } else {
-                false
-            }
-        });
-
-        let target = match target {
-            Found::Myself | Found::WrongSelf => {
-                // Not necessarily impossible, but doesn't seem useful.
-                echo!(
-                    self.info(),
-                    "You can't seem to close yourself, you'll need some assistance.\r\n"
-                );
-                return;
-            }
-            Found::Nothing => {
-                echo!(
-                    self.info(),
-                    "You don't see anything here by that name to close.\r\n"
-                );
-                return;
-            }
-            Found::Other(other) | Found::WrongOther(other) => other,
-        };
-
-        let target_id = target.entity_id();
-        let mut target = self.entity_world.entity_info_mut(target_id);
-
-        let door = match &mut target.components().door {
-            Some(door) if door.closed => Err("is already closed"),
-            Some(door) => Ok(door),
-            None => Err("is not something you can close"),
-        };
-
-        let door = match door {
-            Ok(door) => door,
-            Err(err) => {
-                let myself = self.entity_world.entity_info(self.entity_id);
-                let target = self.entity_world.entity_info(target_id);
-                let mut act = self.players.act_with(&myself, &target);
-                echo!(act.myself(), "But $N {}!\r\n", err);
-                return;
-            }
-        };
-
-        door.closed = true;
-
-        let myself = self.entity_world.entity_info(self.entity_id);
-        let target = self.entity_world.entity_info(target_id);
-        let mut act = self.players.act_with(&myself, &target).store_acts();
-        echo!(act.myself(), "You close $N.\r\n");
-        echo!(act.target(), "$^$n closes you.\r\n");
-        echo!(act.others(), "$^$n closes $N.\r\n");
-        let acts1 = act.into_acts();
-        let mut acts2 = None;
-
-        if let Some(leads_to) = target.leads_to() {
-            let other_room = self.entity_world.entity_info(leads_to);
-
-            let mut other_exit_id = None;
-
-            for other_exit in other_room.exits() {
-                if other_exit.main_keyword() == opposite_direction(target.main_keyword()) {
-                    other_exit_id = Some(other_exit.entity_id());
-                    break;
-                }
-            }
-
-            if let Some(other_exit_id) = other_exit_id {
-                let mut other_exit = self.entity_world.entity_info_mut(other_exit_id);
-
-                if let Some(door) = &mut other_exit.components().door {
-                    if !door.closed {
-                        door.closed = true;
-
-                        let other_exit = self.entity_world.entity_info(other_exit_id);
-                        let mut act = self.players.act_alone(&other_exit).store_acts();
-                        echo!(act.myself(), "You are closed from the other side.\r\n");
-                        echo!(act.others(), "$^$n is closed from the other side.\r\n");
-                        acts2 = Some(act.into_acts());
-                    }
-                }
-            }
-        }
-
-        self.check_act_triggers(acts1);
-        if let Some(acts2) = acts2 {
-            self.check_act_triggers(acts2);
-        }
-    }
-
-    pub fn do_unlock(&mut self, target: &str) {
-        let myself = self.entity_world.entity_info(self.entity_id);
-        let target = long_direction(target);
-
-        let found = myself
-            .visible_entities(target)
-            .filter_or(
-                |e| *e != myself,
-                "You can't seem to unlock yourself, you'll need some assistance",
-            )
-            .with_component_or::<Door>("$^$N is not something you can unlock.")
-            .prefer_component(|_e, door| !door.locked)
-            .find_one_with_component_or("You don't see anything here by that name to unlock.");
-
-        let (target, door) = match found {
-            Ok(object) => object,
-            Err(error) => return self.echo_error(error),
-        };
-
-        if let Some(key_vnum) = door.key {
-            let has_key = myself
-                .contained_entities()
-                .any(|item| item.components().general.vnum == key_vnum);
-
-            if !has_key {
-                echo!(self.info(), "You'll need a key to unlock it!\r\n");
-                return;
-            }
-        }
-
-        let target_id = target.entity_id();
-        let mut target = self.entity_world.entity_info_mut(target_id);
-
-        let door = match &mut target.components().door {
-            Some(door) if !door.locked => Err("is already unlocked"),
-            Some(door) if !door.closed => Err("is not closed"),
-            Some(door) => Ok(door),
-            None => Err("is not something you can unlock"),
-        };
-
-        let door = match door {
-            Ok(door) => door,
-            Err(err) => {
-                let myself = self.entity_world.entity_info(self.entity_id);
-                let target = self.entity_world.entity_info(target_id);
-                let mut act = self.players.act_with(&myself, &target);
-                echo!(act.myself(), "But $N {}!\r\n", err);
-                return;
-            }
-        };
-
-        door.locked = false;
-
-        let myself = self.entity_world.entity_info(self.entity_id);
-        let target = self.entity_world.entity_info(target_id);
-        let mut act = self.players.act_with(&myself, &target).store_acts();
-        echo!(act.myself(), "You unlock $N.\r\n");
-        echo!(act.target(), "$^$n unlocks you.\r\n");
-        echo!(act.others(), "$^$n unlocks $N.\r\n");
-        let acts1 = act.into_acts();
-        let mut acts2 = None;
-
-        if let Some(leads_to) = target.leads_to() {
-            let other_room = self.entity_world.entity_info(leads_to);
-
-            let mut other_exit_id = None;
-
-            for other_exit in other_room.exits() {
-                if other_exit.main_keyword() == opposite_direction(target.main_keyword()) {
-                    other_exit_id = Some(other_exit.entity_id());
-                    break;
-                }
-            }
-
-            if let Some(other_exit_id) = other_exit_id {
-                let mut other_exit = self.entity_world.entity_info_mut(other_exit_id);
-
-                if let Some(door) = &mut other_exit.components().door {
-                    if !door.locked {
-                        door.locked = false;
-
-                        let other_exit = self.entity_world.entity_info(other_exit_id);
-                        let mut act = self.players.act_alone(&other_exit).store_acts();
-                        echo!(act.myself(), "You are unlocked from the other side.\r\n");
-                        echo!(
-                            act.others(),
-                            "You hear a clicking sound as $n is unlocked.\r\n"
-                        );
-                        acts2 = Some(act.into_acts());
-                    }
-                }
-            }
-        }
-
-        self.check_act_triggers(acts1);
-        if let Some(acts2) = acts2 {
-            self.check_act_triggers(acts2);
-        }
-    }
-
-    pub fn do_lock(&mut self, _target: &str) {
-        echo!(self.info(), "Locking is not yet implemented.\r\n");
+        self.check_triggers_target(Action::Give { object_id }, container_id);
+        self.merge_coins(object_id);
     }
 
     pub fn do_inventory(&mut self) {
@@ -2017,58 +2141,6 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         echo!(info, "\r\n");
     }
 
-    pub fn do_list(&mut self) {
-        let myself = self.entity_world.entity_info(self.entity_id);
-
-        let found = myself
-            .room()
-            .contained_entities()
-            .filter_or(
-                |e| *e != myself,
-                "The only shopkeeper here is yourself.\r\n",
-            )
-            .with_component::<Mobile>()
-            .prefer_component(|_e, mobile| mobile.shopkeeper.is_some())
-            .find_one_with_component_or("You don't see anyone here.");
-
-        let (entity, mobile) = match found {
-            Ok(found) => found,
-            Err(error) => return self.echo_error(error),
-        };
-
-        if let Some(shop_info) = &mobile.shopkeeper {
-            let mut act = self.players.act_with(&myself, &entity);
-            echo!(
-                act.target(),
-                "$^$n asks you about your wares, and you show $m what you have.\r\n"
-            );
-            echo!(act.others(), "$^$n asks $N about $S wares.\r\n");
-
-            echo!(act.myself(), "$^$N shows you $S wares:\r\n");
-
-            let mut info = self.players.info(&myself);
-            for item in entity.objects() {
-                if let Some(object_info) = &item.components().object {
-                    let price = object_info.cost * shop_info.profit_buy as i32 / 100;
-                    echo!(
-                        info,
-                        "  {}: `W{}`^ silver coins\r\n",
-                        item.component_info().short_description(),
-                        price
-                    );
-                } else {
-                    echo!(
-                        info,
-                        "  {}: priceless\r\n",
-                        item.component_info().short_description()
-                    );
-                }
-            }
-        } else {
-            echo!(self.info(), "You don't see any shopkeepers here.\r\n");
-        }
-    }
-
     pub fn do_follow(&mut self, target: &str) {
         let myself = self.entity_world.entity_info(self.entity_id);
 
@@ -2097,9 +2169,11 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         echo!(act.others(), "$^$n starts following $N.\r\n");
         let acts = act.into_acts();
 
-        let main_keyword = target.main_keyword().to_string();
+        let leader_id = target.permanent_entity_id();
         let mut myself = self.entity_world.entity_info_mut(self.entity_id);
-        myself.components().general.following = Some(main_keyword);
+        let general = &mut myself.components().general;
+        general.following = Some(leader_id);
+        general.group = None;
 
         self.check_act_triggers(acts);
     }
@@ -2111,6 +2185,8 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         echo!(act.others(), "$^$n stops following anyone.\r\n");
 
         let mut myself = self.entity_world.entity_info_mut(self.entity_id);
-        myself.components().general.following = None;
+        let general = &mut myself.components().general;
+        general.following = None;
+        general.group = None;
     }
 }
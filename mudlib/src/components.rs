@@ -1,7 +1,10 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
 use string_interner::StringInterner;
 
 use crate::{
-    entity::EntityInfo,
+    entity::{EntityInfo, PermanentEntityId},
     world::{Gender, MobProgTrigger, Shop, Vnum},
 };
 
@@ -19,7 +22,12 @@ pub(crate) struct Components {
     pub object: Option<Object>,
     pub door: Option<Door>,
     pub mobprog: Option<MobProg>,
-    pub silver: Option<Silver>,
+    pub coins: Option<Coins>,
+    pub room: Option<Room>,
+    pub player: Option<Player>,
+    pub script: Option<Script>,
+    pub variables: Variables,
+    pub visibility: Visibility,
 }
 
 #[derive(Clone)]
@@ -30,7 +38,9 @@ pub(crate) struct GeneralData {
     pub entity_type: EntityType,
     pub equipped: Option<String>,
     pub command_queue: Vec<(u16, String)>,
-    pub following: Option<String>,
+    pub following: Option<PermanentEntityId>,
+    /// The leader of the group this entity was accepted into, if any
+    pub group: Option<PermanentEntityId>,
 }
 
 #[derive(Hash, Clone, Copy, PartialEq, Eq)]
@@ -48,15 +58,82 @@ pub enum EntityType {
 pub(crate) struct Mobile {
     pub wander: bool,
     pub shopkeeper: Option<Shop>,
+    /// Objects given to a shopkeeper by `G` resets, which it restocks
+    pub restock: Vec<Vnum>,
     pub remember: Option<String>,
+    pub position: Position,
+    /// The furniture the mobile is sitting, resting or sleeping on
+    pub furniture: Option<PermanentEntityId>,
+    /// The position the mobile is described in with its lateral description
+    pub default_position: Position,
+}
+
+/// How far a character is from standing up; see `positions.rs`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub(crate) enum Position {
+    Sleeping,
+    Resting,
+    Sitting,
+    #[default]
+    Standing,
 }
 
 #[derive(Clone)]
 pub(crate) struct Object {
     pub cost: i32,
-    pub key: Option<Vnum>,
+    pub item_type: String,
+    /// Out of 100, where 100 is in perfect condition
+    pub condition: u8,
     pub container: bool,
     pub food: bool,
+    pub light: Option<Light>,
+    pub drink_container: Option<DrinkContainer>,
+    pub furniture: Option<Furniture>,
+    pub spells: Option<Spells>,
+    pub portal: Option<Portal>,
+    pub wear_location: Option<String>,
+}
+
+/// Objects that light up dark rooms while lit; see `items.rs`.
+#[derive(Clone)]
+pub(crate) struct Light {
+    /// Hours of light left, or None if it never burns out
+    pub hours: Option<u16>,
+    pub lit: bool,
+}
+
+#[derive(Clone)]
+pub(crate) struct DrinkContainer {
+    pub liquid: String,
+    pub capacity: u16,
+    pub amount: u16,
+    /// Fountains never run out
+    pub endless: bool,
+}
+
+/// Objects that characters can sit, rest or sleep on; see `positions.rs`.
+#[derive(Clone)]
+pub(crate) struct Furniture {
+    /// How many characters fit on it at once
+    pub capacity: usize,
+    /// Whether one sits "at", "on" or "in" it, if one can at all
+    pub sit: Option<&'static str>,
+    pub rest: Option<&'static str>,
+    pub sleep: Option<&'static str>,
+}
+
+/// Scrolls, potions and pills.
+#[derive(Clone)]
+pub(crate) struct Spells {
+    pub level: u16,
+    pub spells: Vec<String>,
+}
+
+#[derive(Clone)]
+pub(crate) struct Portal {
+    pub destination: Vnum,
+    /// How many more times it can be entered, or None if there's no limit
+    pub charges: Option<u16>,
 }
 
 #[derive(Clone)]
@@ -64,6 +141,8 @@ pub(crate) struct Door {
     pub closed: bool,
     pub locked: bool,
     pub key: Option<Vnum>,
+    pub pickproof: bool,
+    pub bashproof: bool,
 }
 
 #[derive(Clone)]
@@ -72,9 +151,190 @@ pub(crate) struct MobProg {
     pub code: String,
 }
 
+/// A pile of coins; see `money.rs`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Coins {
+    pub gold: usize,
+    pub silver: usize,
+    pub copper: usize,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Room {
+    /// Players can `deposit` and `withdraw` coins here
+    pub bank: bool,
+}
+
 #[derive(Clone)]
-pub(crate) struct Silver {
-    pub amount: usize,
+pub(crate) struct Player {
+    pub immortal: bool,
+    pub holylight: bool,
+    /// Shown after the player's name in `who`, e.g. "the adventurer"
+    pub title: String,
+    /// Chosen with `pronouns`; players who didn't choose yet are "they"
+    pub gender: Gender,
+    /// Chosen with `short`, instead of just the player's name
+    pub short_description: Option<String>,
+    /// Chosen with `description`, seen by those who look at the player
+    pub description: Option<String>,
+    pub quests: QuestLog,
+    pub position: Position,
+    /// The furniture the player is sitting, resting or sleeping on
+    pub furniture: Option<PermanentEntityId>,
+    pub channels: ChannelSettings,
+    /// Value of the coins kept in the bank, in copper
+    pub bank: usize,
+}
+
+/// What makes an entity harder to see, or better at seeing; see
+/// `visibility.rs`.
+#[derive(Clone, Default)]
+pub(crate) struct Visibility {
+    pub invisible: bool,
+    pub hidden: bool,
+    pub sneaking: bool,
+    pub unseen: bool,
+    pub detect_invisible: bool,
+    pub detect_hidden: bool,
+    /// Only used by rooms, which are dark unless something lights them up
+    pub dark: bool,
+}
+
+/// Quests a player has started or finished; see `quests.rs`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct QuestLog {
+    #[serde(default)]
+    pub completed: Vec<String>,
+    #[serde(default)]
+    pub active: BTreeMap<String, QuestProgress>,
+}
+
+/// Which channels a player listens to, and who they don't want to hear
+/// from; see `channels.rs`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct ChannelSettings {
+    #[serde(default)]
+    pub turned_off: Vec<String>,
+    #[serde(default)]
+    pub ignoring: Vec<String>,
+    /// The last player that sent a tell, for `reply`
+    #[serde(skip)]
+    pub reply_to: Option<String>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct QuestProgress {
+    /// Index of the step the player is currently on.
+    pub step: usize,
+    /// How many times each of the current step's objectives was done.
+    #[serde(default)]
+    pub counts: Vec<u32>,
+}
+
+/// A script from `data/scripts/` that reacts to things happening around the
+/// entity.
+#[derive(Clone)]
+pub(crate) struct Script {
+    pub name: String,
+}
+
+/// Named values that mobprogs and scripts keep on an entity, like counters or
+/// quest stages. Saved in player files along with the player.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct Variables {
+    values: BTreeMap<String, Variable>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Variable {
+    pub value: VariableValue,
+    /// Tick at which the variable disappears, if it's temporary.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum VariableValue {
+    Int(i64),
+    Bool(bool),
+    Text(String),
+}
+
+impl VariableValue {
+    /// Numbers become integers, anything else is kept as text.
+    pub fn parse(value: &str) -> Self {
+        match value.parse() {
+            Ok(number) => VariableValue::Int(number),
+            Err(_) => VariableValue::Text(value.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for VariableValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VariableValue::Int(number) => write!(f, "{}", number),
+            VariableValue::Bool(boolean) => write!(f, "{}", boolean),
+            VariableValue::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+impl Variables {
+    pub fn get(&self, name: &str) -> Option<&VariableValue> {
+        self.values.get(name).map(|variable| &variable.value)
+    }
+
+    pub fn set(&mut self, name: &str, value: VariableValue, expires: Option<u64>) {
+        self.values
+            .insert(name.to_string(), Variable { value, expires });
+    }
+
+    /// Add to a number variable, keeping when it expires. Unset variables
+    /// start from 0. Returns None if the variable isn't a number.
+    pub fn increment(&mut self, name: &str, amount: i64) -> Option<i64> {
+        let variable = self.values.entry(name.to_string()).or_insert(Variable {
+            value: VariableValue::Int(0),
+            expires: None,
+        });
+
+        match &mut variable.value {
+            VariableValue::Int(number) => {
+                *number = number.saturating_add(amount);
+                Some(*number)
+            }
+            VariableValue::Bool(_) | VariableValue::Text(_) => None,
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.values.remove(name).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Variable)> {
+        self.values.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Drop the variables that expired at or before the given tick.
+    pub fn remove_expired(&mut self, tick: u64) {
+        self.values
+            .retain(|_name, variable| variable.expires.is_none_or(|expires| expires > tick));
+    }
+
+    /// Change when temporary variables expire, e.g. to store the remaining
+    /// number of ticks instead of the tick at which they expire.
+    pub fn map_expiry<F: Fn(u64) -> u64>(&mut self, f: F) {
+        for variable in self.values.values_mut() {
+            variable.expires = variable.expires.map(&f);
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -122,6 +382,35 @@ impl ComponentFromEntity for Mobile {
     }
 }
 
+impl ComponentFromEntity for Light {
+    fn component_from_entity<'e>(entity: &EntityInfo<'e>) -> Option<&'e Self> {
+        entity.components().object.as_ref()?.light.as_ref()
+    }
+}
+
+impl ComponentFromEntity for DrinkContainer {
+    fn component_from_entity<'e>(entity: &EntityInfo<'e>) -> Option<&'e Self> {
+        entity
+            .components()
+            .object
+            .as_ref()?
+            .drink_container
+            .as_ref()
+    }
+}
+
+impl ComponentFromEntity for Furniture {
+    fn component_from_entity<'e>(entity: &EntityInfo<'e>) -> Option<&'e Self> {
+        entity.components().object.as_ref()?.furniture.as_ref()
+    }
+}
+
+impl ComponentFromEntity for Portal {
+    fn component_from_entity<'e>(entity: &EntityInfo<'e>) -> Option<&'e Self> {
+        entity.components().object.as_ref()?.portal.as_ref()
+    }
+}
+
 impl ComponentFromEntity for Shop {
     fn component_from_entity<'e>(entity: &EntityInfo<'e>) -> Option<&'e Self> {
         entity
@@ -139,7 +428,6 @@ pub(crate) struct EntityComponentInfo<'i, 'c> {
 
 pub(crate) trait InternComponent {
     fn act_info(&mut self, keyword: &str, short_description: &str, gender: Gender) -> ActInfo;
-    fn set_short_description(&mut self, act_info: &mut ActInfo, short_description: &str);
     fn descriptions(
         &mut self,
         title: &str,
@@ -161,13 +449,6 @@ impl InternComponent for StringInterner {
         }
     }
 
-    fn set_short_description(&mut self, act_info: &mut ActInfo, short_description: &str) {
-        // Note: old value is forever lost; this kinda leaks
-        act_info.short_description = IntStr {
-            symbol: self.get_or_intern(short_description),
-        };
-    }
-
     fn descriptions(
         &mut self,
         title: &str,
@@ -221,8 +502,8 @@ impl<'i, 'c> EntityComponentInfo<'i, 'c> {
         self.resolve(&self.components.descriptions.lateral)
     }
 
-    pub fn gender(&self) -> Gender {
-        self.components.act_info.gender
+    pub fn gender(&self) -> &'c Gender {
+        &self.components.act_info.gender
     }
 
     pub fn keyword(&self) -> &'i str {
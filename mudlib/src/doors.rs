@@ -0,0 +1,479 @@
+//! Doors and containers that can be opened, closed, locked, picked and bashed.
+//!
+//! Both exits and containers may have a `Door` component. An exit's door has
+//! two sides, one in each room, which are kept in sync: opening, closing,
+//! locking or unlocking one side does the same to the exit leading back.
+//!
+//! Locks that need a key can only be locked and unlocked by someone carrying
+//! it, but may also be picked, unless they're pickproof. Closed doors can be
+//! bashed open, unless they're bashproof, which containers always are.
+
+use crate::{
+    acting::Acts,
+    agent::EntityAgent,
+    components::Door,
+    echo,
+    entity::{EntityId, Found},
+    find_entities::EntityIterator,
+    mobprogs::random_percent,
+    world::long_direction,
+};
+
+/// Chance out of 100 to pick a lock.
+const PICK_CHANCE: u8 = 50;
+
+/// Chance out of 100 to bash a door open.
+const BASH_CHANCE: u8 = 25;
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    /// Find a door or container with a lock, preferring the ones the command
+    /// would work on.
+    fn find_lock(
+        &mut self,
+        target: &str,
+        prefer: fn(&Door) -> bool,
+        errors: [&'static str; 3],
+    ) -> Option<EntityId> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = long_direction(target);
+        let [myself_error, component_error, nothing_error] = errors;
+
+        let found = myself
+            .visible_entities(target)
+            .filter_or(|e| *e != myself, myself_error)
+            .with_component_or::<Door>(component_error)
+            .prefer_component(|_e, door| prefer(door))
+            .find_one_with_component_or(nothing_error);
+
+        match found {
+            Ok((target, _door)) => Some(target.entity_id()),
+            Err(error) => {
+                self.echo_error(error);
+                None
+            }
+        }
+    }
+
+    /// Check if the agent carries the key for this door, if it needs one.
+    fn has_key(&self, door: &Door) -> bool {
+        let key_vnum = match door.key {
+            Some(key_vnum) => key_vnum,
+            None => return true,
+        };
+
+        self.entity_world
+            .entity_info(self.entity_id)
+            .contained_entities()
+            .any(|item| item.components().general.vnum == key_vnum)
+    }
+
+    /// Check that the door of the target can be changed, telling the agent
+    /// why not otherwise.
+    fn check_door(
+        &mut self,
+        target_id: EntityId,
+        not_a_door: &'static str,
+        check: impl Fn(&Door) -> Result<(), &'static str>,
+    ) -> Option<Door> {
+        let target = self.entity_world.entity_info(target_id);
+
+        let result = match &target.components().door {
+            Some(door) => check(door).map(|()| door.clone()),
+            None => Err(not_a_door),
+        };
+
+        match result {
+            Ok(door) => Some(door),
+            Err(err) => {
+                let myself = self.entity_world.entity_info(self.entity_id);
+                let mut act = self.players.act_with(&myself, &target);
+                echo!(act.myself(), "But $N {}!\r\n", err);
+                None
+            }
+        }
+    }
+
+    /// Change the door of the target, and the reverse side of it if it's an
+    /// exit. Triggers are checked for both sides, using the messages for the
+    /// other side if it changed.
+    fn change_door(
+        &mut self,
+        target_id: EntityId,
+        acts: Acts,
+        change: impl Fn(&mut Door) -> bool,
+        to_other_side: &str,
+        to_others_there: &str,
+    ) {
+        let mut target = self.entity_world.entity_info_mut(target_id);
+        if let Some(door) = &mut target.components().door {
+            change(door);
+        }
+
+        let other_exit_id = self
+            .entity_world
+            .entity_info(target_id)
+            .reverse_exit()
+            .map(|other_exit| other_exit.entity_id());
+
+        let mut other_acts = None;
+        if let Some(other_exit_id) = other_exit_id {
+            let mut other_exit = self.entity_world.entity_info_mut(other_exit_id);
+
+            let changed = match &mut other_exit.components().door {
+                Some(door) => change(door),
+                None => false,
+            };
+
+            if changed {
+                let other_exit = self.entity_world.entity_info(other_exit_id);
+                let mut act = self.players.act_alone(&other_exit).store_acts();
+                echo!(act.myself(), "{}\r\n", to_other_side);
+                echo!(act.others(), "{}\r\n", to_others_there);
+                other_acts = Some(act.into_acts());
+            }
+        }
+
+        self.check_act_triggers(acts);
+        if let Some(other_acts) = other_acts {
+            self.check_act_triggers(other_acts);
+        }
+    }
+
+    pub fn do_open(&mut self, target: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = long_direction(target);
+        let target = myself.find_entity(target, |entity| {
+            // Prefer closed doors
+            if let Some(ref door) = entity.components().door {
+                door.closed
+            } else {
+                false
+            }
+        });
+
+        let target = match target {
+            Found::Myself | Found::WrongSelf => {
+                // Not necessarily impossible, but doesn't seem useful.
+                echo!(
+                    self.info(),
+                    "You can't seem to open yourself, you'll need some assistance.\r\n"
+                );
+                return;
+            }
+            Found::Nothing => {
+                echo!(
+                    self.info(),
+                    "You don't see anything here by that name to open.\r\n"
+                );
+                return;
+            }
+            Found::Other(other) | Found::WrongOther(other) => other,
+        };
+
+        let target_id = target.entity_id();
+        let checked = self.check_door(target_id, "is not something you can open", |door| {
+            if door.locked {
+                Err("appears to be locked")
+            } else if !door.closed {
+                Err("is already open")
+            } else {
+                Ok(())
+            }
+        });
+        if checked.is_none() {
+            return;
+        }
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = self.entity_world.entity_info(target_id);
+        let mut act = self.players.act_with(&myself, &target).store_acts();
+        echo!(act.myself(), "You open $N.\r\n");
+        echo!(act.target(), "$^$n opens you.\r\n");
+        echo!(act.others(), "$^$n opens $N.\r\n");
+        let acts = act.into_acts();
+
+        self.change_door(
+            target_id,
+            acts,
+            |door| std::mem::replace(&mut door.closed, false),
+            "You are opened from the other side.",
+            "$^$n is opened from the other side.",
+        );
+    }
+
+    pub fn do_close(&mut self, target: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = long_direction(target);
+        let target = myself.find_entity(target, |entity| {
+            // Prefer opened doors
+            if let Some(ref door) = entity.components().door {
+                !door.closed
+            } else {
+                false
+            }
+        });
+
+        let target = match target {
+            Found::Myself | Found::WrongSelf => {
+                // Not necessarily impossible, but doesn't seem useful.
+                echo!(
+                    self.info(),
+                    "You can't seem to close yourself, you'll need some assistance.\r\n"
+                );
+                return;
+            }
+            Found::Nothing => {
+                echo!(
+                    self.info(),
+                    "You don't see anything here by that name to close.\r\n"
+                );
+                return;
+            }
+            Found::Other(other) | Found::WrongOther(other) => other,
+        };
+
+        let target_id = target.entity_id();
+        let checked = self.check_door(target_id, "is not something you can close", |door| {
+            if door.closed {
+                Err("is already closed")
+            } else {
+                Ok(())
+            }
+        });
+        if checked.is_none() {
+            return;
+        }
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = self.entity_world.entity_info(target_id);
+        let mut act = self.players.act_with(&myself, &target).store_acts();
+        echo!(act.myself(), "You close $N.\r\n");
+        echo!(act.target(), "$^$n closes you.\r\n");
+        echo!(act.others(), "$^$n closes $N.\r\n");
+        let acts = act.into_acts();
+
+        self.change_door(
+            target_id,
+            acts,
+            |door| !std::mem::replace(&mut door.closed, true),
+            "You are closed from the other side.",
+            "$^$n is closed from the other side.",
+        );
+    }
+
+    pub fn do_unlock(&mut self, target: &str) {
+        let target_id = match self.find_lock(
+            target,
+            |door| door.locked,
+            [
+                "You can't seem to unlock yourself, you'll need some assistance",
+                "$^$N is not something you can unlock.",
+                "You don't see anything here by that name to unlock.",
+            ],
+        ) {
+            Some(target_id) => target_id,
+            None => return,
+        };
+
+        let door = match self.check_door(target_id, "is not something you can unlock", |door| {
+            if !door.closed {
+                Err("is not closed")
+            } else if !door.locked {
+                Err("is already unlocked")
+            } else {
+                Ok(())
+            }
+        }) {
+            Some(door) => door,
+            None => return,
+        };
+
+        if !self.has_key(&door) {
+            echo!(self.info(), "You'll need a key to unlock it!\r\n");
+            return;
+        }
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = self.entity_world.entity_info(target_id);
+        let mut act = self.players.act_with(&myself, &target).store_acts();
+        echo!(act.myself(), "You unlock $N.\r\n");
+        echo!(act.target(), "$^$n unlocks you.\r\n");
+        echo!(act.others(), "$^$n unlocks $N.\r\n");
+        let acts = act.into_acts();
+
+        self.change_door(
+            target_id,
+            acts,
+            |door| std::mem::replace(&mut door.locked, false),
+            "You are unlocked from the other side.",
+            "You hear a clicking sound as $n is unlocked.",
+        );
+    }
+
+    pub fn do_lock(&mut self, target: &str) {
+        let target_id = match self.find_lock(
+            target,
+            |door| door.closed && !door.locked,
+            [
+                "You can't seem to lock yourself, you'll need some assistance",
+                "$^$N is not something you can lock.",
+                "You don't see anything here by that name to lock.",
+            ],
+        ) {
+            Some(target_id) => target_id,
+            None => return,
+        };
+
+        let door = match self.check_door(target_id, "is not something you can lock", |door| {
+            if !door.closed {
+                Err("is not closed")
+            } else if door.locked {
+                Err("is already locked")
+            } else {
+                Ok(())
+            }
+        }) {
+            Some(door) => door,
+            None => return,
+        };
+
+        if !self.has_key(&door) {
+            echo!(self.info(), "You'll need a key to lock it!\r\n");
+            return;
+        }
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = self.entity_world.entity_info(target_id);
+        let mut act = self.players.act_with(&myself, &target).store_acts();
+        echo!(act.myself(), "You lock $N.\r\n");
+        echo!(act.target(), "$^$n locks you.\r\n");
+        echo!(act.others(), "$^$n locks $N.\r\n");
+        let acts = act.into_acts();
+
+        self.change_door(
+            target_id,
+            acts,
+            |door| !std::mem::replace(&mut door.locked, true),
+            "You are locked from the other side.",
+            "You hear a clicking sound as $n is locked.",
+        );
+    }
+
+    pub fn do_pick(&mut self, target: &str) {
+        let target_id = match self.find_lock(
+            target,
+            |door| door.locked,
+            [
+                "You can't seem to pick yourself, you'll need some assistance",
+                "$^$N has no lock to pick.",
+                "You don't see anything here by that name to pick.",
+            ],
+        ) {
+            Some(target_id) => target_id,
+            None => return,
+        };
+
+        let door = match self.check_door(target_id, "has no lock to pick", |door| {
+            if !door.closed {
+                Err("is not closed")
+            } else if !door.locked {
+                Err("is already unlocked")
+            } else {
+                Ok(())
+            }
+        }) {
+            Some(door) => door,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = self.entity_world.entity_info(target_id);
+
+        if door.pickproof || !random_percent(PICK_CHANCE) {
+            let mut act = self.players.act_with(&myself, &target);
+            if door.pickproof {
+                echo!(act.myself(), "The lock of $N is too well made to pick.\r\n");
+            } else {
+                echo!(act.myself(), "You fail to pick the lock of $N.\r\n");
+            }
+            echo!(act.others(), "$^$n fiddles with the lock of $N.\r\n");
+            return;
+        }
+
+        let mut act = self.players.act_with(&myself, &target).store_acts();
+        echo!(act.myself(), "*Click* You pick the lock of $N.\r\n");
+        echo!(act.target(), "$^$n picks your lock.\r\n");
+        echo!(act.others(), "$^$n picks the lock of $N.\r\n");
+        let acts = act.into_acts();
+
+        self.change_door(
+            target_id,
+            acts,
+            |door| std::mem::replace(&mut door.locked, false),
+            "Your lock is picked from the other side.",
+            "You hear a clicking sound as the lock of $n is picked.",
+        );
+    }
+
+    pub fn do_bash(&mut self, target: &str) {
+        let target_id = match self.find_lock(
+            target,
+            |door| door.closed,
+            [
+                "You can't seem to bash yourself, you'll need some assistance",
+                "$^$N is not something you can bash open.",
+                "You don't see anything here by that name to bash.",
+            ],
+        ) {
+            Some(target_id) => target_id,
+            None => return,
+        };
+
+        let door = match self.check_door(target_id, "is not something you can bash open", |door| {
+            if !door.closed {
+                Err("is already open")
+            } else {
+                Ok(())
+            }
+        }) {
+            Some(door) => door,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = self.entity_world.entity_info(target_id);
+
+        if door.bashproof || !random_percent(BASH_CHANCE) {
+            let mut act = self.players.act_with(&myself, &target);
+            if door.bashproof {
+                echo!(
+                    act.myself(),
+                    "You throw yourself at $N, but it doesn't budge.\r\n"
+                );
+            } else {
+                echo!(act.myself(), "You slam into $N, but it holds.\r\n");
+            }
+            echo!(act.others(), "$^$n slams into $N, but it holds.\r\n");
+            return;
+        }
+
+        let mut act = self.players.act_with(&myself, &target).store_acts();
+        echo!(act.myself(), "You slam into $N, and it bursts open!\r\n");
+        echo!(act.target(), "$^$n bashes you open!\r\n");
+        echo!(act.others(), "$^$n slams into $N, and it bursts open!\r\n");
+        let acts = act.into_acts();
+
+        self.change_door(
+            target_id,
+            acts,
+            |door| {
+                let changed = door.closed || door.locked;
+                door.closed = false;
+                door.locked = false;
+                changed
+            },
+            "You are bashed open from the other side!",
+            "$^$n bursts open with a loud crash!",
+        );
+    }
+}
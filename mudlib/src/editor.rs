@@ -0,0 +1,268 @@
+//! Multi-line text editor, for writing descriptions over a connection that
+//! only sends whole lines.
+//!
+//! A command opens an editor with `EntityAgent::start_editor`, saying what the
+//! text is for. The front end takes it with `WorldState::take_editor`, sends
+//! the player's next lines to it instead of running them as commands, and once
+//! the player is done gives it back to `WorldState::finish_editor`, which hands
+//! the text to whatever opened it.
+//!
+//! Editors are kept by the front ends rather than the game, so that they
+//! survive the game being restarted.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{agent::EntityAgent, colors::EscapeColors, echo, world::Vnum};
+
+/// Width that `.f` wraps paragraphs to, like the descriptions in area files.
+const FORMAT_WIDTH: usize = 78;
+
+const EDITOR_HELP: &str = "\
+Type lines of text to add them, then `W@`^ or `W.s`^ to save. Editor commands:
+  `W.l`^               List the text with line numbers
+  `W.d <line>`^        Delete a line
+  `W.i <line> <text>`^ Insert a line before another one
+  `W.r <old> <new>`^   Replace text; use 'quotes' for more than one word
+  `W.f`^               Format paragraphs to the usual width
+  `W.c`^               Clear all the text
+  `W.q`^               Stop editing without saving
+  `W.h`^               Show this help
+";
+
+/// Whose description the text of an editor is for: a room, mobile or object
+/// template's, or the player's own.
+///
+/// Editors are serialized across hot-swaps, so new variants go at the end to
+/// keep the positions of the old ones.
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) enum EditorTarget {
+    Room(Vnum),
+    Mobile(Vnum),
+    Player,
+    Object(Vnum),
+}
+
+/// A text being edited, and what it's for.
+#[derive(Serialize, Deserialize)]
+pub struct Editor {
+    target: EditorTarget,
+    lines: Vec<String>,
+}
+
+/// What happened after a line was sent to an editor.
+#[derive(PartialEq, Eq)]
+pub enum EditorStatus {
+    Editing,
+    /// The text should be given to `WorldState::finish_editor`
+    Saved,
+    /// The editor should be thrown away
+    Cancelled,
+}
+
+impl Editor {
+    fn new(target: EditorTarget, text: &str) -> Editor {
+        Editor {
+            target,
+            lines: text.lines().map(|line| line.to_string()).collect(),
+        }
+    }
+
+    /// Add a line of text, or run an editor command. Messages for the player
+    /// are added to `output`.
+    pub fn input(&mut self, line: &str, output: &mut String) -> EditorStatus {
+        let line = line.trim_matches(['\r', '\n']);
+
+        if !line.starts_with('.') && line != "@" {
+            self.lines.push(line.to_string());
+            return EditorStatus::Editing;
+        }
+
+        let (command, arguments) = match line.split_once(' ') {
+            Some((command, arguments)) => (command, arguments.trim_start()),
+            None => (line, ""),
+        };
+
+        match command {
+            "@" | ".s" => return EditorStatus::Saved,
+            ".q" => {
+                echo!(output, "Stopped editing; nothing was changed.\r\n");
+                return EditorStatus::Cancelled;
+            }
+            ".l" => self.list(output),
+            ".d" => {
+                if let Some(index) = self.line_index(arguments, output) {
+                    self.lines.remove(index);
+                    echo!(output, "Line {} deleted.\r\n", index + 1);
+                }
+            }
+            ".i" => {
+                let (number, text) = arguments.split_once(' ').unwrap_or((arguments, ""));
+                if let Some(index) = self.line_index(number, output) {
+                    self.lines.insert(index, text.to_string());
+                    echo!(output, "Line inserted before line {}.\r\n", index + 1);
+                }
+            }
+            ".r" => self.replace(arguments, output),
+            ".f" => {
+                self.format();
+                echo!(output, "Text formatted.\r\n");
+            }
+            ".c" => {
+                self.lines.clear();
+                echo!(output, "Text cleared.\r\n");
+            }
+            ".h" => echo!(output, "{}", EDITOR_HELP),
+            _ => echo!(
+                output,
+                "Unknown editor command '{}'. Type `W.h`^ for help.\r\n",
+                EscapeColors(command)
+            ),
+        }
+
+        EditorStatus::Editing
+    }
+
+    /// The text, with a newline after every line like descriptions in area
+    /// files have.
+    fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| format!("{}\r\n", line))
+            .collect()
+    }
+
+    fn list(&self, output: &mut String) {
+        if self.lines.is_empty() {
+            echo!(output, "The text is empty.\r\n");
+        }
+
+        for (index, line) in self.lines.iter().enumerate() {
+            echo!(output, "`s{:>3}`^ {}\r\n", index + 1, EscapeColors(line));
+        }
+    }
+
+    /// Turn a line number as seen in `.l` into an index into `lines`.
+    fn line_index(&self, number: &str, output: &mut String) -> Option<usize> {
+        match number.parse::<usize>() {
+            Ok(number) if (1..=self.lines.len()).contains(&number) => Some(number - 1),
+            _ => {
+                echo!(
+                    output,
+                    "There's no line '{}'; the text has {} lines.\r\n",
+                    EscapeColors(number),
+                    self.lines.len()
+                );
+                None
+            }
+        }
+    }
+
+    fn replace(&mut self, arguments: &str, output: &mut String) {
+        let (old, rest) = split_argument(arguments);
+        let (new, _) = split_argument(rest);
+
+        if old.is_empty() {
+            echo!(output, "Syntax: `W.r <old> <new>`^\r\n");
+            return;
+        }
+
+        let mut count = 0;
+        for line in &mut self.lines {
+            count += line.matches(old).count();
+            *line = line.replace(old, new);
+        }
+
+        echo!(
+            output,
+            "Replaced '{}' with '{}' {} times.\r\n",
+            EscapeColors(old),
+            EscapeColors(new),
+            count
+        );
+    }
+
+    /// Wrap each paragraph to `FORMAT_WIDTH`; paragraphs are separated by
+    /// empty lines, which are kept.
+    fn format(&mut self) {
+        let mut formatted = Vec::with_capacity(self.lines.len());
+        let mut current = String::new();
+
+        for line in &self.lines {
+            if line.trim().is_empty() {
+                if !current.is_empty() {
+                    formatted.push(std::mem::take(&mut current));
+                }
+                formatted.push(String::new());
+                continue;
+            }
+
+            for word in line.split_whitespace() {
+                if !current.is_empty() && current.len() + 1 + word.len() > FORMAT_WIDTH {
+                    formatted.push(std::mem::take(&mut current));
+                }
+                if !current.is_empty() {
+                    current.push(' ');
+                }
+                current.push_str(word);
+            }
+        }
+
+        if !current.is_empty() {
+            formatted.push(current);
+        }
+
+        self.lines = formatted;
+    }
+}
+
+/// Split off a word, or a phrase in single quotes, from the start of some
+/// editor command arguments.
+fn split_argument(arguments: &str) -> (&str, &str) {
+    let arguments = arguments.trim_start();
+
+    if let Some(quoted) = arguments.strip_prefix('\'') {
+        if let Some((argument, rest)) = quoted.split_once('\'') {
+            return (argument, rest);
+        }
+    }
+
+    arguments.split_once(' ').unwrap_or((arguments, ""))
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    /// Open an editor with some text to start from; the front end sends the
+    /// player's next lines to it.
+    pub(crate) fn start_editor(&mut self, target: EditorTarget, what: &str, text: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let player = match myself.player_name() {
+            Some(player) => player,
+            None => return,
+        };
+
+        let editor = Editor::new(target, text);
+        let mut output = String::new();
+        echo!(
+            output,
+            "Editing {}. Type `W@`^ or `W.s`^ to save, `W.q`^ to stop, or `W.h`^ for help.\r\n",
+            what
+        );
+        editor.list(&mut output);
+
+        if let Some(player_echo) = self.players.player_echoes.get_mut(player) {
+            player_echo.echo_buffer.push_str(&output);
+            player_echo.editor = Some(editor);
+        }
+    }
+
+    /// Give the text of a saved editor to what it was opened for.
+    pub(crate) fn finish_editor(&mut self, editor: Editor) {
+        let text = editor.text();
+
+        match editor.target {
+            EditorTarget::Room(vnum) => self.set_room_description(vnum, text),
+            EditorTarget::Mobile(vnum) => self.set_mobile_description(vnum, text),
+            EditorTarget::Object(vnum) => self.set_object_description(vnum, text),
+            EditorTarget::Player => self.set_player_description(text),
+        }
+    }
+}
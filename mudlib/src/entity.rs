@@ -3,12 +3,16 @@ use std::{
     num::NonZeroUsize,
 };
 
-use inflector::Inflector;
 use string_interner::StringInterner;
 
 use crate::{
-    components::{Components, EntityComponentInfo, EntityType, GeneralData, InternComponent},
-    world::{Gender, Vnum},
+    appearance::player_descriptions,
+    components::{
+        ChannelSettings, Components, EntityComponentInfo, EntityType, GeneralData, InternComponent,
+        Player, Position, QuestLog, Variables, Visibility,
+    },
+    find_entities::Target,
+    world::{opposite_direction, Gender, Vnum},
 };
 
 pub(crate) struct EntityWorld {
@@ -21,6 +25,7 @@ pub(crate) struct EntityWorld {
     landmarks: BTreeMap<&'static str, RawEntityId>,
     world_entity_id: RawEntityId,
     era: u16,
+    current_tick: u64,
 }
 
 struct Entity {
@@ -118,12 +123,18 @@ impl EntityWorld {
                     equipped: None,
                     command_queue: Vec::new(),
                     following: None,
+                    group: None,
                 },
                 mobile: None,
                 object: None,
                 door: None,
                 mobprog: None,
-                silver: None,
+                coins: None,
+                room: None,
+                player: None,
+                script: None,
+                variables: Variables::default(),
+                visibility: Visibility::default(),
             },
             raw_entity_id: id_generator.next(),
             contents: Vec::new(),
@@ -148,6 +159,7 @@ impl EntityWorld {
             landmarks: BTreeMap::new(),
             world_entity_id,
             era: 1,
+            current_tick: 0,
         }
     }
 
@@ -179,28 +191,29 @@ impl EntityWorld {
         }
     }
 
-    pub fn make_player_components(&mut self, name: &str) -> Components {
-        let keyword = name.to_lowercase();
-        let short_description = name.to_title_case();
-        let proper_name = name.to_title_case();
-        let title = format!("Inside {}.", proper_name);
-        let external = format!(
-            "{} is a player. Players don't yet have a description.",
-            proper_name
-        );
-        let internal = format!(
-            "You are inside {}, the player. How did you get in here?",
-            proper_name
-        );
-        let lateral = format!("{}, a player, is here.", proper_name);
+    pub fn make_player_components(&mut self, name: &str, immortal: bool) -> Components {
+        let player = Player {
+            immortal,
+            holylight: false,
+            title: if immortal {
+                "the immortal".to_string()
+            } else {
+                "the adventurer".to_string()
+            },
+            gender: Gender::Plural,
+            short_description: None,
+            description: None,
+            quests: QuestLog::default(),
+            position: Position::Standing,
+            furniture: None,
+            channels: ChannelSettings::default(),
+            bank: 0,
+        };
+        let (act_info, descriptions) = player_descriptions(&mut self.interner, name, &player);
 
         Components {
-            act_info: self
-                .interner
-                .act_info(&keyword, &short_description, Gender::Male),
-            descriptions: self
-                .interner
-                .descriptions(&title, &internal, &external, &lateral),
+            act_info,
+            descriptions,
             general: GeneralData {
                 vnum: Vnum(0),
                 area: "players".to_string(),
@@ -209,12 +222,18 @@ impl EntityWorld {
                 equipped: None,
                 command_queue: Vec::new(),
                 following: None,
+                group: None,
             },
             mobile: None,
             object: None,
             door: None,
             mobprog: None,
-            silver: None,
+            coins: None,
+            room: None,
+            player: Some(player),
+            script: None,
+            variables: Variables::default(),
+            visibility: Visibility::default(),
         }
     }
 
@@ -388,6 +407,16 @@ impl EntityWorld {
         }
     }
 
+    /// Number of ticks since the world was created; used by things that
+    /// expire, like temporary variables.
+    pub fn current_tick(&self) -> u64 {
+        self.current_tick
+    }
+
+    pub fn advance_tick(&mut self) {
+        self.current_tick += 1;
+    }
+
     pub fn insert_entity(&mut self, container: EntityId, components: Components) -> EntityId {
         let container = self.raw_entity_id(container);
 
@@ -429,6 +458,45 @@ impl EntityWorld {
         self.entity_mut_raw(exit_id).leads_to = Some(to_room_id);
         self.entity_mut_raw(to_room_id).leads_from.push(exit_id);
     }
+
+    /// Remove an entity and everything inside of it, e.g. an exit deleted by a
+    /// builder. Exits leading to a removed room stop leading anywhere.
+    ///
+    /// This must not be used on players, and the IDs of removed entities must
+    /// not be used afterwards.
+    pub(crate) fn remove_entity(&mut self, entity_id: EntityId) {
+        let raw_entity_id = self.raw_entity_id(entity_id);
+        self.remove_entity_raw(raw_entity_id);
+    }
+
+    fn remove_entity_raw(&mut self, raw_entity_id: RawEntityId) {
+        let entity = match self.entities.remove(&raw_entity_id) {
+            Some(entity) => entity,
+            None => return,
+        };
+
+        if let Some(container) = entity.contained_by {
+            if let Some(container) = self.entities.get_mut(&container) {
+                container.contents.retain(|id| *id != raw_entity_id);
+            }
+        }
+
+        if let Some(leads_to) = entity.leads_to {
+            if let Some(room) = self.entities.get_mut(&leads_to) {
+                room.leads_from.retain(|id| *id != raw_entity_id);
+            }
+        }
+
+        for exit_id in entity.leads_from {
+            if let Some(exit) = self.entities.get_mut(&exit_id) {
+                exit.leads_to = None;
+            }
+        }
+
+        for contained_id in entity.contents {
+            self.remove_entity_raw(contained_id);
+        }
+    }
 }
 
 impl<'e> EntityInfoMut<'e> {
@@ -442,14 +510,6 @@ impl<'e> EntityInfoMut<'e> {
     pub fn components(&'e mut self) -> &'e mut Components {
         &mut self.entity.components
     }
-
-    pub fn set_short_description(
-        &mut self,
-        interner: &mut StringInterner,
-        short_description: &str,
-    ) {
-        interner.set_short_description(&mut self.entity.components.act_info, short_description);
-    }
 }
 
 impl<'e> EntityInfo<'e> {
@@ -497,6 +557,17 @@ impl<'e> EntityInfo<'e> {
         })
     }
 
+    /// The exit on the other side of this one, leading back here.
+    pub fn reverse_exit(&self) -> Option<EntityInfo<'e>> {
+        let other_room = self.entity_world.entity_info(self.leads_to()?);
+        let direction = opposite_direction(self.main_keyword());
+        let here = Some(self.room().entity_id());
+
+        other_room.exits().find(|other_exit| {
+            other_exit.main_keyword() == direction && other_exit.leads_to() == here
+        })
+    }
+
     pub fn room(&self) -> EntityInfo<'e> {
         EntityInfo {
             entity: self
@@ -506,6 +577,22 @@ impl<'e> EntityInfo<'e> {
         }
     }
 
+    /// The first container up the chain that is a room, or the entity itself
+    /// if it is a room.
+    pub fn enclosing_room(&self) -> EntityInfo<'e> {
+        let mut entity = self.clone();
+
+        while !entity.is_room() {
+            entity = entity.room();
+        }
+
+        entity
+    }
+
+    pub fn is_room(&self) -> bool {
+        matches!(self.entity.components.general.entity_type, EntityType::Room)
+    }
+
     pub fn is_exit(&self) -> bool {
         matches!(self.entity.components.general.entity_type, EntityType::Exit)
     }
@@ -538,12 +625,33 @@ impl<'e> EntityInfo<'e> {
         )
     }
 
+    pub fn player_name(&self) -> Option<&'e str> {
+        self.entity.player.as_deref()
+    }
+
+    pub fn is_immortal(&self) -> bool {
+        self.entity
+            .components
+            .player
+            .as_ref()
+            .map(|player| player.immortal)
+            .unwrap_or(false)
+    }
+
     pub fn is_player_with_name(&self, player_name: &str) -> bool {
         self.entity_world.player_entities.get(player_name) == Some(&self.entity.raw_entity_id)
     }
 
+    pub fn player_entity(&self, player_name: &str) -> Option<EntityInfo<'e>> {
+        let raw_entity_id = self.entity_world.player_entities.get(player_name)?;
+        Some(self.entity_world.entity_info_raw(*raw_entity_id))
+    }
+
     pub fn colocated_with_player(&self, player_name: &str) -> bool {
-        if let Some(room) = self.entity.contained_by {
+        // Players inside a room are the ones that see what the room does
+        if self.is_room() {
+            Some(&self.entity.raw_entity_id) == self.entity_world.player_locations.get(player_name)
+        } else if let Some(room) = self.entity.contained_by {
             Some(&room) == self.entity_world.player_locations.get(player_name)
         } else {
             false
@@ -605,42 +713,92 @@ impl<'e> EntityInfo<'e> {
         self.iter_by_type(EntityType::ExtraDescription)
     }
 
+    /// Entities that can be reached from here: the entity's own contents,
+    /// then its container's contents, and if the container is not a room
+    /// (e.g. an object carried by someone), the enclosing room's contents.
+    ///
+    /// A room only reaches its own contents.
+    fn nearby_entities(&self) -> impl Iterator<Item = EntityInfo<'e>> {
+        let container = if self.is_room() {
+            None
+        } else {
+            Some(self.room())
+        };
+        let outer_room = container
+            .as_ref()
+            .filter(|container| !container.is_room())
+            .map(|container| container.enclosing_room());
+
+        self.contained_entities_with_descriptions()
+            .chain(
+                container
+                    .into_iter()
+                    .flat_map(|container| container.contained_entities_with_descriptions()),
+            )
+            .chain(
+                outer_room
+                    .into_iter()
+                    .flat_map(|room| room.contained_entities_with_descriptions()),
+            )
+    }
+
     pub fn visible_entities<'a>(
         &'a self,
         keyword: &'a str,
     ) -> impl Iterator<Item = EntityInfo<'a>> + 'a {
-        let room = self.room();
-
-        let is_myself = ["me", "self", "myself"].contains(&keyword);
+        let target = Target::parse(keyword);
+        let sight = self.search_sight();
         let myself_id = self.entity_id();
+        // Multiple targets are limited only after other filters, by
+        // `find_targets_or`.
+        let (skip, take) = if target.is_multiple() {
+            (0, usize::MAX)
+        } else {
+            (target.nth() - 1, 1)
+        };
 
-        let inventory_and_room = self
-            .contained_entities_with_descriptions()
-            .chain(room.contained_entities_with_descriptions());
+        self.nearby_entities()
+            .filter(move |entity| sight.can_see(entity))
+            .filter(move |entity| {
+                if entity.entity_id() == myself_id {
+                    target.is_myself()
+                        || (!target.is_multiple()
+                            && target.matches(entity.component_info().keyword()))
+                } else {
+                    target.matches(entity.component_info().keyword())
+                }
+            })
+            .skip(skip)
+            .take(take)
+    }
 
-        inventory_and_room.filter(move |entity| {
-            if is_myself && entity.entity_id() == myself_id {
-                true
-            } else {
-                entity
-                    .component_info()
-                    .keyword()
-                    .split_whitespace()
-                    .any(|word| word.eq_ignore_ascii_case(keyword))
-            }
-        })
+    /// Find every entity nearby that matches a target like `all.sword`,
+    /// except for this one.
+    pub fn find_entities<F>(&self, keyword: &str, matcher: F) -> Vec<EntityInfo<'e>>
+    where
+        F: Fn(&EntityInfo) -> bool,
+    {
+        let target = Target::parse(keyword);
+        let sight = self.search_sight();
+
+        self.nearby_entities()
+            .filter(|entity| entity.entity_id() != self.entity_id())
+            .filter(|entity| sight.can_see(entity))
+            .filter(|entity| target.matches(entity.component_info().keyword()))
+            .filter(|entity| matcher(entity))
+            .take(target.limit())
+            .collect()
     }
 
     pub fn find_entity<F>(&self, keyword: &str, matcher: F) -> Found<'e>
     where
         F: Fn(&EntityInfo) -> bool,
     {
-        let room_id = self.entity_world.room_of(self.entity_id());
-        let room = self.entity_world.entity_info(room_id);
-
+        let target = Target::parse(keyword);
+        let sight = self.search_sight();
         let mut bad_result = None;
 
-        if ["me", "self", "myself"].contains(&keyword) {
+        if target.is_myself() {
             if matcher(self) {
                 return Found::Myself;
             } else {
@@ -648,18 +806,17 @@ impl<'e> EntityInfo<'e> {
             }
         }
 
-        let inventory_and_room = self
-            .contained_entities_with_descriptions()
-            .chain(room.contained_entities_with_descriptions());
-
-        for entity in inventory_and_room {
-            if entity
-                .component_info()
-                .keyword()
-                .split_whitespace()
-                .any(|word| word.eq_ignore_ascii_case(keyword))
-            {
+        // With `2.sword`, count only the swords that the matcher accepts.
+        let mut seen = 0;
+
+        for entity in self.nearby_entities() {
+            if sight.can_see(&entity) && target.matches(entity.component_info().keyword()) {
                 if matcher(&entity) {
+                    seen += 1;
+                    if seen < target.nth() {
+                        continue;
+                    }
+
                     return if entity.entity_id() == self.entity_id() {
                         Found::Myself
                     } else {
@@ -1,7 +1,123 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Something was skipped, but the rest of the file was loaded
+    Warning,
+    /// The whole file was skipped
+    Error,
+}
+
+/// Something wrong with a data file, and where it was found.
+#[derive(Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file_name: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    /// The line the problem was found on
+    pub context: String,
+}
+
+impl Diagnostic {
+    /// A problem with a whole file, or with something in it that doesn't
+    /// have a line number anymore, like a room's exit.
+    pub fn about_file(severity: Severity, file_name: &str, message: String) -> Diagnostic {
+        Diagnostic {
+            severity,
+            file_name: file_name.to_string(),
+            line: 0,
+            column: 0,
+            message,
+            context: String::new(),
+        }
+    }
+
+    /// `file:line:column`, or just the file if there's no line.
+    pub fn location(&self) -> String {
+        if self.line == 0 {
+            self.file_name.clone()
+        } else {
+            format!("{}:{}:{}", self.file_name, self.line, self.column)
+        }
+    }
+
+    /// An error for a file that couldn't be read at all.
+    pub fn unreadable(file_name: &str, error: &dyn Display) -> Diagnostic {
+        let message = format!("Could not read file: {}", error);
+        Diagnostic::about_file(Severity::Error, file_name, message)
+    }
+
+    /// An error for a TOML file that couldn't be deserialized, at the place
+    /// where it went wrong.
+    pub(crate) fn from_toml(error: toml::de::Error, contents: &str, file_name: &str) -> Diagnostic {
+        let message = error.message().to_string();
+        let mut diagnostic = Diagnostic::about_file(Severity::Error, file_name, message);
+
+        if let Some(span) = error.span() {
+            let line_start = contents[..span.start].rfind('\n').map_or(0, |end| end + 1);
+            diagnostic.line = contents[..line_start].matches('\n').count() + 1;
+            diagnostic.column = contents[line_start..span.start].chars().count();
+            diagnostic.context = contents[line_start..]
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+        }
+
+        diagnostic
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+
+        write!(f, "{}: {}: {}", self.location(), severity, self.message)?;
+        if !self.context.trim().is_empty() {
+            write!(f, "\n    {}", self.context.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// All diagnostics, one per line, followed by a count of errors and warnings,
+/// or `None` if there are none.
+pub fn diagnostics_report(diagnostics: &[Diagnostic]) -> Option<String> {
+    if diagnostics.is_empty() {
+        return None;
+    }
+
+    let mut report = String::new();
+    for diagnostic in diagnostics {
+        report.push_str(&format!("{}\n", diagnostic));
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    report.push_str(&format!(
+        "Loaded with {} error(s) and {} warning(s).\n",
+        errors, warnings
+    ));
+
+    Some(report)
+}
+
 pub(crate) struct FileParser<'a> {
     remaining: &'a str,
     all_text: &'a str,
     file_name: &'a str,
+    /// Problems that didn't stop the file from being read
+    pub warnings: Vec<Diagnostic>,
 }
 
 impl<'a> FileParser<'a> {
@@ -10,64 +126,142 @@ impl<'a> FileParser<'a> {
             remaining: text,
             all_text: text,
             file_name,
+            warnings: Vec::new(),
         }
     }
 
-    pub fn panic_on_line(&self, message: &str) -> ! {
+    fn diagnostic(&self, severity: Severity, message: &str) -> Diagnostic {
         let bytes_read = self.all_text.len() - self.remaining.len();
         let processed_slice = &self.all_text[0..bytes_read];
-        let lines = processed_slice.chars().filter(|c| *c == '\n').count();
-        let columns = processed_slice
+
+        // The line being read, or the last one read if at the start of a line
+        let line_end = processed_slice.trim_end_matches(['\r', '\n']).len();
+        let line_start = processed_slice[..line_end]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let line_end = self.all_text[line_start..]
+            .find('\n')
+            .map_or(self.all_text.len(), |index| line_start + index);
+        let context = &self.all_text[line_start..line_end];
+
+        let line = processed_slice[..line_start]
+            .chars()
+            .filter(|c| *c == '\n')
+            .count();
+        let column = self.all_text[line_start..bytes_read.min(line_end)]
             .chars()
-            .rev()
-            .take_while(|c| *c != '\n')
             .count();
-        let last_line = &processed_slice[processed_slice.len() - columns..];
 
-        panic!(
-            "In file {}, line {}, column {}: {}\nLast line: {:?}\n",
-            self.file_name,
-            lines + 1,
-            columns,
-            message,
-            last_line
-        );
+        Diagnostic {
+            severity,
+            file_name: self.file_name.to_string(),
+            line: line + 1,
+            column,
+            message: message.to_string(),
+            context: context.trim_end_matches('\r').to_string(),
+        }
+    }
+
+    pub fn file_name(&self) -> &'a str {
+        self.file_name
+    }
+
+    /// An error at the current position, for a file that can't be loaded.
+    pub fn error(&self, message: &str) -> Diagnostic {
+        self.diagnostic(Severity::Error, message)
     }
 
-    pub fn read_section(&mut self) -> &'a str {
-        let start = self.remaining.find(|c: char| !c.is_whitespace()).unwrap();
+    /// Note a problem at the current position, and keep going.
+    pub fn warn(&mut self, message: &str) {
+        let warning = self.diagnostic(Severity::Warning, message);
+        self.warnings.push(warning);
+    }
+
+    /// Parse a value that was just read, e.g. a number.
+    pub fn parse<T: FromStr>(&self, value: &str, what: &str) -> Result<T, Diagnostic> {
+        value
+            .trim()
+            .parse()
+            .map_err(|_| self.error(&format!("Invalid {}: '{}'", what, value.trim())))
+    }
+
+    pub fn read_section(&mut self) -> Result<&'a str, Diagnostic> {
+        let start = self
+            .remaining
+            .find(|c: char| !c.is_whitespace())
+            .ok_or_else(|| self.error("Unexpected end of file, expected a '#' section"))?;
         let end = self.remaining[start..]
             .find(|c: char| c.is_whitespace())
-            .unwrap();
+            .unwrap_or(self.remaining.len() - start);
 
-        if &self.remaining[start..start + 1] != "#" {
-            self.panic_on_line(&format!(
-                "Expected '#', got '{}'",
-                &self.remaining[start..start + 1]
-            ))
+        if !self.remaining[start..].starts_with('#') {
+            self.remaining = &self.remaining[start..];
+            let found = self.remaining.chars().next().unwrap_or_default();
+            return Err(self.error(&format!("Expected '#', got '{}'", found)));
         }
 
         let section = &self.remaining[start + 1..start + end];
         self.remaining = &self.remaining[start + end..];
-        self.skip_one_newline();
-        section
+        self.skip_one_newline()?;
+        Ok(section)
     }
 
-    pub fn read_word(&mut self) -> &'a str {
+    /// Skip lines until the next one that starts a named section, like
+    /// `#ROOMS`, or ends the file with `#$`.
+    pub fn skip_to_next_section(&mut self) {
+        loop {
+            let line = self.remaining.trim_start();
+            let is_section = line
+                .strip_prefix('#')
+                .and_then(|section| section.chars().next())
+                .is_some_and(|first| first == '$' || first.is_ascii_uppercase());
+
+            if is_section || self.remaining.is_empty() {
+                break;
+            }
+
+            self.remaining = match self.remaining.find('\n') {
+                Some(end) => &self.remaining[end + 1..],
+                None => "",
+            };
+        }
+    }
+
+    pub fn read_word(&mut self) -> Result<&'a str, Diagnostic> {
         let start = self
             .remaining
             .find(|c: char| !c.is_ascii_whitespace())
-            .unwrap();
+            .ok_or_else(|| self.error("Unexpected end of file, expected a word"))?;
         let end = self.remaining[start..]
             .find(|c: char| c.is_ascii_whitespace())
-            .unwrap();
+            .unwrap_or(self.remaining.len() - start);
 
         let section = &self.remaining[start..start + end];
         self.remaining = &self.remaining[start + end..];
-        section
+        Ok(section)
+    }
+
+    /// The next word, without reading it.
+    pub fn peek_word(&self) -> Option<&'a str> {
+        self.remaining.split_ascii_whitespace().next()
     }
 
-    pub fn skip_one_newline(&mut self) {
+    /// Skip any whitespace, then read up to a `~`, like ROM and Merc's
+    /// `fread_string`. Unlike `read_until_tilde`, anything can follow it.
+    pub fn read_string(&mut self) -> Result<&'a str, Diagnostic> {
+        self.skip_all_space();
+
+        let end = self
+            .remaining
+            .find('~')
+            .ok_or_else(|| self.error("Missing '~' at the end of a text value"))?;
+
+        let section = &self.remaining[..end];
+        self.remaining = &self.remaining[end + 1..];
+        Ok(section)
+    }
+
+    pub fn skip_one_newline(&mut self) -> Result<(), Diagnostic> {
         if self.remaining.is_empty() {
             // Nothing to skip
         } else if self.remaining.starts_with('\r') {
@@ -78,15 +272,19 @@ impl<'a> FileParser<'a> {
         } else if self.remaining.starts_with('\n') {
             self.remaining = &self.remaining[1..];
         } else {
-            self.panic_on_line("No newline found to skip");
+            return Err(self.error("Expected the end of the line"));
         }
+
+        Ok(())
     }
 
-    pub fn skip_one_space(&mut self) {
+    pub fn skip_one_space(&mut self) -> Result<(), Diagnostic> {
         if self.remaining.chars().next() != Some(' ') {
-            self.panic_on_line(&format!("Expected ' ', got '{}'", &self.remaining[..1]))
+            let found = self.remaining.chars().next().unwrap_or_default();
+            return Err(self.error(&format!("Expected ' ', got '{}'", found)));
         }
         self.remaining = &self.remaining[1..];
+        Ok(())
     }
 
     pub fn skip_all_space(&mut self) {
@@ -97,26 +295,33 @@ impl<'a> FileParser<'a> {
         self.remaining = &self.remaining[start..];
     }
 
-    pub fn read_until_newline(&mut self) -> &'a str {
-        let end = self.remaining.find('\n').unwrap();
+    pub fn read_until_newline(&mut self) -> Result<&'a str, Diagnostic> {
+        if self.remaining.is_empty() {
+            return Err(self.error("Unexpected end of file, expected a line"));
+        }
+
+        let end = self.remaining.find('\n').unwrap_or(self.remaining.len());
 
         let section = &self.remaining[..end];
         self.remaining = &self.remaining[end..];
-        self.skip_one_newline();
+        self.skip_one_newline()?;
 
         if section.ends_with('\r') {
-            &section[..section.len() - 1]
+            Ok(&section[..section.len() - 1])
         } else {
-            section
+            Ok(section)
         }
     }
 
-    pub fn read_until_tilde(&mut self) -> &'a str {
-        let end = self.remaining.find('~').unwrap();
+    pub fn read_until_tilde(&mut self) -> Result<&'a str, Diagnostic> {
+        let end = self
+            .remaining
+            .find('~')
+            .ok_or_else(|| self.error("Missing '~' at the end of a text value"))?;
 
         let section = &self.remaining[..end];
         self.remaining = &self.remaining[end + 1..];
-        self.skip_one_newline();
-        section
+        self.skip_one_newline()?;
+        Ok(section)
     }
 }
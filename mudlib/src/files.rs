@@ -1,4 +1,4 @@
-//! Abstraction for reading files.
+//! Abstraction for reading and writing files.
 //!
 //! This is used to emulate reading files on WASM in a browser, while using the
 //! real filesystem otherwise.
@@ -15,9 +15,17 @@ pub trait Files {
         let string = String::from_utf8_lossy(&bytes);
         Ok(fix_newlines(&string).into_owned())
     }
+
+    /// Replace a file's contents, creating it if needed.
+    ///
+    /// By default files can't be written, e.g. when they're embedded into the
+    /// binary.
+    fn write_file(&self, _path: &str, _contents: &str) -> Result<(), std::io::Error> {
+        Err(std::io::Error::other("files can't be written here"))
+    }
 }
 
-pub(crate) struct RealFiles;
+pub struct RealFiles;
 
 impl Files for RealFiles {
     fn read_file_raw(&self, path: &str) -> Result<Vec<u8>, std::io::Error> {
@@ -29,6 +37,20 @@ impl Files for RealFiles {
 
         Ok(bytes)
     }
+
+    fn write_file(&self, path: &str, contents: &str) -> Result<(), std::io::Error> {
+        let path = Path::new(path);
+
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+
+        // Write to a temporary file first, so that a failed write doesn't
+        // leave a truncated file behind.
+        let temporary_path = path.with_extension("tmp");
+        std::fs::write(&temporary_path, contents)?;
+        std::fs::rename(&temporary_path, path)
+    }
 }
 
 fn valid_newlines(text: &str) -> bool {
@@ -13,9 +13,8 @@
 //! ```ignore
 //! let found = myself
 //!     .contained_entities()  -> impl Iterator<Item = EntityInfo>
-//!     .filter_by_keyword(target)  -> impl Iterator<Item = MatchCandidate>
+//!     .filter_by_keyword(&myself, target)  -> impl Iterator<Item = MatchCandidate>
 //!     .filter_or(|e| e.is_mobile() || e.is_player(), "$^$N is not a creature!")  -> impl Iterator<Item = MatchCandidate>
-//!     .prefer(|e| e.is_player())  -> impl Iterator<Item = MatchCandidate>
 //!     .filter_or(|e| *e != myself, "You can't do that with yourself!")  -> impl Iterator<Item = MatchCandidate>
 //!     .find_one_or("You don't see anything here!");  -> Result<EntityInfo, MatchError>
 //!
@@ -33,12 +32,23 @@
 //!
 //! If no match is found, a `MatchCandidate` that matched the most filters
 //! before being turned into an error is used to get an error message.
+//!
+//! The keyword itself is a `Target`, which understands a small grammar shared
+//! by all commands:
+//!
+//! * `sword` - the first thing with a keyword starting with "sword"
+//! * `2.sword` - the second one
+//! * `all.sword` - every one of them
+//! * `all` - everything
+//! * `5 swords` - up to five of them
+//! * `"long sword"` - something with keywords starting with both words
 
 use std::marker::PhantomData;
 
 use crate::{
-    components::ComponentFromEntity,
+    components::{Coins, ComponentFromEntity},
     entity::{EntityId, EntityInfo},
+    visibility::Sight,
 };
 
 pub(crate) enum MatchError {
@@ -49,12 +59,160 @@ pub(crate) enum MatchError {
     MessageWithActor(&'static str, EntityId),
 }
 
+/// A parsed target expression; see the module documentation.
+pub(crate) struct Target<'t> {
+    keywords: &'t str,
+    amount: Amount,
+}
+
+#[derive(Clone, Copy)]
+enum Amount {
+    /// Only the n-th match, starting from 1
+    Nth(usize),
+    /// Every match
+    All,
+    /// At most this many matches
+    Count(usize),
+}
+
+impl<'t> Target<'t> {
+    pub fn parse(expression: &'t str) -> Self {
+        let expression = expression.trim();
+
+        let (amount, keywords) = if expression.eq_ignore_ascii_case("all") {
+            (Amount::All, "")
+        } else if let Some(keywords) = strip_prefix_ignore_case(expression, "all.") {
+            (Amount::All, keywords)
+        } else {
+            match expression.split_once(['.', ' ']) {
+                Some((number, keywords)) => match number.parse::<usize>() {
+                    Ok(n) if n > 0 && expression.as_bytes()[number.len()] == b'.' => {
+                        (Amount::Nth(n), keywords)
+                    }
+                    Ok(n) if n > 0 => (Amount::Count(n), keywords),
+                    _ => (Amount::Nth(1), expression),
+                },
+                None => (Amount::Nth(1), expression),
+            }
+        };
+
+        let keywords = keywords.trim().trim_matches('"').trim();
+
+        Target { keywords, amount }
+    }
+
+    /// Whether this is `me`, `self`, or `myself`.
+    pub fn is_myself(&self) -> bool {
+        matches!(self.amount, Amount::Nth(1))
+            && ["me", "self", "myself"]
+                .iter()
+                .any(|word| word.eq_ignore_ascii_case(self.keywords))
+    }
+
+    /// Whether this can refer to more than one entity, like `all.sword`.
+    pub fn is_multiple(&self) -> bool {
+        matches!(self.amount, Amount::All | Amount::Count(_))
+    }
+
+    /// Which keyword match to pick when only one is wanted, starting from 1.
+    pub fn nth(&self) -> usize {
+        match self.amount {
+            Amount::Nth(n) => n,
+            Amount::All | Amount::Count(_) => 1,
+        }
+    }
+
+    /// How many entities this can refer to at most.
+    pub fn limit(&self) -> usize {
+        match self.amount {
+            Amount::Nth(_) => 1,
+            Amount::All => usize::MAX,
+            Amount::Count(n) => n,
+        }
+    }
+
+    /// Check an entity's keywords; every word in the target must be the
+    /// start of one of them, so `lo sw` matches "long sword". Without any
+    /// words, only `all` matches anything; `2.` matches nothing.
+    pub fn matches(&self, entity_keywords: &str) -> bool {
+        if self.keywords.is_empty() {
+            return matches!(self.amount, Amount::All);
+        }
+
+        self.keywords.split_whitespace().all(|word| {
+            // Allow the plural in "5 swords" or "all.swords"
+            let singular = match self.amount {
+                Amount::Nth(_) => None,
+                Amount::All | Amount::Count(_) => {
+                    word.strip_suffix('s').filter(|word| !word.is_empty())
+                }
+            };
+
+            entity_keywords.split_whitespace().any(|keyword| {
+                strip_prefix_ignore_case(keyword, word).is_some()
+                    || singular.is_some_and(|word| keyword.eq_ignore_ascii_case(word))
+            })
+        })
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let start = text.get(..prefix.len())?;
+
+    if start.eq_ignore_ascii_case(prefix) {
+        Some(&text[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// Put back together words that make up a single target, since commands are
+/// split on whitespace: `"long sword"` in quotes, and the `5 apples` in
+/// commands that can take several objects at once, unless those are coins.
+pub(crate) fn join_target_words(words: &[&str]) -> Option<Vec<String>> {
+    let mut joined: Vec<String> = Vec::with_capacity(words.len());
+    let mut changed = false;
+    let mut in_quotes = false;
+
+    for (index, word) in words.iter().enumerate() {
+        let quantity = index == 2
+            && ["get", "drop", "give", "put"].contains(&words[0])
+            && words[1].parse::<usize>().is_ok()
+            && word.parse::<usize>().is_err()
+            && Coins::parse(words[1], word).is_none();
+
+        if in_quotes || quantity {
+            let last = joined.last_mut().expect("Joined with previous word");
+            last.push(' ');
+            last.push_str(word);
+            changed = true;
+        } else {
+            joined.push(word.to_string());
+        }
+
+        if in_quotes {
+            in_quotes = !word.ends_with('"');
+        } else if word.starts_with('"') {
+            in_quotes = word.len() == 1 || !word.ends_with('"');
+        }
+    }
+
+    if changed {
+        Some(joined)
+    } else {
+        None
+    }
+}
+
 pub(crate) enum MatchCandidate<'e, Component> {
     GoodMatch {
         entity: EntityInfo<'e>,
         matched_conditions: u8,
         preferred: Option<bool>,
         component: &'e Component,
+        /// Which good match to pick when only one is wanted, like 2 for
+        /// `2.sword`. Counted once every filter has run.
+        nth: usize,
     },
     BadMatch {
         entity: EntityInfo<'e>,
@@ -80,6 +238,7 @@ impl<'e> IntoMatchCandidate<'e> for EntityInfo<'e> {
             component: &(),
             matched_conditions: 0,
             preferred: None,
+            nth: 1,
         }
     }
 }
@@ -94,7 +253,8 @@ impl<'e, T> IntoMatchCandidate<'e> for MatchCandidate<'e, T> {
 
 pub(crate) struct FilterByKeyword<'k, I> {
     inner: I,
-    keyword: &'k str,
+    sight: Sight,
+    target: Target<'k>,
 }
 
 impl<'q, I> Iterator for FilterByKeyword<'q, I>
@@ -104,17 +264,26 @@ where
     type Item = MatchCandidate<'q, ()>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next_match_candidate().filter(|item| {
-            let entity = match item {
+        loop {
+            let mut item = self.inner.next_match_candidate()?;
+            let entity = match &item {
                 GoodMatch { entity, .. } => entity,
                 BadMatch { entity, .. } => entity,
             };
-            entity
-                .component_info()
-                .keyword()
-                .split_whitespace()
-                .any(|word| word.eq_ignore_ascii_case(self.keyword))
-        })
+
+            if !self.sight.can_see(entity)
+                || !self.target.matches(entity.component_info().keyword())
+            {
+                continue;
+            }
+
+            // With `2.sword`, the swords are only counted once the later
+            // filters have turned away the ones that don't fit.
+            if let GoodMatch { nth, .. } = &mut item {
+                *nth = self.target.nth();
+            }
+            return Some(item);
+        }
     }
 }
 
@@ -139,6 +308,7 @@ where
                 matched_conditions,
                 preferred,
                 component,
+                nth,
             } => {
                 if (self.predicate)(&entity) {
                     GoodMatch {
@@ -146,6 +316,7 @@ where
                         matched_conditions: matched_conditions + 1,
                         preferred,
                         component,
+                        nth,
                     }
                 } else {
                     BadMatch {
@@ -160,35 +331,6 @@ where
     }
 }
 
-pub(crate) struct Prefer<'q, I: 'q, P> {
-    inner: I,
-    predicate: P,
-    shadow: PhantomData<&'q ()>,
-}
-
-impl<'q, I, P> Iterator for Prefer<'q, I, P>
-where
-    I: EntityIterator<'q, ()>,
-    P: Fn(&EntityInfo<'q>) -> bool,
-{
-    type Item = MatchCandidate<'q, ()>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next_match_candidate().map(|mut item| {
-            match &mut item {
-                GoodMatch {
-                    entity, preferred, ..
-                } => {
-                    let new_preferred = (self.predicate)(&entity);
-                    *preferred = Some(new_preferred);
-                }
-                BadMatch { .. } => (),
-            }
-            item
-        })
-    }
-}
-
 pub(crate) struct PreferComponent<'q, I: 'q, P, C> {
     inner: I,
     predicate: P,
@@ -211,7 +353,7 @@ where
                     component,
                     ..
                 } => {
-                    let new_preferred = (self.predicate)(&entity, component);
+                    let new_preferred = (self.predicate)(entity, component);
                     *preferred = Some(new_preferred);
                 }
                 BadMatch { .. } => (),
@@ -242,6 +384,7 @@ where
                 matched_conditions,
                 preferred,
                 component: _,
+                nth,
             } => {
                 if let Some(component) = C2::component_from_entity(&entity) {
                     GoodMatch {
@@ -249,6 +392,7 @@ where
                         component,
                         matched_conditions: matched_conditions + 1,
                         preferred,
+                        nth,
                     }
                 } else {
                     BadMatch {
@@ -296,6 +440,7 @@ where
                         matched_conditions,
                         preferred,
                         component: _,
+                        nth,
                     } => {
                         if let Some(component) = C2::component_from_entity(&entity) {
                             GoodMatch {
@@ -303,6 +448,7 @@ where
                                 component,
                                 matched_conditions: matched_conditions + 1,
                                 preferred,
+                                nth,
                             }
                         } else {
                             continue;
@@ -336,14 +482,6 @@ where
 pub(crate) trait EntityIterator<'e, C: 'e>: Sized {
     fn next_match_candidate(&mut self) -> Option<MatchCandidate<'e, C>>;
 
-    fn prefer<'p, P: Fn(&EntityInfo<'p>) -> bool>(self, prefer: P) -> Prefer<'p, Self, P> {
-        Prefer {
-            inner: self,
-            predicate: prefer,
-            shadow: PhantomData::default(),
-        }
-    }
-
     fn prefer_component<'p, P: Fn(&EntityInfo<'p>, &C) -> bool>(
         self,
         prefer: P,
@@ -351,7 +489,7 @@ pub(crate) trait EntityIterator<'e, C: 'e>: Sized {
         PreferComponent {
             inner: self,
             predicate: prefer,
-            shadow: PhantomData::default(),
+            shadow: PhantomData,
         }
     }
 
@@ -362,23 +500,29 @@ pub(crate) trait EntityIterator<'e, C: 'e>: Sized {
         WithComponentOrError {
             inner: self,
             error,
-            shadow1: PhantomData::default(),
-            shadow2: PhantomData::default(),
+            shadow1: PhantomData,
+            shadow2: PhantomData,
         }
     }
 
     fn with_component<NewComponent>(self) -> WithComponent<'e, Self, C, NewComponent> {
         WithComponent {
             inner: self,
-            shadow1: PhantomData::default(),
-            shadow2: PhantomData::default(),
+            shadow1: PhantomData,
+            shadow2: PhantomData,
         }
     }
 
-    fn filter_by_keyword(self, keyword: &str) -> FilterByKeyword<'_, Self> {
+    /// Keep only what the viewer can see and matches the keyword.
+    fn filter_by_keyword<'k>(
+        self,
+        viewer: &EntityInfo,
+        keyword: &'k str,
+    ) -> FilterByKeyword<'k, Self> {
         FilterByKeyword {
             inner: self,
-            keyword,
+            sight: viewer.search_sight(),
+            target: Target::parse(keyword),
         }
     }
 
@@ -391,7 +535,7 @@ pub(crate) trait EntityIterator<'e, C: 'e>: Sized {
             inner: self,
             predicate: filter,
             error,
-            shadow: PhantomData::default(),
+            shadow: PhantomData,
         }
     }
 
@@ -402,9 +546,22 @@ pub(crate) trait EntityIterator<'e, C: 'e>: Sized {
         let mut bad_match = None;
         let mut bad_match_conditions = 0;
         let mut unpreferred_match = None;
+        let mut seen = 0;
 
         while let Some(item) = self.next_match_candidate() {
             match item {
+                // With `2.sword`, count the swords that passed every filter
+                GoodMatch {
+                    entity,
+                    component,
+                    nth,
+                    ..
+                } if nth > 1 => {
+                    seen += 1;
+                    if seen == nth {
+                        return Ok((entity, component));
+                    }
+                }
                 GoodMatch {
                     entity,
                     preferred: Some(false),
@@ -440,6 +597,51 @@ pub(crate) trait EntityIterator<'e, C: 'e>: Sized {
         }
     }
 
+    /// Find what a target like `all.sword` refers to: every good match if it
+    /// can refer to several entities, or just one of them otherwise.
+    fn find_targets_or(
+        mut self,
+        keyword: &str,
+        error: &'static str,
+    ) -> Result<Vec<EntityInfo<'e>>, MatchError> {
+        let target = Target::parse(keyword);
+        if !target.is_multiple() {
+            return self.find_one_or(error).map(|entity| vec![entity]);
+        }
+
+        let mut found = Vec::new();
+        let mut bad_match = None;
+        let mut bad_match_conditions = 0;
+
+        while let Some(item) = self.next_match_candidate() {
+            if found.len() >= target.limit() {
+                break;
+            }
+
+            match item {
+                GoodMatch { entity, .. } => found.push(entity),
+                BadMatch {
+                    matched_conditions,
+                    error,
+                    ..
+                } => {
+                    if matched_conditions >= bad_match_conditions {
+                        bad_match = Some(error);
+                        bad_match_conditions = matched_conditions;
+                    }
+                }
+            }
+        }
+
+        if !found.is_empty() {
+            Ok(found)
+        } else if let Some(bad_match) = bad_match {
+            Err(bad_match)
+        } else {
+            Err(MatchError::Message(error))
+        }
+    }
+
     fn find_one_or(self, error: &'static str) -> Result<EntityInfo<'e>, MatchError> {
         self.find_one_with_component_or(error)
             .map(|result| result.0)
@@ -0,0 +1,277 @@
+//! Groups of players and mobiles that travel, talk and get rewarded together.
+//!
+//! Anyone can `follow` someone else, and whoever is being followed can then
+//! `group` their followers. Members of a group can talk with `gtell` from
+//! anywhere, and see how everyone is doing with `group`. When members are in
+//! the same room, silver picked up by one of them is split with the others,
+//! and a kill counts for everyone's quests.
+//!
+//! Both following and groups refer to others by `PermanentEntityId`, so that
+//! two mobiles with the same keyword can't be mixed up.
+
+use crate::{
+    agent::EntityAgent,
+    colors::recolor,
+    components::Coins,
+    echo,
+    entity::{EntityId, EntityInfo, Found, PermanentEntityId},
+    mobprogs::Action,
+};
+
+const GROUP_COLOR: &str = "`G";
+
+impl<'e> EntityInfo<'e> {
+    /// The leader of the group this entity is in; an entity that wasn't
+    /// accepted into anyone's group leads its own.
+    pub fn group_leader(&self) -> PermanentEntityId {
+        self.components()
+            .general
+            .group
+            .unwrap_or_else(|| self.permanent_entity_id())
+    }
+
+    pub fn is_same_group(&self, other: &EntityInfo) -> bool {
+        self.group_leader() == other.group_leader()
+    }
+
+    /// Other players in the same group and the same room.
+    fn group_players_here(&self) -> Vec<EntityId> {
+        self.room()
+            .players()
+            .filter(|player| player != self && player.is_same_group(self))
+            .map(|player| player.entity_id())
+            .collect()
+    }
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    pub fn do_group(&mut self, target: Option<&str>) {
+        let target = match target {
+            Some(target) => target,
+            None => return self.do_group_list(),
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        if myself.components().general.following.is_some() {
+            echo!(
+                self.info(),
+                "You can't lead a group while following someone else.\r\n"
+            );
+            return;
+        }
+
+        let target = match myself.find_entity(target, |e| e.is_mobile() || e.is_player()) {
+            Found::Other(other) => other,
+            Found::Myself | Found::WrongSelf => {
+                echo!(self.info(), "You are always in your own group.\r\n");
+                return;
+            }
+            Found::Nothing | Found::WrongOther(_) => {
+                echo!(self.info(), "You don't see anyone like that here.\r\n");
+                return;
+            }
+        };
+
+        let leader_id = myself.permanent_entity_id();
+        if target.components().general.following != Some(leader_id) {
+            let mut act = self.players.act_with(&myself, &target);
+            echo!(act.myself(), "$^$N isn't following you.\r\n");
+            return;
+        }
+
+        let joining = target.components().general.group != Some(leader_id);
+
+        let mut act = self.players.act_with(&myself, &target);
+        if joining {
+            echo!(act.myself(), "$^$N joins your group.\r\n");
+            echo!(act.target(), "You join $n's group.\r\n");
+            echo!(act.others(), "$^$N joins $n's group.\r\n");
+        } else {
+            echo!(act.myself(), "You remove $N from your group.\r\n");
+            echo!(act.target(), "$^$n removes you from $s group.\r\n");
+            echo!(act.others(), "$^$n removes $N from $s group.\r\n");
+        }
+
+        let target_id = target.entity_id();
+        let mut target = self.entity_world.entity_info_mut(target_id);
+        target.components().general.group = if joining { Some(leader_id) } else { None };
+    }
+
+    fn do_group_list(&mut self) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let leader_id = myself.group_leader();
+
+        let mut members: Vec<_> = self
+            .entity_world
+            .all_entities()
+            .filter(|entity| entity.is_mobile() || entity.is_player())
+            .filter(|entity| entity.is_same_group(&myself))
+            .collect();
+
+        if members.len() < 2 {
+            echo!(self.info(), "You are not in a group.\r\n");
+            return;
+        }
+
+        // Leader first
+        members.sort_by_key(|member| member.permanent_entity_id() != leader_id);
+
+        let mut info = self.players.info(&myself);
+        echo!(info, "Your group:\r\n");
+        for member in members {
+            let rank = if member.permanent_entity_id() == leader_id {
+                "Leader"
+            } else {
+                "Member"
+            };
+
+            echo!(
+                info,
+                "  [{}] `M{:<20}`^ {:<9} {}\r\n",
+                rank,
+                member.component_info().short_description(),
+                member.position().describe(),
+                member.room().component_info().internal_title(),
+            );
+        }
+    }
+
+    pub fn do_gtell(&mut self, message: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let members: Vec<String> = self
+            .players
+            .player_echoes
+            .keys()
+            .filter_map(|name| self.entity_world.player_entity_id(name))
+            .map(|player_id| self.entity_world.entity_info(player_id))
+            .filter(|player| player != &myself && player.is_same_group(&myself))
+            .filter(|player| player.is_awake())
+            .filter_map(|player| player.player_name().map(String::from))
+            .collect();
+
+        let grouped = self
+            .entity_world
+            .all_entities()
+            .any(|entity| entity != myself && entity.is_same_group(&myself));
+
+        if !grouped {
+            echo!(self.info(), "You are not in a group.\r\n");
+            return;
+        }
+
+        let message = recolor(GROUP_COLOR, message);
+        let short_description = myself.component_info().short_description();
+
+        for name in members {
+            if let Some(player_echo) = self.players.player_echoes.get_mut(&name) {
+                echo!(
+                    player_echo.echo_buffer,
+                    "{}{} tells the group, '{}'`^\r\n",
+                    GROUP_COLOR,
+                    short_description,
+                    message
+                );
+            }
+        }
+
+        echo!(
+            self.players.info(&myself),
+            "{}You tell the group, '{}'`^\r\n",
+            GROUP_COLOR,
+            message
+        );
+    }
+
+    pub fn do_split(&mut self, amount: &str, denomination: &str) {
+        let coins = match Coins::parse(amount, denomination) {
+            Some(coins) => coins,
+            None => {
+                echo!(self.info(), "How many coins do you want to split?\r\n");
+                return;
+            }
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        if myself.group_players_here().is_empty() {
+            echo!(
+                self.info(),
+                "There is no one in your group here to split it with.\r\n"
+            );
+            return;
+        }
+
+        let value = match self.coins_value(coins) {
+            Some(value) => value,
+            None => return,
+        };
+
+        if !self.split_coins(value) {
+            echo!(self.info(), "You don't have that much money.\r\n");
+        }
+    }
+
+    /// Give the other players of the group in this room an equal share of
+    /// some of the value of the current entity's coins.
+    pub fn split_coins(&mut self, value: usize) -> bool {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let members = myself.group_players_here();
+
+        let share = value / (members.len() + 1);
+        if members.is_empty() || share == 0 {
+            return true;
+        }
+
+        if !self.pay(share * members.len(), self.entity_id) {
+            return false;
+        }
+
+        let total = Coins::from_value(value);
+        let kept = Coins::from_value(value - share * members.len());
+        let share = Coins::from_value(share);
+
+        echo!(self.info(), "You split {}, and keep {}.\r\n", total, kept);
+
+        for member_id in members {
+            self.add_coins(share, member_id);
+
+            let myself = self.entity_world.entity_info(self.entity_id);
+            let member = self.entity_world.entity_info(member_id);
+            let mut act = self.players.act_with(&myself, &member);
+            echo!(
+                act.target(),
+                "$^$n splits {}; your share is {}.\r\n",
+                total,
+                share
+            );
+        }
+
+        true
+    }
+
+    /// Coins picked up join the ones already carried, and are then split
+    /// with the group.
+    pub fn collect_coins(&mut self, pile_id: EntityId) {
+        let pile = self.entity_world.entity_info(pile_id);
+
+        let coins = match pile.components().coins {
+            Some(coins) if pile.room().entity_id() == self.entity_id => coins,
+            _ => return,
+        };
+
+        self.merge_coins(pile_id);
+        self.split_coins(coins.value().unwrap_or(usize::MAX));
+    }
+
+    /// Let the other players of the group in this room count a kill for
+    /// their quests.
+    pub fn share_kill(&mut self, victim_id: EntityId) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        for member_id in myself.group_players_here() {
+            self.switch_agent(member_id)
+                .update_quests(&Action::Kill { victim_id }, None);
+        }
+    }
+}
@@ -9,17 +9,25 @@ use std::collections::HashMap;
 use string_interner::StringInterner;
 
 use crate::{
-    components::{Components, Door, EntityType, GeneralData, InternComponent, MobProg},
+    components::{
+        Components, Door, DrinkContainer, EntityType, Furniture, GeneralData, InternComponent,
+        Light, MobProg, Portal, Position, Script, Spells, Variables, Visibility,
+    },
     entity::{EntityId, EntityWorld, PermanentEntityId},
+    money::{insert_coin_pile, random_wealth},
     state::Area,
-    world::{Gender, MobProgTrigger, Mobile, Object, ObjectFlags, ResetCommand, Vnum, World},
+    world::{
+        Exit, ExtraDescription, Gender, MobProgTrigger, Mobile, Object, ObjectFlags, ResetCommand,
+        Room, Vnum, World,
+    },
 };
 
+/// Everything that can be created again by vnum after the world is imported.
 pub(crate) struct VnumTemplates {
-    pub vnum_to_room_entity: Vec<Option<PermanentEntityId>>,
-    pub vnum_to_mobprog: Vec<Option<String>>,
-    pub object_components: Vec<Option<(Components, Vec<Components>)>>,
-    pub mobile_components: Vec<Option<(Components, Vec<Components>)>>,
+    pub vnum_to_room_entity: HashMap<Vnum, PermanentEntityId>,
+    pub vnum_to_mobprog: HashMap<Vnum, String>,
+    pub object_components: HashMap<Vnum, (Components, Vec<Components>)>,
+    pub mobile_components: HashMap<Vnum, (Components, Vec<Components>)>,
 }
 
 pub(crate) fn import_from_world(
@@ -29,145 +37,39 @@ pub(crate) fn import_from_world(
     let mut room_vnum_to_id = HashMap::new();
     let mut exit_leads_to = HashMap::new();
 
-    for room in &world.rooms {
-        let room_id = {
-            let keyword = &room.name;
-            let short_description = &room.name;
-
-            let title = &room.name;
-            let external = format!("It's a room called '{}'.", room.name);
-            let internal = &room.description;
-            let lateral = format!("A room called '{}' is here.", room.name);
-
-            let room_components = Components {
-                act_info: entity_world.interner.act_info(
-                    keyword,
-                    short_description,
-                    Gender::Neutral,
-                ),
-                descriptions: entity_world
-                    .interner
-                    .descriptions(title, internal, &external, &lateral),
-                general: GeneralData {
-                    vnum: room.vnum,
-                    area: room.area.to_string(),
-                    sector: Some(room.sector.to_string()),
-                    entity_type: EntityType::Room,
-                    equipped: None,
-                    command_queue: Vec::new(),
-                    following: None,
-                },
-                mobile: None,
-                object: None,
-                door: None,
-                mobprog: None,
-                silver: None,
-            };
-
-            entity_world.insert_entity(entity_world.world_entity_id(), room_components)
-        };
+    for room in world.rooms.values() {
+        let room_components = import_room_components(room, &mut entity_world.interner);
+        let room_id = entity_world.insert_entity(entity_world.world_entity_id(), room_components);
 
         for exit in &room.exits {
-            let mut keyword = exit.name.to_string();
-            let short_description = format!("the {} exit", exit.name);
-
-            let title = format!("Inside an {} exit.", exit.name);
-            let external = exit
-                .description
-                .as_deref()
-                .unwrap_or("You don't see anything special in that direction.");
-            let internal = format!(
-                "You are inside an {} exit. That normally shouldn't be possible.",
-                exit.name
-            );
-            let lateral = format!("An exit leading {} is here.", exit.name);
-
-            let door = if exit.has_door {
-                keyword = String::from("door ") + &keyword;
-                Some(Door {
-                    closed: exit.is_closed,
-                    locked: exit.is_locked,
-                    key: exit.key,
-                })
-            } else {
-                None
-            };
-
-            if let Some(extra_keywords) = &exit.extra_keywords {
-                keyword = extra_keywords.clone() + " " + &keyword;
-            }
-
-            let exit_components = Components {
-                act_info: entity_world.interner.act_info(
-                    &keyword,
-                    &short_description,
-                    Gender::Neutral,
-                ),
-                descriptions: entity_world
-                    .interner
-                    .descriptions(&title, &internal, external, &lateral),
-                general: GeneralData {
-                    vnum: Vnum(0),
-                    area: room.area.to_string(),
-                    sector: None,
-                    entity_type: EntityType::Exit,
-                    equipped: None,
-                    command_queue: Vec::new(),
-                    following: None,
-                },
-                mobile: None,
-                object: None,
-                door,
-                mobprog: None,
-                silver: None,
-            };
+            let exit_components =
+                import_exit_components(exit, &room.area, &mut entity_world.interner);
             let exit_id = entity_world.insert_entity(room_id, exit_components);
 
-            exit_leads_to.insert(exit_id, exit.vnum.0);
+            exit_leads_to.insert(exit_id, exit.vnum);
         }
 
         for extra_description in &room.extra_descriptions {
-            let keyword = &extra_description.keyword;
-            let short_description =
-                format!("extra description called '{}'", extra_description.keyword);
-
-            let title = "Inside an extra description.";
-            let external = &extra_description.description;
-            let internal =
-                "You are inside an extra description. That normally shouldn't be possible.";
-            let lateral = format!(
-                "An extra description called '{}' is here.",
-                extra_description.keyword
+            let extra_description_components = import_extra_description_components(
+                extra_description,
+                &room.area,
+                &mut entity_world.interner,
             );
-            let extra_description_components = Components {
-                act_info: entity_world.interner.act_info(
-                    keyword,
-                    &short_description,
-                    Gender::Neutral,
-                ),
-                descriptions: entity_world
-                    .interner
-                    .descriptions(title, internal, external, &lateral),
-                general: GeneralData {
-                    vnum: Vnum(0),
-                    area: room.area.to_string(),
-                    sector: None,
-                    entity_type: EntityType::ExtraDescription,
-                    equipped: None,
-                    command_queue: Vec::new(),
-                    following: None,
-                },
-                mobile: None,
-                object: None,
-                door: None,
-                mobprog: None,
-                silver: None,
-            };
-
             entity_world.insert_entity(room_id, extra_description_components);
         }
 
-        room_vnum_to_id.insert(room.vnum.0, room_id);
+        let mobprogs = import_mobprog_components(
+            &room.mobprog_triggers,
+            &room.area,
+            world,
+            &mut entity_world.interner,
+        );
+
+        for mobprog_components in mobprogs {
+            entity_world.insert_entity(room_id, mobprog_components);
+        }
+
+        room_vnum_to_id.insert(room.vnum, room_id);
     }
 
     for (exit_id, leads_to) in exit_leads_to {
@@ -189,60 +91,49 @@ pub(crate) fn import_from_world(
         entity_world.add_landmark(
             landmark,
             *room_vnum_to_id
-                .get(&vnum)
+                .get(&Vnum(*vnum))
                 .expect("GnomeHill landmark room not found."),
         );
     }
 
     let mut vnum_templates = VnumTemplates {
-        vnum_to_room_entity: Vec::with_capacity(world.rooms.len()),
-        vnum_to_mobprog: Vec::with_capacity(world.mobprogs.len()),
-        object_components: Vec::with_capacity(world.objects.len()),
-        mobile_components: Vec::with_capacity(world.mobiles.len()),
+        vnum_to_room_entity: HashMap::with_capacity(world.rooms.len()),
+        vnum_to_mobprog: HashMap::with_capacity(world.mobprogs.len()),
+        object_components: HashMap::with_capacity(world.objects.len()),
+        mobile_components: HashMap::with_capacity(world.mobiles.len()),
     };
 
-    vnum_templates
-        .vnum_to_room_entity
-        .resize(world.rooms.len(), None);
-    vnum_templates
-        .vnum_to_mobprog
-        .resize(world.rooms.len(), None);
-    vnum_templates
-        .object_components
-        .resize(world.rooms.len(), None);
-    vnum_templates
-        .mobile_components
-        .resize(world.rooms.len(), None);
-
-    for room in &world.rooms {
-        if room.vnum.0 != 0 {
-            let room_entity = entity_world.entity_info(room_vnum_to_id[&room.vnum.0]);
-            vnum_templates.vnum_to_room_entity[room.vnum.0] =
-                Some(room_entity.permanent_entity_id());
-        }
+    for (vnum, room_id) in &room_vnum_to_id {
+        let room_entity = entity_world.entity_info(*room_id);
+        vnum_templates
+            .vnum_to_room_entity
+            .insert(*vnum, room_entity.permanent_entity_id());
     }
 
-    for object in &world.objects {
-        if object.vnum.0 != 0 {
-            let components = import_object_components(object, &mut entity_world.interner);
-            vnum_templates.object_components[object.vnum.0] = Some(components);
-        }
+    for object in world.objects.values() {
+        let components = import_object_components(object, world, &mut entity_world.interner);
+        vnum_templates
+            .object_components
+            .insert(object.vnum, components);
     }
 
-    for mobile in &world.mobiles {
-        if mobile.vnum.0 != 0 {
-            let components = import_mobile_components(mobile, world, &mut entity_world.interner);
-            vnum_templates.mobile_components[mobile.vnum.0] = Some(components);
-        }
+    for mobile in world.mobiles.values() {
+        let components = import_mobile_components(mobile, world, &mut entity_world.interner);
+        vnum_templates
+            .mobile_components
+            .insert(mobile.vnum, components);
     }
 
-    for mobprog in &world.mobprogs {
-        vnum_templates.vnum_to_mobprog[mobprog.vnum.0] = Some(mobprog.code.clone());
+    for mobprog in world.mobprogs.values() {
+        vnum_templates
+            .vnum_to_mobprog
+            .insert(mobprog.vnum, mobprog.code.clone());
     }
 
     for (_area_data, area_resets) in &world.areas {
         let mut last_mobile_id = None;
 
+        // Resets with vnums that don't exist are skipped; `arealint` reports them
         for reset_command in area_resets {
             match reset_command {
                 ResetCommand::Mob {
@@ -251,10 +142,16 @@ pub(crate) fn import_from_world(
                     r_num,
                     room_limit: _,
                 } => {
-                    let room_entity_id = room_vnum_to_id[&r_num.0];
-                    let mobile_components = vnum_templates.mobile_components[m_num.0]
-                        .as_ref()
-                        .expect("Mobile with vnum does not exist");
+                    last_mobile_id = None;
+
+                    let room_entity_id = match room_vnum_to_id.get(r_num) {
+                        Some(room_entity_id) => *room_entity_id,
+                        None => continue,
+                    };
+                    let mobile_components = match vnum_templates.mobile_components.get(m_num) {
+                        Some(mobile_components) => mobile_components,
+                        None => continue,
+                    };
 
                     let mobile_entity_id =
                         entity_world.insert_entity(room_entity_id, mobile_components.0.clone());
@@ -263,32 +160,54 @@ pub(crate) fn import_from_world(
                     for mobprog_components in &mobile_components.1 {
                         entity_world.insert_entity(mobile_entity_id, mobprog_components.clone());
                     }
+
+                    let wealth = world.mobiles[m_num].wealth;
+                    if wealth > 0 {
+                        insert_coin_pile(entity_world, mobile_entity_id, random_wealth(wealth));
+                    }
                 }
                 ResetCommand::Object {
                     o_num,
                     global_limit: _,
                     r_num,
                 } => {
-                    let room_entity_id = room_vnum_to_id[&r_num.0];
-                    load_object(o_num.0, room_entity_id, &vnum_templates, entity_world);
+                    if let Some(room_entity_id) = room_vnum_to_id.get(r_num) {
+                        load_object(*o_num, *room_entity_id, &vnum_templates, entity_world);
+                    }
                 }
                 ResetCommand::Door { .. } => {}
                 ResetCommand::Give {
                     o_num,
                     global_limit: _,
                 } => {
-                    let last_mobile_id = last_mobile_id.unwrap();
-                    load_object(o_num.0, last_mobile_id, &vnum_templates, entity_world);
+                    let last_mobile_id = match last_mobile_id {
+                        Some(last_mobile_id) => last_mobile_id,
+                        None => continue,
+                    };
+                    load_object(*o_num, last_mobile_id, &vnum_templates, entity_world);
+
+                    let mut mobile_entity = entity_world.entity_info_mut(last_mobile_id);
+                    if let Some(mobile) = &mut mobile_entity.components().mobile {
+                        if mobile.shopkeeper.is_some() {
+                            mobile.restock.push(*o_num);
+                        }
+                    }
                 }
                 ResetCommand::Equip {
                     o_num,
                     global_limit: _,
                     location,
                 } => {
-                    let last_mobile_id = last_mobile_id.unwrap();
+                    let last_mobile_id = match last_mobile_id {
+                        Some(last_mobile_id) => last_mobile_id,
+                        None => continue,
+                    };
 
                     let object_id =
-                        load_object(o_num.0, last_mobile_id, &vnum_templates, entity_world);
+                        match load_object(*o_num, last_mobile_id, &vnum_templates, entity_world) {
+                            Some(object_id) => object_id,
+                            None => continue,
+                        };
                     let location = location.to_string();
                     let mut object_entity = entity_world.entity_info_mut(object_id);
                     object_entity.components().general.equipped = Some(location);
@@ -309,7 +228,7 @@ pub(crate) fn import_from_world(
                         }
                     }
                     if let Some(container_id) = container_id {
-                        load_object(o_num.0, container_id, &vnum_templates, entity_world);
+                        load_object(*o_num, container_id, &vnum_templates, entity_world);
                     }
                 }
             }
@@ -329,18 +248,156 @@ pub(crate) fn import_from_world(
     (vnum_templates, areas)
 }
 
-fn import_mobile_components(
+/// A room's own components; its exits and extra descriptions are separate
+/// entities inside of it.
+pub(crate) fn import_room_components(room: &Room, interner: &mut StringInterner) -> Components {
+    let keyword = &room.name;
+    let short_description = &room.name;
+
+    let title = &room.name;
+    let external = format!("It's a room called '{}'.", room.name);
+    let internal = &room.description;
+    let lateral = format!("A room called '{}' is here.", room.name);
+
+    Components {
+        act_info: interner.act_info(keyword, short_description, Gender::Neutral),
+        descriptions: interner.descriptions(title, internal, &external, &lateral),
+        general: GeneralData {
+            vnum: room.vnum,
+            area: room.area.to_string(),
+            sector: Some(room.sector.to_string()),
+            entity_type: EntityType::Room,
+            equipped: None,
+            command_queue: Vec::new(),
+            following: None,
+            group: None,
+        },
+        mobile: None,
+        object: None,
+        door: None,
+        mobprog: None,
+        coins: None,
+        room: Some(crate::components::Room { bank: room.bank }),
+        player: None,
+        script: import_script_component(&room.script),
+        variables: Variables::default(),
+        visibility: Visibility {
+            dark: room.dark,
+            ..Default::default()
+        },
+    }
+}
+
+/// An exit's components; it still needs to be linked with `set_leads_to`.
+pub(crate) fn import_exit_components(
+    exit: &Exit,
+    area: &str,
+    interner: &mut StringInterner,
+) -> Components {
+    let mut keyword = exit.name.to_string();
+    let short_description = format!("the {} exit", exit.name);
+
+    let title = format!("Inside an {} exit.", exit.name);
+    let external = exit
+        .description
+        .as_deref()
+        .unwrap_or("You don't see anything special in that direction.");
+    let internal = format!(
+        "You are inside an {} exit. That normally shouldn't be possible.",
+        exit.name
+    );
+    let lateral = format!("An exit leading {} is here.", exit.name);
+
+    let door = if exit.has_door {
+        keyword = String::from("door ") + keyword.as_str();
+        Some(Door {
+            closed: exit.is_closed,
+            locked: exit.is_locked,
+            key: exit.key,
+            pickproof: exit.is_pickproof,
+            bashproof: exit.is_bashproof,
+        })
+    } else {
+        None
+    };
+
+    if let Some(extra_keywords) = &exit.extra_keywords {
+        keyword = extra_keywords.clone() + " " + keyword.as_str();
+    }
+
+    Components {
+        act_info: interner.act_info(&keyword, &short_description, Gender::Neutral),
+        descriptions: interner.descriptions(&title, &internal, external, &lateral),
+        general: GeneralData {
+            vnum: Vnum(0),
+            area: area.to_string(),
+            sector: None,
+            entity_type: EntityType::Exit,
+            equipped: None,
+            command_queue: Vec::new(),
+            following: None,
+            group: None,
+        },
+        mobile: None,
+        object: None,
+        door,
+        mobprog: None,
+        coins: None,
+        room: None,
+        player: None,
+        script: None,
+        variables: Variables::default(),
+        visibility: Visibility::default(),
+    }
+}
+
+pub(crate) fn import_extra_description_components(
+    extra_description: &ExtraDescription,
+    area: &str,
+    interner: &mut StringInterner,
+) -> Components {
+    let keyword = &extra_description.keyword;
+    let short_description = format!("extra description called '{}'", extra_description.keyword);
+
+    let title = "Inside an extra description.";
+    let external = &extra_description.description;
+    let internal = "You are inside an extra description. That normally shouldn't be possible.";
+    let lateral = format!(
+        "An extra description called '{}' is here.",
+        extra_description.keyword
+    );
+    Components {
+        act_info: interner.act_info(keyword, &short_description, Gender::Neutral),
+        descriptions: interner.descriptions(title, internal, external, &lateral),
+        general: GeneralData {
+            vnum: Vnum(0),
+            area: area.to_string(),
+            sector: None,
+            entity_type: EntityType::ExtraDescription,
+            equipped: None,
+            command_queue: Vec::new(),
+            following: None,
+            group: None,
+        },
+        mobile: None,
+        object: None,
+        door: None,
+        mobprog: None,
+        coins: None,
+        room: None,
+        player: None,
+        script: None,
+        variables: Variables::default(),
+        visibility: Visibility::default(),
+    }
+}
+
+pub(crate) fn import_mobile_components(
     mobile: &Mobile,
     world: &World,
     interner: &mut StringInterner,
 ) -> (Components, Vec<Components>) {
-    let mut mobprogs = Vec::with_capacity(mobile.mobprog_triggers.len());
-
-    let objective_pronoun = match mobile.gender {
-        Gender::Male => "him",
-        Gender::Female => "her",
-        Gender::Neutral => "it",
-    };
+    let (_, objective_pronoun, _) = mobile.gender.pronouns();
 
     let keyword = &mobile.name;
     let short_description = &mobile.short_description;
@@ -353,13 +410,15 @@ fn import_mobile_components(
     );
     let lateral = &mobile.long_description;
 
-    let act_info = interner.act_info(keyword, &short_description, mobile.gender);
+    let act_info = interner.act_info(keyword, short_description, mobile.gender.clone());
     let descriptions = interner.descriptions(&title, &internal, external, lateral);
 
-    let shop = world
-        .shops
-        .get(mobile.vnum.0)
-        .filter(|shop| shop.vnum.0 != 0);
+    let shop = world.shops.get(&mobile.vnum);
+
+    let affected_by = |flag: &str| mobile.affected_by.iter().any(|word| word == flag);
+
+    let position = Position::from_word(&mobile.start_position).unwrap_or_default();
+    let default_position = Position::from_word(&mobile.default_position).unwrap_or(position);
 
     let mobile_components = Components {
         act_info,
@@ -372,23 +431,57 @@ fn import_mobile_components(
             equipped: None,
             command_queue: Vec::new(),
             following: None,
+            group: None,
         },
         mobile: Some(crate::components::Mobile {
             wander: !mobile.sentinel,
             shopkeeper: shop.cloned(),
+            restock: Vec::new(),
             remember: None,
+            position,
+            furniture: None,
+            default_position,
         }),
         object: None,
         door: None,
         mobprog: None,
-        silver: None,
+        coins: None,
+        room: None,
+        player: None,
+        script: import_script_component(&mobile.script),
+        variables: Variables::default(),
+        visibility: Visibility {
+            invisible: affected_by("invisible"),
+            hidden: affected_by("hide"),
+            sneaking: affected_by("sneak"),
+            unseen: mobile.unseen,
+            detect_invisible: affected_by("detect_invis"),
+            detect_hidden: affected_by("detect_hidden"),
+            dark: false,
+        },
     };
 
-    for (mobprog_trigger, vnum) in &mobile.mobprog_triggers {
-        let mobprog = world
-            .mobprogs
-            .get(vnum.0)
-            .expect("Triggered mobprog should exist");
+    let mobprogs =
+        import_mobprog_components(&mobile.mobprog_triggers, &mobile.area, world, interner);
+
+    (mobile_components, mobprogs)
+}
+
+/// Creates the `MobProg` child entities for a mobile, room, or object.
+fn import_mobprog_components(
+    mobprog_triggers: &[(MobProgTrigger, Vnum)],
+    area: &str,
+    world: &World,
+    interner: &mut StringInterner,
+) -> Vec<Components> {
+    let mut mobprogs = Vec::with_capacity(mobprog_triggers.len());
+
+    for (mobprog_trigger, vnum) in mobprog_triggers {
+        // Triggers for mobprogs that don't exist are reported by `arealint`
+        let mobprog = match world.mobprogs.get(vnum) {
+            Some(mobprog) => mobprog,
+            None => continue,
+        };
         let keyword = "mobprog";
         let trigger = match mobprog_trigger {
             MobProgTrigger::Random { .. } => "on-chance",
@@ -403,6 +496,10 @@ fn import_mobile_components(
             MobProgTrigger::Death { .. } => "on-death",
             MobProgTrigger::Hour { .. } => "on-hour",
             MobProgTrigger::LoginRoom { .. } => "on-login",
+            MobProgTrigger::Get { .. } => "on-get",
+            MobProgTrigger::Drop { .. } => "on-drop",
+            MobProgTrigger::Wear { .. } => "on-wear",
+            MobProgTrigger::Use { .. } => "on-use",
         };
         let short_description = format!("an {} mobprog titled '`S{}`^'", trigger, mobprog.title);
 
@@ -422,12 +519,13 @@ fn import_mobile_components(
             descriptions,
             general: GeneralData {
                 vnum: mobprog.vnum,
-                area: mobile.area.to_string(),
+                area: area.to_string(),
                 sector: None,
                 entity_type: EntityType::MobProg,
                 equipped: None,
                 command_queue: Vec::new(),
                 following: None,
+                group: None,
             },
             mobile: None,
             object: None,
@@ -436,36 +534,48 @@ fn import_mobile_components(
                 trigger: mobprog_trigger.clone(),
                 code: mobprog.code.clone(),
             }),
-            silver: None,
+            coins: None,
+            room: None,
+            player: None,
+            script: None,
+            variables: Variables::default(),
+            visibility: Visibility::default(),
         });
     }
 
-    (mobile_components, mobprogs)
+    mobprogs
 }
 
-fn import_object_components(
+fn import_script_component(script: &Option<String>) -> Option<Script> {
+    script.as_ref().map(|name| Script { name: name.clone() })
+}
+
+/// Check if this extra description's keywords coincides with the object's
+/// keywords. In that case, it's used as the object's main description.
+pub(crate) fn is_main_description(object: &Object, extra_description: &ExtraDescription) -> bool {
+    extra_description
+        .keyword
+        .split_whitespace()
+        .all(|desc_keyword| {
+            object
+                .name
+                .split_whitespace()
+                .any(|obj_keyword| obj_keyword == desc_keyword)
+        })
+}
+
+pub(crate) fn import_object_components(
     object: &Object,
+    world: &World,
     interner: &mut StringInterner,
 ) -> (Components, Vec<Components>) {
-    let mut extra_description_components = Vec::with_capacity(object.extra_descriptions.len());
+    let mut child_components =
+        Vec::with_capacity(object.extra_descriptions.len() + object.mobprog_triggers.len());
 
     let mut main_description = None;
 
     for extra_description in &object.extra_descriptions {
-        // Check if this extra description's keywords coincides with the object's keywords
-        // In that case, just set it as the object's main description
-
-        let keywords_match = extra_description
-            .keyword
-            .split_whitespace()
-            .all(|desc_keyword| {
-                object
-                    .name
-                    .split_whitespace()
-                    .any(|obj_keyword| obj_keyword == desc_keyword)
-            });
-
-        if keywords_match {
+        if is_main_description(object, extra_description) {
             main_description = Some(&extra_description.description);
             continue;
         }
@@ -486,7 +596,7 @@ fn import_object_components(
         let act_info = interner.act_info(keyword, &short_description, Gender::Neutral);
         let descriptions = interner.descriptions(&title, &internal, external, &lateral);
 
-        extra_description_components.push(Components {
+        child_components.push(Components {
             act_info,
             descriptions,
             general: GeneralData {
@@ -497,12 +607,18 @@ fn import_object_components(
                 equipped: None,
                 command_queue: Vec::new(),
                 following: None,
+                group: None,
             },
             mobile: None,
             object: None,
             door: None,
             mobprog: None,
-            silver: None,
+            coins: None,
+            room: None,
+            player: None,
+            script: None,
+            variables: Variables::default(),
+            visibility: Visibility::default(),
         });
     }
 
@@ -523,20 +639,25 @@ fn import_object_components(
     };
 
     let act_info = interner.act_info(keyword, short_description, Gender::Neutral);
-    let descriptions = interner.descriptions(&title, &internal, external, lateral);
+    let descriptions = interner.descriptions(&title, internal, external, lateral);
 
     // If you squint hard enough at a lid, it might start to look like a door.
     let door = if let ObjectFlags::Container {
         closable,
         closed,
         locked,
+        pickproof,
+        key,
     } = object.flags
     {
         if closable {
             Some(Door {
                 closed,
                 locked,
-                key: None,
+                key,
+                pickproof,
+                // Containers can't be bashed open
+                bashproof: true,
             })
         } else {
             None
@@ -556,41 +677,125 @@ fn import_object_components(
             equipped: None,
             command_queue: Vec::new(),
             following: None,
+            group: None,
         },
         mobile: None,
-        object: Some(crate::components::Object {
-            cost: object.cost,
-            key: if object.item_type == "key" {
-                Some(object.vnum)
-            } else {
-                None
-            },
-            container: object.item_type == "container",
-            food: object.item_type == "food",
-        }),
+        object: Some(import_item_components(object)),
         door,
         mobprog: None,
-        silver: None,
+        coins: None,
+        room: None,
+        player: None,
+        script: import_script_component(&object.script),
+        variables: Variables::default(),
+        visibility: Visibility {
+            invisible: object.extra_flags.iter().any(|flag| flag == "invis"),
+            ..Default::default()
+        },
     };
 
-    (components, extra_description_components)
+    child_components.extend(import_mobprog_components(
+        &object.mobprog_triggers,
+        &object.area,
+        world,
+        interner,
+    ));
+
+    (components, child_components)
+}
+
+/// Whether one sits, rests or sleeps "at", "on" or "in" a piece of furniture.
+fn furniture_preposition(flags: &[String], position: &str) -> Option<&'static str> {
+    ["at", "on", "in"].iter().copied().find(|preposition| {
+        flags
+            .iter()
+            .any(|flag| *flag == format!("{}_{}", position, preposition))
+    })
+}
+
+fn import_item_components(object: &Object) -> crate::components::Object {
+    let mut item = crate::components::Object {
+        cost: object.cost,
+        item_type: object.item_type.clone(),
+        condition: object.condition,
+        container: object.item_type == "container",
+        food: object.item_type == "food",
+        light: None,
+        drink_container: None,
+        furniture: None,
+        spells: None,
+        portal: None,
+        wear_location: object
+            .wear_flags
+            .iter()
+            .find(|flag| *flag != "take")
+            .cloned(),
+    };
+
+    match &object.flags {
+        ObjectFlags::DrinkContainer {
+            capacity,
+            amount,
+            liquid,
+            fountain,
+        } => {
+            item.drink_container = Some(DrinkContainer {
+                liquid: liquid.clone(),
+                capacity: *capacity,
+                amount: *amount,
+                endless: *fountain,
+            })
+        }
+        ObjectFlags::Light { hours } => {
+            item.light = Some(Light {
+                hours: *hours,
+                // Lights start lit, so that they light up rooms like they
+                // always did until someone puts them out
+                lit: true,
+            })
+        }
+        ObjectFlags::Furniture { capacity, flags } => {
+            item.furniture = Some(Furniture {
+                capacity: *capacity,
+                sit: furniture_preposition(flags, "sit"),
+                rest: furniture_preposition(flags, "rest"),
+                sleep: furniture_preposition(flags, "sleep"),
+            })
+        }
+        ObjectFlags::Spells { level, spells } => {
+            item.spells = Some(Spells {
+                level: *level,
+                spells: spells.clone(),
+            })
+        }
+        ObjectFlags::Portal {
+            charges,
+            destination,
+        } => {
+            item.portal = Some(Portal {
+                destination: *destination,
+                charges: *charges,
+            })
+        }
+        ObjectFlags::Container { .. } | ObjectFlags::Unknown => (),
+    }
+
+    item
 }
 
 fn load_object(
-    vnum: usize,
+    vnum: Vnum,
     container: EntityId,
     vnum_templates: &VnumTemplates,
     entity_world: &mut EntityWorld,
-) -> EntityId {
-    let components = vnum_templates.object_components[vnum]
-        .as_ref()
-        .expect("Object with vnum does not exist");
+) -> Option<EntityId> {
+    let components = vnum_templates.object_components.get(&vnum)?;
 
     let object_id = entity_world.insert_entity(container, components.0.clone());
 
-    for extra_description_components in &components.1 {
-        entity_world.insert_entity(object_id, extra_description_components.clone());
+    for child_components in &components.1 {
+        entity_world.insert_entity(object_id, child_components.clone());
     }
 
-    object_id
+    Some(object_id)
 }
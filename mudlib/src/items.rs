@@ -0,0 +1,532 @@
+//! Objects that do something when used: lights, drink containers and
+//! fountains, and portals. Furniture is used from `positions.rs`.
+//!
+//! Lights start lit, only light up dark rooms while lit, and burn for an hour
+//! every minute while carried until they burn out, unless they last forever.
+//! Drink containers hold a liquid that can be drunk, poured out or into another
+//! container, and refilled from a fountain, which never runs out. Portals take
+//! whoever enters them to another room, and fade away once their charges are
+//! used up.
+
+use crate::{
+    agent::EntityAgent,
+    components::{DrinkContainer, Light, Object, Portal},
+    echo,
+    entity::{EntityId, EntityInfo},
+    find_entities::EntityIterator,
+    mobprogs::Action,
+};
+
+/// Extra lines shown when looking at an object, about what it holds or how
+/// it can be used.
+pub(crate) fn describe_item(object: &Object) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if let Some(drink) = &object.drink_container {
+        lines.push(match drink.amount {
+            _ if drink.endless => format!("$^$E is full of {}.", drink.liquid),
+            0 => "$^$E is empty.".to_string(),
+            amount if amount >= drink.capacity => format!("$^$E is full of {}.", drink.liquid),
+            amount if amount * 2 < drink.capacity => {
+                format!("$^$E is less than half full of {}.", drink.liquid)
+            }
+            amount if amount * 2 == drink.capacity => {
+                format!("$^$E is half full of {}.", drink.liquid)
+            }
+            _ => format!("$^$E is more than half full of {}.", drink.liquid),
+        });
+    }
+
+    if let Some(light) = &object.light {
+        lines.push(match (light.lit, light.hours) {
+            (_, Some(0)) => "$^$E has burnt out.".to_string(),
+            (true, None) => "$^$E gives off a steady light.".to_string(),
+            (true, Some(hours)) => format!("$^$E is lit, and will burn for {}.", hours_left(hours)),
+            (false, None) => "$^$E isn't lit.".to_string(),
+            (false, Some(hours)) => {
+                format!("$^$E isn't lit, and could burn for {}.", hours_left(hours))
+            }
+        });
+    }
+
+    if let Some(spells) = &object.spells {
+        if !spells.spells.is_empty() {
+            lines.push(format!(
+                "$^$E is imbued with level {} {}: '{}'.",
+                spells.level,
+                if spells.spells.len() == 1 {
+                    "spell"
+                } else {
+                    "spells"
+                },
+                spells.spells.join("', '")
+            ));
+        }
+    }
+
+    if let Some(Portal {
+        charges: Some(charges),
+        ..
+    }) = &object.portal
+    {
+        lines.push(match charges {
+            1 => "$^$E looks like it could be entered only once more.".to_string(),
+            _ => format!(
+                "$^$E looks like it could be entered {} more times.",
+                charges
+            ),
+        });
+    }
+
+    lines
+}
+
+fn hours_left(hours: u16) -> String {
+    match hours {
+        1 => "one more hour".to_string(),
+        _ => format!("{} more hours", hours),
+    }
+}
+
+fn drink_container<'e>(entity: &EntityInfo<'e>) -> Option<&'e DrinkContainer> {
+    entity
+        .components()
+        .object
+        .as_ref()?
+        .drink_container
+        .as_ref()
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    fn find_light(&mut self, item_name: &str) -> Option<EntityId> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let found = myself
+            .contained_entities()
+            .filter_by_keyword(&myself, item_name)
+            .with_component_or::<Light>("$^$N is not a light.")
+            .find_one_with_component_or("You aren't holding anything named like that.");
+
+        match found {
+            Ok((item, _light)) => Some(item.entity_id()),
+            Err(error) => {
+                self.echo_error(error);
+                None
+            }
+        }
+    }
+
+    fn set_lit(&mut self, item_id: EntityId, lit: bool) {
+        let mut item = self.entity_world.entity_info_mut(item_id);
+        let light = item
+            .components()
+            .object
+            .as_mut()
+            .and_then(|object| object.light.as_mut());
+
+        if let Some(light) = light {
+            light.lit = lit;
+        }
+    }
+
+    pub fn do_light(&mut self, item_name: &str) {
+        let item_id = match self.find_light(item_name) {
+            Some(item_id) => item_id,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let item = self.entity_world.entity_info(item_id);
+        let light = item
+            .components()
+            .object
+            .as_ref()
+            .and_then(|o| o.light.as_ref());
+
+        let mut act = self.players.act_with(&myself, &item);
+        match light {
+            Some(light) if light.lit => echo!(act.myself(), "$^$N is already lit.\r\n"),
+            Some(light) if light.hours == Some(0) => {
+                echo!(act.myself(), "$^$N has burnt out.\r\n")
+            }
+            _ => {
+                echo!(act.myself(), "You light $N.\r\n");
+                echo!(act.others(), "$^$n lights $N.\r\n");
+                self.set_lit(item_id, true);
+            }
+        }
+    }
+
+    pub fn do_extinguish(&mut self, item_name: &str) {
+        let item_id = match self.find_light(item_name) {
+            Some(item_id) => item_id,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let item = self.entity_world.entity_info(item_id);
+        let light = item
+            .components()
+            .object
+            .as_ref()
+            .and_then(|o| o.light.as_ref());
+
+        let mut act = self.players.act_with(&myself, &item);
+        match light {
+            Some(light) if light.lit => {
+                echo!(act.myself(), "You put out $N.\r\n");
+                echo!(act.others(), "$^$n puts out $N.\r\n");
+                self.set_lit(item_id, false);
+            }
+            _ => echo!(act.myself(), "$^$N isn't lit.\r\n"),
+        }
+    }
+
+    /// Tell those around a light that burnt out that it went out.
+    pub fn burn_out(&mut self) {
+        let light = self.entity_world.entity_info(self.entity_id);
+        let holder = light.room();
+
+        if holder.is_mobile() || holder.is_player() {
+            let mut act = self.players.act_with(&holder, &light);
+            echo!(act.myself(), "$^$N flickers and goes out.\r\n");
+            echo!(
+                act.others(),
+                "$^$N carried by $n flickers and goes out.\r\n"
+            );
+        } else {
+            let mut act = self.players.act_alone(&light);
+            echo!(act.others(), "$^$n flickers and goes out.\r\n");
+        }
+    }
+
+    /// Find a drink container, either carried by the agent or in its room.
+    fn find_drink_container(
+        &mut self,
+        item_name: &str,
+        carried_only: bool,
+        not_found: &'static str,
+    ) -> Option<EntityId> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let room = myself.room();
+        let in_room = room.objects().filter(|_| !carried_only);
+
+        let found = myself
+            .contained_entities()
+            .chain(in_room)
+            .filter_by_keyword(&myself, item_name)
+            .with_component_or::<DrinkContainer>("$^$N doesn't hold anything to drink.")
+            .find_one_with_component_or(not_found);
+
+        match found {
+            Ok((item, _drink)) => Some(item.entity_id()),
+            Err(error) => {
+                self.echo_error(error);
+                None
+            }
+        }
+    }
+
+    fn set_liquid(&mut self, item_id: EntityId, liquid: &str, amount: u16) {
+        let mut item = self.entity_world.entity_info_mut(item_id);
+        let drink = item
+            .components()
+            .object
+            .as_mut()
+            .and_then(|object| object.drink_container.as_mut());
+
+        if let Some(drink) = drink {
+            if !drink.endless {
+                drink.amount = amount;
+                drink.liquid = liquid.to_string();
+            }
+        }
+    }
+
+    /// Drink from a container, or from a fountain in the room.
+    pub fn do_drink(&mut self, item_name: Option<&str>) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let item_id = match item_name {
+            Some(item_name) => self.find_drink_container(
+                item_name,
+                false,
+                "You don't see anything named like that to drink from.",
+            ),
+            None => {
+                let fountain = myself
+                    .room()
+                    .objects()
+                    .find(|object| drink_container(object).is_some_and(|drink| drink.endless));
+
+                match fountain {
+                    Some(fountain) => Some(fountain.entity_id()),
+                    None => {
+                        echo!(self.info(), "Drink from what?\r\n");
+                        return;
+                    }
+                }
+            }
+        };
+
+        let item_id = match item_id {
+            Some(item_id) => item_id,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let item = self.entity_world.entity_info(item_id);
+        let drink = match drink_container(&item) {
+            Some(drink) => drink,
+            None => return,
+        };
+
+        let mut act = self.players.act_with(&myself, &item);
+        if drink.amount == 0 && !drink.endless {
+            echo!(act.myself(), "$^$N is empty.\r\n");
+            return;
+        }
+
+        echo!(act.myself(), "You drink {} from $N.\r\n", drink.liquid);
+        echo!(act.others(), "$^$n drinks {} from $N.\r\n", drink.liquid);
+
+        let liquid = drink.liquid.clone();
+        let amount = drink.amount.saturating_sub(1);
+        self.set_liquid(item_id, &liquid, amount);
+    }
+
+    /// Fill a carried container from a fountain in the room.
+    pub fn do_fill(&mut self, item_name: &str, fountain_name: Option<&str>) {
+        let item_id = match self.find_drink_container(
+            item_name,
+            true,
+            "You aren't holding anything named like that to fill.",
+        ) {
+            Some(item_id) => item_id,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let is_fountain = |object: &EntityInfo| drink_container(object).is_some_and(|d| d.endless);
+
+        let fountain = match fountain_name {
+            Some(fountain_name) => myself
+                .room()
+                .objects()
+                .filter_by_keyword(&myself, fountain_name)
+                .filter_or(is_fountain, "$^$N is not a fountain.")
+                .find_one_or("You don't see any fountains named like that here."),
+            None => myself
+                .room()
+                .objects()
+                .filter(is_fountain)
+                .find_one_or("There is no fountain here!"),
+        };
+
+        let fountain = match fountain {
+            Ok(fountain) => fountain,
+            Err(error) => return self.echo_error(error),
+        };
+
+        let item = self.entity_world.entity_info(item_id);
+        let (drink, source) = match (drink_container(&item), drink_container(&fountain)) {
+            (Some(drink), Some(source)) => (drink, source),
+            _ => return,
+        };
+
+        let mut act = self.players.act_with(&myself, &item);
+        if drink.endless {
+            echo!(act.myself(), "$^$N doesn't need filling.\r\n");
+            return;
+        }
+        if drink.amount > 0 && drink.liquid != source.liquid {
+            echo!(act.myself(), "There is already another liquid in $N.\r\n");
+            return;
+        }
+        if drink.amount >= drink.capacity {
+            echo!(act.myself(), "$^$N is already full.\r\n");
+            return;
+        }
+
+        echo!(
+            act.myself(),
+            "You fill $N with {} from {}.\r\n",
+            source.liquid,
+            fountain
+        );
+        echo!(
+            act.others(),
+            "$^$n fills $N with {} from {}.\r\n",
+            source.liquid,
+            fountain
+        );
+
+        let liquid = source.liquid.clone();
+        let capacity = drink.capacity;
+        self.set_liquid(item_id, &liquid, capacity);
+    }
+
+    /// Pour a carried container out, or into another container.
+    pub fn do_pour(&mut self, item_name: &str, target: &str) {
+        let item_id = match self.find_drink_container(
+            item_name,
+            true,
+            "You aren't holding anything named like that to pour.",
+        ) {
+            Some(item_id) => item_id,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let item = self.entity_world.entity_info(item_id);
+        let drink = match drink_container(&item) {
+            Some(drink) => drink,
+            None => return,
+        };
+
+        let mut act = self.players.act_with(&myself, &item);
+        if drink.endless {
+            echo!(act.myself(), "You can't pour $N out.\r\n");
+            return;
+        }
+        if drink.amount == 0 {
+            echo!(act.myself(), "$^$N is already empty.\r\n");
+            return;
+        }
+
+        let liquid = drink.liquid.clone();
+        let amount = drink.amount;
+
+        if target == "out" {
+            echo!(act.myself(), "You pour {} out of $N.\r\n", liquid);
+            echo!(act.others(), "$^$n pours {} out of $N.\r\n", liquid);
+            self.set_liquid(item_id, &liquid, 0);
+            return;
+        }
+
+        let target_id = match self.find_drink_container(
+            target,
+            false,
+            "You don't see anything named like that to pour into.",
+        ) {
+            Some(target_id) => target_id,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let item = self.entity_world.entity_info(item_id);
+        let target = self.entity_world.entity_info(target_id);
+        let other = match drink_container(&target) {
+            Some(other) => other,
+            None => return,
+        };
+
+        let mut act = self.players.act_with(&myself, &target);
+        if target_id == item_id {
+            echo!(act.myself(), "You can't pour $N into itself.\r\n");
+            return;
+        }
+        if other.endless {
+            echo!(act.myself(), "$^$N doesn't need filling.\r\n");
+            return;
+        }
+        if other.amount > 0 && other.liquid != liquid {
+            echo!(act.myself(), "There is already another liquid in $N.\r\n");
+            return;
+        }
+        if other.amount >= other.capacity {
+            echo!(act.myself(), "$^$N is already full.\r\n");
+            return;
+        }
+
+        echo!(
+            act.myself(),
+            "You pour {} from {} into $N.\r\n",
+            liquid,
+            item
+        );
+        echo!(
+            act.others(),
+            "$^$n pours {} from {} into $N.\r\n",
+            liquid,
+            item
+        );
+
+        let poured = amount.min(other.capacity - other.amount);
+        let other_amount = other.amount + poured;
+        self.set_liquid(item_id, &liquid, amount - poured);
+        self.set_liquid(target_id, &liquid, other_amount);
+    }
+
+    /// Step into a portal, and come out at its destination.
+    pub fn do_enter(&mut self, target: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let found = myself
+            .room()
+            .objects()
+            .filter_by_keyword(&myself, target)
+            .with_component_or::<Portal>("You can't enter $N.")
+            .find_one_with_component_or("You don't see anything named like that to enter.");
+
+        let (portal, portal_component) = match found {
+            Ok(found) => found,
+            Err(error) => return self.echo_error(error),
+        };
+
+        let destination = self
+            .vnum_templates
+            .vnum_to_room_entity
+            .get(&portal_component.destination)
+            .and_then(|permanent_id| self.entity_world.old_entity(permanent_id))
+            .map(|room| room.entity_id());
+
+        let mut act = self.players.act_with(&myself, &portal);
+        let room_id = match destination {
+            Some(room_id) => room_id,
+            None => {
+                echo!(act.myself(), "$^$N doesn't lead anywhere.\r\n");
+                return;
+            }
+        };
+
+        echo!(act.myself(), "You step into $N.\r\n");
+        echo!(act.others(), "$^$n steps into $N, and is gone.\r\n");
+
+        let portal_id = portal.entity_id();
+        let mut portal = self.entity_world.entity_info_mut(portal_id);
+        let charges = portal
+            .components()
+            .object
+            .as_mut()
+            .and_then(|object| object.portal.as_mut())
+            .and_then(|portal| portal.charges.as_mut());
+
+        let faded = match charges {
+            Some(charges) => {
+                *charges = charges.saturating_sub(1);
+                *charges == 0
+            }
+            None => false,
+        };
+
+        if faded {
+            let portal = self.entity_world.entity_info(portal_id);
+            let mut act = self.players.act_alone(&portal);
+            echo!(act.others(), "$^$n fades out of existence.\r\n");
+
+            let mut agent = self.switch_agent(portal_id);
+            agent.do_die();
+        }
+
+        self.entity_world.move_entity(self.entity_id, room_id);
+        self.reveal_hidden();
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let mut act = self.players.act_alone(&myself);
+        echo!(act.others(), "$^$n steps out of thin air.\r\n");
+
+        self.do_look();
+        self.check_triggers_others(Action::Greet);
+    }
+}
@@ -1,23 +1,47 @@
 mod acting; // Process and output things like "$n flexes $s muscles."
 mod agent; // Object providing access to all game resources needed for commands
+mod appearance; // Players' pronouns, descriptions, titles and short descriptions
+mod channels; // Global channels, tells and replies between players
 mod colors; // Turn codes like "`w" into "\e[37m".
 mod commands; // do_say, do_look, do_get, etc, implemented upon EntityAgent
 mod components; // Types of game data (mob, obj, etc) attached to entities
+mod doors; // Doors and containers that can be opened, locked, picked and bashed
+mod editor; // Multi-line text editor for descriptions
 mod entity; // Every object in the world and relation between objects
 mod file_parser; // Dawn of Time area format parser primitives
-mod files; // Abstraction trait for reading files
+mod files; // Abstraction trait for reading and writing files
 mod find_entities; // Primitives to help with matching and filtering entities
+mod groups; // Groups that follow a leader, with gtell and shared rewards
 mod import; // Use templates from a DoT world to insert new EntityWorld entities
+mod items; // Lights, drink containers, fountains and portals
+mod lint; // Consistency checks for a set of areas, used by the arealint binary
 mod load; // Dawn of Time area loader
+mod load_rom; // ROM 2.4 and Merc area loader
 mod mapper; // Map generator
 mod mobprogs; // MobProg script runner, and additional do_mob_... commands
+mod money; // Gold, silver and copper coins, and banks
+mod native_area; // DemiMUD's own area format, in TOML
 #[cfg(feature = "net")]
 mod net; // Handle network players from NetServer; not used in WASM or CLI.
+mod olc; // Online building: redit, medit, oedit, resetedit and asave
+mod pfiles; // Save and load player files
+mod positions; // Sleeping, resting, sitting and standing, and what each allows
+mod quests; // Quest definitions, and players' progress through them
+mod save; // Write areas back in the Dawn of Time format
+mod scripts; // Rhai scripts attached to entities, reloadable at runtime
+mod shops; // Shopkeepers that buy, sell and restock their wares
 mod socials; // Load socials from socials.txt
 mod state; // Main game object, glues everything together
 mod tick; // Things that mobs do every second (e.g. wandering around rooms)
+mod visibility; // Who can see what: darkness, invisibility and unseen mobiles
+mod who; // Listings of who is playing, and where they're connected from
 mod world; // Read-only representation of a set of Dawn of Time areas
 
 pub use colors::colorize;
-pub use files::Files;
+pub use editor::{Editor, EditorStatus};
+pub use file_parser::{diagnostics_report, Diagnostic, Severity};
+pub use files::{Files, RealFiles};
+pub use lint::lint_areas;
+pub use native_area::{convert_area, AreaFormat};
 pub use state::WorldState;
+pub use who::ConnectionInfo;
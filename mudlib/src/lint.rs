@@ -0,0 +1,181 @@
+//! Consistency checks for a set of areas, used by the `arealint` binary.
+//!
+//! Loading a world only checks that each file can be parsed; this also makes
+//! sure that the vnums areas refer to exist, and that exits lead both ways.
+
+use crate::{
+    file_parser::{Diagnostic, Severity},
+    files::Files,
+    world::{load_world, long_direction, MobProgTrigger, ResetCommand, Vnum, World},
+};
+
+/// Load all areas listed in `<path>/arealist.txt`, and return every problem
+/// found in them, both while loading and afterwards.
+pub fn lint_areas(files: &dyn Files, path: &str) -> Vec<Diagnostic> {
+    let mut world = load_world(files, path);
+    let mut lint = Lint {
+        world: &world,
+        diagnostics: Vec::new(),
+    };
+
+    lint.check_rooms();
+    lint.check_mobiles_and_objects();
+    lint.check_resets();
+
+    let mut diagnostics = lint.diagnostics;
+    world.diagnostics.append(&mut diagnostics);
+    world.diagnostics
+}
+
+struct Lint<'w> {
+    world: &'w World,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Lint<'_> {
+    fn report(&mut self, severity: Severity, area: &str, message: String) {
+        let file_name = self
+            .world
+            .areas
+            .iter()
+            .find(|(area_data, _)| area_data.short_name == area)
+            .map_or(area, |(area_data, _)| &area_data.file_name);
+
+        let diagnostic = Diagnostic::about_file(severity, file_name, message);
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn room_exists(&self, vnum: Vnum) -> bool {
+        self.world.rooms.contains_key(&vnum)
+    }
+
+    fn mobile_exists(&self, vnum: Vnum) -> bool {
+        self.world.mobiles.contains_key(&vnum)
+    }
+
+    fn object_exists(&self, vnum: Vnum) -> bool {
+        self.world.objects.contains_key(&vnum)
+    }
+
+    fn mobprog_exists(&self, vnum: Vnum) -> bool {
+        self.world.mobprogs.contains_key(&vnum)
+    }
+
+    fn check_triggers(&mut self, area: &str, owner: &str, triggers: &[(MobProgTrigger, Vnum)]) {
+        for (trigger, vnum) in triggers {
+            if !self.mobprog_exists(*vnum) {
+                let message = format!(
+                    "{} has a '{}' trigger for mobprog #{}, which doesn't exist",
+                    owner, trigger, vnum.0
+                );
+                self.report(Severity::Error, area, message);
+            }
+        }
+    }
+
+    fn check_rooms(&mut self) {
+        let world = self.world;
+
+        for room in world.rooms.values() {
+            let owner = format!("Room #{}", room.vnum.0);
+            self.check_triggers(&room.area, &owner, &room.mobprog_triggers);
+
+            for exit in &room.exits {
+                let direction = long_direction(&exit.name);
+
+                let other_room = match world.rooms.get(&exit.vnum) {
+                    Some(other_room) => other_room,
+                    None => {
+                        let message = format!(
+                            "{} has an exit {} to room #{}, which doesn't exist",
+                            owner, direction, exit.vnum.0
+                        );
+                        self.report(Severity::Error, &room.area, message);
+                        continue;
+                    }
+                };
+
+                let leads_back = other_room
+                    .exits
+                    .iter()
+                    .any(|other_exit| other_exit.vnum == room.vnum);
+
+                if exit.vnum != room.vnum && !leads_back {
+                    let message = format!(
+                        "{} has a one-way exit {} to room #{}",
+                        owner, direction, exit.vnum.0
+                    );
+                    self.report(Severity::Warning, &room.area, message);
+                }
+            }
+        }
+    }
+
+    fn check_mobiles_and_objects(&mut self) {
+        let world = self.world;
+
+        for mobile in world.mobiles.values() {
+            let owner = format!("Mobile #{}", mobile.vnum.0);
+            self.check_triggers(&mobile.area, &owner, &mobile.mobprog_triggers);
+        }
+
+        for object in world.objects.values() {
+            let owner = format!("Object #{}", object.vnum.0);
+            self.check_triggers(&object.area, &owner, &object.mobprog_triggers);
+        }
+    }
+
+    fn check_resets(&mut self) {
+        let world = self.world;
+
+        for (area_data, resets) in &world.areas {
+            let area = &area_data.short_name;
+
+            for reset in resets {
+                let mut missing = Vec::new();
+
+                match reset {
+                    ResetCommand::Mob { m_num, r_num, .. } => {
+                        if !self.mobile_exists(*m_num) {
+                            missing.push(format!("mobile #{}", m_num.0));
+                        }
+                        if !self.room_exists(*r_num) {
+                            missing.push(format!("room #{}", r_num.0));
+                        }
+                    }
+                    ResetCommand::Object { o_num, r_num, .. } => {
+                        if !self.object_exists(*o_num) {
+                            missing.push(format!("object #{}", o_num.0));
+                        }
+                        if !self.room_exists(*r_num) {
+                            missing.push(format!("room #{}", r_num.0));
+                        }
+                    }
+                    ResetCommand::Door { r_num, .. } => {
+                        if !self.room_exists(*r_num) {
+                            missing.push(format!("room #{}", r_num.0));
+                        }
+                    }
+                    ResetCommand::Give { o_num, .. } | ResetCommand::Equip { o_num, .. } => {
+                        if !self.object_exists(*o_num) {
+                            missing.push(format!("object #{}", o_num.0));
+                        }
+                    }
+                    ResetCommand::Put { o_num, c_num, .. } => {
+                        if !self.object_exists(*o_num) {
+                            missing.push(format!("object #{}", o_num.0));
+                        }
+                        if !self.object_exists(*c_num) {
+                            missing.push(format!("container #{}", c_num.0));
+                        }
+                    }
+                }
+
+                for missing in missing {
+                    let message = format!("A reset uses {}, which doesn't exist", missing);
+                    self.report(Severity::Error, area, message);
+                }
+            }
+        }
+    }
+}
@@ -3,143 +3,231 @@
 //! This module uses the basic primitives in `crate::file_parser` to read area
 //! files, parse rooms/mobiles/objects from them, and convert them into the
 //! plain object types from `crate::world`.
+//!
+//! Files in the older ROM and Merc formats are handed to `crate::load_rom`, and
+//! `.toml` files in DemiMUD's own format to `crate::native_area`.
+
+use std::str::FromStr;
 
 use rand::random;
 
 use crate::{
-    file_parser::FileParser,
+    file_parser::{Diagnostic, FileParser},
     world::{
         Area, AreaData, Exit, ExtraDescription, Gender, MobProg, MobProgTrigger, Mobile, Object,
         ObjectFlags, ResetCommand, Room, Shop, Vnum, VnumOrKeyword,
     },
 };
 
-pub(super) fn load_area(area_file_contents: &str, file_name: &str) -> Area {
-    let mut parser = FileParser::new(area_file_contents, file_name);
+/// Loads one area file.
+///
+/// Problems that only lose part of the area, like an unknown key, are added
+/// to `diagnostics` as warnings. Problems that make the rest of the file
+/// unreadable are returned as an error, and the area is skipped.
+pub(super) fn load_area(
+    area_file_contents: &str,
+    file_name: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Area, Diagnostic> {
+    let mut area = if file_name.ends_with(".toml") {
+        crate::native_area::load_native_area(area_file_contents, file_name)?
+    } else {
+        let mut parser = FileParser::new(area_file_contents, file_name);
+
+        // Dawn of Time areas always start with their area data
+        let area = if area_file_contents.trim_start().starts_with("#DAWNAREADATA") {
+            load_area_sections(&mut parser)
+        } else {
+            crate::load_rom::load_rom_area(&mut parser)
+        };
+        diagnostics.append(&mut parser.warnings);
+        area?
+    };
+
+    let short_name = &area.area_data.short_name;
+    for room in &mut area.rooms {
+        room.area = short_name.clone();
+    }
+    for mobile in &mut area.mobiles {
+        mobile.area = short_name.clone();
+    }
+    for object in &mut area.objects {
+        object.area = short_name.clone();
+    }
+    for mobprog in &mut area.mobprogs {
+        mobprog.area = short_name.clone();
+    }
+    area.area_data.file_name = file_name.to_string();
+
+    Ok(area)
+}
 
+fn load_area_sections(parser: &mut FileParser) -> Result<Area, Diagnostic> {
     let mut area_data = None;
-    let mut mobiles = None;
-    let mut objects = None;
-    let mut rooms = None;
-    let mut resets = None;
-    let mut shops = None;
-    let mut mobprogs = None;
+    let mut mobiles = Vec::new();
+    let mut objects = Vec::new();
+    let mut rooms: Vec<Room> = Vec::new();
+    let mut resets = Vec::new();
+    let mut shops = Vec::new();
+    let mut mobprogs = Vec::new();
+    let mut specials = Vec::new();
 
     loop {
-        let section = parser.read_section();
+        let section = parser.read_section()?;
 
         match section {
             "$" => break,
-            "DAWNAREADATA" => area_data = Some(load_area_data(&mut parser)),
-            "MOBILES" => mobiles = Some(load_mobile_data(&mut parser)),
-            "OBJECTS" => objects = Some(load_object_data(&mut parser)),
-            "ROOMS" => rooms = Some(load_room_data(&mut parser)),
-            "SPECIALS" => skip_specials(&mut parser),
-            "RESETS2" => resets = Some(load_resets(&mut parser)),
-            "SHOPS" => shops = Some(load_shops(&mut parser)),
-            "MOBPROGS" => mobprogs = Some(load_mobprogs(&mut parser)),
-            section => panic!("Unrecognized section: '#{}'", section),
+            "DAWNAREADATA" => area_data = Some(load_area_data(parser)?),
+            "MOBILES" => mobiles = load_mobile_data(parser)?,
+            "OBJECTS" => objects = load_object_data(parser)?,
+            "ROOMS" => rooms = load_room_data(parser)?,
+            "SPECIALS" => specials = read_specials(parser)?,
+            "RESETS2" => resets = load_resets(parser)?,
+            "SHOPS" => shops = load_shops(parser)?,
+            "MOBPROGS" => mobprogs = load_mobprogs(parser)?,
+            section => {
+                parser.warn(&format!("Unrecognized section: '#{}'", section));
+                parser.skip_to_next_section();
+            }
         }
     }
 
-    let area_data = area_data.unwrap();
-    let mut rooms = rooms.unwrap();
-
-    for room in &mut rooms {
-        room.area = area_data.short_name.clone();
-    }
+    let mut area_data = area_data.ok_or_else(|| parser.error("Missing #DAWNAREADATA section"))?;
+    area_data.specials = specials;
 
-    Area {
+    Ok(Area {
         area_data,
         rooms,
-        objects: objects.unwrap(),
-        mobiles: mobiles.unwrap(),
-        resets: resets.unwrap(),
-        shops: shops.unwrap(),
-        mobprogs: mobprogs.unwrap(),
-    }
+        objects,
+        mobiles,
+        resets,
+        shops,
+        mobprogs,
+    })
+}
+
+/// Reads the vnum that starts a mobile, object, room, shop or mobprog, where
+/// `#0` ends the section.
+pub(super) fn read_vnum_section(parser: &mut FileParser) -> Result<Option<usize>, Diagnostic> {
+    let section = parser.read_section()?;
+    let vnum = parser.parse(section, "vnum")?;
+
+    Ok(Some(vnum).filter(|vnum| *vnum != 0))
+}
+
+/// Reads the next word as a number.
+pub(super) fn read_number<T: FromStr>(
+    parser: &mut FileParser,
+    what: &str,
+) -> Result<T, Diagnostic> {
+    let word = parser.read_word()?;
+    parser.parse(word, what)
+}
+
+/// Reads the rest of the line after an unrecognized key, so loading can
+/// carry on with the next one.
+fn skip_unknown_key(parser: &mut FileParser, kind: &str, key: &str) -> Result<(), Diagnostic> {
+    parser.warn(&format!("Unrecognized {} key: '{}'", kind, key));
+    parser.read_until_newline()?;
+    Ok(())
+}
+
+/// A key that doesn't do anything here, like `Level 10` or `Race human~`,
+/// as it was written, so that saving the area writes it back.
+fn unused_key(key: &str, value: &str, tilde: bool) -> String {
+    let end = if tilde { "~" } else { "" };
+    format!("{} {}{}", key, value, end)
 }
 
-fn load_area_data(parser: &mut FileParser) -> AreaData {
+fn load_area_data(parser: &mut FileParser) -> Result<AreaData, Diagnostic> {
     let mut area_data = AreaData {
         name: Default::default(),
         short_name: Default::default(),
+        file_name: Default::default(),
         vnums: Default::default(),
         credits: Default::default(),
         continent: Default::default(),
+        unused_keys: Default::default(),
+        specials: Default::default(),
     };
 
     loop {
-        let key = parser.read_word();
+        let key = parser.read_word()?;
         parser.skip_all_space();
 
-        let value = match key {
+        let (value, tilde) = match key {
             "End" | "END" => break,
             "Version" | "*parent_codebase" | "VNUMs" | "LRange" | "LComment" | "Security"
-            | "colourcode" | "MapScale" | "MapLevel" | "Vnum_offset" => parser.read_until_newline(),
+            | "colourcode" | "MapScale" | "MapLevel" | "Vnum_offset" => {
+                (parser.read_until_newline()?, false)
+            }
             "FromMUD" | "Name" | "ShortName" | "Builders" | "Credits" | "build_restricts"
-            | "AFlags" | "Colour" | "Continent" | "*LastSaved" => parser.read_until_tilde(),
-            section => panic!("Unrecognized area data section: '{}'", section),
+            | "AFlags" | "Colour" | "Continent" | "*LastSaved" => {
+                (parser.read_until_tilde()?, true)
+            }
+            key => {
+                skip_unknown_key(parser, "area data", key)?;
+                continue;
+            }
         };
 
         match key {
             "Name" => area_data.name = value.to_string(),
             "ShortName" => area_data.short_name = value.to_string(),
             "VNUMs" => {
-                let mut vnums = value.split_whitespace().map(|word| word.parse().unwrap());
+                let mut vnums = value.split_whitespace();
 
-                let vnum_1 = vnums.next().unwrap();
-                let vnum_2 = vnums.next().unwrap();
+                let vnum_1 = parser.parse(vnums.next().unwrap_or_default(), "vnum")?;
+                let vnum_2 = parser.parse(vnums.next().unwrap_or_default(), "vnum")?;
 
                 area_data.vnums = (Vnum(vnum_1), Vnum(vnum_2));
             }
             "Credits" => area_data.credits = value.to_string(),
             "Continent" => area_data.continent = value.to_string(),
-            _ => (),
+            // It's saved again when the area is
+            "*LastSaved" => (),
+            _ => area_data.unused_keys.push(unused_key(key, value, tilde)),
         }
     }
 
-    area_data
+    Ok(area_data)
 }
 
-fn load_mobile_data(parser: &mut FileParser) -> Vec<Mobile> {
+fn load_mobile_data(parser: &mut FileParser) -> Result<Vec<Mobile>, Diagnostic> {
     let mut mobiles = Vec::new();
 
-    loop {
-        let vnum = parser.read_section().parse().unwrap();
-
-        if vnum == 0 {
-            break;
-        }
-
-        mobiles.push(load_mobile(parser, vnum))
+    while let Some(vnum) = read_vnum_section(parser)? {
+        mobiles.push(load_mobile(parser, vnum)?)
     }
 
-    mobiles
+    Ok(mobiles)
 }
 
-fn load_mobile(parser: &mut FileParser, vnum: usize) -> Mobile {
+fn load_mobile(parser: &mut FileParser, vnum: usize) -> Result<Mobile, Diagnostic> {
     let mut mobile = Mobile {
         vnum: Vnum(vnum),
         ..Default::default()
     };
 
     loop {
-        let key = parser.read_word();
+        let key = parser.read_word()?;
 
         if key != "End" && key != "END" {
-            parser.skip_one_space();
+            parser.skip_one_space()?;
         }
 
-        let value = match key {
+        let (value, tilde) = match key {
             "END" | "End" => break,
             "Name" | "ShortD" | "LongD" | "Desc" | "Race" | "Act" | "Act2" | "AffBy" | "AffBy2"
             | "Off" | "Imm" | "Res" | "Vuln" | "Form" | "Part" | "StartP" | "DefPos" | "Size"
-            | "Sex" | "MProg" => parser.read_until_tilde(),
+            | "Sex" | "MProg" | "Script" => (parser.read_until_tilde()?, true),
             "Align" | "XPMod" | "Level" | "Hitroll" | "HitDice" | "ManaDice" | "DamDice"
             | "DamType" | "AC" | "Wealth" | "Material" | "Helpgroup" | "InnBuy" | "InnSell"
-            | "InnOpen" | "InnClose" | "InnRoom" => parser.read_until_newline(),
-            key => panic!("Unrecognized mobile data key: '{}'", key),
+            | "InnOpen" | "InnClose" | "InnRoom" => (parser.read_until_newline()?, false),
+            key => {
+                skip_unknown_key(parser, "mobile data", key)?;
+                continue;
+            }
         };
 
         match key {
@@ -159,7 +247,10 @@ fn load_mobile(parser: &mut FileParser, vnum: usize) -> Mobile {
                             Gender::Female
                         }
                     }
-                    gender => parser.panic_on_line(&format!("Unknown sex/gender: {}", gender)),
+                    gender => {
+                        parser.warn(&format!("Unknown sex/gender: {}", gender));
+                        Gender::Neutral
+                    }
                 }
             }
             "Act" => {
@@ -167,249 +258,464 @@ fn load_mobile(parser: &mut FileParser, vnum: usize) -> Mobile {
                     match word {
                         "dont_wander" => mobile.sentinel = true,
                         "unseen" => mobile.unseen = true,
-                        _ => (),
+                        flag => mobile.unused_act_flags.push(flag.to_string()),
                     }
                 }
             }
+            "AffBy" | "AffBy2" => {
+                let flags = value.split_whitespace().map(String::from);
+                mobile.affected_by.extend(flags);
+            }
             "MProg" => {
-                let mut words = value.split_whitespace();
-
-                let (vnum, trigger) = match words.next().unwrap() {
-                    "SPEECH" => (
-                        words.next(),
-                        MobProgTrigger::Speech {
-                            pattern: words.collect::<Vec<_>>().join(" "),
-                        },
-                    ),
-                    "RANDOM" => (
-                        words.next(),
-                        MobProgTrigger::Random {
-                            chance: words.next().unwrap().parse().unwrap(),
-                        },
-                    ),
-                    "DEATH" => (
-                        words.next(),
-                        MobProgTrigger::Death {
-                            chance: words
-                                .next()
-                                .map(|word| if word == "all" { "100" } else { word })
-                                .unwrap()
-                                .parse()
-                                .unwrap(),
-                        },
-                    ),
-                    "EXIT" | "EXALL" => (
-                        words.next(),
-                        MobProgTrigger::Exit {
-                            direction: words.next().unwrap().to_string(),
-                        },
-                    ),
-                    "HOUR" => (
-                        words.next(),
-                        MobProgTrigger::Hour {
-                            hour: words.next().unwrap().parse().unwrap(),
-                        },
-                    ),
-                    "GREET" | "GRALL" => (
-                        words.next(),
-                        MobProgTrigger::Greet {
-                            chance: words.next().unwrap().parse().unwrap(),
-                        },
-                    ),
-                    "GIVE" => {
-                        let mopprog_vnum = words.next();
-                        let item = words.next().unwrap();
-                        let item_vnum = if let Ok(vnum) = item.parse() {
-                            VnumOrKeyword::Vnum(Vnum(vnum))
-                        } else {
-                            VnumOrKeyword::Keyword(item.to_string())
-                        };
-
-                        (mopprog_vnum, MobProgTrigger::Give { item_vnum })
-                    }
-                    "ACT" => (
-                        words.next(),
-                        MobProgTrigger::Act {
-                            pattern: words.collect::<Vec<_>>().join(" ").to_string(),
-                        },
-                    ),
-                    "BRIBE" => (
-                        words.next(),
-                        MobProgTrigger::Bribe {
-                            amount: words.next().unwrap().parse().unwrap(),
-                        },
-                    ),
-                    "KILL" => (
-                        words.next(),
-                        MobProgTrigger::Kill {
-                            chance: words.next().unwrap().parse().unwrap(),
-                        },
-                    ),
-                    "ENTRY" => (
-                        words.next(),
-                        MobProgTrigger::Entry {
-                            chance: words.next().unwrap().parse().unwrap(),
-                        },
-                    ),
-                    "LOGINROOM" => (words.next(), MobProgTrigger::LoginRoom {}),
-                    "REPOP" | "COMMAND" | "SAYTO" | "TICK" | "FIGHT" | "HPCNT" | "DELAY"
-                    | "PREKILL" | "LOGOUTROOM" | "LOGINAREA" | "ROOMDEATH" => continue,
-                    trigger => panic!("Unknown mobprog trigger: {}", trigger),
-                };
-
-                let vnum = Vnum(vnum.unwrap().parse().unwrap());
-
-                mobile.mobprog_triggers.push((trigger, vnum));
+                if let Some(mobprog_trigger) = parse_mobprog_trigger(parser, value)? {
+                    mobile.mobprog_triggers.push(mobprog_trigger);
+                }
             }
-            _ => (),
+            "Script" => mobile.script = Some(value.trim().to_string()),
+            "StartP" => mobile.start_position = value.trim().to_string(),
+            "DefPos" => mobile.default_position = value.trim().to_string(),
+            "Wealth" => mobile.wealth = value.trim().parse().unwrap_or(0),
+            _ => mobile.unused_keys.push(unused_key(key, value, tilde)),
         }
     }
 
-    mobile
+    Ok(mobile)
 }
 
-fn load_object_data(parser: &mut FileParser) -> Vec<Object> {
-    let mut objects = Vec::new();
-
-    loop {
-        let vnum = parser.read_section().parse().unwrap();
+/// Parses an `MProg` line shared by mobiles, rooms and objects.
+///
+/// Returns `None` for triggers that are recognized but not implemented, and
+/// for unknown ones after warning about them.
+pub(super) fn parse_mobprog_trigger(
+    parser: &mut FileParser,
+    value: &str,
+) -> Result<Option<(MobProgTrigger, Vnum)>, Diagnostic> {
+    let mut words = value.split_whitespace();
+    fn number<T: FromStr>(parser: &FileParser, word: Option<&str>) -> Result<T, Diagnostic> {
+        parser.parse(word.unwrap_or_default(), "mobprog trigger value")
+    }
 
-        if vnum == 0 {
-            break;
+    let (vnum, trigger) = match words.next().unwrap_or_default() {
+        "SPEECH" => (
+            words.next(),
+            MobProgTrigger::Speech {
+                pattern: words.collect::<Vec<_>>().join(" "),
+            },
+        ),
+        "RANDOM" => (
+            words.next(),
+            MobProgTrigger::Random {
+                chance: number(parser, words.next())?,
+            },
+        ),
+        "DEATH" => (
+            words.next(),
+            MobProgTrigger::Death {
+                chance: number(
+                    parser,
+                    words
+                        .next()
+                        .map(|word| if word == "all" { "100" } else { word }),
+                )?,
+            },
+        ),
+        "EXIT" | "EXALL" => (
+            words.next(),
+            MobProgTrigger::Exit {
+                direction: words.next().unwrap_or_default().to_string(),
+            },
+        ),
+        "HOUR" => (
+            words.next(),
+            MobProgTrigger::Hour {
+                hour: number(parser, words.next())?,
+            },
+        ),
+        "GREET" | "GRALL" => (
+            words.next(),
+            MobProgTrigger::Greet {
+                chance: number(parser, words.next())?,
+            },
+        ),
+        "GIVE" => {
+            let mopprog_vnum = words.next();
+            let item = words.next().unwrap_or_default();
+            let item_vnum = if let Ok(vnum) = item.parse() {
+                VnumOrKeyword::Vnum(Vnum(vnum))
+            } else {
+                VnumOrKeyword::Keyword(item.to_string())
+            };
+
+            (mopprog_vnum, MobProgTrigger::Give { item_vnum })
+        }
+        "ACT" => (
+            words.next(),
+            MobProgTrigger::Act {
+                pattern: words.collect::<Vec<_>>().join(" ").to_string(),
+            },
+        ),
+        "BRIBE" => (
+            words.next(),
+            MobProgTrigger::Bribe {
+                amount: number(parser, words.next())?,
+            },
+        ),
+        "KILL" => (
+            words.next(),
+            MobProgTrigger::Kill {
+                chance: number(parser, words.next())?,
+            },
+        ),
+        "ENTRY" => (
+            words.next(),
+            MobProgTrigger::Entry {
+                chance: number(parser, words.next())?,
+            },
+        ),
+        "LOGINROOM" => (words.next(), MobProgTrigger::LoginRoom {}),
+        "GET" => (
+            words.next(),
+            MobProgTrigger::Get {
+                chance: number(parser, words.next())?,
+            },
+        ),
+        "DROP" => (
+            words.next(),
+            MobProgTrigger::Drop {
+                chance: number(parser, words.next())?,
+            },
+        ),
+        "WEAR" => (
+            words.next(),
+            MobProgTrigger::Wear {
+                chance: number(parser, words.next())?,
+            },
+        ),
+        "USE" => (
+            words.next(),
+            MobProgTrigger::Use {
+                chance: number(parser, words.next())?,
+            },
+        ),
+        "REPOP" | "COMMAND" | "SAYTO" | "TICK" | "FIGHT" | "HPCNT" | "DELAY" | "PREKILL"
+        | "LOGOUTROOM" | "LOGINAREA" | "ROOMDEATH" => return Ok(None),
+        trigger => {
+            parser.warn(&format!("Unknown mobprog trigger: {}", trigger));
+            return Ok(None);
         }
+    };
+
+    let vnum = Vnum(number(parser, vnum)?);
 
-        objects.push(load_object(parser, vnum))
+    Ok(Some((trigger, vnum)))
+}
+
+fn load_object_data(parser: &mut FileParser) -> Result<Vec<Object>, Diagnostic> {
+    let mut objects = Vec::new();
+
+    while let Some(vnum) = read_vnum_section(parser)? {
+        objects.push(load_object(parser, vnum)?)
     }
 
-    objects
+    Ok(objects)
 }
 
-fn load_object(parser: &mut FileParser, vnum: usize) -> Object {
+fn load_object(parser: &mut FileParser, vnum: usize) -> Result<Object, Diagnostic> {
     let mut object = Object {
         vnum: Vnum(vnum),
+        condition: 100,
         ..Default::default()
     };
 
     loop {
-        let key = parser.read_word();
+        let key = parser.read_word()?;
 
         if key != "End" && key != "END" {
-            parser.skip_one_space();
+            parser.skip_one_space()?;
         }
 
         let mut value2 = None;
 
-        let value = match key {
+        let (value, tilde) = match key {
             "END" | "End" => break,
             "Name" | "Short" | "Desc" | "ItemType" | "Material" | "Extra" | "Extra2" | "Wear"
-            | "ClassAllowances" | "AttuneFlags" => parser.read_until_tilde(),
+            | "ClassAllowances" | "AttuneFlags" | "MProg" | "Script" => {
+                (parser.read_until_tilde()?, true)
+            }
             "Level" | "Cost" | "Condition" | "Asize" | "Rsize" | "Values" | "Weight" | "Affect" => {
-                parser.read_until_newline()
+                (parser.read_until_newline()?, false)
             }
             "ExtraDesc" => {
-                value2 = Some(parser.read_until_tilde());
-                parser.read_until_tilde()
+                value2 = Some(parser.read_until_tilde()?);
+                (parser.read_until_tilde()?, true)
+            }
+            key => {
+                skip_unknown_key(parser, "object data", key)?;
+                continue;
             }
-            key => panic!("Unrecognized object data key: '{}'", key),
         };
 
         match key {
             "Name" => object.name = value.to_string(),
             "Short" => object.short_description = value.to_string(),
-            "Cost" => object.cost = value.parse().expect("Invalid cost"),
+            "Cost" => object.cost = parser.parse(value, "cost")?,
+            "Condition" => object.condition = value.trim().parse().unwrap_or(100).min(100),
             "Desc" => object.description = value.to_string(),
             "ItemType" => object.item_type = value.to_string(),
+            "Wear" => {
+                object.wear_flags = value.split_whitespace().map(String::from).collect();
+            }
+            "Extra" | "Extra2" => {
+                let flags = value.split_whitespace().map(String::from);
+                object.extra_flags.extend(flags);
+            }
             "Values" => {
-                if object.item_type == "container" {
-                    let mut values = value.split_whitespace();
-                    let _ignored = values.next();
-                    let flags = values.next().expect("Second value missing");
-
-                    let mut closable = false;
-                    let mut closed = false;
-                    let mut locked = false;
-
-                    for char in flags.chars() {
-                        match char {
-                            'A' => closable = true,
-                            'C' => closed = true,
-                            'D' => locked = true,
-                            _ => (),
-                        };
-                    }
-
-                    object.flags = ObjectFlags::Container {
-                        closable,
-                        closed,
-                        locked,
-                    }
+                object.flags = parse_item_values(&object.item_type, value);
+                if let ObjectFlags::Unknown = object.flags {
+                    object.unused_keys.push(unused_key(key, value, tilde));
                 }
             }
             "ExtraDesc" => object.extra_descriptions.push(ExtraDescription {
-                keyword: value2.unwrap().to_string(),
+                keyword: value2.unwrap_or_default().to_string(),
                 description: value.to_string(),
             }),
-            _ => (),
+            "MProg" => {
+                if let Some(mobprog_trigger) = parse_mobprog_trigger(parser, value)? {
+                    object.mobprog_triggers.push(mobprog_trigger);
+                }
+            }
+            "Script" => object.script = Some(value.trim().to_string()),
+            _ => object.unused_keys.push(unused_key(key, value, tilde)),
         }
     }
 
-    object
+    Ok(object)
 }
 
-fn load_room_data(parser: &mut FileParser) -> Vec<Room> {
-    let mut rooms = Vec::new();
+/// Liquids by number, for area files that don't give their name.
+const LIQUIDS: &[&str] = &[
+    "water",
+    "beer",
+    "red wine",
+    "ale",
+    "dark ale",
+    "whisky",
+    "lemonade",
+    "firebreather",
+    "local specialty",
+    "slime mold juice",
+    "milk",
+    "tea",
+    "coffee",
+    "blood",
+    "salt water",
+    "coke",
+    "root beer",
+    "elvish wine",
+    "white wine",
+    "champagne",
+    "mead",
+    "rose wine",
+    "benedictine wine",
+    "vodka",
+    "cranberry juice",
+    "orange juice",
+    "absinthe",
+    "brandy",
+    "aquavit",
+    "schnapps",
+    "icewine",
+    "amontillado",
+    "sherry",
+    "framboise",
+    "rum",
+    "cordial",
+];
+
+/// Furniture flags, in the order of the letters that stand for them.
+const FURNITURE_FLAGS: &[&str] = &[
+    "stand_at", "stand_on", "stand_in", "sit_at", "sit_on", "sit_in", "rest_at", "rest_on",
+    "rest_in", "sleep_at", "sleep_on", "sleep_in",
+];
+
+/// Split a `Values` line, keeping quoted values like `'cure light'` together.
+fn split_values(values: &str) -> Vec<String> {
+    let mut split = Vec::new();
+    let mut rest = values.trim_start();
+
+    while !rest.is_empty() {
+        let (value, remaining) = match rest.strip_prefix('\'') {
+            Some(quoted) => quoted.split_once('\'').unwrap_or((quoted, "")),
+            None => rest.split_once(char::is_whitespace).unwrap_or((rest, "")),
+        };
 
-    loop {
-        let vnum = parser.read_section().parse().unwrap();
+        split.push(value.to_string());
+        rest = remaining.trim_start();
+    }
 
-        if vnum == 0 {
-            break;
+    split
+}
+
+fn parse_furniture_flags(flags: &str) -> Vec<String> {
+    if flags.contains('_') {
+        return flags
+            .split(|c: char| c == '|' || c.is_whitespace())
+            .filter(|flag| !flag.is_empty())
+            .map(String::from)
+            .collect();
+    }
+
+    flags
+        .bytes()
+        .filter_map(|letter| FURNITURE_FLAGS.get(letter.checked_sub(b'A')? as usize))
+        .map(|flag| flag.to_string())
+        .collect()
+}
+
+/// Decode the values of the item types that have any behavior.
+pub(super) fn parse_item_values(item_type: &str, values: &str) -> ObjectFlags {
+    let values = split_values(values);
+    let number = |index: usize| -> i64 {
+        values
+            .get(index)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0)
+    };
+    let small_number = |index: usize| number(index).clamp(0, u16::MAX as i64) as u16;
+    let text = |index: usize| values.get(index).map(String::as_str).unwrap_or("");
+
+    match item_type {
+        "container" => {
+            let mut closable = false;
+            let mut pickproof = false;
+            let mut closed = false;
+            let mut locked = false;
+
+            for char in text(1).chars() {
+                match char {
+                    'A' => closable = true,
+                    'B' => pickproof = true,
+                    'C' => closed = true,
+                    'D' => locked = true,
+                    _ => (),
+                };
+            }
+
+            ObjectFlags::Container {
+                closable,
+                closed,
+                locked,
+                pickproof,
+                // Skip it if it's -1 or missing
+                key: Some(number(2))
+                    .filter(|key| *key > 0)
+                    .map(|key| Vnum(key as usize)),
+            }
+        }
+        "drink" | "drink_con" | "fountain" => {
+            let liquid = match text(2).parse::<usize>() {
+                Ok(number) => LIQUIDS.get(number).unwrap_or(&LIQUIDS[0]),
+                Err(_) if text(2).is_empty() => LIQUIDS[0],
+                Err(_) => text(2),
+            };
+
+            ObjectFlags::DrinkContainer {
+                capacity: small_number(0),
+                amount: small_number(1).min(small_number(0)),
+                liquid: liquid.to_string(),
+                fountain: item_type == "fountain",
+            }
         }
+        "light" => ObjectFlags::Light {
+            // Both mean a light that never burns out
+            hours: match number(2) {
+                -1 | 999 => None,
+                _ => Some(small_number(2)),
+            },
+        },
+        "furniture" => ObjectFlags::Furniture {
+            capacity: small_number(0).max(1) as usize,
+            flags: parse_furniture_flags(text(2)),
+        },
+        "scroll" | "potion" | "pill" => ObjectFlags::Spells {
+            level: small_number(0),
+            spells: values
+                .iter()
+                .skip(1)
+                .filter(|spell| !spell.is_empty() && *spell != "reserved")
+                .filter(|spell| spell.parse::<i64>().is_err())
+                .cloned()
+                .collect(),
+        },
+        "portal" => ObjectFlags::Portal {
+            // Portals without charges can be used forever
+            charges: Some(small_number(0)).filter(|charges| *charges > 0),
+            destination: Vnum(number(3).max(0) as usize),
+        },
+        _ => ObjectFlags::Unknown,
+    }
+}
+
+fn load_room_data(parser: &mut FileParser) -> Result<Vec<Room>, Diagnostic> {
+    let mut rooms = Vec::new();
 
-        rooms.push(load_room(parser, vnum))
+    while let Some(vnum) = read_vnum_section(parser)? {
+        rooms.push(load_room(parser, vnum)?)
     }
 
-    rooms
+    Ok(rooms)
 }
 
-fn load_room(parser: &mut FileParser, vnum: usize) -> Room {
+fn load_room(parser: &mut FileParser, vnum: usize) -> Result<Room, Diagnostic> {
     let mut room = Room {
         vnum: Vnum(vnum),
         ..Default::default()
     };
 
     loop {
-        let key = parser.read_word();
+        let key = parser.read_word()?;
 
         if key != "End" && key != "END" {
-            parser.skip_one_space();
+            parser.skip_one_space()?;
         }
 
         let mut value2 = None;
 
-        let value = match key {
+        let (value, tilde) = match key {
             "END" | "End" => break,
             "Name" | "Desc" | "RoomFlags" | "Sector" | "RoomEcho" | "EDesc" | "EFlags"
-            | "EKeywords" => parser.read_until_tilde(),
+            | "EKeywords" | "MProg" | "Script" => (parser.read_until_tilde()?, true),
             "Mana" | "Heal" | "LockerQuant" | "LockerInitRent" | "LockerOngoRent"
             | "LockerWeight" | "LockerCapacity" | "LockerPickProof" | "Exit" | "EKeyvnum" => {
-                parser.read_until_newline()
+                (parser.read_until_newline()?, false)
             }
             "ExtraDesc" => {
-                value2 = Some(parser.read_until_tilde());
-                parser.read_until_tilde()
+                value2 = Some(parser.read_until_tilde()?);
+                (parser.read_until_tilde()?, true)
+            }
+            key => {
+                skip_unknown_key(parser, "room data", key)?;
+                continue;
             }
-            key => panic!("Unrecognized room data key: '{}'", key),
         };
 
         match key {
             "Name" => room.name = value.to_string(),
             "Desc" => room.description = value.to_string(),
             "Sector" => room.sector = value.to_string(),
+            "RoomFlags" => {
+                for flag in value.split_whitespace() {
+                    match flag {
+                        "dark" => room.dark = true,
+                        "bank" => room.bank = true,
+                        flag => room.unused_room_flags.push(flag.to_string()),
+                    }
+                }
+            }
             "Exit" => {
                 let mut args = value.split_whitespace();
-                let name = args.next().unwrap();
-                let vnum = args.next().unwrap().parse().unwrap();
+                let name = args.next().unwrap_or_default();
+                let vnum = parser.parse(args.next().unwrap_or_default(), "exit vnum")?;
                 room.exits.push(Exit {
                     name: name.to_string(),
                     vnum: Vnum(vnum),
@@ -418,17 +724,19 @@ fn load_room(parser: &mut FileParser, vnum: usize) -> Room {
                 })
             }
             "EDesc" => {
-                let exit = room.exits.last_mut().unwrap();
+                let exit = last_exit(parser, &mut room.exits)?;
                 exit.description = Some(value.to_string());
             }
             "EFlags" => {
-                let exit = room.exits.last_mut().unwrap();
+                let exit = last_exit(parser, &mut room.exits)?;
 
                 for flag in value.split_whitespace() {
                     match flag {
                         "door" => exit.has_door = true,
                         "closed" => exit.is_closed = true,
                         "locked" => exit.is_locked = true,
+                        "pickproof" => exit.is_pickproof = true,
+                        "bashproof" | "nobash" => exit.is_bashproof = true,
                         _ => (),
                     }
                 }
@@ -436,55 +744,84 @@ fn load_room(parser: &mut FileParser, vnum: usize) -> Room {
             "EKeyvnum" => {
                 use std::convert::TryInto;
 
-                let exit = room.exits.last_mut().unwrap();
-                let vnum: i32 = value.parse().unwrap();
+                let exit = last_exit(parser, &mut room.exits)?;
+                let vnum: i32 = parser.parse(value, "key vnum")?;
                 // Skip it if it's -1
                 if let Ok(vnum) = vnum.try_into() {
                     exit.key = Some(Vnum(vnum));
                 }
             }
             "EKeywords" => {
-                let exit = room.exits.last_mut().unwrap();
+                let exit = last_exit(parser, &mut room.exits)?;
                 exit.extra_keywords = Some(value.to_string());
             }
             "ExtraDesc" => room.extra_descriptions.push(ExtraDescription {
-                keyword: value2.unwrap().to_string(),
+                keyword: value2.unwrap_or_default().to_string(),
                 description: value.to_string(),
             }),
-            _ => (),
+            "MProg" => {
+                if let Some(mobprog_trigger) = parse_mobprog_trigger(parser, value)? {
+                    room.mobprog_triggers.push(mobprog_trigger);
+                }
+            }
+            "Script" => room.script = Some(value.trim().to_string()),
+            _ => room.unused_keys.push(unused_key(key, value, tilde)),
         }
     }
 
-    room
+    Ok(room)
+}
+
+/// The exit that `EDesc`, `EFlags` and the like describe.
+fn last_exit<'a>(parser: &FileParser, exits: &'a mut [Exit]) -> Result<&'a mut Exit, Diagnostic> {
+    exits
+        .last_mut()
+        .ok_or_else(|| parser.error("Exit details before any 'Exit'"))
 }
 
-fn skip_specials(parser: &mut FileParser) {
+/// Reads the lines of a `#SPECIALS` section, which are only kept to be
+/// written back.
+pub(super) fn read_specials(parser: &mut FileParser) -> Result<Vec<String>, Diagnostic> {
+    let mut specials = Vec::new();
+
     loop {
-        let line = parser.read_until_newline();
+        let line = parser.read_until_newline()?;
         if line == "S" {
             break;
         }
+        specials.push(line.to_string());
     }
+
+    Ok(specials)
 }
 
-fn load_resets(parser: &mut FileParser) -> Vec<ResetCommand> {
+fn load_resets(parser: &mut FileParser) -> Result<Vec<ResetCommand>, Diagnostic> {
     let mut resets = Vec::new();
 
+    // Every reset starts with a `0` that used to be a conditional flag
+    fn read_zero(parser: &mut FileParser) -> Result<(), Diagnostic> {
+        match parser.read_word()? {
+            "0" => Ok(()),
+            word => Err(parser.error(&format!(
+                "Expected '0' after the reset type, got '{}'",
+                word
+            ))),
+        }
+    }
+
     loop {
-        let reset_type = parser.read_word();
+        let reset_type = parser.read_word()?;
 
         match reset_type {
             "S" => {
-                parser.skip_one_newline();
+                parser.skip_one_newline()?;
                 break;
             }
             "O" => {
-                let zero = parser.read_word();
-                let o_num = parser.read_word().parse().unwrap();
-                let global_limit = parser.read_word().parse().unwrap();
-                let r_num = parser.read_word().parse().unwrap();
-
-                assert_eq!(zero, "0");
+                read_zero(parser)?;
+                let o_num = read_number(parser, "object vnum")?;
+                let global_limit = read_number(parser, "global limit")?;
+                let r_num = read_number(parser, "room vnum")?;
 
                 resets.push(ResetCommand::Object {
                     o_num: Vnum(o_num),
@@ -493,13 +830,11 @@ fn load_resets(parser: &mut FileParser) -> Vec<ResetCommand> {
                 })
             }
             "M" => {
-                let zero = parser.read_word();
-                let m_num = parser.read_word().parse().unwrap();
-                let global_limit = parser.read_word().parse().unwrap();
-                let r_num = parser.read_word().parse().unwrap();
-                let room_limit = parser.read_word().parse().unwrap();
-
-                assert_eq!(zero, "0");
+                read_zero(parser)?;
+                let m_num = read_number(parser, "mobile vnum")?;
+                let global_limit = read_number(parser, "global limit")?;
+                let r_num = read_number(parser, "room vnum")?;
+                let room_limit = read_number(parser, "room limit")?;
 
                 resets.push(ResetCommand::Mob {
                     m_num: Vnum(m_num),
@@ -509,11 +844,9 @@ fn load_resets(parser: &mut FileParser) -> Vec<ResetCommand> {
                 })
             }
             "G" => {
-                let zero = parser.read_word();
-                let o_num = parser.read_word().parse().unwrap();
-                let global_limit = parser.read_word().parse().unwrap();
-
-                assert_eq!(zero, "0");
+                read_zero(parser)?;
+                let o_num = read_number(parser, "object vnum")?;
+                let global_limit = read_number(parser, "global limit")?;
 
                 resets.push(ResetCommand::Give {
                     o_num: Vnum(o_num),
@@ -521,14 +854,14 @@ fn load_resets(parser: &mut FileParser) -> Vec<ResetCommand> {
                 })
             }
             "E" => {
-                let zero = parser.read_word();
-                let o_num = parser.read_word().parse().unwrap();
-                let global_limit = parser.read_word().parse().unwrap();
-                let mut location = parser.read_word().to_string();
-
-                assert_eq!(location.pop(), Some('~'));
-
-                assert_eq!(zero, "0");
+                read_zero(parser)?;
+                let o_num = read_number(parser, "object vnum")?;
+                let global_limit = read_number(parser, "global limit")?;
+                let location = parser.read_word()?;
+                let location = location
+                    .strip_suffix('~')
+                    .ok_or_else(|| parser.error("Missing '~' after the wear location"))?
+                    .to_string();
 
                 resets.push(ResetCommand::Equip {
                     o_num: Vnum(o_num),
@@ -537,13 +870,11 @@ fn load_resets(parser: &mut FileParser) -> Vec<ResetCommand> {
                 })
             }
             "P" => {
-                let zero = parser.read_word();
-                let o_num = parser.read_word().parse().unwrap();
-                let global_limit = parser.read_word().parse().unwrap();
-                let c_num = parser.read_word().parse().unwrap();
-                let container_limit = parser.read_word().parse().unwrap();
-
-                assert_eq!(zero, "0");
+                read_zero(parser)?;
+                let o_num = read_number(parser, "object vnum")?;
+                let global_limit = read_number(parser, "global limit")?;
+                let c_num = read_number(parser, "container vnum")?;
+                let container_limit = read_number(parser, "container limit")?;
 
                 resets.push(ResetCommand::Put {
                     o_num: Vnum(o_num),
@@ -553,31 +884,25 @@ fn load_resets(parser: &mut FileParser) -> Vec<ResetCommand> {
                 })
             }
             _ => {
-                parser.read_until_newline();
+                parser.read_until_newline()?;
             }
         }
     }
 
-    resets
+    Ok(resets)
 }
 
-fn load_shops(parser: &mut FileParser) -> Vec<Shop> {
+fn load_shops(parser: &mut FileParser) -> Result<Vec<Shop>, Diagnostic> {
     let mut shops = Vec::new();
 
-    loop {
-        let vnum = parser.read_section().parse().unwrap();
-
-        if vnum == 0 {
-            break;
-        }
-
-        shops.push(load_shop(parser, vnum))
+    while let Some(vnum) = read_vnum_section(parser)? {
+        shops.push(load_shop(parser, vnum)?)
     }
 
-    shops
+    Ok(shops)
 }
 
-fn load_shop(parser: &mut FileParser, vnum: usize) -> Shop {
+fn load_shop(parser: &mut FileParser, vnum: usize) -> Result<Shop, Diagnostic> {
     let mut shop = Shop {
         vnum: Vnum(vnum),
         buy_types: Vec::new(),
@@ -589,91 +914,78 @@ fn load_shop(parser: &mut FileParser, vnum: usize) -> Shop {
     };
 
     loop {
-        let key = parser.read_word();
+        let key = parser.read_word()?;
 
         match key {
-            "buy_type" => shop.buy_types.push(parser.read_until_tilde().to_string()),
-            "sell_type" => shop.buy_types.push(parser.read_until_tilde().to_string()),
+            "buy_type" => shop
+                .buy_types
+                .push(parser.read_until_tilde()?.trim().to_string()),
+            "sell_type" => shop
+                .sell_types
+                .push(parser.read_until_tilde()?.trim().to_string()),
             "open_hour" => {
-                shop.open_hour = parser
-                    .read_until_newline()
-                    .trim_start()
-                    .parse()
-                    .expect("Open hour")
+                let value = parser.read_until_newline()?;
+                shop.open_hour = parser.parse(value, "open hour")?;
             }
             "close_hour" => {
-                shop.close_hour = parser
-                    .read_until_newline()
-                    .trim_start()
-                    .parse()
-                    .expect("Close hour")
+                let value = parser.read_until_newline()?;
+                shop.close_hour = parser.parse(value, "close hour")?;
             }
             "profit_buy" => {
-                shop.profit_buy = parser
-                    .read_until_newline()
-                    .trim_start()
-                    .parse()
-                    .unwrap_or_else(|string| {
-                        parser.panic_on_line(&format!("Profit error for {}: '{}'", vnum, string))
-                    })
+                let value = parser.read_until_newline()?;
+                shop.profit_buy = parser.parse(value, "buying profit")?;
             }
             "profit_sell" => {
-                shop.profit_sell = parser
-                    .read_until_newline()
-                    .trim_start()
-                    .parse()
-                    .expect("Profit sell")
+                let value = parser.read_until_newline()?;
+                shop.profit_sell = parser.parse(value, "selling profit")?;
             }
             "END" => break,
-            key => parser.panic_on_line(&format!("Unknown shop key {}", key)),
+            key => skip_unknown_key(parser, "shop", key)?,
         }
     }
 
-    shop
+    Ok(shop)
 }
 
-fn load_mobprogs(parser: &mut FileParser) -> Vec<MobProg> {
+fn load_mobprogs(parser: &mut FileParser) -> Result<Vec<MobProg>, Diagnostic> {
     let mut mobprogs = Vec::new();
 
-    loop {
-        let vnum = parser.read_section().parse().unwrap();
-
-        if vnum == 0 {
-            break;
-        }
-
-        mobprogs.push(load_mobprog(parser, vnum))
+    while let Some(vnum) = read_vnum_section(parser)? {
+        mobprogs.push(load_mobprog(parser, vnum)?)
     }
 
-    mobprogs
+    Ok(mobprogs)
 }
 
-fn load_mobprog(parser: &mut FileParser, vnum: usize) -> MobProg {
+fn load_mobprog(parser: &mut FileParser, vnum: usize) -> Result<MobProg, Diagnostic> {
     let mut title = None;
     let mut code = None;
     let mut disabled = None;
 
     loop {
-        let key = parser.read_word();
+        let key = parser.read_word()?;
 
         if key == "END" {
             break;
         }
 
-        parser.skip_one_space();
+        parser.skip_one_space()?;
 
         match key {
-            "title" => title = Some(parser.read_until_tilde().to_string()),
-            "code" => code = Some(parser.read_until_tilde().to_string()),
-            "disabled" => disabled = Some(parser.read_until_newline()),
-            key => parser.panic_on_line(&format!("Unknown mobprog key {}", key)),
+            "title" => title = Some(parser.read_until_tilde()?.to_string()),
+            "code" => code = Some(parser.read_until_tilde()?.to_string()),
+            "disabled" => disabled = Some(parser.read_until_newline()?),
+            key => skip_unknown_key(parser, "mobprog", key)?,
         }
     }
 
-    MobProg {
+    let disabled = disabled.ok_or_else(|| parser.error("Mobprog is missing 'disabled'"))?;
+
+    Ok(MobProg {
         vnum: Vnum(vnum),
         title: title.unwrap_or_else(|| "<untitled>".to_string()),
         code: code.unwrap_or_else(|| "".to_string()),
-        disabled: disabled.expect("Needs disabled") != "true",
-    }
+        disabled: disabled == "true",
+        area: String::new(),
+    })
 }
@@ -0,0 +1,841 @@
+//! ROM 2.4 and Merc area loader.
+//!
+//! Unlike Dawn of Time's keyed records, these formats depend on the order of
+//! their fields, and are read the same way as ROM's own `db.c` reads them.
+//! This includes Merc's older mobiles and objects, which ROM keeps in its
+//! `#MOBOLD` and `#OBJOLD` sections, and plain Merc areas in `#MOBILES` and
+//! `#OBJECTS`; they are told apart by what follows their descriptions.
+//!
+//! Everything is converted into the same `crate::world` types as Dawn of Time
+//! areas are, so both are imported the same way.
+
+use crate::{
+    file_parser::{Diagnostic, FileParser},
+    load::{
+        parse_item_values, parse_mobprog_trigger, read_number, read_specials, read_vnum_section,
+    },
+    world::{
+        Area, AreaData, Exit, ExtraDescription, Gender, MobProg, Mobile, Object, ResetCommand,
+        Room, Shop, Vnum,
+    },
+};
+
+/// Exit directions, in the order of `D0` to `D5`.
+const DIRECTIONS: &[&str] = &["north", "east", "south", "west", "up", "down"];
+
+/// Sector types by number.
+const SECTORS: &[&str] = &[
+    "inside", "city", "field", "forest", "hills", "mountain", "swim", "noswim", "inside", "air",
+    "desert",
+];
+
+/// Item types by number, as used by Merc objects and by shops.
+const ITEM_TYPES: &[&str] = &[
+    "",
+    "light",
+    "scroll",
+    "wand",
+    "staff",
+    "weapon",
+    "",
+    "",
+    "treasure",
+    "armor",
+    "potion",
+    "clothing",
+    "furniture",
+    "trash",
+    "",
+    "container",
+    "",
+    "drink_con",
+    "key",
+    "food",
+    "money",
+    "",
+    "boat",
+    "npc_corpse",
+    "pc_corpse",
+    "fountain",
+    "pill",
+    "protect",
+    "map",
+    "portal",
+    "warp_stone",
+    "room_key",
+    "gem",
+    "jewelry",
+    "jukebox",
+];
+
+/// Where `E` resets equip objects, by number.
+const WEAR_LOCATIONS: &[&str] = &[
+    "light", "finger", "finger", "neck", "neck", "body", "head", "legs", "feet", "hands", "arms",
+    "shield", "about", "waist", "wrist", "wrist", "wield", "hold", "float",
+];
+
+/// Flag names, in the order of the letters (or bits) that stand for them.
+const AFFECT_FLAGS: &[&str] = &[
+    "blind",
+    "invisible",
+    "detect_evil",
+    "detect_invis",
+    "detect_magic",
+    "detect_hidden",
+    "detect_good",
+    "sanctuary",
+    "faerie_fire",
+    "infrared",
+    "curse",
+    "",
+    "poison",
+    "protect_evil",
+    "protect_good",
+    "sneak",
+    "hide",
+    "sleep",
+    "charm",
+    "flying",
+    "pass_door",
+    "haste",
+    "calm",
+    "plague",
+    "weaken",
+    "dark_vision",
+    "berserk",
+    "swim",
+    "regeneration",
+    "slow",
+];
+
+const EXTRA_FLAGS: &[&str] = &[
+    "glow",
+    "hum",
+    "dark",
+    "lock",
+    "evil",
+    "invis",
+    "magic",
+    "nodrop",
+    "bless",
+    "anti_good",
+    "anti_evil",
+    "anti_neutral",
+    "noremove",
+    "inventory",
+    "nopurge",
+    "rot_death",
+    "vis_death",
+    "",
+    "nonmetal",
+    "nolocate",
+    "melt_drop",
+    "had_timer",
+    "sell_extract",
+    "",
+    "burn_proof",
+    "nouncurse",
+];
+
+// `no_sac` is left out, since the first wear flag that isn't `take` is where
+// the object is worn
+const WEAR_FLAGS: &[&str] = &[
+    "take", "finger", "neck", "body", "head", "legs", "feet", "hands", "arms", "shield", "about",
+    "waist", "wrist", "wield", "hold", "", "float",
+];
+
+const ACT_SENTINEL: u64 = 1 << 1;
+const ROOM_DARK: u64 = 1;
+
+pub(super) fn load_rom_area(parser: &mut FileParser) -> Result<Area, Diagnostic> {
+    let mut area_data = None;
+    let mut mobiles = Vec::new();
+    let mut objects = Vec::new();
+    let mut rooms = Vec::new();
+    let mut resets = Vec::new();
+    let mut shops = Vec::new();
+    let mut mobprogs = Vec::new();
+    let mut specials = Vec::new();
+
+    loop {
+        let section = read_section_name(parser)?;
+
+        match section {
+            "$" => break,
+            "AREA" => area_data = Some(load_area_header(parser)?),
+            "AREADATA" => area_data = Some(load_olc_area_data(parser)?),
+            "HELPS" => skip_helps(parser)?,
+            "MOBILES" | "MOBOLD" => mobiles.append(&mut load_mobiles(parser)?),
+            "OBJECTS" | "OBJOLD" => objects.append(&mut load_objects(parser)?),
+            "ROOMS" => rooms.append(&mut load_rooms(parser)?),
+            "RESETS" => resets.append(&mut load_resets(parser)?),
+            "SHOPS" => shops.append(&mut load_shops(parser)?),
+            "SPECIALS" => specials.append(&mut read_specials(parser)?),
+            "MOBPROGS" => mobprogs.append(&mut load_mobprogs(parser)?),
+            section => {
+                parser.warn(&format!("Unrecognized section: '#{}'", section));
+                parser.skip_to_next_section();
+            }
+        }
+    }
+
+    // Files with only helps have no area header
+    let mut area_data = match area_data {
+        Some(area_data) => area_data,
+        None => {
+            let name = parser.file_name().rsplit('/').next().unwrap_or_default();
+            new_area_data(name, "", (Vnum(0), Vnum(0)))
+        }
+    };
+    area_data.specials = specials;
+
+    close_doors(&mut rooms, &resets);
+
+    Ok(Area {
+        area_data,
+        rooms,
+        objects,
+        mobiles,
+        resets,
+        shops,
+        mobprogs,
+    })
+}
+
+/// Unlike `FileParser::read_section`, this allows the section to be followed
+/// by something on the same line, like Merc's `#AREA`.
+fn read_section_name<'a>(parser: &mut FileParser<'a>) -> Result<&'a str, Diagnostic> {
+    let word = parser.read_word()?;
+
+    word.strip_prefix('#')
+        .ok_or_else(|| parser.error(&format!("Expected a '#' section, got '{}'", word)))
+}
+
+fn new_area_data(name: &str, credits: &str, vnums: (Vnum, Vnum)) -> AreaData {
+    AreaData {
+        name: name.to_string(),
+        short_name: name.to_string(),
+        file_name: Default::default(),
+        vnums,
+        credits: credits.to_string(),
+        continent: Default::default(),
+        unused_keys: Default::default(),
+        specials: Default::default(),
+    }
+}
+
+/// Credits look like `{ 5 35} Author  Area name`; only the author is kept.
+fn split_credits(credits: &str) -> (&str, &str) {
+    let credits = credits
+        .split_once('}')
+        .map_or(credits, |(_levels, credits)| credits)
+        .trim();
+
+    match credits.split_once(char::is_whitespace) {
+        Some((author, name)) => (author, name.trim()),
+        None => (credits, credits),
+    }
+}
+
+fn load_area_header(parser: &mut FileParser) -> Result<AreaData, Diagnostic> {
+    // Merc areas have it all on the same line, ROM areas on the next ones
+    let header = parser.read_until_newline()?.trim();
+    if !header.is_empty() {
+        let (author, name) = split_credits(header.trim_end_matches('~'));
+        return Ok(new_area_data(name, author, (Vnum(0), Vnum(0))));
+    }
+
+    let _file_name = parser.read_string()?;
+    let name = parser.read_string()?;
+    let credits = parser.read_string()?;
+    let low = read_number(parser, "vnum")?;
+    let high = read_number(parser, "vnum")?;
+
+    let (author, _name) = split_credits(credits);
+    Ok(new_area_data(name, author, (Vnum(low), Vnum(high))))
+}
+
+/// The keyed area header written by ROM's OLC.
+fn load_olc_area_data(parser: &mut FileParser) -> Result<AreaData, Diagnostic> {
+    let mut area_data = new_area_data("", "", (Vnum(0), Vnum(0)));
+    let mut builders = "";
+
+    loop {
+        match parser.read_word()? {
+            "End" => break,
+            "Name" => {
+                area_data.name = parser.read_string()?.trim().to_string();
+                area_data.short_name = area_data.name.clone();
+            }
+            "Credits" => area_data.credits = split_credits(parser.read_string()?).0.to_string(),
+            "Builders" => builders = parser.read_string()?.trim(),
+            "VNUMs" => {
+                let low = read_number(parser, "vnum")?;
+                let high = read_number(parser, "vnum")?;
+                area_data.vnums = (Vnum(low), Vnum(high));
+            }
+            "Security" | "Recall" | "Flags" => {
+                parser.read_until_newline()?;
+            }
+            key => {
+                parser.warn(&format!("Unrecognized area data key: '{}'", key));
+                parser.read_until_newline()?;
+            }
+        }
+    }
+
+    if area_data.credits.is_empty() && builders != "None" {
+        area_data.credits = builders.to_string();
+    }
+
+    Ok(area_data)
+}
+
+fn skip_helps(parser: &mut FileParser) -> Result<(), Diagnostic> {
+    loop {
+        let _level = parser.read_word()?;
+        let keywords = parser.read_string()?;
+
+        if keywords.trim() == "$" {
+            return Ok(());
+        }
+
+        let _text = parser.read_string()?;
+    }
+}
+
+/// Numbers, or letters where `A` to `Z` are the first bits and `a` to `z` the
+/// next ones. Either can be added together with `|`.
+fn parse_flags(flags: &str) -> u64 {
+    flags
+        .split('|')
+        .map(|part| match part.parse::<i64>() {
+            Ok(number) => number as u64,
+            Err(_) => part
+                .bytes()
+                .filter_map(|letter| match letter {
+                    b'A'..=b'Z' => Some(1 << (letter - b'A')),
+                    b'a'..=b'z' => Some(1 << (26 + letter - b'a')),
+                    _ => None,
+                })
+                .fold(0, |flags, flag| flags | flag),
+        })
+        .fold(0, |flags, flag| flags | flag)
+}
+
+fn flag_names(flags: &str, names: &[&str]) -> Vec<String> {
+    let flags = parse_flags(flags);
+
+    names
+        .iter()
+        .enumerate()
+        .filter(|(bit, name)| flags & (1 << bit) != 0 && !name.is_empty())
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+/// Turn flags that may be numbers into letters, which is what
+/// `parse_item_values` expects.
+fn flag_letters(flags: &str) -> String {
+    let flags = parse_flags(flags);
+
+    (b'A'..=b'Z')
+        .enumerate()
+        .filter(|(bit, _)| flags & (1 << bit) != 0)
+        .map(|(_, letter)| letter as char)
+        .collect()
+}
+
+fn load_mobiles(parser: &mut FileParser) -> Result<Vec<Mobile>, Diagnostic> {
+    let mut mobiles = Vec::new();
+
+    while let Some(vnum) = read_vnum_section(parser)? {
+        mobiles.push(load_mobile(parser, vnum)?)
+    }
+
+    Ok(mobiles)
+}
+
+fn load_mobile(parser: &mut FileParser, vnum: usize) -> Result<Mobile, Diagnostic> {
+    let mut mobile = Mobile {
+        vnum: Vnum(vnum),
+        name: parser.read_string()?.to_string(),
+        short_description: parser.read_string()?.to_string(),
+        long_description: parser.read_string()?.trim().to_string(),
+        description: parser.read_string()?.to_string(),
+        ..Default::default()
+    };
+
+    // ROM mobiles have a race here, Merc mobiles go straight to their flags
+    parser.skip_all_space();
+    let line = parser.read_until_newline()?;
+    if line.trim_end().ends_with('~') {
+        load_rom_mobile_stats(parser, &mut mobile)?;
+    } else {
+        load_merc_mobile_stats(parser, line, &mut mobile)?;
+    }
+
+    loop {
+        match parser.peek_word() {
+            Some("F") => {
+                // Removes flags that the race would add
+                parser.read_word()?;
+                parser.read_word()?;
+                parser.read_word()?;
+            }
+            Some("M") => {
+                parser.read_word()?;
+                // ROM doesn't care about the case of trigger names
+                let trigger = parser.read_word()?.to_ascii_uppercase();
+                let trigger = format!("{} {}", trigger, parser.read_string()?.trim());
+                if let Some(mobprog_trigger) = parse_mobprog_trigger(parser, &trigger)? {
+                    mobile.mobprog_triggers.push(mobprog_trigger);
+                }
+            }
+            Some(word) if word.starts_with('>') => {
+                parser.warn("Merc mobprogs are not supported");
+                while parser.read_until_newline()?.trim() != "|" {}
+            }
+            _ => break,
+        }
+    }
+
+    Ok(mobile)
+}
+
+fn set_mobile_flags(mobile: &mut Mobile, act: &str, affected_by: &str) {
+    mobile.sentinel = parse_flags(act) & ACT_SENTINEL != 0;
+    mobile.affected_by = flag_names(affected_by, AFFECT_FLAGS);
+}
+
+fn load_rom_mobile_stats(parser: &mut FileParser, mobile: &mut Mobile) -> Result<(), Diagnostic> {
+    let act = parser.read_word()?;
+    let affected_by = parser.read_word()?;
+    set_mobile_flags(mobile, act, affected_by);
+
+    // Alignment and group; level, hitroll, dice and damage type; armor
+    // classes; offensive flags, immunities, resistances and vulnerabilities
+    for _ in 0..16 {
+        parser.read_word()?;
+    }
+
+    mobile.start_position = parser.read_word()?.to_string();
+    mobile.default_position = parser.read_word()?.to_string();
+    mobile.gender = match parser.read_word()? {
+        "male" => Gender::Male,
+        "female" => Gender::Female,
+        "none" | "neutral" => Gender::Neutral,
+        "either" | "random" => {
+            if rand::random() {
+                Gender::Male
+            } else {
+                Gender::Female
+            }
+        }
+        gender => {
+            parser.warn(&format!("Unknown sex/gender: {}", gender));
+            Gender::Neutral
+        }
+    };
+    mobile.wealth = read_number(parser, "wealth")?;
+
+    // Form, parts, size and material
+    for _ in 0..4 {
+        parser.read_word()?;
+    }
+
+    Ok(())
+}
+
+fn load_merc_mobile_stats(
+    parser: &mut FileParser,
+    flags_line: &str,
+    mobile: &mut Mobile,
+) -> Result<(), Diagnostic> {
+    let mut flags = flags_line.split_whitespace();
+    let act = flags.next().unwrap_or_default();
+    let affected_by = flags.next().unwrap_or_default();
+    set_mobile_flags(mobile, act, affected_by);
+
+    // Level, hitroll, armor class, and hit and damage dice
+    for _ in 0..5 {
+        parser.read_word()?;
+    }
+
+    // Merc gives gold, ROM turns it into silver like this
+    let gold: usize = read_number(parser, "gold")?;
+    mobile.wealth = gold / 20;
+    let _experience = parser.read_word()?;
+
+    // Merc's positions are numbered differently, and ROM ignores them too
+    let _start_position = parser.read_word()?;
+    let _default_position = parser.read_word()?;
+    mobile.gender = match parser.read_word()? {
+        "1" => Gender::Male,
+        "2" => Gender::Female,
+        _ => Gender::Neutral,
+    };
+
+    Ok(())
+}
+
+fn load_objects(parser: &mut FileParser) -> Result<Vec<Object>, Diagnostic> {
+    let mut objects = Vec::new();
+
+    while let Some(vnum) = read_vnum_section(parser)? {
+        objects.push(load_object(parser, vnum)?)
+    }
+
+    Ok(objects)
+}
+
+fn load_object(parser: &mut FileParser, vnum: usize) -> Result<Object, Diagnostic> {
+    let mut object = Object {
+        vnum: Vnum(vnum),
+        name: parser.read_string()?.to_string(),
+        short_description: parser.read_string()?.to_string(),
+        description: parser.read_string()?.trim().to_string(),
+        condition: 100,
+        ..Default::default()
+    };
+    // ROM's material, or Merc's unused action description
+    let _material = parser.read_string()?;
+
+    parser.skip_all_space();
+    let mut types = parser.read_until_newline()?.split_whitespace();
+    let item_type = types.next().unwrap_or_default();
+
+    // Merc objects have numbered item types
+    let merc = item_type.parse::<usize>().is_ok();
+    object.item_type = match item_type.parse::<usize>() {
+        Ok(number) => ITEM_TYPES.get(number).copied().unwrap_or_default(),
+        Err(_) => item_type,
+    }
+    .to_string();
+    object.extra_flags = flag_names(types.next().unwrap_or_default(), EXTRA_FLAGS);
+    object.wear_flags = flag_names(types.next().unwrap_or_default(), WEAR_FLAGS);
+
+    parser.skip_all_space();
+    let values = parser.read_until_newline()?;
+    object.flags = parse_item_values(&object.item_type, &item_values(&object.item_type, values));
+
+    if merc {
+        let _weight = parser.read_word()?;
+        object.cost = read_number(parser, "cost")?;
+        let _rent = parser.read_word()?;
+    } else {
+        let _level = parser.read_word()?;
+        let _weight = parser.read_word()?;
+        object.cost = read_number(parser, "cost")?;
+        object.condition = match parser.read_word()? {
+            "P" => 100,
+            "G" => 90,
+            "A" => 75,
+            "W" => 50,
+            "D" => 25,
+            "B" => 10,
+            "R" => 0,
+            condition => parser.parse(condition, "condition")?,
+        };
+    }
+
+    loop {
+        match parser.peek_word() {
+            Some("E") => {
+                parser.read_word()?;
+                let keyword = parser.read_string()?.to_string();
+                let description = parser.read_string()?.to_string();
+                object.extra_descriptions.push(ExtraDescription {
+                    keyword,
+                    description,
+                });
+            }
+            Some("A") => {
+                // Affects, like bonuses to stats
+                for _ in 0..3 {
+                    parser.read_word()?;
+                }
+            }
+            Some("F") => {
+                // Affects that also set flags
+                for _ in 0..5 {
+                    parser.read_word()?;
+                }
+            }
+            _ => break,
+        }
+    }
+
+    Ok(object)
+}
+
+/// Values with flags may be numbers, e.g. in Merc areas; make them letters.
+fn item_values(item_type: &str, values: &str) -> String {
+    let flags_index = match item_type {
+        "container" => 1,
+        "furniture" => 2,
+        _ => return values.to_string(),
+    };
+
+    values
+        .split_whitespace()
+        .enumerate()
+        .map(|(index, value)| match index == flags_index {
+            true => flag_letters(value),
+            false => value.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn load_rooms(parser: &mut FileParser) -> Result<Vec<Room>, Diagnostic> {
+    let mut rooms = Vec::new();
+
+    while let Some(vnum) = read_vnum_section(parser)? {
+        rooms.push(load_room(parser, vnum)?)
+    }
+
+    Ok(rooms)
+}
+
+fn load_room(parser: &mut FileParser, vnum: usize) -> Result<Room, Diagnostic> {
+    let mut room = Room {
+        vnum: Vnum(vnum),
+        name: parser.read_string()?.to_string(),
+        description: parser.read_string()?.to_string(),
+        ..Default::default()
+    };
+
+    let _area_number = parser.read_word()?;
+    room.dark = parse_flags(parser.read_word()?) & ROOM_DARK != 0;
+    let sector: usize = read_number(parser, "sector")?;
+    room.sector = SECTORS.get(sector).unwrap_or(&SECTORS[0]).to_string();
+
+    loop {
+        match parser.read_word()? {
+            "S" => break,
+            // Healing and mana rates
+            "H" | "M" => {
+                parser.read_word()?;
+            }
+            // Clan and owner
+            "C" | "O" => {
+                parser.read_string()?;
+            }
+            "E" => {
+                let keyword = parser.read_string()?.to_string();
+                let description = parser.read_string()?.to_string();
+                room.extra_descriptions.push(ExtraDescription {
+                    keyword,
+                    description,
+                });
+            }
+            door if door.starts_with('D') => {
+                let direction = door[1..]
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|direction| DIRECTIONS.get(direction))
+                    .ok_or_else(|| parser.error(&format!("Unknown exit '{}'", door)))?;
+
+                let description = parser.read_string()?.to_string();
+                let keywords = parser.read_string()?.trim().to_string();
+                let locks: i64 = read_number(parser, "exit locks")?;
+                let key: i64 = read_number(parser, "key vnum")?;
+                let to_vnum: i64 = read_number(parser, "exit vnum")?;
+
+                // Exits that lead nowhere
+                if to_vnum <= 0 {
+                    continue;
+                }
+
+                room.exits.push(Exit {
+                    name: direction.to_string(),
+                    vnum: Vnum(to_vnum as usize),
+                    description: Some(description).filter(|text| !text.trim().is_empty()),
+                    extra_keywords: Some(keywords).filter(|keywords| !keywords.is_empty()),
+                    has_door: locks != 0,
+                    is_pickproof: locks == 2 || locks == 4,
+                    key: Some(key)
+                        .filter(|key| *key > 0)
+                        .map(|key| Vnum(key as usize)),
+                    ..Default::default()
+                })
+            }
+            word => return Err(parser.error(&format!("Unknown room field '{}'", word))),
+        }
+    }
+
+    Ok(room)
+}
+
+fn load_resets(parser: &mut FileParser) -> Result<Vec<ResetCommand>, Diagnostic> {
+    let mut resets = Vec::new();
+
+    loop {
+        let line = parser.read_until_newline()?;
+        let mut words = line.split_whitespace();
+
+        let command = match words.next() {
+            Some(command) if !command.starts_with('*') => command,
+            _ => continue,
+        };
+
+        if command == "S" {
+            break;
+        }
+
+        // Skip the `if` flag; whatever follows the numbers is a comment
+        let args: Vec<i64> = words.skip(1).map_while(|word| word.parse().ok()).collect();
+        let arg = |index: usize| {
+            args.get(index)
+                .copied()
+                .ok_or_else(|| parser.error(&format!("Missing values in '{}' reset", command)))
+        };
+        let vnum = |index: usize| {
+            let vnum = arg(index)?;
+            if vnum < 0 {
+                return Err(parser.error(&format!("Invalid vnum: '{}'", vnum)));
+            }
+            Ok(Vnum(vnum as usize))
+        };
+        let limit = |index: usize| args.get(index).map_or(1, |limit| *limit);
+
+        let reset = match command {
+            "M" => ResetCommand::Mob {
+                m_num: vnum(0)?,
+                global_limit: arg(1)?.clamp(0, u16::MAX as i64) as u16,
+                r_num: vnum(2)?,
+                room_limit: limit(3).clamp(0, u16::MAX as i64) as u16,
+            },
+            "O" => ResetCommand::Object {
+                o_num: vnum(0)?,
+                global_limit: arg(1)? as i16,
+                r_num: vnum(2)?,
+            },
+            "P" => ResetCommand::Put {
+                o_num: vnum(0)?,
+                global_limit: arg(1)? as i16,
+                c_num: vnum(2)?,
+                container_limit: limit(3) as i16,
+            },
+            "G" => ResetCommand::Give {
+                o_num: vnum(0)?,
+                global_limit: arg(1)? as i16,
+            },
+            "E" => ResetCommand::Equip {
+                o_num: vnum(0)?,
+                global_limit: arg(1)? as i16,
+                location: WEAR_LOCATIONS
+                    .get(arg(2)? as usize)
+                    .copied()
+                    .unwrap_or_default()
+                    .to_string(),
+            },
+            "D" => {
+                let direction = DIRECTIONS
+                    .get(arg(1)? as usize)
+                    .ok_or_else(|| parser.error("Unknown direction in 'D' reset"))?;
+                let door_flags: &[&str] = match arg(2)? {
+                    1 => &["closed"],
+                    2 => &["closed", "locked"],
+                    _ => &[],
+                };
+
+                ResetCommand::Door {
+                    r_num: vnum(0)?,
+                    direction: direction.to_string(),
+                    door_flags: door_flags.iter().map(|flag| flag.to_string()).collect(),
+                }
+            }
+            // Randomized exits are not supported
+            "R" => continue,
+            command => {
+                parser.warn(&format!("Unknown reset '{}'", command));
+                continue;
+            }
+        };
+
+        resets.push(reset);
+    }
+
+    Ok(resets)
+}
+
+/// Dawn of Time areas keep the state of doors on their exits, so do the same
+/// with the `D` resets of doors in this area.
+fn close_doors(rooms: &mut [Room], resets: &[ResetCommand]) {
+    for reset in resets {
+        if let ResetCommand::Door {
+            r_num,
+            direction,
+            door_flags,
+        } = reset
+        {
+            let exit = rooms
+                .iter_mut()
+                .find(|room| room.vnum == *r_num)
+                .and_then(|room| room.exits.iter_mut().find(|exit| exit.name == *direction));
+
+            if let Some(exit) = exit {
+                exit.is_closed = door_flags.iter().any(|flag| flag == "closed");
+                exit.is_locked = door_flags.iter().any(|flag| flag == "locked");
+            }
+        }
+    }
+}
+
+fn load_shops(parser: &mut FileParser) -> Result<Vec<Shop>, Diagnostic> {
+    let mut shops = Vec::new();
+
+    loop {
+        let line = parser.read_until_newline()?;
+        let numbers: Vec<i64> = line
+            .split_whitespace()
+            .map_while(|word| word.parse().ok())
+            .collect();
+
+        match numbers[..] {
+            [] => continue,
+            [0, ..] => break,
+            [keeper, ref buy_types @ .., profit_buy, profit_sell, open_hour, close_hour]
+                if numbers.len() == 10 && keeper > 0 =>
+            {
+                shops.push(Shop {
+                    vnum: Vnum(keeper as usize),
+                    buy_types: buy_types
+                        .iter()
+                        .filter_map(|item_type| ITEM_TYPES.get(*item_type as usize))
+                        .filter(|item_type| !item_type.is_empty())
+                        .map(|item_type| item_type.to_string())
+                        .collect(),
+                    sell_types: Vec::new(),
+                    profit_buy: profit_buy.max(0) as u32,
+                    profit_sell: profit_sell.max(0) as u32,
+                    open_hour: open_hour.clamp(0, 24) as u8,
+                    close_hour: close_hour.clamp(0, 24) as u8,
+                })
+            }
+            _ => {
+                return Err(parser.error("Shops need a keeper, 5 item types, 2 profits and 2 hours"))
+            }
+        }
+    }
+
+    Ok(shops)
+}
+
+fn load_mobprogs(parser: &mut FileParser) -> Result<Vec<MobProg>, Diagnostic> {
+    let mut mobprogs = Vec::new();
+
+    while let Some(vnum) = read_vnum_section(parser)? {
+        mobprogs.push(MobProg {
+            vnum: Vnum(vnum),
+            title: "<untitled>".to_string(),
+            code: parser.read_string()?.to_string(),
+            disabled: false,
+            area: String::new(),
+        })
+    }
+
+    Ok(mobprogs)
+}
@@ -1,10 +1,12 @@
 use crate::{
-    acting::Acts,
+    acting::{Acts, Players},
     agent::EntityAgent,
+    colors::EscapeColors,
     commands::process_agent_command,
-    components::EntityComponentInfo,
+    components::{EntityComponentInfo, VariableValue},
     echo,
-    entity::{EntityId, Found},
+    entity::{EntityId, EntityInfo, Found, PermanentEntityId},
+    money::COPPER_PER_SILVER,
     world::{MobProgTrigger, Vnum, VnumOrKeyword},
 };
 
@@ -36,6 +38,24 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             ["remember", target] => {
                 self.do_mob_remember(target);
             }
+            ["var", "set", target, name, value] => {
+                self.do_mob_var_set(target, name, value, None);
+            }
+            ["var", "set", target, name, value, ticks] => {
+                self.do_mob_var_set(target, name, value, Some(ticks));
+            }
+            ["var", "incr", target, name] => {
+                self.do_mob_var_incr(target, name, "1");
+            }
+            ["var", "incr", target, name, amount] => {
+                self.do_mob_var_incr(target, name, amount);
+            }
+            ["var", "clear", target, name] => {
+                self.do_mob_var_clear(target, name);
+            }
+            ["quest", command, target, quest_id] => {
+                self.do_mob_quest(command, target, quest_id);
+            }
             ["rsay", ref message @ ..] => {
                 self.do_mob_rsay(&message.join(" "));
             }
@@ -67,7 +87,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
 
     pub fn do_mob_transfer(&mut self, target_name: &str, to_room: &str) {
         let myself = self.entity_world.entity_info(self.entity_id);
-        let to_room_vnum: usize = match to_room.parse() {
+        let to_room_vnum: Vnum = match to_room.parse() {
             Ok(vnum) => vnum,
             Err(_) => {
                 echo!(
@@ -79,14 +99,19 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             }
         };
 
-        let room = self
-            .vnum_templates
-            .vnum_to_room_entity
-            .get(to_room_vnum)
-            .and_then(|permanent_id| *permanent_id)
-            .and_then(|permanent_id| self.entity_world.old_entity(&permanent_id));
+        let permanent_id = match self.vnum_templates.vnum_to_room_entity.get(&to_room_vnum) {
+            Some(permanent_id) => permanent_id,
+            None => {
+                echo!(
+                    self.info(),
+                    "Transfer room target '{}' does not exist.\r\n",
+                    to_room
+                );
+                return;
+            }
+        };
 
-        let room_id = match room {
+        let room_id = match self.entity_world.old_entity(permanent_id) {
             Some(entity) => entity.entity_id(),
             None => {
                 echo!(
@@ -152,7 +177,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
 
     pub fn do_mob_at(&mut self, at_room: &str, commands: &[&str]) {
         let myself = self.entity_world.entity_info(self.entity_id);
-        let at_room_vnum: usize = match at_room.parse() {
+        let at_room_vnum: Vnum = match at_room.parse() {
             Ok(vnum) => vnum,
             Err(_) => {
                 echo!(self.info(), "Room '{}' is not a valid vnum.\r\n", at_room);
@@ -163,9 +188,8 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         let room = self
             .vnum_templates
             .vnum_to_room_entity
-            .get(at_room_vnum)
-            .and_then(|permanent_id| *permanent_id)
-            .and_then(|permanent_id| self.entity_world.old_entity(&permanent_id));
+            .get(&at_room_vnum)
+            .and_then(|permanent_id| self.entity_world.old_entity(permanent_id));
 
         let room_id = match room {
             Some(entity) => entity.entity_id(),
@@ -188,7 +212,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
     }
 
     pub fn do_mob_goto(&mut self, to_room: &str) {
-        let to_room_vnum: usize = match to_room.parse() {
+        let to_room_vnum: Vnum = match to_room.parse() {
             Ok(vnum) => vnum,
             Err(_) => {
                 echo!(self.info(), "Room '{}' is not a valid vnum.\r\n", to_room);
@@ -199,9 +223,8 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         let room = self
             .vnum_templates
             .vnum_to_room_entity
-            .get(to_room_vnum)
-            .and_then(|permanent_id| *permanent_id)
-            .and_then(|permanent_id| self.entity_world.old_entity(&permanent_id));
+            .get(&to_room_vnum)
+            .and_then(|permanent_id| self.entity_world.old_entity(permanent_id));
 
         let room = match room {
             Some(room) => room,
@@ -223,7 +246,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
 
     pub fn do_mob_mload(&mut self, m_vnum: &str) {
         let myself = self.entity_world.entity_info(self.entity_id);
-        let m_vnum: usize = match m_vnum.parse() {
+        let m_vnum: Vnum = match m_vnum.parse() {
             Ok(vnum) => vnum,
             Err(_) => {
                 echo!(self.info(), "Vnum '{}' is not a valid number.\r\n", m_vnum);
@@ -231,11 +254,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             }
         };
 
-        let mobile_components = self
-            .vnum_templates
-            .mobile_components
-            .get(m_vnum)
-            .and_then(|components| components.as_ref());
+        let mobile_components = self.vnum_templates.mobile_components.get(&m_vnum);
 
         let (mobile_components, mobprogs) = match mobile_components {
             Some(components) => components,
@@ -249,7 +268,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             }
         };
 
-        let room_id = myself.room().entity_id();
+        let room_id = myself.enclosing_room().entity_id();
 
         let mobile_id = self
             .entity_world
@@ -277,7 +296,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
     }
 
     pub fn do_mob_oload(&mut self, o_vnum: &str) {
-        let o_vnum: usize = match o_vnum.parse() {
+        let o_vnum: Vnum = match o_vnum.parse() {
             Ok(vnum) => vnum,
             Err(_) => {
                 echo!(self.info(), "Vnum '{}' is not a valid number.\r\n", o_vnum);
@@ -285,14 +304,8 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             }
         };
 
-        let object_components = self
-            .vnum_templates
-            .object_components
-            .get(o_vnum)
-            .and_then(|components| components.as_ref());
-
-        let (object_components, extra_descriptions) = match object_components {
-            Some(components) => components,
+        let object_id = match self.create_object(o_vnum, self.entity_id) {
+            Some(object_id) => object_id,
             None => {
                 echo!(
                     self.info(),
@@ -303,14 +316,6 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             }
         };
 
-        let object_id = self
-            .entity_world
-            .insert_entity(self.entity_id, object_components.clone());
-        for extra_description_components in extra_descriptions {
-            self.entity_world
-                .insert_entity(object_id, extra_description_components.clone());
-        }
-
         let myself = self.entity_world.entity_info(self.entity_id);
         let object = self.entity_world.entity_info(object_id);
         let mut act = self.players.act_with(&myself, &object);
@@ -331,8 +336,29 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         );
     }
 
+    /// Create an object from its template inside a container, which can also
+    /// be a room or someone's inventory.
+    pub(crate) fn create_object(
+        &mut self,
+        o_vnum: Vnum,
+        container_id: EntityId,
+    ) -> Option<EntityId> {
+        let (object_components, extra_descriptions) =
+            self.vnum_templates.object_components.get(&o_vnum)?;
+
+        let object_id = self
+            .entity_world
+            .insert_entity(container_id, object_components.clone());
+        for extra_description_components in extra_descriptions {
+            self.entity_world
+                .insert_entity(object_id, extra_description_components.clone());
+        }
+
+        Some(object_id)
+    }
+
     pub fn do_mob_call(&mut self, p_vnum: &str, target: &str) {
-        let p_vnum: usize = match p_vnum.parse() {
+        let p_vnum: Vnum = match p_vnum.parse() {
             Ok(vnum) => vnum,
             Err(_) => {
                 echo!(self.info(), "Vnum '{}' is not a valid number.\r\n", p_vnum);
@@ -340,11 +366,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             }
         };
 
-        let mobprog = self
-            .vnum_templates
-            .vnum_to_mobprog
-            .get(p_vnum)
-            .and_then(|mobprog| mobprog.as_ref());
+        let mobprog = self.vnum_templates.vnum_to_mobprog.get(&p_vnum);
 
         let code = match mobprog {
             Some(code) => code.clone(),
@@ -382,6 +404,116 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         }
     }
 
+    /// Check that the agent can change what mobprogs rely on, like variables
+    /// and quests: mobprogs can, and so can immortals, but players can't.
+    /// Players can't `force` mobiles either, so that they can't get around
+    /// this.
+    pub(crate) fn check_trusted(&mut self) -> bool {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        if !myself.is_player() || myself.is_immortal() {
+            return true;
+        }
+
+        echo!(self.info(), "Only mobprogs and immortals can do that.\r\n");
+        false
+    }
+
+    /// Find the entity whose variables a mobprog wants; "here" is the room
+    /// the mobprog runs in.
+    fn find_variable_holder(&self, target: &str) -> Option<EntityId> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        match myself.find_entity(target, |entity| !entity.is_extra_description()) {
+            Found::Nothing if target == "here" => Some(myself.enclosing_room().entity_id()),
+            Found::Myself | Found::WrongSelf => Some(myself.entity_id()),
+            Found::Other(other) | Found::WrongOther(other) => Some(other.entity_id()),
+            Found::Nothing => None,
+        }
+    }
+
+    pub fn do_mob_var_set(&mut self, target: &str, name: &str, value: &str, ticks: Option<&str>) {
+        if !self.check_trusted() {
+            return;
+        }
+
+        let expires = match ticks.map(|ticks| ticks.parse::<u64>()) {
+            None => None,
+            Some(Ok(ticks)) if ticks > 0 => Some(self.entity_world.current_tick() + ticks),
+            Some(_) => {
+                echo!(
+                    self.info(),
+                    "Variables can only expire after a positive number of ticks.\r\n"
+                );
+                return;
+            }
+        };
+
+        let target_id = match self.find_variable_holder(target) {
+            Some(target_id) => target_id,
+            None => {
+                echo!(self.info(), "I don't see '{}' here.\r\n", target);
+                return;
+            }
+        };
+
+        let mut target = self.entity_world.entity_info_mut(target_id);
+        let variables = &mut target.components().variables;
+        variables.set(name, VariableValue::parse(value), expires);
+
+        echo!(self.info(), "Variable '{}' set.\r\n", name);
+    }
+
+    pub fn do_mob_var_incr(&mut self, target: &str, name: &str, amount: &str) {
+        if !self.check_trusted() {
+            return;
+        }
+
+        let amount: i64 = match amount.parse() {
+            Ok(amount) => amount,
+            Err(_) => {
+                echo!(self.info(), "Amount '{}' is not a number.\r\n", amount);
+                return;
+            }
+        };
+
+        let target_id = match self.find_variable_holder(target) {
+            Some(target_id) => target_id,
+            None => {
+                echo!(self.info(), "I don't see '{}' here.\r\n", target);
+                return;
+            }
+        };
+
+        let mut target = self.entity_world.entity_info_mut(target_id);
+        let variables = &mut target.components().variables;
+
+        match variables.increment(name, amount) {
+            Some(value) => echo!(self.info(), "Variable '{}' is now {}.\r\n", name, value),
+            None => echo!(self.info(), "Variable '{}' is not a number.\r\n", name),
+        }
+    }
+
+    pub fn do_mob_var_clear(&mut self, target: &str, name: &str) {
+        if !self.check_trusted() {
+            return;
+        }
+
+        let target_id = match self.find_variable_holder(target) {
+            Some(target_id) => target_id,
+            None => {
+                echo!(self.info(), "I don't see '{}' here.\r\n", target);
+                return;
+            }
+        };
+
+        let mut target = self.entity_world.entity_info_mut(target_id);
+        if target.components().variables.remove(name) {
+            echo!(self.info(), "Variable '{}' cleared.\r\n", name);
+        } else {
+            echo!(self.info(), "Variable '{}' was not set.\r\n", name);
+        }
+    }
+
     pub fn do_mob_rsay(&mut self, message: &str) {
         let myself = self.entity_world.entity_info(self.entity_id);
 
@@ -408,9 +540,11 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
 
     pub fn do_mob_echo(&mut self, message: &str) {
         let myself = self.entity_world.entity_info(self.entity_id);
-        let mut act = self.players.act_alone(&myself);
+        // Echo into the room even if coming from a room or a carried object
+        let room = myself.enclosing_room();
+        let mut act = self.players.act_with(&room, &myself);
 
-        echo!(act.myself(), "You echo: {}\r\n", message);
+        echo!(act.target(), "You echo: {}\r\n", message);
         echo!(act.others(), "{}\r\n", message);
     }
 
@@ -420,7 +554,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
     /// that vnum, but for now it just turns that vnum into a name and calls
     /// the original 'force'.
     pub fn do_mob_vforce(&mut self, m_vnum: &str, command: &[&str]) {
-        let m_vnum: usize = match m_vnum.parse() {
+        let m_vnum: Vnum = match m_vnum.parse() {
             Ok(vnum) => vnum,
             Err(_) => {
                 echo!(self.info(), "Vnum '{}' is not a valid number.\r\n", m_vnum);
@@ -428,11 +562,7 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
             }
         };
 
-        let mobile_components = self
-            .vnum_templates
-            .mobile_components
-            .get(m_vnum)
-            .and_then(|components| components.as_ref());
+        let mobile_components = self.vnum_templates.mobile_components.get(&m_vnum);
 
         if let Some((mobile_components, _mobprogs)) = mobile_components {
             let component_info =
@@ -488,128 +618,171 @@ pub(crate) enum Action<'a> {
 
     /// You gave an object to someone
     Give { object_id: EntityId },
-}
 
-impl<'e, 'p> EntityAgent<'e, 'p> {
-    pub fn check_triggers_self(&mut self, action: Action<'_>) {
-        let mut triggered = Vec::new();
-        let myself = self.entity_world.entity_info(self.entity_id);
-        for item in myself.contained_entities() {
-            if let Some(mobprog) = &item.components().mobprog {
-                if let (Action::Entry, MobProgTrigger::Entry { chance }) =
-                    (&action, &mobprog.trigger)
-                {
-                    if random_percent(*chance) {
-                        triggered.push(mobprog.code.clone());
-                    }
-                }
-            }
-        }
+    /// You gave coins to someone, worth this much copper
+    Bribe { value: usize },
 
-        if triggered.is_empty() {
-            return;
-        }
+    /// You picked up an object
+    Get,
 
-        let self_keyword = myself.main_keyword().to_string();
+    /// You dropped an object
+    Drop,
 
-        for code in triggered {
-            // TODO: Maybe running with this target isn't how it's supposed
-            // to work?
-            self.run_mobprog(code, self_keyword.clone());
-        }
-    }
+    /// You wore or wielded an object
+    Wear,
 
-    pub fn check_triggers_target(&mut self, action: Action<'_>, target_id: EntityId) {
-        let mut triggered = Vec::new();
-        let myself = self.entity_world.entity_info(self.entity_id);
-        let target = self.entity_world.entity_info(target_id);
+    /// You used an object
+    Use,
 
-        for item in target.contained_entities() {
-            if let Some(mobprog) = &item.components().mobprog {
-                if let (Action::Give { object_id }, MobProgTrigger::Give { item_vnum }) =
-                    (&action, &mobprog.trigger)
-                {
-                    let object = self.entity_world.entity_info(*object_id);
-                    let object_matches = match item_vnum {
-                        VnumOrKeyword::Vnum(vnum) => object.components().general.vnum == *vnum,
-                        VnumOrKeyword::Keyword(keyword) => object
-                            .component_info()
-                            .keyword()
-                            .split_whitespace()
-                            .any(|word| word == keyword),
-                    };
-                    if object.is_object() && object_matches {
-                        triggered.push(mobprog.code.clone());
-                    }
-                }
-            }
-        }
+    /// Some time has passed while you were around
+    Random,
 
-        if triggered.is_empty() {
-            return;
-        }
+    /// You killed someone
+    Kill { victim_id: EntityId },
+}
 
-        let self_keyword = myself.main_keyword().to_string();
+/// Whether mobprogs on this kind of holder react to the action at all. Mobiles
+/// keep the triggers they always had, rooms react to players entering,
+/// speaking and to time passing, and objects to what is done with them.
+fn holder_reacts_to(holder: &EntityInfo<'_>, action: &Action<'_>) -> bool {
+    if holder.is_room() {
+        matches!(
+            action,
+            Action::Greet | Action::Speech { .. } | Action::Random
+        )
+    } else if holder.is_object() {
+        matches!(
+            action,
+            Action::Get | Action::Drop | Action::Wear | Action::Use | Action::Give { .. }
+        )
+    } else {
+        !matches!(
+            action,
+            Action::Get | Action::Drop | Action::Wear | Action::Use | Action::Random
+        )
+    }
+}
 
-        for code in triggered {
-            let mut agent = self.switch_agent(target_id);
-            agent.run_mobprog(code, self_keyword.clone());
-        }
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    /// Run mobprogs on the current entity itself.
+    pub fn check_triggers_self(&mut self, action: Action<'_>) {
+        let holders = [self.entity_id];
+        let triggered = self.triggered_mobprogs(&action, &holders);
+        self.run_triggered_mobprogs(triggered);
+        self.run_scripts(&action, &holders);
+        self.update_quests(&action, None);
     }
 
+    /// Run mobprogs on the target of an action, like the mobile that was
+    /// given something, or the object that was picked up.
+    pub fn check_triggers_target(&mut self, action: Action<'_>, target_id: EntityId) {
+        let holders = [target_id];
+        let triggered = self.triggered_mobprogs(&action, &holders);
+        self.run_triggered_mobprogs(triggered);
+        self.run_scripts(&action, &holders);
+        self.update_quests(&action, Some(target_id));
+    }
+
+    /// Run mobprogs on everything around a player: the room itself, anything
+    /// in it, and the objects the player is carrying.
     pub fn check_triggers_others(&mut self, action: Action<'_>) {
         let myself = self.entity_world.entity_info(self.entity_id);
-        let mut triggered = Vec::new();
 
         if !myself.is_player() {
             return;
         }
 
-        for entity in myself.room().contained_entities() {
-            for item in entity.contained_entities() {
+        let room = myself.room();
+        let holders: Vec<_> = Some(room.clone())
+            .into_iter()
+            .chain(room.contained_entities())
+            .chain(myself.objects())
+            .map(|entity| entity.entity_id())
+            .collect();
+
+        let triggered = self.triggered_mobprogs(&action, &holders);
+        self.run_triggered_mobprogs(triggered);
+        self.run_scripts(&action, &holders);
+        self.update_quests(&action, None);
+    }
+
+    /// Find the mobprogs installed on any of the holders that react to the
+    /// action, as pairs of holder and mobprog.
+    fn triggered_mobprogs(
+        &self,
+        action: &Action<'_>,
+        holders: &[EntityId],
+    ) -> Vec<(EntityId, EntityId)> {
+        let mut triggered = Vec::new();
+
+        for holder_id in holders {
+            let holder = self.entity_world.entity_info(*holder_id);
+
+            // Sleepers don't notice anything
+            if !holder.is_awake() || !holder_reacts_to(&holder, action) {
+                continue;
+            }
+
+            for item in holder.contained_entities() {
                 if let Some(mobprog) = &item.components().mobprog {
-                    match (&action, &mobprog.trigger) {
-                        (Action::Speech { message }, MobProgTrigger::Speech { pattern }) => {
-                            if message.contains(pattern) {
-                                triggered.push((entity.entity_id(), mobprog.code.clone()));
-                            }
-                        }
-                        (Action::Greet, MobProgTrigger::Greet { chance }) => {
-                            if random_percent(*chance) {
-                                triggered.push((entity.entity_id(), mobprog.code.clone()));
-                            }
-                        }
-                        (
-                            Action::Exit { direction: dir1 },
-                            MobProgTrigger::Exit { direction: dir2 },
-                        ) => {
-                            if dir1 == dir2 {
-                                triggered.push((entity.entity_id(), mobprog.code.clone()));
-                            }
-                        }
-                        (Action::Entry, MobProgTrigger::Entry { chance }) => {
-                            if random_percent(*chance) {
-                                triggered.push((entity.entity_id(), mobprog.code.clone()));
-                            }
-                        }
-                        (Action::Login, MobProgTrigger::LoginRoom) => {
-                            triggered.push((entity.entity_id(), mobprog.code.clone()));
-                        }
-                        _ => (),
-                    };
+                    if self.trigger_matches(action, &mobprog.trigger) {
+                        triggered.push((*holder_id, item.entity_id()));
+                    }
                 }
             }
         }
 
+        triggered
+    }
+
+    fn trigger_matches(&self, action: &Action<'_>, trigger: &MobProgTrigger) -> bool {
+        match (action, trigger) {
+            (Action::Speech { message }, MobProgTrigger::Speech { pattern }) => {
+                message.contains(pattern)
+            }
+            (Action::Greet, MobProgTrigger::Greet { chance })
+            | (Action::Entry, MobProgTrigger::Entry { chance })
+            | (Action::Get, MobProgTrigger::Get { chance })
+            | (Action::Drop, MobProgTrigger::Drop { chance })
+            | (Action::Wear, MobProgTrigger::Wear { chance })
+            | (Action::Use, MobProgTrigger::Use { chance })
+            | (Action::Random, MobProgTrigger::Random { chance }) => random_percent(*chance),
+            (Action::Exit { direction: dir1 }, MobProgTrigger::Exit { direction: dir2 }) => {
+                dir1 == dir2
+            }
+            (Action::Login, MobProgTrigger::LoginRoom) => true,
+            (Action::Bribe { value }, MobProgTrigger::Bribe { amount }) => {
+                *value >= amount * COPPER_PER_SILVER
+            }
+            (Action::Give { object_id }, MobProgTrigger::Give { item_vnum }) => {
+                let object = self.entity_world.entity_info(*object_id);
+                let object_matches = match item_vnum {
+                    VnumOrKeyword::Vnum(vnum) => object.components().general.vnum == *vnum,
+                    VnumOrKeyword::Keyword(keyword) => object
+                        .component_info()
+                        .keyword()
+                        .split_whitespace()
+                        .any(|word| word == keyword),
+                };
+                object.is_object() && object_matches
+            }
+            _ => false,
+        }
+    }
+
+    /// Run each triggered mobprog with its holder as "self", and the current
+    /// entity as `$n`.
+    fn run_triggered_mobprogs(&mut self, triggered: Vec<(EntityId, EntityId)>) {
         if triggered.is_empty() {
             return;
         }
 
+        let myself = self.entity_world.entity_info(self.entity_id);
         let self_keyword = myself.main_keyword().to_string();
 
-        for (entity_id, code) in triggered {
-            let mut agent = self.switch_agent(entity_id);
-            agent.run_mobprog(code, self_keyword.clone());
+        for (holder_id, mobprog_id) in triggered {
+            let mut agent = self.switch_agent(holder_id);
+            agent.run_triggered_mobprog(mobprog_id, self_keyword.clone());
         }
     }
 
@@ -623,8 +796,13 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
         }
 
         for entity in myself.room().contained_entities() {
-            for mobprog in entity.contained_entities() {
-                let mobprog = match &mobprog.components().mobprog {
+            // Only mobiles react to ACT
+            if !entity.is_mobile() || !entity.is_awake() {
+                continue;
+            }
+
+            for item in entity.contained_entities() {
+                let mobprog = match &item.components().mobprog {
                     Some(mobprog) => mobprog,
                     None => continue,
                 };
@@ -640,26 +818,42 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
                 for line in lines {
                     if let MobProgTrigger::Act { pattern } = &mobprog.trigger {
                         if line.contains(pattern) {
-                            triggered.push((entity.entity_id(), mobprog.code.clone()));
+                            triggered.push((entity.entity_id(), item.entity_id()));
                         }
                     }
                 }
             }
         }
-        if triggered.is_empty() {
-            return;
-        }
 
-        let self_keyword = myself.main_keyword().to_string();
+        let mut agent = self.switch_agent(acts.myself_entity_id);
+        agent.run_triggered_mobprogs(triggered);
+    }
 
-        for (entity_id, code) in triggered {
-            let mut agent = self.switch_agent(entity_id);
-            agent.run_mobprog(code, self_keyword.clone());
-        }
+    fn run_triggered_mobprog(&mut self, mobprog_id: EntityId, target: String) {
+        let mobprog_entity = self.entity_world.entity_info(mobprog_id);
+        let vnum = mobprog_entity.components().general.vnum;
+        let mobprog = mobprog_entity
+            .components()
+            .mobprog
+            .as_ref()
+            .expect("Only mobprog entities get triggered");
+
+        let code = mobprog.code.clone();
+        let trigger = mobprog.trigger.to_string();
+
+        self.trace(format_args!(
+            "Trigger '{}' matched; running mobprog #{} with $n = '{}'.",
+            trigger, vnum.0, target
+        ));
+
+        self.run_mobprog(code, target);
     }
 
     pub fn run_mobprog(&mut self, code: String, target: String) {
         let mut accept_commands = true;
+        let mut inside_if = false;
+
+        self.players.mobprog_traces.depth += 1;
 
         for command in code.lines() {
             let command = command.replace("$n", &target);
@@ -680,113 +874,401 @@ impl<'e, 'p> EntityAgent<'e, 'p> {
 
             let words: Vec<_> = command.split_whitespace().collect();
 
-            let remembered = myself
-                .components()
-                .mobile
-                .as_ref()
-                .and_then(|mobile| mobile.remember.as_deref())
-                .unwrap_or("nobody");
-
             match words[..] {
                 ["if", ref condition @ ..] => {
-                    accept_commands = match *condition {
-                        ["room", target, "==", vnum] => {
-                            assert!(
-                                ["$i", "$I"].contains(&target),
-                                "Don't know how to handle other targets"
-                            );
+                    let result = self.check_condition(condition);
+
+                    let condition = condition.join(" ");
+                    match result {
+                        Some(result) => self.trace(format_args!(
+                            "if {} => {}",
+                            EscapeColors(&condition),
+                            result
+                        )),
+                        None => self.trace(format_args!(
+                            "if {} => unknown condition, assuming false",
+                            EscapeColors(&condition)
+                        )),
+                    }
 
-                            let vnum: usize = vnum.parse().expect("Invalid vnum in mobprog");
+                    accept_commands = result.unwrap_or(false);
 
-                            let myself = self.entity_world.entity_info(self.entity_id);
-                            let room = myself.room();
+                    if !inside_if {
+                        inside_if = true;
+                        self.players.mobprog_traces.depth += 1;
+                    }
+                }
+                ["else"] => {
+                    accept_commands = !accept_commands;
+                    self.trace(format_args!("else => {}", accept_commands));
+                }
+                ["endif"] => {
+                    accept_commands = true;
 
-                            vnum == room.components().general.vnum.0
-                        }
-                        ["objhere", vnum] => {
-                            let objname = match vnum.parse() {
-                                Ok(vnum) => VnumOrKeyword::Vnum(Vnum(vnum)),
-                                Err(_) => VnumOrKeyword::Keyword(vnum.to_string()),
-                            };
-
-                            let mut found = false;
-
-                            for item in myself.room().objects() {
-                                let matches_item = match objname {
-                                    VnumOrKeyword::Vnum(vnum) => {
-                                        vnum == item.components().general.vnum
-                                    }
-                                    VnumOrKeyword::Keyword(ref keyword) => item
-                                        .component_info()
-                                        .keyword()
-                                        .split_whitespace()
-                                        .any(|word| word == keyword),
-                                };
-                                found = found || matches_item;
-                            }
-
-                            found
-                        }
-                        ["carries", target, object] => {
-                            let target =
-                                myself.find_entity(target, |e| e.is_mobile() || e.is_player());
-                            let target = match target {
-                                Found::Myself => Some(myself),
-                                Found::Other(other) => Some(other),
-                                Found::WrongSelf | Found::WrongOther(_) | Found::Nothing => None,
-                            };
-
-                            if let Some(target) = target {
-                                target.objects().any(|o| {
-                                    o.component_info()
-                                        .keyword()
-                                        .split_whitespace()
-                                        .any(|word| word == object)
-                                })
-                            } else {
-                                false
-                            }
-                        }
-                        // Silly, but temporary until I get a check_condition function
-                        ["!carries", target, object] => {
-                            let target =
-                                myself.find_entity(target, |e| e.is_mobile() || e.is_player());
-                            let target = match target {
-                                Found::Myself => Some(myself),
-                                Found::Other(other) => Some(other),
-                                Found::WrongSelf | Found::WrongOther(_) | Found::Nothing => None,
-                            };
-
-                            !if let Some(target) = target {
-                                target.objects().any(|o| {
-                                    o.component_info()
-                                        .keyword()
-                                        .split_whitespace()
-                                        .any(|word| word == object)
-                                })
-                            } else {
-                                false
-                            }
-                        }
-                        // FIXME: Wrong, but, I don't know how it can be one, ever
-                        ["isnpc", _target] => true,
-                        ["istarget", target] => target == remembered,
-                        ["!istarget", target] => target != remembered,
-                        _ => false,
-                    };
+                    if inside_if {
+                        inside_if = false;
+                        self.players.mobprog_traces.depth -= 1;
+                    }
+                    self.trace(format_args!("endif"));
                 }
-                ["else"] => accept_commands = !accept_commands,
-                ["endif"] => accept_commands = true,
-                ["end"] if accept_commands => break,
+                ["end"] if accept_commands => {
+                    self.trace(format_args!("end"));
+                    break;
+                }
+                [] => (),
                 ref command if accept_commands => {
+                    self.trace(format_args!("> {}", EscapeColors(&command.join(" "))));
                     process_agent_command(self, command);
                 }
                 _ => (),
             };
         }
+
+        if inside_if {
+            self.players.mobprog_traces.depth -= 1;
+        }
+        self.players.mobprog_traces.depth -= 1;
+    }
+
+    /// Evaluate the condition of an `if` line in a mobprog.
+    ///
+    /// Returns None if the condition is not understood. A condition can be
+    /// negated by prefixing it with '!', as in "if !carries $n key".
+    fn check_condition(&self, condition: &[&str]) -> Option<bool> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let remembered = myself
+            .components()
+            .mobile
+            .as_ref()
+            .and_then(|mobile| mobile.remember.as_deref())
+            .unwrap_or("nobody");
+
+        let (negated, check) = match condition.first() {
+            Some(check) if check.starts_with('!') => (true, &check[1..]),
+            Some(check) => (false, *check),
+            None => return None,
+        };
+
+        let result = match (check, &condition[1..]) {
+            ("room", [target, "==", vnum]) if ["$i", "$I"].contains(target) => {
+                let vnum: usize = vnum.parse().ok()?;
+
+                vnum == myself.enclosing_room().components().general.vnum.0
+            }
+            ("objhere", [vnum]) => {
+                let objname = match vnum.parse() {
+                    Ok(vnum) => VnumOrKeyword::Vnum(Vnum(vnum)),
+                    Err(_) => VnumOrKeyword::Keyword(vnum.to_string()),
+                };
+
+                myself.enclosing_room().objects().any(|item| match objname {
+                    VnumOrKeyword::Vnum(vnum) => vnum == item.components().general.vnum,
+                    VnumOrKeyword::Keyword(ref keyword) => item
+                        .component_info()
+                        .keyword()
+                        .split_whitespace()
+                        .any(|word| word == keyword),
+                })
+            }
+            ("carries", [target, object]) => {
+                let target = myself.find_entity(target, |e| e.is_mobile() || e.is_player());
+                let target = match target {
+                    Found::Myself => Some(myself),
+                    Found::Other(other) => Some(other),
+                    Found::WrongSelf | Found::WrongOther(_) | Found::Nothing => None,
+                };
+
+                if let Some(target) = target {
+                    target.objects().any(|o| {
+                        o.component_info()
+                            .keyword()
+                            .split_whitespace()
+                            .any(|word| word == *object)
+                    })
+                } else {
+                    false
+                }
+            }
+            ("var", [target, name]) => {
+                let target_id = self.find_variable_holder(target)?;
+                let target = self.entity_world.entity_info(target_id);
+
+                target.components().variables.get(name).is_some()
+            }
+            ("var", [target, name, operator, value]) => {
+                let target_id = self.find_variable_holder(target)?;
+                let target = self.entity_world.entity_info(target_id);
+                let variable = target.components().variables.get(name);
+
+                compare_variable(variable, operator, value)?
+            }
+            ("quest", [target, quest_id, ref check @ ..]) => {
+                self.check_quest_condition(target, quest_id, check)?
+            }
+            ("isvisible", [target]) => match myself.find_entity(target, |_| true) {
+                Found::Myself => true,
+                Found::Other(other) => myself.can_see(&other),
+                Found::WrongSelf | Found::WrongOther(_) | Found::Nothing => false,
+            },
+            // FIXME: Wrong, but, I don't know how it can be one, ever
+            ("isnpc", [_target]) => true,
+            ("istarget", [target]) => *target == remembered,
+            _ => return None,
+        };
+
+        Some(result != negated)
+    }
+}
+
+/// Compare a variable with a value written in a mobprog, as in
+/// "if var $n stage >= 2". Unset variables count as 0.
+fn compare_variable(variable: Option<&VariableValue>, operator: &str, value: &str) -> Option<bool> {
+    let variable = variable
+        .cloned()
+        .unwrap_or(VariableValue::Int(0))
+        .to_string();
+
+    let ordering = match (variable.parse::<i64>(), value.parse::<i64>()) {
+        (Ok(variable), Ok(value)) => Some(variable.cmp(&value)),
+        _ => None,
+    };
+
+    use std::cmp::Ordering::*;
+
+    let result = match (operator, ordering) {
+        ("==", _) => variable == value,
+        ("!=", _) => variable != value,
+        ("<", Some(ordering)) => ordering == Less,
+        ("<=", Some(ordering)) => ordering != Greater,
+        (">", Some(ordering)) => ordering == Greater,
+        (">=", Some(ordering)) => ordering != Less,
+        _ => return None,
+    };
+
+    Some(result)
+}
+
+/// Players that watch what an entity's mobprogs do, set up with `mptrace`.
+#[derive(Default)]
+pub(crate) struct MobProgTraces {
+    tracers: Vec<(PermanentEntityId, String)>,
+    depth: usize,
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    /// Send a line about what the current entity's mobprogs are doing to all
+    /// players tracing it, indented by how deeply nested the mobprog is.
+    pub(crate) fn trace(&mut self, message: std::fmt::Arguments) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let permanent_id = myself.permanent_entity_id();
+
+        let Players {
+            player_echoes,
+            mobprog_traces,
+            ..
+        } = &mut *self.players;
+
+        for (traced_id, player) in &mobprog_traces.tracers {
+            if *traced_id != permanent_id {
+                continue;
+            }
+
+            if let Some(player_echo) = player_echoes.get_mut(player) {
+                echo!(
+                    player_echo.echo_buffer,
+                    "`s[{}]`^ {:indent$}{}\r\n",
+                    myself,
+                    "",
+                    message,
+                    indent = mobprog_traces.depth.saturating_sub(1) * 2
+                );
+            }
+        }
+    }
+}
+
+// Immortal commands for debugging mobprogs
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    pub fn do_mptrace(&mut self, target: Option<&str>) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let player_name = match myself.player_name() {
+            Some(player_name) => player_name.to_string(),
+            None => {
+                echo!(self.info(), "Only players can trace mobprogs.\r\n");
+                return;
+            }
+        };
+
+        let target = match target {
+            Some(target) => target,
+            None => {
+                let traces = &mut self.players.mobprog_traces;
+                let traced = traces.tracers.len();
+                traces
+                    .tracers
+                    .retain(|(_id, tracer)| *tracer != player_name);
+                let stopped = traced - traces.tracers.len();
+
+                echo!(self.info(), "Stopped tracing {} entities.\r\n", stopped);
+                return;
+            }
+        };
+
+        let target = match myself.find_entity(target, |_| true) {
+            // Rooms can't be found by keyword from inside them
+            Found::Nothing if target == "here" => myself.room(),
+            Found::Myself | Found::WrongSelf => myself.clone(),
+            Found::Other(other) | Found::WrongOther(other) => other,
+            Found::Nothing => {
+                echo!(self.info(), "You don't see anything here by that name.\r\n");
+                return;
+            }
+        };
+
+        let target_id = target.permanent_entity_id();
+        let tracer = (target_id, player_name);
+
+        let traces = &mut self.players.mobprog_traces;
+        let mut act = if traces.tracers.contains(&tracer) {
+            traces.tracers.retain(|other| *other != tracer);
+            let mut act = self.players.act_with(&myself, &target);
+            echo!(act.myself(), "You stop tracing $N's mobprogs.\r\n");
+            act
+        } else {
+            traces.tracers.push(tracer);
+            let mut act = self.players.act_with(&myself, &target);
+            echo!(act.myself(), "You start tracing $N's mobprogs.\r\n");
+            act
+        };
+
+        if !target
+            .contained_entities()
+            .any(|e| e.components().mobprog.is_some())
+        {
+            echo!(act.myself(), "Note that $E $Vhas no mobprogs.\r\n");
+        }
+    }
+
+    pub fn do_mpstat(&mut self, target: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let target = match myself.find_entity(target, |_| true) {
+            // Rooms can't be found by keyword from inside them
+            Found::Nothing if target == "here" => myself.room(),
+            Found::Myself | Found::WrongSelf => myself.clone(),
+            Found::Other(other) | Found::WrongOther(other) => other,
+            Found::Nothing => {
+                echo!(self.info(), "You don't see anything here by that name.\r\n");
+                return;
+            }
+        };
+
+        let traced = self
+            .players
+            .mobprog_traces
+            .tracers
+            .iter()
+            .any(|(traced_id, _)| *traced_id == target.permanent_entity_id());
+
+        let mut act = self.players.act_with(&myself, &target);
+        echo!(
+            act.myself(),
+            "MobProgs on $N (vnum {}){}:\r\n",
+            target.components().general.vnum.0,
+            if traced { ", currently traced" } else { "" }
+        );
+
+        let mut found_any = false;
+
+        for item in target.contained_entities() {
+            if let Some(mobprog) = &item.components().mobprog {
+                found_any = true;
+                echo!(
+                    self.players.info(&myself),
+                    "  `W#{:<6}`^ {}\r\n",
+                    item.components().general.vnum.0,
+                    EscapeColors(&mobprog.trigger.to_string())
+                );
+            }
+        }
+
+        if !found_any {
+            echo!(self.players.info(&myself), "  None.\r\n");
+        }
+
+        if !target.components().variables.is_empty() {
+            echo!(self.players.info(&myself), "Variables:\r\n");
+            let target_id = target.entity_id();
+            self.show_variables(target_id);
+        }
+    }
+
+    /// List an entity's variables, and how many ticks the temporary ones
+    /// have left.
+    pub(crate) fn show_variables(&mut self, target_id: EntityId) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = self.entity_world.entity_info(target_id);
+        let current_tick = self.entity_world.current_tick();
+        let mut info = self.players.info(&myself);
+
+        for (name, variable) in target.components().variables.iter() {
+            echo!(
+                info,
+                "  {} = {}",
+                name,
+                EscapeColors(&variable.value.to_string())
+            );
+            if let Some(expires) = variable.expires {
+                echo!(
+                    info,
+                    " `s({} ticks left)`^",
+                    expires.saturating_sub(current_tick)
+                );
+            }
+            echo!(info, "\r\n");
+        }
+    }
+
+    pub fn do_mpdump(&mut self, p_vnum: &str) {
+        let p_vnum: Vnum = match p_vnum.parse() {
+            Ok(vnum) => vnum,
+            Err(_) => {
+                echo!(self.info(), "Vnum '{}' is not a valid number.\r\n", p_vnum);
+                return;
+            }
+        };
+
+        let mobprog = self.vnum_templates.vnum_to_mobprog.get(&p_vnum);
+
+        let code = match mobprog {
+            Some(code) => code,
+            None => {
+                echo!(
+                    self.info(),
+                    "MobProg with vnum '{}' does not exist.\r\n",
+                    p_vnum
+                );
+                return;
+            }
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let mut info = self.players.info(&myself);
+
+        echo!(info, "Source of mobprog #{}:\r\n", p_vnum);
+        for (line_number, line) in code.lines().enumerate() {
+            echo!(
+                info,
+                "`s{:>3}`^ {}\r\n",
+                line_number + 1,
+                EscapeColors(line)
+            );
+        }
     }
 }
 
-fn random_percent(chance: u8) -> bool {
+pub(crate) fn random_percent(chance: u8) -> bool {
     rand::random::<u32>() % 100 < chance.into()
 }
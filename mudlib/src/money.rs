@@ -0,0 +1,543 @@
+//! Gold, silver and copper coins, and the banks that keep them.
+//!
+//! Money is carried as a pile of coins: an object with the `Coins` component.
+//! A pile that ends up next to another one, whether in an inventory, in a
+//! container or on the floor, joins it, so that there's only ever one pile in
+//! each place. Paying uses the smaller coins first, and breaks larger ones to
+//! get the change back.
+//!
+//! Prices are given in silver coins, and values are counted in copper. In
+//! rooms flagged as a `bank`, players can `deposit` and `withdraw` coins; the
+//! balance is kept as a value, and saved in their player file.
+
+use std::fmt;
+
+use string_interner::StringInterner;
+
+use crate::{
+    agent::EntityAgent,
+    components::{
+        Coins, Components, EntityType, GeneralData, InternComponent, Variables, Visibility,
+    },
+    echo,
+    entity::{EntityId, EntityInfo, EntityWorld},
+    world::{Gender, Vnum},
+};
+
+pub(crate) const COPPER_PER_SILVER: usize = 10;
+pub(crate) const SILVER_PER_GOLD: usize = 100;
+const COPPER_PER_GOLD: usize = COPPER_PER_SILVER * SILVER_PER_GOLD;
+/// The most coins of one kind that a command can be about.
+const MAX_COINS: usize = 1_000_000;
+
+impl Coins {
+    pub fn silver(amount: usize) -> Coins {
+        Coins {
+            silver: amount,
+            ..Default::default()
+        }
+    }
+
+    /// The fewest coins that are worth a value.
+    pub fn from_value(value: usize) -> Coins {
+        Coins {
+            gold: value / COPPER_PER_GOLD,
+            silver: value % COPPER_PER_GOLD / COPPER_PER_SILVER,
+            copper: value % COPPER_PER_SILVER,
+        }
+    }
+
+    /// Parse an amount of one kind of coins, as in "drop 10 gold".
+    pub fn parse(amount: &str, denomination: &str) -> Option<Coins> {
+        let amount = amount
+            .parse::<usize>()
+            .ok()
+            .filter(|amount| (1..=MAX_COINS).contains(amount))?;
+
+        let coins = match denomination {
+            "gold" => Coins {
+                gold: amount,
+                ..Default::default()
+            },
+            "silver" => Coins::silver(amount),
+            "copper" => Coins {
+                copper: amount,
+                ..Default::default()
+            },
+            _ => return None,
+        };
+
+        Some(coins)
+    }
+
+    /// The value of the coins, in copper, unless it's too large to count.
+    pub fn value(&self) -> Option<usize> {
+        self.gold
+            .checked_mul(COPPER_PER_GOLD)?
+            .checked_add(self.silver.checked_mul(COPPER_PER_SILVER)?)?
+            .checked_add(self.copper)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Coins::default()
+    }
+
+    fn add(&mut self, other: Coins) {
+        self.gold = self.gold.saturating_add(other.gold);
+        self.silver = self.silver.saturating_add(other.silver);
+        self.copper = self.copper.saturating_add(other.copper);
+    }
+
+    /// Pay a value with the smaller coins first, breaking a larger coin and
+    /// keeping the change when there aren't enough of them.
+    fn pay(&mut self, value: usize) -> bool {
+        if self.value().is_some_and(|own| own < value) {
+            return false;
+        }
+
+        let mut remaining = value;
+
+        let copper = self.copper.min(remaining);
+        self.copper -= copper;
+        remaining -= copper;
+
+        let silver = self.silver.min(remaining.div_ceil(COPPER_PER_SILVER));
+        self.silver -= silver;
+        let paid = silver * COPPER_PER_SILVER;
+        if paid >= remaining {
+            self.copper += paid - remaining;
+            return true;
+        }
+        remaining -= paid;
+
+        // What is left is surely covered by the gold coins.
+        let gold = remaining.div_ceil(COPPER_PER_GOLD);
+        self.gold -= gold;
+        let change = gold * COPPER_PER_GOLD - remaining;
+        self.silver += change / COPPER_PER_SILVER;
+        self.copper += change % COPPER_PER_SILVER;
+
+        true
+    }
+
+    /// Keywords to find a pile by, e.g. "coins gold silver".
+    fn keywords(&self) -> String {
+        let mut keywords = vec!["coins", "money"];
+        for (amount, denomination) in self.denominations() {
+            if amount > 0 {
+                keywords.push(denomination);
+            }
+        }
+
+        keywords.join(" ")
+    }
+
+    fn denominations(&self) -> [(usize, &'static str); 3] {
+        [
+            (self.gold, "gold"),
+            (self.silver, "silver"),
+            (self.copper, "copper"),
+        ]
+    }
+}
+
+/// Written as e.g. "3 gold, 12 silver and 5 copper coins".
+impl fmt::Display for Coins {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .denominations()
+            .iter()
+            .filter(|(amount, _)| *amount > 0)
+            .map(|(amount, denomination)| format!("{} {}", amount, denomination))
+            .collect();
+
+        let coins = if self.gold + self.silver + self.copper == 1 {
+            "coin"
+        } else {
+            "coins"
+        };
+
+        match parts.as_slice() {
+            [] => write!(f, "no coins"),
+            [one] => write!(f, "{} {}", one, coins),
+            [rest @ .., last] => write!(f, "{} and {} {}", rest.join(", "), last, coins),
+        }
+    }
+}
+
+/// Roughly the amount of silver coins given as a mobile's `Wealth`, some of
+/// which may be in gold.
+pub(crate) fn random_wealth(wealth: usize) -> Coins {
+    if wealth == 0 {
+        return Coins::default();
+    }
+
+    let wealth = wealth / 2 + rand::random::<usize>() % (wealth + 1);
+    let gold = rand::random::<usize>() % (wealth / SILVER_PER_GOLD + 1);
+
+    Coins {
+        gold,
+        silver: wealth - gold * SILVER_PER_GOLD,
+        copper: 0,
+    }
+}
+
+fn set_pile_description(interner: &mut StringInterner, components: &mut Components, coins: Coins) {
+    components.act_info = interner.act_info(&coins.keywords(), &coins.to_string(), Gender::Neutral);
+    components.coins = Some(coins);
+}
+
+/// Put a new pile of coins in an entity, without joining any pile there.
+pub(crate) fn insert_coin_pile(
+    entity_world: &mut EntityWorld,
+    to_entity: EntityId,
+    coins: Coins,
+) -> EntityId {
+    let title = "Swimming in coins.";
+    let internal = "You are inside a pile of coins.";
+    let external = "A pile of coins.";
+    let lateral = "A pile of coins is on the ground here.";
+
+    let act_info =
+        entity_world
+            .interner
+            .act_info(&coins.keywords(), &coins.to_string(), Gender::Neutral);
+    let descriptions = entity_world
+        .interner
+        .descriptions(title, internal, external, lateral);
+
+    entity_world.insert_entity(
+        to_entity,
+        Components {
+            act_info,
+            descriptions,
+            general: GeneralData {
+                vnum: Vnum(0),
+                area: "coins".to_string(),
+                sector: None,
+                entity_type: EntityType::Object,
+                equipped: None,
+                command_queue: Vec::new(),
+                following: None,
+                group: None,
+            },
+            mobile: None,
+            object: None,
+            door: None,
+            mobprog: None,
+            coins: Some(coins),
+            room: None,
+            player: None,
+            script: None,
+            variables: Variables::default(),
+            visibility: Visibility::default(),
+        },
+    )
+}
+
+impl<'e> EntityInfo<'e> {
+    /// The pile of coins carried by this entity, or lying in this room.
+    pub fn coin_pile(&self) -> Option<EntityInfo<'e>> {
+        self.objects()
+            .find(|object| object.components().coins.is_some())
+    }
+
+    pub fn coins(&self) -> Coins {
+        self.coin_pile()
+            .and_then(|pile| pile.components().coins)
+            .unwrap_or_default()
+    }
+
+    fn is_bank(&self) -> bool {
+        self.components()
+            .room
+            .as_ref()
+            .is_some_and(|room| room.bank)
+    }
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    /// Change the coins in a pile, getting rid of it once it's empty.
+    fn set_coins(&mut self, pile_id: EntityId, coins: Coins) {
+        if coins.is_empty() {
+            self.switch_agent(pile_id).do_die();
+            return;
+        }
+
+        let (mut pile, interner) = self.entity_world.entity_info_mut_with_interner(pile_id);
+        set_pile_description(interner, pile.components(), coins);
+    }
+
+    pub fn add_coins(&mut self, coins: Coins, to_entity: EntityId) {
+        if coins.is_empty() {
+            return;
+        }
+
+        let pile = self
+            .entity_world
+            .entity_info(to_entity)
+            .coin_pile()
+            .map(|pile| {
+                (
+                    pile.entity_id(),
+                    pile.components().coins.unwrap_or_default(),
+                )
+            });
+
+        match pile {
+            Some((pile_id, mut pile_coins)) => {
+                pile_coins.add(coins);
+                self.set_coins(pile_id, pile_coins);
+            }
+            None => {
+                insert_coin_pile(self.entity_world, to_entity, coins);
+            }
+        }
+    }
+
+    pub fn add_silver(&mut self, amount: usize, to_entity: EntityId) {
+        self.add_coins(Coins::silver(amount), to_entity);
+    }
+
+    /// Pay a value, in copper, out of an entity's coins.
+    pub fn pay(&mut self, value: usize, from_entity: EntityId) -> bool {
+        if value == 0 {
+            return true;
+        }
+
+        let pile = self
+            .entity_world
+            .entity_info(from_entity)
+            .coin_pile()
+            .map(|pile| {
+                (
+                    pile.entity_id(),
+                    pile.components().coins.unwrap_or_default(),
+                )
+            });
+
+        match pile {
+            Some((pile_id, mut coins)) => {
+                let paid = coins.pay(value);
+                if paid {
+                    self.set_coins(pile_id, coins);
+                }
+                paid
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove_silver(&mut self, amount: usize, from_entity: EntityId) -> bool {
+        self.pay(amount * COPPER_PER_SILVER, from_entity)
+    }
+
+    /// Join a pile of coins with the other pile where it is now, if any.
+    pub fn merge_coins(&mut self, pile_id: EntityId) {
+        let pile = self.entity_world.entity_info(pile_id);
+        let coins = match pile.components().coins {
+            Some(coins) => coins,
+            None => return,
+        };
+
+        let other_pile = pile
+            .room()
+            .objects()
+            .find(|other| other.entity_id() != pile_id && other.components().coins.is_some())
+            .map(|other| {
+                (
+                    other.entity_id(),
+                    other.components().coins.unwrap_or_default(),
+                )
+            });
+
+        if let Some((other_id, mut other_coins)) = other_pile {
+            self.switch_agent(pile_id).do_die();
+            other_coins.add(coins);
+            self.set_coins(other_id, other_coins);
+        }
+    }
+
+    /// Take some coins out of the agent's pile, into a pile of their own.
+    fn split_off_coins(&mut self, amount: &str, denomination: &str) -> Option<EntityId> {
+        let coins = match Coins::parse(amount, denomination) {
+            Some(coins) => coins,
+            None => {
+                echo!(self.info(), "How many gold, silver or copper coins?\r\n");
+                return None;
+            }
+        };
+        let value = self.coins_value(coins)?;
+
+        if !self.pay(value, self.entity_id) {
+            echo!(self.info(), "You don't have that much money.\r\n");
+            return None;
+        }
+
+        Some(insert_coin_pile(self.entity_world, self.entity_id, coins))
+    }
+
+    pub fn do_drop_coins(&mut self, amount: &str, denomination: &str) {
+        if let Some(pile_id) = self.split_off_coins(amount, denomination) {
+            self.drop_object(pile_id);
+        }
+    }
+
+    pub fn do_give_coins(&mut self, amount: &str, denomination: &str, target: &str) {
+        let target_id = match self.find_receiver(target, false) {
+            Some(target_id) => target_id,
+            None => return,
+        };
+
+        if let Some(pile_id) = self.split_off_coins(amount, denomination) {
+            self.give_object(pile_id, target_id);
+        }
+    }
+
+    /// The value of some coins that a command is about, telling the agent
+    /// when there are too many to count.
+    pub(crate) fn coins_value(&mut self, coins: Coins) -> Option<usize> {
+        let value = coins.value();
+        if value.is_none() {
+            echo!(
+                self.info(),
+                "That's more money than anyone could count.\r\n"
+            );
+        }
+        value
+    }
+
+    /// Check that the agent is in a bank, telling it otherwise.
+    fn check_bank(&mut self) -> bool {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        if myself.room().is_bank() {
+            return true;
+        }
+
+        echo!(self.info(), "You can only do that in a bank.\r\n");
+        false
+    }
+
+    fn change_balance(&mut self, change: impl FnOnce(&mut usize) -> bool) -> bool {
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        match &mut myself.components().player {
+            Some(player) => change(&mut player.bank),
+            None => false,
+        }
+    }
+
+    /// Deposit some coins, or all of them if no amount is given.
+    pub fn do_deposit(&mut self, amount: Option<(&str, &str)>) {
+        if !self.check_bank() {
+            return;
+        }
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        if !myself.is_player() {
+            echo!(self.info(), "Only players can keep coins in the bank.\r\n");
+            return;
+        }
+
+        let coins = match amount {
+            Some((amount, denomination)) => match Coins::parse(amount, denomination) {
+                Some(coins) => coins,
+                None => {
+                    echo!(self.info(), "How many gold, silver or copper coins?\r\n");
+                    return;
+                }
+            },
+            None if myself.coins().is_empty() => {
+                echo!(self.info(), "You don't have any coins to deposit.\r\n");
+                return;
+            }
+            None => myself.coins(),
+        };
+        let value = match self.coins_value(coins) {
+            Some(value) => value,
+            None => return,
+        };
+
+        if !self.change_balance(|bank| bank.checked_add(value).is_some()) {
+            echo!(
+                self.info(),
+                "The bank can't keep that much money for you.\r\n"
+            );
+            return;
+        }
+
+        if !self.pay(value, self.entity_id) {
+            echo!(self.info(), "You don't have that much money.\r\n");
+            return;
+        }
+
+        self.change_balance(|bank| {
+            *bank += value;
+            true
+        });
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let mut act = self.players.act_alone(&myself);
+        echo!(act.myself(), "You deposit {}.\r\n", coins);
+        echo!(act.others(), "$^$n deposits some coins.\r\n");
+    }
+
+    pub fn do_withdraw(&mut self, amount: &str, denomination: &str) {
+        if !self.check_bank() {
+            return;
+        }
+
+        let coins = match Coins::parse(amount, denomination) {
+            Some(coins) => coins,
+            None => {
+                echo!(self.info(), "How many gold, silver or copper coins?\r\n");
+                return;
+            }
+        };
+        let value = match self.coins_value(coins) {
+            Some(value) => value,
+            None => return,
+        };
+
+        let withdrawn = self.change_balance(|bank| {
+            if *bank < value {
+                return false;
+            }
+            *bank -= value;
+            true
+        });
+
+        if !withdrawn {
+            echo!(
+                self.info(),
+                "You don't have that much money in the bank.\r\n"
+            );
+            return;
+        }
+
+        self.add_coins(coins, self.entity_id);
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let mut act = self.players.act_alone(&myself);
+        echo!(act.myself(), "You withdraw {}.\r\n", coins);
+        echo!(act.others(), "$^$n withdraws some coins.\r\n");
+    }
+
+    pub fn do_balance(&mut self) {
+        if !self.check_bank() {
+            return;
+        }
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let balance = match &myself.components().player {
+            Some(player) => Coins::from_value(player.bank),
+            None => Coins::default(),
+        };
+
+        echo!(
+            self.players.info(&myself),
+            "You have {} in the bank, and carry {}.\r\n",
+            balance,
+            myself.coins()
+        );
+    }
+}
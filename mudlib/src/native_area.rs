@@ -0,0 +1,118 @@
+//! DemiMUD's own area format, in TOML.
+//!
+//! These files hold the same `crate::world` types that the other loaders
+//! produce, as serialized by serde, so an area looks like this:
+//!
+//! ```toml
+//! [area]
+//! name = "Gnome Hill"
+//! short_name = "GnomeHill"
+//! vnums = [100, 199]
+//! credits = "Someone"
+//! continent = ""
+//!
+//! [[room]]
+//! vnum = 100
+//! name = "A clearing"
+//! description = '''
+//! A small clearing.
+//! '''
+//! sector = "field"
+//! exits = [{ name = "north", vnum = 101 }]
+//!
+//! [[reset]]
+//! type = "mob"
+//! m_num = 100
+//! global_limit = 1
+//! r_num = 100
+//! room_limit = 1
+//! ```
+//!
+//! Area files in the other formats can be converted with the `areaconv`
+//! binary.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    file_parser::{Diagnostic, Severity},
+    world::{Area, AreaData, MobProg, Mobile, Object, ResetCommand, Room, Shop},
+};
+
+#[derive(Serialize, Deserialize)]
+struct NativeArea {
+    area: AreaData,
+    #[serde(default, rename = "room", skip_serializing_if = "Vec::is_empty")]
+    rooms: Vec<Room>,
+    #[serde(default, rename = "mobile", skip_serializing_if = "Vec::is_empty")]
+    mobiles: Vec<Mobile>,
+    #[serde(default, rename = "object", skip_serializing_if = "Vec::is_empty")]
+    objects: Vec<Object>,
+    #[serde(default, rename = "reset", skip_serializing_if = "Vec::is_empty")]
+    resets: Vec<ResetCommand>,
+    #[serde(default, rename = "shop", skip_serializing_if = "Vec::is_empty")]
+    shops: Vec<Shop>,
+    #[serde(default, rename = "mobprog", skip_serializing_if = "Vec::is_empty")]
+    mobprogs: Vec<MobProg>,
+}
+
+pub(super) fn load_native_area(contents: &str, file_name: &str) -> Result<Area, Diagnostic> {
+    let native: NativeArea = toml::from_str(contents)
+        .map_err(|error| Diagnostic::from_toml(error, contents, file_name))?;
+
+    Ok(Area {
+        area_data: native.area,
+        rooms: native.rooms,
+        objects: native.objects,
+        mobiles: native.mobiles,
+        resets: native.resets,
+        shops: native.shops,
+        mobprogs: native.mobprogs,
+    })
+}
+
+/// Write an area in the native format.
+pub(crate) fn save_native_area(area: Area) -> Result<String, toml::ser::Error> {
+    let native = NativeArea {
+        area: area.area_data,
+        rooms: area.rooms,
+        mobiles: area.mobiles,
+        objects: area.objects,
+        resets: area.resets,
+        shops: area.shops,
+        mobprogs: area.mobprogs,
+    };
+
+    toml::to_string(&native)
+}
+
+/// The formats areas can be converted into.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AreaFormat {
+    /// DemiMUD's own TOML format
+    Native,
+    /// Dawn of Time's `#DAWNAREADATA` format
+    Dawn,
+}
+
+/// Load an area file in any of the formats the game understands, and write it
+/// in the given format.
+///
+/// Like `load_area`, warnings are added to `diagnostics`, and the area isn't
+/// converted if it has errors.
+pub fn convert_area(
+    contents: &str,
+    file_name: &str,
+    format: AreaFormat,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<String, Diagnostic> {
+    let area = crate::load::load_area(contents, file_name, diagnostics)?;
+
+    if format == AreaFormat::Dawn {
+        return Ok(crate::save::save_area(&area));
+    }
+
+    save_native_area(area).map_err(|error| {
+        let message = format!("Could not convert area: {}", error);
+        Diagnostic::about_file(Severity::Error, file_name, message)
+    })
+}
@@ -1,11 +1,14 @@
-use std::panic::catch_unwind;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::{collections::BTreeMap, net::SocketAddr};
 
 use libtelnet_rs::{events::TelnetEvents, Parser};
 use netcore::{self, EntryCode, ExitCode, NetServer, Source};
 use serde::{Deserialize, Serialize};
 
-use crate::{acting::Players, colorize, files::RealFiles, socials, state, world, WorldState};
+use crate::{
+    acting::Players, colorize, files::RealFiles, socials, state, world, ConnectionInfo, Editor,
+    EditorStatus, WorldState,
+};
 
 #[derive(Serialize, Deserialize)]
 struct ConnectionState {
@@ -19,6 +22,21 @@ struct Connection {
     command_buffer: String,
     sent_command: bool,
     no_prompt: bool,
+    /// Where the player's input goes instead of commands, if they're writing
+    /// a description. It's kept here so that it survives restarts.
+    editor: Option<Editor>,
+}
+
+impl Connection {
+    fn info(&self, source: usize) -> ConnectionInfo {
+        ConnectionInfo {
+            socket: source,
+            address: self
+                .address
+                .map(|address| address.to_string())
+                .unwrap_or_default(),
+        }
+    }
 }
 
 struct Game {
@@ -30,15 +48,23 @@ impl Game {
         let files = RealFiles;
 
         println!("Loading area data...");
-        let world = world::load_world(&files, "data/area");
+        let mut world = world::load_world(&files, "data/area");
         println!("Loading socials.txt data...");
-        let socials = socials::load_socials(&files, "data/socials.txt");
+        let socials = socials::load_socials(&files, "data/socials.txt", &mut world.diagnostics);
         println!("Importing areas into entity world...");
-        let mut world_state = state::create_state(world, socials);
+        // Logging in only takes a name, so anyone could claim to be one of
+        // `data/immortals.txt`; there are no immortals here until there are
+        // passwords.
+        let mut world_state = state::create_state(world, socials, Box::new(RealFiles));
+        // Only now, since quests are loaded along with the entity world
+        if let Some(report) = world_state.load_report() {
+            print!("{}", report);
+        }
 
-        for connection in connection_state.connections.values() {
+        for (&source, connection) in &connection_state.connections {
             if let Some(player) = &connection.player {
                 world_state.add_player(player);
+                world_state.set_connection_info(player, connection.info(source));
             }
         }
 
@@ -56,6 +82,22 @@ impl Game {
 
         game
     }
+
+    /// Load a new game, or `None` if loading it panicked. Broken data files
+    /// are reported as diagnostics instead, so this should only happen on a
+    /// bug.
+    fn load(connection_state: &mut ConnectionState, reason: &str) -> Option<Game> {
+        catch_unwind(AssertUnwindSafe(|| Game::new(connection_state, reason))).ok()
+    }
+
+    /// Replace a crashed game. There's nothing to fall back to if the new one
+    /// can't be loaded either, so the server exits.
+    fn reload(connection_state: &mut ConnectionState) -> Game {
+        Game::load(connection_state, "crashed").unwrap_or_else(|| {
+            println!("Could not reload the game after a crash, exiting.");
+            std::process::exit(1)
+        })
+    }
 }
 
 #[no_mangle]
@@ -77,6 +119,10 @@ pub extern "C" fn do_things(net_server: &mut NetServer, entry_code: EntryCode) -
             continue;
         }
         net_server.send_bytes(&Source(target), b"Server is back online.\r\n");
+        if connection.editor.is_some() {
+            net_server.send_bytes(&Source(target), b"You are still editing.\r\n] ");
+            continue;
+        }
         if let Some(player) = &connection.player {
             net_server.send_bytes(&Source(target), player.as_bytes());
         }
@@ -85,7 +131,18 @@ pub extern "C" fn do_things(net_server: &mut NetServer, entry_code: EntryCode) -
 
     let mut telnet_parser = Parser::new();
 
-    let mut game = Game::new(&mut connection_state, "restarted");
+    let mut game = match Game::load(&mut connection_state, "restarted") {
+        Some(game) => game,
+        None => {
+            println!("Could not load the game, exiting.");
+            for &target in connection_state.connections.keys() {
+                if target != 0 && target != 1 {
+                    net_server.send_bytes(&Source(target), b"Server failed to start.\r\n");
+                }
+            }
+            return ExitCode::Exit;
+        }
+    };
 
     send_echoes(
         net_server,
@@ -116,6 +173,7 @@ pub extern "C" fn do_things(net_server: &mut NetServer, entry_code: EntryCode) -
                     command_buffer: String::new(),
                     sent_command: false,
                     no_prompt: false,
+                    editor: None,
                 };
 
                 connection_state
@@ -133,6 +191,9 @@ pub extern "C" fn do_things(net_server: &mut NetServer, entry_code: EntryCode) -
                         player,
                         connection.address.unwrap()
                     );
+                    if let Err(err) = game.world_state.save_player(player) {
+                        println!("Could not save player {}: {}", player, err);
+                    }
                 } else {
                     println!("Disconnected {}", connection.address.unwrap());
                 }
@@ -165,6 +226,35 @@ pub extern "C" fn do_things(net_server: &mut NetServer, entry_code: EntryCode) -
                                     .drain(..original_buffer + index)
                                     .collect();
 
+                                if let (Some(editor), Some(player)) =
+                                    (&mut connection.editor, &connection.player)
+                                {
+                                    let mut output = String::new();
+                                    let status = editor.input(&command, &mut output);
+                                    if let Some(echoes) = world_state.player_echoes(player) {
+                                        echoes.push_str(&output);
+                                    }
+
+                                    if status == EditorStatus::Editing {
+                                        continue;
+                                    }
+
+                                    let editor = connection.editor.take().expect("Checked above");
+                                    if status == EditorStatus::Saved {
+                                        let player = player.clone();
+                                        let old_game = catch_unwind(AssertUnwindSafe(move || {
+                                            game.world_state.finish_editor(&player, editor);
+                                            game
+                                        }));
+
+                                        game = match old_game {
+                                            Ok(game) => game,
+                                            Err(_err) => Game::reload(&mut connection_state),
+                                        };
+                                    }
+                                    continue;
+                                }
+
                                 let words: Vec<&str> = command.split_whitespace().collect();
 
                                 let mut echo = |message: &str| {
@@ -172,19 +262,6 @@ pub extern "C" fn do_things(net_server: &mut NetServer, entry_code: EntryCode) -
                                 };
 
                                 match words.as_slice() {
-                                    &["who"] => {
-                                        echo("Players currently connected to the realm:\r\n");
-                                        for (target, connection) in &connection_state.connections {
-                                            if let Some(address) = &connection.address {
-                                                echo(&colorize(&format!(
-                                                    "{}: `M{}`^ ({})\r\n",
-                                                    target,
-                                                    connection.player.as_deref().unwrap_or("unset"),
-                                                    address
-                                                )));
-                                            }
-                                        }
-                                    }
                                     &["exit"] => {
                                         echo("Bye!\r\n");
                                         net_server.schedule_disconnect(&source);
@@ -193,6 +270,7 @@ pub extern "C" fn do_things(net_server: &mut NetServer, entry_code: EntryCode) -
                                     command_words if connection.player.is_none() => {
                                         process_login_command(
                                             echo,
+                                            source.0,
                                             connection,
                                             world_state,
                                             command_words,
@@ -212,18 +290,30 @@ pub extern "C" fn do_things(net_server: &mut NetServer, entry_code: EntryCode) -
                                             .as_ref()
                                             .expect("Checked in previous match arm");
 
-                                        let old_game = catch_unwind(move || {
+                                        // A crashed game is thrown away, so nothing
+                                        // broken can be observed afterwards.
+                                        let old_game = catch_unwind(AssertUnwindSafe(move || {
                                             game.world_state.process_player_command(player, words);
                                             game
-                                        });
+                                        }));
 
                                         game = match old_game {
                                             Ok(game) => game,
                                             Err(_err) => {
                                                 // Old game's kaput, make a new one
-                                                Game::new(&mut connection_state, "crashed")
+                                                Game::reload(&mut connection_state)
                                             }
                                         };
+
+                                        // The command may have opened an editor
+                                        let connection = connection_state
+                                            .connections
+                                            .get_mut(&source.0)
+                                            .expect("Unregistered connection");
+                                        if let Some(player) = &connection.player {
+                                            connection.editor =
+                                                game.world_state.take_editor(player);
+                                        }
                                     }
                                 }
                             }
@@ -233,15 +323,15 @@ pub extern "C" fn do_things(net_server: &mut NetServer, entry_code: EntryCode) -
                 }
             }
             netcore::NetEvent::Tick => {
-                let old_game = catch_unwind(move || {
+                let old_game = catch_unwind(AssertUnwindSafe(move || {
                     game.world_state.update_world();
                     game
-                });
+                }));
                 game = match old_game {
                     Ok(game) => game,
                     Err(_err) => {
                         // Old game's kaput, make a new one
-                        Game::new(&mut connection_state, "crashed")
+                        Game::reload(&mut connection_state)
                     }
                 };
             }
@@ -254,6 +344,12 @@ pub extern "C" fn do_things(net_server: &mut NetServer, entry_code: EntryCode) -
             &mut connection_state,
         );
 
+        if schedule_restart || schedule_exit {
+            for err in game.world_state.save_players() {
+                println!("Could not save player: {}", err);
+            }
+        }
+
         if schedule_restart {
             for &target in connection_state.connections.keys() {
                 if target == 0 || target == 1 {
@@ -292,11 +388,19 @@ pub extern "C" fn do_things(net_server: &mut NetServer, entry_code: EntryCode) -
 
 fn process_login_command<F: FnMut(&str)>(
     mut echo: F,
+    source: usize,
     connection: &mut Connection,
     world_state: &mut WorldState,
     command_words: &[&str],
 ) {
     match command_words {
+        &["who"] => {
+            // Logged in players get the game's own `who` instead
+            echo("Players currently in the realm:\r\n");
+            for player in world_state.player_names() {
+                echo(&colorize(&format!("  `M{}`^\r\n", player)));
+            }
+        }
         &["name", name] => {
             println!(
                 "Player {} logged in from {}.",
@@ -306,6 +410,7 @@ fn process_login_command<F: FnMut(&str)>(
             connection.player = Some(name.to_string());
             echo("Name set. Welcome!\r\n");
             world_state.add_player(name);
+            world_state.set_connection_info(name, connection.info(source));
             // FIXME
             // write!(
             //     world_state.players.others(),
@@ -352,8 +457,10 @@ fn send_echoes(
 
                 net_server.send_bytes(&target, colorize(echoes).as_bytes());
 
-                // Also send them a prompt
-                if !connection.no_prompt {
+                // Also send them a prompt, which is shorter while editing
+                if connection.editor.is_some() {
+                    net_server.send_bytes(&target, b"] \xFF\xF9");
+                } else if !connection.no_prompt {
                     if let Some(player) = &connection.player {
                         net_server.send_bytes(&target, player.as_bytes());
                     }
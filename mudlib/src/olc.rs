@@ -0,0 +1,1430 @@
+//! Online building, or OLC: immortals editing rooms, mobiles, objects and
+//! resets from inside the game.
+//!
+//! The editors change the templates in `World`, which `asave` writes back to
+//! the area files. Where it makes sense, changes also reach what was already
+//! created from those templates: `redit` changes the room itself, and `medit`
+//! and `oedit` update the mobiles and objects in the game. Resets only run when
+//! the world is loaded, so `resetedit` takes effect after a restart.
+
+use crate::{
+    agent::EntityAgent,
+    colors::EscapeColors,
+    echo,
+    editor::EditorTarget,
+    entity::EntityId,
+    import::{
+        import_exit_components, import_extra_description_components, import_mobile_components,
+        import_object_components, import_room_components, is_main_description,
+    },
+    world::{
+        common_direction, long_direction, opposite_direction, Exit, ExtraDescription, Gender,
+        Mobile, Object, ResetCommand, Room, Vnum, World,
+    },
+};
+
+/// Sectors that the mapper knows how to draw.
+const SECTORS: &[&str] = &[
+    "inside", "city", "field", "forest", "hills", "mountain", "desert", "cave", "swamp", "swim",
+    "noswim",
+];
+
+/// The kinds of templates that are edited by vnum.
+#[derive(Clone, Copy)]
+enum Template {
+    Mobile,
+    Object,
+}
+
+impl Template {
+    fn name(self) -> &'static str {
+        match self {
+            Template::Mobile => "Mobile",
+            Template::Object => "Object",
+        }
+    }
+
+    fn exists(self, world: &World, vnum: Vnum) -> bool {
+        match self {
+            Template::Mobile => world.mobiles.contains_key(&vnum),
+            Template::Object => world.objects.contains_key(&vnum),
+        }
+    }
+
+    fn short_description(self, world: &World, vnum: Vnum) -> String {
+        let short_description = match self {
+            Template::Mobile => world
+                .mobiles
+                .get(&vnum)
+                .map(|mobile| &mobile.short_description),
+            Template::Object => world
+                .objects
+                .get(&vnum)
+                .map(|object| &object.short_description),
+        };
+
+        match short_description {
+            Some(short_description) => EscapeColors(short_description).to_string(),
+            None => "missing".to_string(),
+        }
+    }
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    pub fn do_redit(&mut self, words: &[&str]) {
+        match *words {
+            [] | ["show"] => self.do_redit_show(),
+            ["name", ref name @ ..] if !name.is_empty() => self.do_redit_name(&name.join(" ")),
+            ["desc"] => self.do_redit_description(None),
+            ["desc", ref description @ ..] => {
+                self.do_redit_description(Some(&description.join(" ")))
+            }
+            ["sector", sector] => self.do_redit_sector(sector),
+            ["dig", direction, vnum] => self.do_redit_link(direction, vnum, true),
+            ["link", direction, vnum] => self.do_redit_link(direction, vnum, false),
+            ["unlink", direction] => self.do_redit_unlink(direction),
+            ["ed", keyword] => self.do_redit_extra_description(keyword, None),
+            ["ed", keyword, ref description @ ..] => {
+                self.do_redit_extra_description(keyword, Some(&description.join(" ")))
+            }
+            _ => {
+                echo!(
+                    self.info(),
+                    "Syntax: `Wredit [name <name>|desc [text]|sector <sector>|dig <direction> <vnum>|link <direction> <vnum>|unlink <direction>|ed <keyword> [text]]`^\r\n"
+                );
+            }
+        }
+    }
+
+    /// The room the builder is in, if it was loaded from an area.
+    fn olc_room(&mut self) -> Option<(EntityId, Vnum)> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let room = myself.room();
+        let vnum = room.components().general.vnum;
+
+        if room.is_room() && self.world.rooms.contains_key(&vnum) {
+            Some((room.entity_id(), vnum))
+        } else {
+            echo!(
+                self.info(),
+                "This room isn't part of an area, and can't be edited.\r\n"
+            );
+            None
+        }
+    }
+
+    /// Apply a room template's name, description and sector to the room.
+    fn refresh_room(&mut self, room_id: EntityId, vnum: Vnum) {
+        let room = &self.world.rooms[&vnum];
+        let components = import_room_components(room, &mut self.entity_world.interner);
+
+        let mut room_entity = self.entity_world.entity_info_mut(room_id);
+        let room_components = room_entity.components();
+        room_components.act_info = components.act_info;
+        room_components.descriptions = components.descriptions;
+        room_components.general.sector = components.general.sector;
+    }
+
+    fn do_redit_show(&mut self) {
+        let (_, vnum) = match self.olc_room() {
+            Some(room) => room,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let mut info = self.players.info(&myself);
+        let room = &self.world.rooms[&vnum];
+
+        echo!(info, "Room `W#{}`^ in area `W{}`^\r\n", vnum, room.area);
+        echo!(info, "Name:   {}\r\n", EscapeColors(&room.name));
+        echo!(info, "Sector: {}\r\n", room.sector);
+        echo!(info, "Description:\r\n{}", EscapeColors(&room.description));
+        for exit in &room.exits {
+            echo!(
+                info,
+                "Exit `W{}`^ leads to `W#{}`^{}\r\n",
+                exit.name,
+                exit.vnum,
+                if exit.has_door { ", with a door" } else { "" }
+            );
+        }
+        for extra_description in &room.extra_descriptions {
+            echo!(
+                info,
+                "Extra description `W{}`^\r\n",
+                EscapeColors(&extra_description.keyword)
+            );
+        }
+    }
+
+    fn do_redit_name(&mut self, name: &str) {
+        let (room_id, vnum) = match self.olc_room() {
+            Some(room) => room,
+            None => return,
+        };
+
+        if let Some(room) = self.world.rooms.get_mut(&vnum) {
+            room.name = name.to_string();
+        }
+        self.refresh_room(room_id, vnum);
+
+        echo!(self.info(), "Room name set.\r\n");
+    }
+
+    /// Set the room description, or open the editor on it if there's no
+    /// text.
+    fn do_redit_description(&mut self, description: Option<&str>) {
+        let (_, vnum) = match self.olc_room() {
+            Some(room) => room,
+            None => return,
+        };
+
+        match description {
+            Some(description) => self.set_room_description(vnum, format!("{}\r\n", description)),
+            None => {
+                let description = self.world.rooms[&vnum].description.clone();
+                let what = format!("the description of room #{}", vnum);
+                self.start_editor(EditorTarget::Room(vnum), &what, &description);
+            }
+        }
+    }
+
+    pub(crate) fn set_room_description(&mut self, vnum: Vnum, description: String) {
+        let room = match self.world.rooms.get_mut(&vnum) {
+            Some(room) => room,
+            None => {
+                echo!(self.info(), "Room #{} doesn't exist any more.\r\n", vnum);
+                return;
+            }
+        };
+        room.description = description;
+
+        let room_id = self
+            .vnum_templates
+            .vnum_to_room_entity
+            .get(&vnum)
+            .and_then(|permanent_id| self.entity_world.old_entity(permanent_id))
+            .map(|room| room.entity_id());
+        if let Some(room_id) = room_id {
+            self.refresh_room(room_id, vnum);
+        }
+
+        echo!(self.info(), "Room description set.\r\n");
+    }
+
+    fn do_redit_sector(&mut self, sector: &str) {
+        if !SECTORS.contains(&sector) {
+            echo!(
+                self.info(),
+                "Unknown sector '{}'. Sectors are: {}.\r\n",
+                sector,
+                SECTORS.join(", ")
+            );
+            return;
+        }
+
+        let (room_id, vnum) = match self.olc_room() {
+            Some(room) => room,
+            None => return,
+        };
+
+        if let Some(room) = self.world.rooms.get_mut(&vnum) {
+            room.sector = sector.to_string();
+        }
+        self.refresh_room(room_id, vnum);
+
+        echo!(self.info(), "Room sector set to {}.\r\n", sector);
+    }
+
+    /// Add an exit to another room, which is created first when digging, and
+    /// an exit leading back unless that room already has one.
+    fn do_redit_link(&mut self, direction: &str, to_vnum: &str, dig: bool) {
+        let (room_id, vnum) = match self.olc_room() {
+            Some(room) => room,
+            None => return,
+        };
+        let direction = long_direction(direction);
+
+        if !common_direction(direction) {
+            echo!(
+                self.info(),
+                "'{}' is not a direction. Syntax: `Wredit {} <north|east|south|west|up|down|northeast|southeast|southwest|northwest> <vnum>`^\r\n",
+                EscapeColors(direction),
+                if dig { "dig" } else { "link" }
+            );
+            return;
+        }
+
+        let to_vnum: Vnum = match to_vnum.parse() {
+            Ok(to_vnum) => to_vnum,
+            Err(_) => {
+                echo!(self.info(), "Vnum '{}' is not a valid number.\r\n", to_vnum);
+                return;
+            }
+        };
+
+        let room = &self.world.rooms[&vnum];
+        let sector = room.sector.clone();
+        if room.exits.iter().any(|exit| exit.name == direction) {
+            echo!(
+                self.info(),
+                "There's already an exit leading {}; unlink it first.\r\n",
+                direction
+            );
+            return;
+        }
+
+        if dig {
+            if self.world.rooms.contains_key(&to_vnum) {
+                echo!(
+                    self.info(),
+                    "Room #{} already exists; use `Wredit link`^ instead.\r\n",
+                    to_vnum
+                );
+                return;
+            }
+
+            let area = match self.olc_area_for_vnum(to_vnum) {
+                Some(area) => area,
+                None => return,
+            };
+
+            let new_room = Room {
+                vnum: to_vnum,
+                name: "A new room".to_string(),
+                sector,
+                area,
+                ..Default::default()
+            };
+            let components = import_room_components(&new_room, &mut self.entity_world.interner);
+            let new_room_id = self
+                .entity_world
+                .insert_entity(self.entity_world.world_entity_id(), components);
+            let new_room_entity = self.entity_world.entity_info(new_room_id);
+
+            self.vnum_templates
+                .vnum_to_room_entity
+                .insert(to_vnum, new_room_entity.permanent_entity_id());
+            self.world.rooms.insert(to_vnum, new_room);
+        }
+
+        let to_room_id = self
+            .vnum_templates
+            .vnum_to_room_entity
+            .get(&to_vnum)
+            .and_then(|permanent_id| self.entity_world.old_entity(permanent_id))
+            .map(|room| room.entity_id());
+        let to_room_id = match to_room_id {
+            Some(to_room_id) => to_room_id,
+            None => {
+                echo!(self.info(), "Room #{} does not exist.\r\n", to_vnum);
+                return;
+            }
+        };
+
+        self.add_exit(room_id, vnum, direction, to_room_id, to_vnum);
+
+        let reverse_direction = opposite_direction(direction);
+        let to_room = &self.world.rooms[&to_vnum];
+        let reverse_exit = to_room
+            .exits
+            .iter()
+            .find(|exit| exit.name == reverse_direction);
+
+        match reverse_exit {
+            Some(reverse_exit) if reverse_exit.vnum != vnum => {
+                echo!(
+                    self.info(),
+                    "Linked {} to room #{}. It already has an exit leading {} elsewhere, which was left alone.\r\n",
+                    direction,
+                    to_vnum,
+                    reverse_direction
+                );
+            }
+            Some(_) => {
+                echo!(
+                    self.info(),
+                    "Linked {} to room #{}.\r\n",
+                    direction,
+                    to_vnum
+                );
+            }
+            None => {
+                self.add_exit(to_room_id, to_vnum, reverse_direction, room_id, vnum);
+                echo!(
+                    self.info(),
+                    "Linked {} to room #{}, and {} back here.\r\n",
+                    direction,
+                    to_vnum,
+                    reverse_direction
+                );
+            }
+        }
+    }
+
+    /// Add an exit to a room template, and to the room itself.
+    fn add_exit(
+        &mut self,
+        room_id: EntityId,
+        vnum: Vnum,
+        direction: &str,
+        to_room_id: EntityId,
+        to_vnum: Vnum,
+    ) {
+        let exit = Exit {
+            name: direction.to_string(),
+            vnum: to_vnum,
+            ..Default::default()
+        };
+
+        let room = match self.world.rooms.get_mut(&vnum) {
+            Some(room) => room,
+            None => return,
+        };
+        let components = import_exit_components(&exit, &room.area, &mut self.entity_world.interner);
+        room.exits.push(exit);
+
+        let exit_id = self.entity_world.insert_entity(room_id, components);
+        self.entity_world.set_leads_to(exit_id, to_room_id);
+    }
+
+    /// Remove an exit, and the exit leading back through it.
+    fn do_redit_unlink(&mut self, direction: &str) {
+        let (room_id, vnum) = match self.olc_room() {
+            Some(room) => room,
+            None => return,
+        };
+        let direction = long_direction(direction);
+
+        let to_vnum = match self.remove_exit(room_id, vnum, direction) {
+            Some(to_vnum) => to_vnum,
+            None => {
+                echo!(
+                    self.info(),
+                    "There's no exit leading {} here.\r\n",
+                    direction
+                );
+                return;
+            }
+        };
+
+        let to_room_id = self
+            .vnum_templates
+            .vnum_to_room_entity
+            .get(&to_vnum)
+            .and_then(|permanent_id| self.entity_world.old_entity(permanent_id))
+            .map(|room| room.entity_id());
+
+        let reverse_direction = opposite_direction(direction);
+        let leads_back = self.world.rooms.get(&to_vnum).is_some_and(|to_room| {
+            to_room
+                .exits
+                .iter()
+                .any(|exit| exit.name == reverse_direction && exit.vnum == vnum)
+        });
+
+        match to_room_id {
+            Some(to_room_id) if leads_back => {
+                self.remove_exit(to_room_id, to_vnum, reverse_direction);
+                echo!(
+                    self.info(),
+                    "Removed the exit {} to room #{}, and the one leading back.\r\n",
+                    direction,
+                    to_vnum
+                );
+            }
+            _ => {
+                echo!(
+                    self.info(),
+                    "Removed the exit {} to room #{}.\r\n",
+                    direction,
+                    to_vnum
+                );
+            }
+        }
+    }
+
+    /// Remove an exit from a room template and from the room itself,
+    /// returning the vnum it led to.
+    fn remove_exit(&mut self, room_id: EntityId, vnum: Vnum, direction: &str) -> Option<Vnum> {
+        let room = self.world.rooms.get_mut(&vnum)?;
+        let index = room.exits.iter().position(|exit| exit.name == direction)?;
+        let exit = room.exits.remove(index);
+
+        let room_entity = self.entity_world.entity_info(room_id);
+        let exit_id = room_entity
+            .exits()
+            .find(|exit| exit.main_keyword() == direction)
+            .map(|exit| exit.entity_id());
+        if let Some(exit_id) = exit_id {
+            self.entity_world.remove_entity(exit_id);
+        }
+
+        Some(exit.vnum)
+    }
+
+    /// Add or replace an extra description, or remove it if there's no text.
+    fn do_redit_extra_description(&mut self, keyword: &str, description: Option<&str>) {
+        let (room_id, vnum) = match self.olc_room() {
+            Some(room) => room,
+            None => return,
+        };
+
+        let room = match self.world.rooms.get_mut(&vnum) {
+            Some(room) => room,
+            None => return,
+        };
+        let existing = room
+            .extra_descriptions
+            .iter()
+            .position(|extra_description| extra_description.keyword == keyword);
+
+        let message = match (description, existing) {
+            (Some(description), Some(index)) => {
+                room.extra_descriptions[index].description = format!("{}\r\n", description);
+                "Extra description changed."
+            }
+            (Some(description), None) => {
+                room.extra_descriptions.push(ExtraDescription {
+                    keyword: keyword.to_string(),
+                    description: format!("{}\r\n", description),
+                });
+                "Extra description added."
+            }
+            (None, Some(index)) => {
+                room.extra_descriptions.remove(index);
+                "Extra description removed."
+            }
+            (None, None) => "There's no extra description with that keyword here.",
+        };
+
+        // Extra descriptions have no state of their own, so the room's are
+        // simply created again
+        let room_entity = self.entity_world.entity_info(room_id);
+        let old_ids: Vec<EntityId> = room_entity
+            .extra_descriptions()
+            .map(|extra_description| extra_description.entity_id())
+            .collect();
+        for old_id in old_ids {
+            self.entity_world.remove_entity(old_id);
+        }
+
+        for extra_description in &room.extra_descriptions {
+            let components = import_extra_description_components(
+                extra_description,
+                &room.area,
+                &mut self.entity_world.interner,
+            );
+            self.entity_world.insert_entity(room_id, components);
+        }
+
+        echo!(self.info(), "{}\r\n", message);
+    }
+
+    pub fn do_medit(&mut self, words: &[&str]) {
+        match *words {
+            ["create", vnum] => self.do_medit_create(vnum),
+            [vnum] => self.do_medit_show(vnum),
+            [vnum, "desc"] => self.do_medit_description(vnum),
+            [vnum, field, ref value @ ..] if !value.is_empty() => {
+                self.do_medit_set(vnum, field, &value.join(" "))
+            }
+            _ => {
+                echo!(
+                    self.info(),
+                    "Syntax: `Wmedit <vnum> [name|short|long|desc|gender|sentinel <value>]`^, `Wmedit <vnum> desc`^ or `Wmedit create <vnum>`^\r\n"
+                );
+            }
+        }
+    }
+
+    /// Parse a vnum of an existing template, or explain what's wrong with it.
+    fn olc_vnum(&mut self, vnum: &str, template: Template) -> Option<Vnum> {
+        let parsed_vnum: Vnum = match vnum.parse() {
+            Ok(parsed_vnum) => parsed_vnum,
+            Err(_) => {
+                echo!(self.info(), "Vnum '{}' is not a valid number.\r\n", vnum);
+                return None;
+            }
+        };
+
+        if !template.exists(self.world, parsed_vnum) {
+            echo!(
+                self.info(),
+                "{} template with vnum '{}' does not exist.\r\n",
+                template.name(),
+                vnum
+            );
+            return None;
+        }
+
+        Some(parsed_vnum)
+    }
+
+    /// Parse the vnum for a new template, which must be free and belong to
+    /// an area, returning the area's short name.
+    fn olc_new_vnum(&mut self, vnum: &str, template: Template) -> Option<(Vnum, String)> {
+        let parsed_vnum: Vnum = match vnum.parse() {
+            Ok(parsed_vnum) => parsed_vnum,
+            Err(_) => {
+                echo!(self.info(), "Vnum '{}' is not a valid number.\r\n", vnum);
+                return None;
+            }
+        };
+
+        if template.exists(self.world, parsed_vnum) {
+            echo!(
+                self.info(),
+                "{} template with vnum '{}' already exists.\r\n",
+                template.name(),
+                vnum
+            );
+            return None;
+        }
+
+        let area = self.olc_area_for_vnum(parsed_vnum)?;
+        Some((parsed_vnum, area))
+    }
+
+    /// The short name of the area a new template belongs to: the one whose
+    /// `VNUMs` range includes its vnum, or else the builder's own area if it
+    /// doesn't have a range.
+    fn olc_area_for_vnum(&mut self, vnum: Vnum) -> Option<String> {
+        if let Some(area_data) = self.world.area_for_vnum(vnum) {
+            return Some(area_data.short_name.clone());
+        }
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let own_area = &myself.room().components().general.area;
+        let without_range = self.world.areas.iter().find(|(area_data, _)| {
+            area_data.short_name == *own_area && area_data.vnums == (Vnum(0), Vnum(0))
+        });
+
+        match without_range {
+            Some((area_data, _)) => Some(area_data.short_name.clone()),
+            None => {
+                echo!(
+                    self.info(),
+                    "Vnum {} isn't in the VNUMs range of any area.\r\n",
+                    vnum
+                );
+                None
+            }
+        }
+    }
+
+    fn do_medit_show(&mut self, vnum: &str) {
+        let vnum = match self.olc_vnum(vnum, Template::Mobile) {
+            Some(vnum) => vnum,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let mut info = self.players.info(&myself);
+        let mobile = &self.world.mobiles[&vnum];
+        let gender = match mobile.gender {
+            Gender::Male => "male",
+            Gender::Female => "female",
+            Gender::Neutral => "neutral",
+            Gender::Plural => "plural",
+            Gender::Custom(_) => "custom",
+        };
+
+        echo!(info, "Mobile `W#{}`^ in area `W{}`^\r\n", vnum, mobile.area);
+        echo!(info, "Name:     {}\r\n", EscapeColors(&mobile.name));
+        echo!(
+            info,
+            "Short:    {}\r\n",
+            EscapeColors(&mobile.short_description)
+        );
+        echo!(
+            info,
+            "Long:     {}\r\n",
+            EscapeColors(&mobile.long_description)
+        );
+        echo!(info, "Gender:   {}\r\n", gender);
+        echo!(
+            info,
+            "Sentinel: {}\r\n",
+            if mobile.sentinel { "on" } else { "off" }
+        );
+        echo!(
+            info,
+            "Description:\r\n{}",
+            EscapeColors(&mobile.description)
+        );
+    }
+
+    fn do_medit_create(&mut self, vnum: &str) {
+        let (vnum, area) = match self.olc_new_vnum(vnum, Template::Mobile) {
+            Some(new_vnum) => new_vnum,
+            None => return,
+        };
+
+        self.world.mobiles.insert(
+            vnum,
+            Mobile {
+                vnum,
+                name: "mobile new".to_string(),
+                short_description: "a new mobile".to_string(),
+                long_description: "A new mobile is here.".to_string(),
+                area: area.clone(),
+                sentinel: true,
+                ..Default::default()
+            },
+        );
+        self.refresh_mobile(vnum);
+
+        echo!(
+            self.info(),
+            "Created mobile #{} in area {}. Use `Wmob mload {}`^ to bring one here.\r\n",
+            vnum,
+            area,
+            vnum
+        );
+    }
+
+    fn do_medit_set(&mut self, vnum: &str, field: &str, value: &str) {
+        let vnum = match self.olc_vnum(vnum, Template::Mobile) {
+            Some(vnum) => vnum,
+            None => return,
+        };
+
+        let mobile = match self.world.mobiles.get_mut(&vnum) {
+            Some(mobile) => mobile,
+            None => return,
+        };
+
+        match field {
+            "name" => mobile.name = value.to_string(),
+            "short" => mobile.short_description = value.to_string(),
+            "long" => mobile.long_description = value.to_string(),
+            "desc" => {
+                self.set_mobile_description(vnum, format!("{}\r\n", value));
+                return;
+            }
+            "gender" => {
+                mobile.gender = match value {
+                    "male" => Gender::Male,
+                    "female" => Gender::Female,
+                    "neutral" => Gender::Neutral,
+                    _ => {
+                        echo!(self.info(), "Gender must be male, female or neutral.\r\n");
+                        return;
+                    }
+                }
+            }
+            "sentinel" => {
+                mobile.sentinel = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => {
+                        echo!(self.info(), "Sentinel must be on or off.\r\n");
+                        return;
+                    }
+                }
+            }
+            _ => {
+                echo!(
+                    self.info(),
+                    "Unknown field '{}'. Fields are: name, short, long, desc, gender, sentinel.\r\n",
+                    field
+                );
+                return;
+            }
+        }
+
+        let count = self.refresh_mobile(vnum);
+        echo!(
+            self.info(),
+            "Mobile #{} changed, along with {} in the game.\r\n",
+            vnum,
+            count
+        );
+    }
+
+    /// Open the editor on a mobile's description.
+    fn do_medit_description(&mut self, vnum: &str) {
+        let vnum = match self.olc_vnum(vnum, Template::Mobile) {
+            Some(vnum) => vnum,
+            None => return,
+        };
+
+        let description = self.world.mobiles[&vnum].description.clone();
+        let what = format!("the description of mobile #{}", vnum);
+        self.start_editor(EditorTarget::Mobile(vnum), &what, &description);
+    }
+
+    pub(crate) fn set_mobile_description(&mut self, vnum: Vnum, description: String) {
+        match self.world.mobiles.get_mut(&vnum) {
+            Some(mobile) => mobile.description = description,
+            None => {
+                echo!(self.info(), "Mobile #{} doesn't exist any more.\r\n", vnum);
+                return;
+            }
+        }
+
+        let count = self.refresh_mobile(vnum);
+        echo!(
+            self.info(),
+            "Mobile #{} changed, along with {} in the game.\r\n",
+            vnum,
+            count
+        );
+    }
+
+    /// Import a mobile template again, and apply it to the mobiles created
+    /// from it. Returns how many mobiles were changed.
+    fn refresh_mobile(&mut self, vnum: Vnum) -> usize {
+        let mobile = &self.world.mobiles[&vnum];
+        let (components, mobprogs) =
+            import_mobile_components(mobile, self.world, &mut self.entity_world.interner);
+
+        let mut count = 0;
+        for mut entity in self.entity_world.all_entities_mut() {
+            let entity_components = entity.components();
+            if entity_components.general.vnum != vnum {
+                continue;
+            }
+
+            if let (Some(live_mobile), Some(template_mobile)) =
+                (&mut entity_components.mobile, &components.mobile)
+            {
+                live_mobile.wander = template_mobile.wander;
+                entity_components.act_info = components.act_info.clone();
+                entity_components.descriptions = components.descriptions.clone();
+                count += 1;
+            }
+        }
+
+        self.vnum_templates
+            .mobile_components
+            .insert(vnum, (components, mobprogs));
+
+        count
+    }
+
+    pub fn do_oedit(&mut self, words: &[&str]) {
+        match *words {
+            ["create", vnum] => self.do_oedit_create(vnum),
+            [vnum] => self.do_oedit_show(vnum),
+            [vnum, "desc"] => self.do_oedit_description(vnum),
+            [vnum, field, ref value @ ..] if !value.is_empty() => {
+                self.do_oedit_set(vnum, field, &value.join(" "))
+            }
+            _ => {
+                echo!(
+                    self.info(),
+                    "Syntax: `Woedit <vnum> [name|short|long|cost <value>]`^, `Woedit <vnum> desc`^ or `Woedit create <vnum>`^\r\n"
+                );
+            }
+        }
+    }
+
+    fn do_oedit_show(&mut self, vnum: &str) {
+        let vnum = match self.olc_vnum(vnum, Template::Object) {
+            Some(vnum) => vnum,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let mut info = self.players.info(&myself);
+        let object = &self.world.objects[&vnum];
+
+        echo!(info, "Object `W#{}`^ in area `W{}`^\r\n", vnum, object.area);
+        echo!(info, "Name:  {}\r\n", EscapeColors(&object.name));
+        echo!(
+            info,
+            "Short: {}\r\n",
+            EscapeColors(&object.short_description)
+        );
+        echo!(info, "Long:  {}\r\n", EscapeColors(&object.description));
+        echo!(info, "Type:  {}\r\n", object.item_type);
+        echo!(info, "Cost:  {}\r\n", object.cost);
+        for extra_description in &object.extra_descriptions {
+            echo!(
+                info,
+                "Extra description `W{}`^\r\n",
+                EscapeColors(&extra_description.keyword)
+            );
+        }
+    }
+
+    fn do_oedit_create(&mut self, vnum: &str) {
+        let (vnum, area) = match self.olc_new_vnum(vnum, Template::Object) {
+            Some(new_vnum) => new_vnum,
+            None => return,
+        };
+
+        self.world.objects.insert(
+            vnum,
+            Object {
+                vnum,
+                name: "object new".to_string(),
+                short_description: "a new object".to_string(),
+                description: "A new object is here.".to_string(),
+                area: area.clone(),
+                condition: 100,
+                item_type: "trash".to_string(),
+                wear_flags: vec!["take".to_string()],
+                ..Default::default()
+            },
+        );
+        self.refresh_object(vnum);
+
+        echo!(
+            self.info(),
+            "Created object #{} in area {}. Use `Wmob oload {}`^ to get one.\r\n",
+            vnum,
+            area,
+            vnum
+        );
+    }
+
+    fn do_oedit_set(&mut self, vnum: &str, field: &str, value: &str) {
+        let vnum = match self.olc_vnum(vnum, Template::Object) {
+            Some(vnum) => vnum,
+            None => return,
+        };
+
+        let object = match self.world.objects.get_mut(&vnum) {
+            Some(object) => object,
+            None => return,
+        };
+
+        match field {
+            "name" => object.name = value.to_string(),
+            "short" => object.short_description = value.to_string(),
+            "long" => object.description = value.to_string(),
+            "cost" => {
+                object.cost = match value.parse() {
+                    Ok(cost) => cost,
+                    Err(_) => {
+                        echo!(self.info(), "Cost '{}' is not a valid number.\r\n", value);
+                        return;
+                    }
+                }
+            }
+            _ => {
+                echo!(
+                    self.info(),
+                    "Unknown field '{}'. Fields are: name, short, long, cost.\r\n",
+                    field
+                );
+                return;
+            }
+        }
+
+        let count = self.refresh_object(vnum);
+        echo!(
+            self.info(),
+            "Object #{} changed, along with {} in the game.\r\n",
+            vnum,
+            count
+        );
+    }
+
+    /// Open the editor on an object's description, which is the extra
+    /// description named like the object itself.
+    fn do_oedit_description(&mut self, vnum: &str) {
+        let vnum = match self.olc_vnum(vnum, Template::Object) {
+            Some(vnum) => vnum,
+            None => return,
+        };
+
+        let object = &self.world.objects[&vnum];
+        let description = object
+            .extra_descriptions
+            .iter()
+            .find(|extra_description| is_main_description(object, extra_description))
+            .map(|extra_description| extra_description.description.clone())
+            .unwrap_or_default();
+        let what = format!("the description of object #{}", vnum);
+        self.start_editor(EditorTarget::Object(vnum), &what, &description);
+    }
+
+    pub(crate) fn set_object_description(&mut self, vnum: Vnum, description: String) {
+        let object = match self.world.objects.get_mut(&vnum) {
+            Some(object) => object,
+            None => {
+                echo!(self.info(), "Object #{} doesn't exist any more.\r\n", vnum);
+                return;
+            }
+        };
+
+        let index = object
+            .extra_descriptions
+            .iter()
+            .position(|extra_description| is_main_description(object, extra_description));
+        match index {
+            Some(index) => object.extra_descriptions[index].description = description,
+            None => object.extra_descriptions.push(ExtraDescription {
+                keyword: object.name.clone(),
+                description,
+            }),
+        }
+
+        let count = self.refresh_object(vnum);
+        echo!(
+            self.info(),
+            "Object #{} changed, along with {} in the game.\r\n",
+            vnum,
+            count
+        );
+    }
+
+    /// Import an object template again, and apply it to the objects created
+    /// from it. Returns how many objects were changed.
+    fn refresh_object(&mut self, vnum: Vnum) -> usize {
+        let object = &self.world.objects[&vnum];
+        let (components, children) =
+            import_object_components(object, self.world, &mut self.entity_world.interner);
+
+        let mut count = 0;
+        for mut entity in self.entity_world.all_entities_mut() {
+            let entity_components = entity.components();
+            if entity_components.general.vnum != vnum {
+                continue;
+            }
+
+            if let (Some(live_object), Some(template_object)) =
+                (&mut entity_components.object, &components.object)
+            {
+                live_object.cost = template_object.cost;
+                entity_components.act_info = components.act_info.clone();
+                entity_components.descriptions = components.descriptions.clone();
+                count += 1;
+            }
+        }
+
+        self.vnum_templates
+            .object_components
+            .insert(vnum, (components, children));
+
+        count
+    }
+
+    pub fn do_resetedit(&mut self, words: &[&str]) {
+        match *words {
+            [] | ["show"] => self.do_resetedit_show(),
+            ["mob", m_vnum] => self.do_resetedit_mob(m_vnum),
+            ["object", o_vnum] => self.do_resetedit_object(o_vnum),
+            ["give", number, o_vnum] => self.do_resetedit_follow(number, o_vnum, None),
+            ["equip", number, o_vnum, location] => {
+                self.do_resetedit_follow(number, o_vnum, Some(location))
+            }
+            ["put", number, o_vnum] => self.do_resetedit_put(number, o_vnum),
+            ["delete", number] => self.do_resetedit_delete(number),
+            _ => {
+                echo!(
+                    self.info(),
+                    "Syntax: `Wresetedit [mob <vnum>|object <vnum>|give <reset> <vnum>|equip <reset> <vnum> <location>|put <reset> <vnum>|delete <reset>]`^\r\n"
+                );
+            }
+        }
+    }
+
+    /// The resets of the area that the builder's room belongs to.
+    fn olc_resets(&mut self) -> Option<(Vnum, &mut Vec<ResetCommand>)> {
+        let (_, vnum) = self.olc_room()?;
+        let area = &self.world.rooms[&vnum].area;
+
+        self.world
+            .areas
+            .iter_mut()
+            .find(|(area_data, _)| area_data.short_name == *area)
+            .map(|(_, resets)| (vnum, resets))
+    }
+
+    /// List the resets that load things into the current room, numbered by
+    /// their position in the area.
+    fn do_resetedit_show(&mut self) {
+        let (vnum, resets) = match self.olc_resets() {
+            Some((vnum, resets)) => (vnum, resets.clone()),
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let mut info = self.players.info(&myself);
+        echo!(info, "Resets in room `W#{}`^:\r\n", vnum);
+
+        // Give, equip and put resets load into whatever the reset before
+        // them loaded, so they belong to the same room
+        let mut last_room = None;
+        let mut found_any = false;
+        for (index, reset) in resets.iter().enumerate() {
+            let (description, room) = match reset {
+                ResetCommand::Mob {
+                    m_num,
+                    global_limit,
+                    r_num,
+                    room_limit,
+                } => (
+                    format!(
+                        "Mobile `W#{}`^ ({}), up to {} here and {} in the world",
+                        m_num,
+                        Template::Mobile.short_description(self.world, *m_num),
+                        room_limit,
+                        global_limit
+                    ),
+                    Some(*r_num),
+                ),
+                ResetCommand::Object { o_num, r_num, .. } => (
+                    format!(
+                        "Object `W#{}`^ ({})",
+                        o_num,
+                        Template::Object.short_description(self.world, *o_num)
+                    ),
+                    Some(*r_num),
+                ),
+                ResetCommand::Door {
+                    r_num,
+                    direction,
+                    door_flags,
+                } => (
+                    format!("Door {} is {}", direction, door_flags.join(" ")),
+                    Some(*r_num),
+                ),
+                ResetCommand::Give { o_num, .. } => (
+                    format!(
+                        "  gives object `W#{}`^ ({})",
+                        o_num,
+                        Template::Object.short_description(self.world, *o_num)
+                    ),
+                    last_room,
+                ),
+                ResetCommand::Equip {
+                    o_num, location, ..
+                } => (
+                    format!(
+                        "  equips object `W#{}`^ ({}) on {}",
+                        o_num,
+                        Template::Object.short_description(self.world, *o_num),
+                        location
+                    ),
+                    last_room,
+                ),
+                ResetCommand::Put { o_num, c_num, .. } => (
+                    format!(
+                        "  puts object `W#{}`^ ({}) into `W#{}`^",
+                        o_num,
+                        Template::Object.short_description(self.world, *o_num),
+                        c_num
+                    ),
+                    last_room,
+                ),
+            };
+            last_room = room;
+
+            if room == Some(vnum) {
+                found_any = true;
+                echo!(info, "`W{:>4}`^ {}\r\n", index + 1, description);
+            }
+        }
+
+        if !found_any {
+            echo!(info, "  None.\r\n");
+        }
+    }
+
+    fn do_resetedit_mob(&mut self, m_vnum: &str) {
+        let m_num = match self.olc_vnum(m_vnum, Template::Mobile) {
+            Some(m_num) => m_num,
+            None => return,
+        };
+
+        let (r_num, resets) = match self.olc_resets() {
+            Some(resets) => resets,
+            None => return,
+        };
+        resets.push(ResetCommand::Mob {
+            m_num,
+            global_limit: 1,
+            r_num,
+            room_limit: 1,
+        });
+        let number = resets.len();
+
+        echo!(
+            self.info(),
+            "Added reset {}, loading mobile #{} here when the world is loaded.\r\n",
+            number,
+            m_num
+        );
+    }
+
+    fn do_resetedit_object(&mut self, o_vnum: &str) {
+        let o_num = match self.olc_vnum(o_vnum, Template::Object) {
+            Some(o_num) => o_num,
+            None => return,
+        };
+
+        let (r_num, resets) = match self.olc_resets() {
+            Some(resets) => resets,
+            None => return,
+        };
+        resets.push(ResetCommand::Object {
+            o_num,
+            global_limit: -1,
+            r_num,
+        });
+        let number = resets.len();
+
+        echo!(
+            self.info(),
+            "Added reset {}, loading object #{} here when the world is loaded.\r\n",
+            number,
+            o_num
+        );
+    }
+
+    /// Find an existing reset by its number, as shown by `resetedit`.
+    fn olc_reset(&mut self, number: &str) -> Option<(usize, ResetCommand)> {
+        let (_, resets) = self.olc_resets()?;
+        let reset = number
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| Some((number.checked_sub(1)?, number)))
+            .and_then(|(index, _)| Some((index, resets.get(index)?.clone())));
+
+        if reset.is_none() {
+            echo!(
+                self.info(),
+                "There's no reset number '{}' in this area.\r\n",
+                number
+            );
+        }
+
+        reset
+    }
+
+    /// Add a reset that gives or equips an object to the mobile of a mob
+    /// reset, right after it.
+    fn do_resetedit_follow(&mut self, number: &str, o_vnum: &str, location: Option<&str>) {
+        let o_num = match self.olc_vnum(o_vnum, Template::Object) {
+            Some(o_num) => o_num,
+            None => return,
+        };
+
+        let index = match self.olc_reset(number) {
+            Some((index, ResetCommand::Mob { .. })) => index,
+            Some(_) => {
+                echo!(self.info(), "Reset {} doesn't load a mobile.\r\n", number);
+                return;
+            }
+            None => return,
+        };
+
+        let reset = match location {
+            Some(location) => ResetCommand::Equip {
+                o_num,
+                global_limit: -1,
+                location: location.to_string(),
+            },
+            None => ResetCommand::Give {
+                o_num,
+                global_limit: -1,
+            },
+        };
+        if let Some((_, resets)) = self.olc_resets() {
+            resets.insert(index + 1, reset);
+        }
+
+        echo!(
+            self.info(),
+            "Added reset {}, {} object #{} to the mobile of reset {}.\r\n",
+            index + 2,
+            if location.is_some() {
+                "equipping"
+            } else {
+                "giving"
+            },
+            o_num,
+            number
+        );
+    }
+
+    /// Add a reset that puts an object into the object loaded by another
+    /// reset, right after it.
+    fn do_resetedit_put(&mut self, number: &str, o_vnum: &str) {
+        let o_num = match self.olc_vnum(o_vnum, Template::Object) {
+            Some(o_num) => o_num,
+            None => return,
+        };
+
+        let (index, c_num) = match self.olc_reset(number) {
+            Some((index, ResetCommand::Object { o_num: c_num, .. }))
+            | Some((index, ResetCommand::Give { o_num: c_num, .. }))
+            | Some((index, ResetCommand::Equip { o_num: c_num, .. }))
+            | Some((index, ResetCommand::Put { o_num: c_num, .. })) => (index, c_num),
+            Some(_) => {
+                echo!(self.info(), "Reset {} doesn't load an object.\r\n", number);
+                return;
+            }
+            None => return,
+        };
+
+        if let Some((_, resets)) = self.olc_resets() {
+            resets.insert(
+                index + 1,
+                ResetCommand::Put {
+                    o_num,
+                    global_limit: -1,
+                    c_num,
+                    container_limit: 1,
+                },
+            );
+        }
+
+        echo!(
+            self.info(),
+            "Added reset {}, putting object #{} into object #{}.\r\n",
+            index + 2,
+            o_num,
+            c_num
+        );
+    }
+
+    /// Remove a reset; mob resets take the resets that give and equip their
+    /// mobile with them.
+    fn do_resetedit_delete(&mut self, number: &str) {
+        let index = match self.olc_reset(number) {
+            Some((index, _)) => index,
+            None => return,
+        };
+
+        let (_, resets) = match self.olc_resets() {
+            Some(resets) => resets,
+            None => return,
+        };
+        let mut end = index + 1;
+        if let ResetCommand::Mob { .. } = resets[index] {
+            while let Some(ResetCommand::Give { .. } | ResetCommand::Equip { .. }) = resets.get(end)
+            {
+                end += 1;
+            }
+        }
+        resets.drain(index..end);
+
+        if end - index == 1 {
+            echo!(self.info(), "Reset {} removed.\r\n", number);
+        } else {
+            echo!(
+                self.info(),
+                "Reset {} removed, along with the {} after it that equipped its mobile.\r\n",
+                number,
+                end - index - 1
+            );
+        }
+    }
+
+    pub fn do_asave(&mut self, words: &[&str]) {
+        let areas: Vec<String> = match *words {
+            [] => {
+                let (_, vnum) = match self.olc_room() {
+                    Some(room) => room,
+                    None => return,
+                };
+                vec![self.world.rooms[&vnum].area.clone()]
+            }
+            ["all"] => self
+                .world
+                .areas
+                .iter()
+                .map(|(area_data, _)| area_data.short_name.clone())
+                .collect(),
+            _ => {
+                echo!(self.info(), "Syntax: `Wasave [all]`^\r\n");
+                return;
+            }
+        };
+
+        for area in areas {
+            match self.save_area_file(&area) {
+                Ok(file_name) => echo!(
+                    self.info(),
+                    "Saved area {} to {}.\r\n",
+                    area,
+                    EscapeColors(&file_name)
+                ),
+                Err(error) => echo!(
+                    self.info(),
+                    "`RCould not save area {}:`^ {}\r\n",
+                    area,
+                    EscapeColors(&error)
+                ),
+            }
+        }
+    }
+
+    /// Write an area in DemiMUD's own format, which keeps everything the game
+    /// knows about it. Areas loaded from other formats are written to a new
+    /// file next to theirs instead of over it, since those files can have
+    /// more in them than the game keeps, and `arealist.txt` is changed to
+    /// load the new file from then on.
+    fn save_area_file(&mut self, short_name: &str) -> Result<String, String> {
+        let area = self
+            .world
+            .area(short_name)
+            .ok_or_else(|| "No such area".to_string())?;
+        let file_name = area.area_data.file_name.clone();
+
+        let contents =
+            crate::native_area::save_native_area(area).map_err(|error| error.to_string())?;
+        // Text loaded through `Files` uses telnet's newlines
+        let contents = contents.replace("\r\n", "\n");
+
+        if file_name.ends_with(".toml") {
+            self.files
+                .write_file(&file_name, &contents)
+                .map_err(|error| error.to_string())?;
+            return Ok(file_name);
+        }
+
+        let (directory, old_name) = file_name.rsplit_once('/').unwrap_or(("", &file_name));
+        let stem = old_name.rsplit_once('.').map_or(old_name, |(stem, _)| stem);
+        let new_name = format!("{}.toml", stem);
+        let new_file_name = format!("{}/{}", directory, new_name);
+
+        if self.files.read_file(&new_file_name).is_ok() {
+            return Err(format!(
+                "{} already exists; move it away first",
+                new_file_name
+            ));
+        }
+
+        self.files
+            .write_file(&new_file_name, &contents)
+            .map_err(|error| error.to_string())?;
+
+        let arealist_path = format!("{}/arealist.txt", directory);
+        let arealist = self
+            .files
+            .read_file(&arealist_path)
+            .map_err(|error| error.to_string())?;
+        let arealist: String = arealist
+            .lines()
+            .map(|line| {
+                if line.trim() == old_name {
+                    format!("{}\n", new_name)
+                } else {
+                    format!("{}\n", line)
+                }
+            })
+            .collect();
+        self.files
+            .write_file(&arealist_path, &arealist)
+            .map_err(|error| error.to_string())?;
+
+        if let Some((area_data, _)) = self
+            .world
+            .areas
+            .iter_mut()
+            .find(|(area_data, _)| area_data.short_name == short_name)
+        {
+            area_data.file_name = new_file_name.clone();
+        }
+
+        Ok(new_file_name)
+    }
+}
@@ -0,0 +1,171 @@
+//! Player files, keeping what players gathered between logins.
+//!
+//! Each player is saved as TOML in `data/players/<name>.toml`. Players are
+//! saved when they use the `save` command, when they disconnect, before a
+//! restart, and every minute. Since the world itself is thrown away on a
+//! restart or crash, this is the only state that survives one.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    agent::EntityAgent,
+    components::{ChannelSettings, Coins, QuestLog, Variables},
+    echo,
+    world::Gender,
+    Files,
+};
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct PlayerFile {
+    pub name: String,
+    /// Temporary variables store how many ticks they have left instead of
+    /// the tick they expire at, since ticks restart from 0 with the world.
+    #[serde(default)]
+    pub variables: Variables,
+    #[serde(default)]
+    pub quests: QuestLog,
+    #[serde(default)]
+    pub channels: ChannelSettings,
+    /// In copper coins.
+    #[serde(default)]
+    pub bank: usize,
+    /// The coins the player was carrying.
+    #[serde(default)]
+    pub coins: Coins,
+    #[serde(default)]
+    pub gender: Option<Gender>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub short_description: Option<String>,
+    /// With "\n" newlines, instead of the game's "\r\n".
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Only simple names get a file, so that a name can't point anywhere else.
+fn player_file_path(name: &str) -> Option<String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    Some(format!("data/players/{}.toml", name.to_ascii_lowercase()))
+}
+
+/// Read a player's file; a player without one is a new player.
+pub(crate) fn load_player_file(
+    files: &dyn Files,
+    name: &str,
+) -> Result<Option<PlayerFile>, String> {
+    let path = match player_file_path(name) {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+
+    let contents = match files.read_file_raw(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("{}: {}", path, err)),
+    };
+
+    let contents = String::from_utf8_lossy(&contents);
+    toml::from_str(&contents)
+        .map(Some)
+        .map_err(|err| format!("{}: {}", path, err))
+}
+
+pub(crate) fn save_player_file(files: &dyn Files, player_file: &PlayerFile) -> Result<(), String> {
+    let path = match player_file_path(&player_file.name) {
+        Some(path) => path,
+        None => return Err(format!("'{}' is not a valid file name", player_file.name)),
+    };
+
+    // Going through a `Value` puts plain values before tables, which TOML
+    // requires, no matter in which order the fields are declared.
+    let contents = toml::Value::try_from(player_file)
+        .and_then(|value| toml::to_string(&value))
+        .map_err(|err| err.to_string())?;
+    files
+        .write_file(&path, &contents)
+        .map_err(|err| format!("{}: {}", path, err))
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    /// Write the current player's file.
+    pub fn save_player(&mut self) -> Result<(), String> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let current_tick = self.entity_world.current_tick();
+
+        let name = match myself.player_name() {
+            Some(name) => name.to_string(),
+            None => return Err("Only players can be saved".to_string()),
+        };
+
+        let mut variables = myself.components().variables.clone();
+        variables.map_expiry(|expires| expires.saturating_sub(current_tick));
+
+        let player = match &myself.components().player {
+            Some(player) => player,
+            None => return Err("Only players can be saved".to_string()),
+        };
+
+        let player_file = PlayerFile {
+            name,
+            variables,
+            quests: player.quests.clone(),
+            channels: player.channels.clone(),
+            bank: player.bank,
+            coins: myself.coins(),
+            gender: Some(player.gender.clone()),
+            title: Some(player.title.clone()),
+            short_description: player.short_description.clone(),
+            description: player
+                .description
+                .as_ref()
+                .map(|description| description.replace("\r\n", "\n")),
+        };
+
+        save_player_file(self.files, &player_file)
+    }
+
+    /// Restore what was saved in a player file onto the current player.
+    pub fn restore_player(&mut self, player_file: PlayerFile) {
+        let current_tick = self.entity_world.current_tick();
+
+        let mut variables = player_file.variables;
+        variables.map_expiry(|remaining| remaining + current_tick);
+
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        let components = myself.components();
+        components.variables = variables;
+        if let Some(player) = &mut components.player {
+            player.quests = player_file.quests;
+            player.channels = player_file.channels;
+            player.bank = player_file.bank;
+            if let Some(gender) = player_file.gender {
+                player.gender = gender;
+            }
+            if let Some(title) = player_file.title {
+                player.title = title;
+            }
+            player.short_description = player_file.short_description;
+            player.description = player_file
+                .description
+                .map(|description| description.replace("\r\n", "\n").replace('\n', "\r\n"));
+        }
+
+        self.refresh_player_descriptions();
+        self.add_coins(player_file.coins, self.entity_id);
+    }
+
+    pub fn do_save(&mut self) {
+        match self.save_player() {
+            Ok(()) => echo!(self.info(), "Saved.\r\n"),
+            Err(err) => echo!(
+                self.info(),
+                "Your character could not be saved: {}.\r\n",
+                err
+            ),
+        }
+    }
+}
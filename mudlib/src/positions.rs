@@ -0,0 +1,411 @@
+//! Sleeping, resting, sitting and standing.
+//!
+//! Players and mobiles are always in one of these positions. Each command
+//! needs its user to be in at least some position: most need you to be
+//! awake, and going anywhere needs you to stand up first.
+//!
+//! Sleepers don't hear what goes on around them, and their programs and
+//! scripts don't react to it until they wake up.
+//!
+//! Characters can also sit, rest or sleep on furniture in their room, as many
+//! at once as it has room for, until they stand up.
+
+use crate::{
+    agent::EntityAgent,
+    components::{Components, Furniture, Position},
+    echo,
+    entity::{EntityInfo, Found, PermanentEntityId},
+    find_entities::EntityIterator,
+};
+
+impl Position {
+    /// Parse a position as written in area files, e.g. `stand` or
+    /// `sleeping`. Fighting counts as standing.
+    pub fn from_word(word: &str) -> Option<Position> {
+        let word = word.trim().to_ascii_lowercase();
+
+        if word.starts_with("sleep") {
+            Some(Position::Sleeping)
+        } else if word.starts_with("rest") {
+            Some(Position::Resting)
+        } else if word.starts_with("sit") {
+            Some(Position::Sitting)
+        } else if word.starts_with("stand") || word.starts_with("fight") {
+            Some(Position::Standing)
+        } else {
+            None
+        }
+    }
+
+    /// The word used in "$n is ... here."
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Position::Sleeping => "sleeping",
+            Position::Resting => "resting",
+            Position::Sitting => "sitting",
+            Position::Standing => "standing",
+        }
+    }
+}
+
+impl Furniture {
+    /// Whether one is in this position "at", "on" or "in" the furniture, if
+    /// the furniture can be used in this position at all.
+    pub fn preposition(&self, position: Position) -> Option<&'static str> {
+        match position {
+            Position::Sleeping => self.sleep,
+            Position::Resting => self.rest,
+            Position::Sitting => self.sit,
+            Position::Standing => None,
+        }
+    }
+}
+
+/// The lowest position a command can be used in; commands not listed here
+/// need their user to be awake.
+pub(crate) fn minimum_position(command: &str) -> Position {
+    match command {
+        "help" | "die" | "areas" | "who" | "whois" | "finger" | "save" | "quit" | "i" | "inv"
+        | "inventory" | "quest" | "quests" | "sleep" | "rest" | "sit" | "stand" | "wake"
+        | "social" | "socials" | "emotes" | "pronouns" | "title" | "short" | "description"
+        | "mob" => Position::Sleeping,
+        command if command.starts_with("mq") => Position::Sleeping,
+        "buy" | "sell" | "recall" | "sneak" | "follow" | "bash" | "enter" => Position::Standing,
+        _ => Position::Resting,
+    }
+}
+
+fn set_position(
+    components: &mut Components,
+    position: Position,
+    furniture: Option<PermanentEntityId>,
+) {
+    if let Some(mobile) = &mut components.mobile {
+        mobile.position = position;
+        mobile.furniture = furniture;
+    }
+    if let Some(player) = &mut components.player {
+        player.position = position;
+        player.furniture = furniture;
+    }
+}
+
+/// How the character is on the furniture, e.g. "sitting on a bench".
+fn describe_on_furniture(furniture: &EntityInfo, position: Position) -> Option<String> {
+    let preposition = furniture
+        .components()
+        .object
+        .as_ref()?
+        .furniture
+        .as_ref()?
+        .preposition(position)?;
+
+    Some(format!(
+        "{} {} {}",
+        position.describe(),
+        preposition,
+        furniture.component_info().short_description()
+    ))
+}
+
+impl<'e> EntityInfo<'e> {
+    /// Anything that isn't a player or a mobile is always standing.
+    pub fn position(&self) -> Position {
+        let components = self.components();
+
+        if let Some(mobile) = &components.mobile {
+            mobile.position
+        } else if let Some(player) = &components.player {
+            player.position
+        } else {
+            Position::Standing
+        }
+    }
+
+    /// The furniture this character is on, as long as it's still in the
+    /// same room.
+    pub fn furniture(&self) -> Option<EntityInfo<'e>> {
+        let components = self.components();
+        let furniture = match (&components.mobile, &components.player) {
+            (Some(mobile), _) => mobile.furniture?,
+            (None, Some(player)) => player.furniture?,
+            (None, None) => return None,
+        };
+
+        self.room()
+            .objects()
+            .find(|object| object.permanent_entity_id() == furniture)
+    }
+
+    pub fn is_awake(&self) -> bool {
+        self.position() > Position::Sleeping
+    }
+
+    /// Check if the player with this name is awake, so that they can be told
+    /// about what happens around them.
+    pub fn player_is_awake(&self, player_name: &str) -> bool {
+        match self.player_entity(player_name) {
+            Some(player) => player.is_awake(),
+            None => true,
+        }
+    }
+
+    /// What to show instead of the lateral description when this character
+    /// isn't in its usual position, e.g. "A guard is sleeping here."
+    pub fn position_description(&self) -> Option<String> {
+        let usual_position = match &self.components().mobile {
+            Some(mobile) => mobile.default_position,
+            None => Position::Standing,
+        };
+
+        let position = self.position();
+        let on_furniture = self
+            .furniture()
+            .and_then(|furniture| describe_on_furniture(&furniture, position));
+        if (position == usual_position && on_furniture.is_none())
+            || !(self.is_mobile() || self.is_player())
+        {
+            return None;
+        }
+
+        let short_description = self.component_info().short_description();
+        let mut chars = short_description.chars();
+        let first = chars.next()?;
+
+        Some(format!(
+            "{}{} is {} here.",
+            first.to_uppercase(),
+            chars.as_str(),
+            on_furniture.unwrap_or_else(|| position.describe().to_string())
+        ))
+    }
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    /// Check if the agent is in a position to use a command, telling it why
+    /// not otherwise.
+    pub fn check_position(&mut self, command: &str) -> bool {
+        let position = self.entity_world.entity_info(self.entity_id).position();
+        if position >= minimum_position(command) {
+            return true;
+        }
+
+        match position {
+            Position::Sleeping => echo!(self.info(), "In your dreams, or what?\r\n"),
+            _ => echo!(self.info(), "You need to stand up first.\r\n"),
+        }
+
+        false
+    }
+
+    fn change_position(&mut self, position: Position, to_myself: &str, to_others: &str) {
+        // Stay on the same furniture if it can be used in the new position
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let furniture = myself
+            .furniture()
+            .filter(|furniture| describe_on_furniture(furniture, position).is_some())
+            .map(|furniture| furniture.permanent_entity_id());
+
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        set_position(myself.components(), position, furniture);
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let mut act = self.players.act_alone(&myself);
+        echo!(act.myself(), "{}\r\n", to_myself);
+        echo!(act.others(), "{}\r\n", to_others);
+    }
+
+    pub fn do_sleep(&mut self) {
+        match self.entity_world.entity_info(self.entity_id).position() {
+            Position::Sleeping => echo!(self.info(), "You are already asleep.\r\n"),
+            _ => self.change_position(
+                Position::Sleeping,
+                "You go to sleep.",
+                "$^$n goes to sleep.",
+            ),
+        }
+    }
+
+    pub fn do_rest(&mut self) {
+        match self.entity_world.entity_info(self.entity_id).position() {
+            Position::Sleeping => self.change_position(
+                Position::Resting,
+                "You wake up and start resting.",
+                "$^$n wakes up and starts resting.",
+            ),
+            Position::Resting => echo!(self.info(), "You are already resting.\r\n"),
+            Position::Sitting => {
+                self.change_position(Position::Resting, "You rest.", "$^$n rests.")
+            }
+            Position::Standing => self.change_position(
+                Position::Resting,
+                "You sit down and rest.",
+                "$^$n sits down and rests.",
+            ),
+        }
+    }
+
+    pub fn do_sit(&mut self) {
+        match self.entity_world.entity_info(self.entity_id).position() {
+            Position::Sleeping => self.change_position(
+                Position::Sitting,
+                "You wake up and sit up.",
+                "$^$n wakes up and sits up.",
+            ),
+            Position::Resting => self.change_position(
+                Position::Sitting,
+                "You stop resting.",
+                "$^$n stops resting.",
+            ),
+            Position::Sitting => echo!(self.info(), "You are already sitting down.\r\n"),
+            Position::Standing => self.change_position(
+                Position::Sitting,
+                "You sit down.",
+                "$^$n sits down on the ground.",
+            ),
+        }
+    }
+
+    pub fn do_stand(&mut self) {
+        match self.entity_world.entity_info(self.entity_id).position() {
+            Position::Sleeping => self.change_position(
+                Position::Standing,
+                "You wake and stand up.",
+                "$^$n wakes and stands up.",
+            ),
+            Position::Resting | Position::Sitting => {
+                self.change_position(Position::Standing, "You stand up.", "$^$n stands up.")
+            }
+            Position::Standing => echo!(self.info(), "You are already standing.\r\n"),
+        }
+    }
+
+    pub fn do_wake(&mut self, target: Option<&str>) {
+        let target = match target {
+            Some(target) => target,
+            None => return self.do_stand(),
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        if !myself.is_awake() {
+            echo!(self.info(), "You are asleep yourself!\r\n");
+            return;
+        }
+
+        let target = myself.find_entity(target, |entity| entity.is_mobile() || entity.is_player());
+
+        let target = match target {
+            Found::Myself | Found::WrongSelf => {
+                echo!(self.info(), "You are already awake.\r\n");
+                return;
+            }
+            Found::Nothing | Found::WrongOther(_) => {
+                echo!(self.info(), "You don't see anyone like that here.\r\n");
+                return;
+            }
+            Found::Other(other) => other,
+        };
+
+        if target.is_awake() {
+            let mut act = self.players.act_with(&myself, &target);
+            echo!(act.myself(), "$^$N is already awake.\r\n");
+            return;
+        }
+
+        let target_id = target.entity_id();
+        let mut target = self.entity_world.entity_info_mut(target_id);
+        set_position(target.components(), Position::Standing, None);
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = self.entity_world.entity_info(target_id);
+        let mut act = self.players.act_with(&myself, &target).store_acts();
+        echo!(act.myself(), "You wake $N up.\r\n");
+        echo!(act.target(), "$^$n wakes you up.\r\n");
+        echo!(act.others(), "$^$n wakes $N up.\r\n");
+        let acts = act.into_acts();
+
+        self.check_act_triggers(acts);
+    }
+
+    /// Sit, rest or sleep on a piece of furniture in the room.
+    pub fn do_use_furniture(&mut self, position: Position, target: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        if !myself.is_awake() {
+            echo!(self.info(), "You need to wake up first.\r\n");
+            return;
+        }
+
+        let (verb, to_myself, to_others, not_found) = match position {
+            Position::Sleeping => (
+                "sleep",
+                "go to sleep",
+                "goes to sleep",
+                "You don't see anything like that to sleep on.",
+            ),
+            Position::Resting => (
+                "rest",
+                "rest",
+                "rests",
+                "You don't see anything like that to rest on.",
+            ),
+            Position::Sitting | Position::Standing => (
+                "sit",
+                "sit down",
+                "sits down",
+                "You don't see anything like that to sit on.",
+            ),
+        };
+
+        let found = myself
+            .room()
+            .objects()
+            .filter_by_keyword(&myself, target)
+            .with_component::<Furniture>()
+            .find_one_with_component_or(not_found);
+
+        let (furniture, furniture_component) = match found {
+            Ok(found) => found,
+            Err(error) => return self.echo_error(error),
+        };
+
+        let mut act = self.players.act_with(&myself, &furniture);
+        let preposition = match furniture_component.preposition(position) {
+            Some(preposition) => preposition,
+            None => {
+                echo!(act.myself(), "You can't {} on $N.\r\n", verb);
+                return;
+            }
+        };
+
+        if myself.position() == position && myself.furniture().as_ref() == Some(&furniture) {
+            echo!(
+                act.myself(),
+                "You are already {} {} $N.\r\n",
+                position.describe(),
+                preposition
+            );
+            return;
+        }
+
+        let occupants = myself
+            .room()
+            .contained_entities()
+            .filter(|other| *other != myself && other.furniture().as_ref() == Some(&furniture))
+            .count();
+        if occupants >= furniture_component.capacity {
+            echo!(
+                act.myself(),
+                "There's no room for you {} $N.\r\n",
+                preposition
+            );
+            return;
+        }
+
+        echo!(act.myself(), "You {} {} $N.\r\n", to_myself, preposition);
+        echo!(act.others(), "$^$n {} {} $N.\r\n", to_others, preposition);
+
+        let furniture_id = furniture.permanent_entity_id();
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        set_position(myself.components(), position, Some(furniture_id));
+    }
+}
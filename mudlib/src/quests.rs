@@ -0,0 +1,633 @@
+//! Quests read from `data/quests.toml`, and the players' progress in them.
+//!
+//! A quest is a list of steps, and each step has objectives like reaching a
+//! room or killing a few mobiles. Objectives are checked against the same
+//! actions that trigger mobprogs; once all of a step's objectives are done the
+//! player moves on to the next step, and finishing the last step completes
+//! the quest and hands out its rewards.
+//!
+//! Quests are started by mobprogs with `mob quest start`. A step without
+//! objectives waits for a mobprog to use `mob quest advance` or `mob quest
+//! complete`, for example once the player returns to whoever gave the quest.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{
+    agent::EntityAgent,
+    components::{Components, EntityComponentInfo, QuestLog, QuestProgress},
+    echo,
+    entity::{EntityId, Found},
+    file_parser::Diagnostic,
+    mobprogs::Action,
+    world::Vnum,
+    Files,
+};
+
+#[derive(Deserialize, Default)]
+pub(crate) struct Quests {
+    #[serde(default, rename = "quest")]
+    quests: Vec<Quest>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Quest {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, rename = "step")]
+    pub steps: Vec<Step>,
+    #[serde(default)]
+    pub silver: usize,
+    #[serde(default)]
+    pub objects: Vec<Vnum>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Step {
+    pub description: String,
+    #[serde(default)]
+    pub objectives: Vec<Objective>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Objective {
+    /// Reach the room with this vnum
+    Room(Vnum),
+    /// Say something containing this phrase
+    Say(String),
+    /// Give an object to a mobile
+    Give { object: Vnum, to: Vnum },
+    /// Kill a number of mobiles
+    Kill {
+        mobile: Vnum,
+        #[serde(default = "one")]
+        count: u32,
+    },
+}
+
+fn one() -> u32 {
+    1
+}
+
+impl Objective {
+    fn required(&self) -> u32 {
+        match self {
+            Objective::Kill { count, .. } => *count,
+            Objective::Room(_) | Objective::Say(_) | Objective::Give { .. } => 1,
+        }
+    }
+}
+
+impl Quests {
+    pub fn get(&self, id: &str) -> Option<&Quest> {
+        self.quests.iter().find(|quest| quest.id == id)
+    }
+}
+
+/// Read all quests; a missing file simply means there are no quests, and one
+/// that can't be read is reported and skipped.
+pub(crate) fn load_quests(
+    files: &dyn Files,
+    path: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Quests {
+    let contents = match files.read_file(path) {
+        Ok(contents) => contents,
+        Err(_) => return Quests::default(),
+    };
+
+    match toml::from_str(&contents) {
+        Ok(quests) => quests,
+        Err(error) => {
+            diagnostics.push(Diagnostic::from_toml(error, &contents, path));
+            Quests::default()
+        }
+    }
+}
+
+// Quest progress
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    fn quest_log(&self) -> Option<&QuestLog> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        myself
+            .components()
+            .player
+            .as_ref()
+            .map(|player| &player.quests)
+    }
+
+    fn with_quest_log<T>(&mut self, f: impl FnOnce(&mut QuestLog) -> T) -> Option<T> {
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        myself
+            .components()
+            .player
+            .as_mut()
+            .map(|player| f(&mut player.quests))
+    }
+
+    /// Check if an action done by the current player completes any of the
+    /// objectives in their quests. The target is whoever the action was done
+    /// to, if anyone.
+    pub fn update_quests(&mut self, action: &Action<'_>, target_id: Option<EntityId>) {
+        let quest_log = match self.quest_log() {
+            Some(quest_log) if !quest_log.active.is_empty() => quest_log,
+            _ => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let room_vnum = myself.room().components().general.vnum;
+        let vnum_of = |entity_id: EntityId| {
+            let entity = self.entity_world.entity_info(entity_id);
+            (entity.is_mobile(), entity.components().general.vnum)
+        };
+
+        let mut completed = Vec::new();
+
+        for (quest_id, progress) in &quest_log.active {
+            let step = self
+                .quests
+                .get(quest_id)
+                .and_then(|quest| quest.steps.get(progress.step));
+            let step = match step {
+                Some(step) => step,
+                None => continue,
+            };
+
+            for (index, objective) in step.objectives.iter().enumerate() {
+                let done = progress.counts.get(index).copied().unwrap_or(0);
+                if done >= objective.required() {
+                    continue;
+                }
+
+                let matches = match (objective, action) {
+                    (Objective::Room(vnum), Action::Greet | Action::Entry | Action::Login) => {
+                        *vnum == room_vnum
+                    }
+                    (Objective::Say(phrase), Action::Speech { message }) => {
+                        message.to_lowercase().contains(&phrase.to_lowercase())
+                    }
+                    (Objective::Give { object, to }, Action::Give { object_id }) => {
+                        let recipient = target_id.map(vnum_of);
+                        let (_, object_vnum) = vnum_of(*object_id);
+                        recipient == Some((true, *to)) && object_vnum == *object
+                    }
+                    (Objective::Kill { mobile, .. }, Action::Kill { victim_id }) => {
+                        vnum_of(*victim_id) == (true, *mobile)
+                    }
+                    _ => false,
+                };
+
+                if matches {
+                    completed.push((quest_id.clone(), index));
+                }
+            }
+        }
+
+        for (quest_id, index) in completed {
+            self.advance_objective(&quest_id, index);
+        }
+    }
+
+    fn advance_objective(&mut self, quest_id: &str, index: usize) {
+        let counts = self.with_quest_log(|quest_log| {
+            let progress = quest_log.active.get_mut(quest_id)?;
+            if progress.counts.len() <= index {
+                progress.counts.resize(index + 1, 0);
+            }
+            progress.counts[index] += 1;
+            Some((progress.step, progress.counts.clone()))
+        });
+
+        let (step, counts) = match counts.flatten() {
+            Some(progress) => progress,
+            None => return,
+        };
+
+        let step = match self
+            .quests
+            .get(quest_id)
+            .and_then(|quest| quest.steps.get(step))
+        {
+            Some(step) => step,
+            None => return,
+        };
+
+        let objective = &step.objectives[index];
+        let description = self.describe_objective(objective);
+        if objective.required() > 1 {
+            echo!(
+                self.info(),
+                "`YQuest progress:`^ {} ({}/{}).\r\n",
+                description,
+                counts[index],
+                objective.required()
+            );
+        }
+
+        let step_done = step
+            .objectives
+            .iter()
+            .enumerate()
+            .all(|(index, objective)| {
+                counts.get(index).copied().unwrap_or(0) >= objective.required()
+            });
+
+        if step_done {
+            self.advance_quest(quest_id);
+        }
+    }
+
+    /// Move the current player to the next step of a quest, completing it
+    /// after the last one.
+    pub fn advance_quest(&mut self, quest_id: &str) {
+        let quest = match self.quests.get(quest_id) {
+            Some(quest) => quest,
+            None => return,
+        };
+
+        let step = self.with_quest_log(|quest_log| {
+            let progress = quest_log.active.get_mut(quest_id)?;
+            progress.step += 1;
+            progress.counts.clear();
+            Some(progress.step)
+        });
+
+        let step = match step.flatten() {
+            Some(step) => step,
+            None => return,
+        };
+
+        match quest.steps.get(step) {
+            Some(step) => {
+                echo!(
+                    self.info(),
+                    "`YQuest updated:`^ {}\r\n  {}\r\n",
+                    quest.name,
+                    step.description
+                );
+            }
+            None => self.complete_quest(quest_id),
+        }
+    }
+
+    pub fn start_quest(&mut self, quest_id: &str) -> Result<(), &'static str> {
+        let quest = match self.quests.get(quest_id) {
+            Some(quest) => quest,
+            None => return Err("There is no quest with that id."),
+        };
+
+        let quest_log = match self.quest_log() {
+            Some(quest_log) => quest_log,
+            None => return Err("Only players can go on quests."),
+        };
+
+        if quest_log.active.contains_key(quest_id) {
+            return Err("That quest was already started.");
+        } else if quest_log.completed.iter().any(|id| id == quest_id) {
+            return Err("That quest was already completed.");
+        }
+
+        self.with_quest_log(|quest_log| {
+            quest_log
+                .active
+                .insert(quest_id.to_string(), QuestProgress::default())
+        });
+
+        echo!(
+            self.info(),
+            "`YYou have started the quest:`^ {}\r\n",
+            quest.name
+        );
+
+        match quest.steps.first() {
+            Some(step) => echo!(self.info(), "  {}\r\n", step.description),
+            None => self.complete_quest(quest_id),
+        }
+
+        Ok(())
+    }
+
+    /// Complete a quest the current player is on, and give out its rewards.
+    pub fn complete_quest(&mut self, quest_id: &str) {
+        let quest = match self.quests.get(quest_id) {
+            Some(quest) => quest,
+            None => return,
+        };
+
+        let was_active = self.with_quest_log(|quest_log| {
+            let was_active = quest_log.active.remove(quest_id).is_some();
+            if was_active {
+                quest_log.completed.push(quest_id.to_string());
+            }
+            was_active
+        });
+
+        if was_active != Some(true) {
+            return;
+        }
+
+        echo!(
+            self.info(),
+            "`YYou have completed the quest:`^ {}\r\n",
+            quest.name
+        );
+
+        if quest.silver > 0 {
+            self.add_silver(quest.silver, self.entity_id);
+            echo!(
+                self.info(),
+                "You receive {} silver coins.\r\n",
+                quest.silver
+            );
+        }
+
+        for vnum in &quest.objects {
+            if let Some(object_id) = self.create_object(*vnum, self.entity_id) {
+                let myself = self.entity_world.entity_info(self.entity_id);
+                let object = self.entity_world.entity_info(object_id);
+                let mut act = self.players.act_with(&myself, &object);
+                echo!(act.myself(), "You receive $N.\r\n");
+            }
+        }
+    }
+
+    fn template_name(
+        &self,
+        templates: &HashMap<Vnum, (Components, Vec<Components>)>,
+        vnum: Vnum,
+    ) -> Option<String> {
+        let (components, _) = templates.get(&vnum)?;
+        let component_info = EntityComponentInfo::new(components, &self.entity_world.interner);
+        Some(component_info.short_description().to_string())
+    }
+
+    fn object_name(&self, vnum: Vnum) -> String {
+        self.template_name(&self.vnum_templates.object_components, vnum)
+            .unwrap_or_else(|| format!("object #{}", vnum.0))
+    }
+
+    fn mobile_name(&self, vnum: Vnum) -> String {
+        self.template_name(&self.vnum_templates.mobile_components, vnum)
+            .unwrap_or_else(|| format!("mobile #{}", vnum.0))
+    }
+
+    fn describe_objective(&self, objective: &Objective) -> String {
+        match objective {
+            Objective::Room(vnum) => {
+                let room = self
+                    .vnum_templates
+                    .vnum_to_room_entity
+                    .get(vnum)
+                    .and_then(|permanent_id| self.entity_world.old_entity(permanent_id));
+                let title = match room {
+                    Some(room) => room
+                        .component_info()
+                        .internal_title()
+                        .trim_end_matches('.')
+                        .to_string(),
+                    None => format!("room #{}", vnum.0),
+                };
+                format!("Reach {}", title)
+            }
+            Objective::Say(phrase) => format!("Say '{}'", phrase),
+            Objective::Give { object, to } => format!(
+                "Give {} to {}",
+                self.object_name(*object),
+                self.mobile_name(*to)
+            ),
+            Objective::Kill { mobile, .. } => format!("Kill {}", self.mobile_name(*mobile)),
+        }
+    }
+}
+
+// Quest commands
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    pub fn do_quest_list(&mut self) {
+        let quest_log = match self.quest_log() {
+            Some(quest_log) => quest_log.clone(),
+            None => {
+                echo!(self.info(), "Only players can go on quests.\r\n");
+                return;
+            }
+        };
+
+        if quest_log.active.is_empty() && quest_log.completed.is_empty() {
+            echo!(self.info(), "You have not started any quests yet.\r\n");
+            return;
+        }
+
+        if !quest_log.active.is_empty() {
+            echo!(self.info(), "Your quests:\r\n");
+        }
+        for (quest_id, progress) in &quest_log.active {
+            if let Some(quest) = self.quests.get(quest_id) {
+                let step = quest
+                    .steps
+                    .get(progress.step)
+                    .map(|step| step.description.as_str())
+                    .unwrap_or("");
+                echo!(
+                    self.info(),
+                    "  `W{}`^ `s({})`^: {}\r\n",
+                    quest.name,
+                    quest.id,
+                    step
+                );
+            }
+        }
+
+        if !quest_log.completed.is_empty() {
+            echo!(self.info(), "Completed quests:\r\n");
+        }
+        for quest_id in &quest_log.completed {
+            if let Some(quest) = self.quests.get(quest_id) {
+                echo!(self.info(), "  `W{}`^ `s({})`^\r\n", quest.name, quest.id);
+            }
+        }
+    }
+
+    pub fn do_quest_info(&mut self, name: &str) {
+        let quest_log = match self.quest_log() {
+            Some(quest_log) => quest_log.clone(),
+            None => {
+                echo!(self.info(), "Only players can go on quests.\r\n");
+                return;
+            }
+        };
+
+        // Look for the quest by id, or by a word in its name.
+        let matches = |quest_id: &String| {
+            self.quests.get(quest_id).is_some_and(|quest| {
+                quest.id.eq_ignore_ascii_case(name)
+                    || quest
+                        .name
+                        .split_whitespace()
+                        .any(|word| word.eq_ignore_ascii_case(name))
+            })
+        };
+
+        let progress = quest_log
+            .active
+            .iter()
+            .find(|(quest_id, _)| matches(quest_id));
+        let (quest, progress) = match progress {
+            Some((quest_id, progress)) => (self.quests.get(quest_id), Some(progress)),
+            None => match quest_log
+                .completed
+                .iter()
+                .find(|quest_id| matches(quest_id))
+            {
+                Some(quest_id) => (self.quests.get(quest_id), None),
+                None => (None, None),
+            },
+        };
+
+        let quest = match quest {
+            Some(quest) => quest,
+            None => {
+                echo!(self.info(), "You don't know of any quest like that.\r\n");
+                return;
+            }
+        };
+
+        let status = if progress.is_some() {
+            "in progress"
+        } else {
+            "completed"
+        };
+        echo!(self.info(), "`W{}`^ `s({})`^\r\n", quest.name, status);
+        if !quest.description.is_empty() {
+            echo!(self.info(), "{}\r\n", quest.description.trim_end());
+        }
+
+        for (index, step) in quest.steps.iter().enumerate() {
+            let current = progress.map(|progress| progress.step);
+
+            match current {
+                Some(current) if index > current => break,
+                Some(current) if index == current => {
+                    echo!(self.info(), "  `Y*`^ {}\r\n", step.description);
+
+                    let progress = progress.expect("Current step exists");
+                    for (index, objective) in step.objectives.iter().enumerate() {
+                        let done = progress.counts.get(index).copied().unwrap_or(0);
+                        let description = self.describe_objective(objective);
+                        echo!(
+                            self.info(),
+                            "      {} ({}/{})\r\n",
+                            description,
+                            done.min(objective.required()),
+                            objective.required()
+                        );
+                    }
+                }
+                _ => echo!(self.info(), "  `G+`^ {}\r\n", step.description),
+            }
+        }
+
+        let mut rewards = Vec::new();
+        if quest.silver > 0 {
+            rewards.push(format!("{} silver coins", quest.silver));
+        }
+        for vnum in &quest.objects {
+            rewards.push(self.object_name(*vnum));
+        }
+        if !rewards.is_empty() {
+            echo!(self.info(), "Reward: {}.\r\n", rewards.join(", "));
+        }
+    }
+}
+
+// Mobprog commands and conditions for quests
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    fn find_quest_player(&mut self, target: &str) -> Option<EntityId> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        match myself.find_entity(target, |entity| entity.is_player()) {
+            Found::Myself => Some(myself.entity_id()),
+            Found::Other(other) => Some(other.entity_id()),
+            Found::WrongSelf | Found::WrongOther(_) | Found::Nothing => {
+                echo!(
+                    self.info(),
+                    "There is no player named '{}' here.\r\n",
+                    target
+                );
+                None
+            }
+        }
+    }
+
+    pub fn do_mob_quest(&mut self, command: &str, target: &str, quest_id: &str) {
+        if !self.check_trusted() {
+            return;
+        }
+
+        let player_id = match self.find_quest_player(target) {
+            Some(player_id) => player_id,
+            None => return,
+        };
+
+        if self.quests.get(quest_id).is_none() {
+            echo!(self.info(), "There is no quest with id '{}'.\r\n", quest_id);
+            return;
+        }
+
+        let mut player = self.switch_agent(player_id);
+        let active = player
+            .quest_log()
+            .is_some_and(|quest_log| quest_log.active.contains_key(quest_id));
+
+        let result = match command {
+            "start" => player.start_quest(quest_id),
+            "advance" if active => {
+                player.advance_quest(quest_id);
+                Ok(())
+            }
+            "complete" if active => {
+                player.complete_quest(quest_id);
+                Ok(())
+            }
+            "advance" | "complete" => Err("The player is not on that quest."),
+            _ => Err("Syntax: mob quest <start|advance|complete> <player> <quest id>"),
+        };
+
+        if let Err(err) = result {
+            echo!(self.info(), "{}\r\n", err);
+        }
+    }
+
+    /// Check a quest condition, as in "if quest $n academy completed".
+    pub fn check_quest_condition(
+        &self,
+        target: &str,
+        quest_id: &str,
+        check: &[&str],
+    ) -> Option<bool> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let player = match myself.find_entity(target, |entity| entity.is_player()) {
+            Found::Myself => myself,
+            Found::Other(other) => other,
+            Found::WrongSelf | Found::WrongOther(_) | Found::Nothing => return Some(false),
+        };
+
+        let quest_log = &player.components().player.as_ref()?.quests;
+        let progress = quest_log.active.get(quest_id);
+
+        let result = match check {
+            [] | ["active"] => progress.is_some(),
+            ["completed"] => quest_log.completed.iter().any(|id| id == quest_id),
+            ["step", step] => {
+                let step: usize = step.parse().ok()?;
+                progress.map(|progress| progress.step + 1) == Some(step)
+            }
+            _ => return None,
+        };
+
+        Some(result)
+    }
+}
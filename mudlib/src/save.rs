@@ -0,0 +1,721 @@
+//! Dawn of Time area writer.
+//!
+//! Writes a `crate::world::Area` back as a `#DAWNAREADATA` file, with the
+//! keys that `crate::load` reads. The keys and flags the loader keeps without
+//! using them, like a mobile's level, the values of a weapon or the
+//! `#SPECIALS` section, are written back as they were.
+//!
+//! A stock area that is loaded and written again has everything it had and
+//! loads into the same `Area`, but it isn't the same file: keys are written
+//! in their own order, without comments, and unrecognized keys and sections,
+//! which the loader warns about, are gone.
+
+use std::fmt::Write;
+
+use crate::world::{
+    Area, AreaData, Exit, ExtraDescription, Gender, MobProg, MobProgTrigger, Mobile, Object,
+    ObjectFlags, ResetCommand, Room, Shop, Vnum,
+};
+
+pub(crate) fn save_area(area: &Area) -> String {
+    let mut file = String::new();
+
+    write_area_data(&mut file, &area.area_data);
+
+    file.push_str("#MOBILES\n");
+    for mobile in &area.mobiles {
+        write_mobile(&mut file, mobile);
+    }
+    file.push_str("#0\n\n\n");
+
+    file.push_str("#OBJECTS\n");
+    for object in &area.objects {
+        write_object(&mut file, object);
+    }
+    file.push_str("#0\n\n\n");
+
+    file.push_str("#ROOMS\n");
+    for room in &area.rooms {
+        write_room(&mut file, room);
+    }
+    file.push_str("#0\n\n\n");
+
+    if !area.area_data.specials.is_empty() {
+        file.push_str("#SPECIALS\n");
+        for special in &area.area_data.specials {
+            writeln!(file, "{}", special).unwrap();
+        }
+        file.push_str("S\n\n\n");
+    }
+
+    file.push_str("#RESETS2\n");
+    for reset in &area.resets {
+        write_reset(&mut file, reset);
+    }
+    file.push_str("S\n\n\n");
+
+    file.push_str("#SHOPS\n");
+    for shop in &area.shops {
+        write_shop(&mut file, shop);
+    }
+    file.push_str("#0\n\n\n");
+
+    file.push_str("#MOBPROGS\n");
+    for mobprog in &area.mobprogs {
+        write_mobprog(&mut file, mobprog);
+    }
+    file.push_str("#0\n\n\n");
+
+    file.push_str("#$\n");
+    file
+}
+
+fn write_area_data(file: &mut String, area_data: &AreaData) {
+    file.push_str("#DAWNAREADATA\n");
+    writeln!(file, "Name        {}~", area_data.name).unwrap();
+    writeln!(file, "ShortName   {}~", area_data.short_name).unwrap();
+
+    let (Vnum(low), Vnum(high)) = area_data.vnums;
+    if (low, high) != (0, 0) {
+        writeln!(file, "VNUMs       {} {}", low, high).unwrap();
+    }
+    if !area_data.credits.is_empty() {
+        writeln!(file, "Credits     {}~", area_data.credits).unwrap();
+    }
+    if !area_data.continent.is_empty() {
+        writeln!(file, "Continent   {}~", area_data.continent).unwrap();
+    }
+    write_unused_keys(file, &area_data.unused_keys);
+    file.push_str("End\n\n\n");
+}
+
+/// Writes back the keys the loader kept without using them.
+fn write_unused_keys(file: &mut String, unused_keys: &[String]) {
+    for key in unused_keys {
+        writeln!(file, "{}", key).unwrap();
+    }
+}
+
+/// Writes `Key value~` for text values, unless there's nothing to write.
+fn write_text(file: &mut String, key: &str, value: &str) {
+    if !value.is_empty() {
+        writeln!(file, "{} {}~", key, value).unwrap();
+    }
+}
+
+fn write_flags(file: &mut String, key: &str, flags: &[impl AsRef<str>]) {
+    let flags: Vec<&str> = flags.iter().map(AsRef::as_ref).collect();
+    write_text(file, key, &flags.join(" "));
+}
+
+fn write_mobprog_triggers(file: &mut String, triggers: &[(MobProgTrigger, Vnum)]) {
+    for (trigger, vnum) in triggers {
+        // Area files put the mobprog's vnum between the trigger and its value
+        let trigger = trigger.to_string();
+        match trigger.split_once(' ') {
+            Some((name, value)) => writeln!(file, "MProg {} {} {}~", name, vnum.0, value),
+            None => writeln!(file, "MProg {} {}~", trigger, vnum.0),
+        }
+        .unwrap();
+    }
+}
+
+fn write_extra_descriptions(file: &mut String, extra_descriptions: &[ExtraDescription]) {
+    for extra_description in extra_descriptions {
+        writeln!(
+            file,
+            "ExtraDesc {}~\n{}~",
+            extra_description.keyword, extra_description.description
+        )
+        .unwrap();
+    }
+}
+
+fn write_mobile(file: &mut String, mobile: &Mobile) {
+    writeln!(file, "#{}", mobile.vnum.0).unwrap();
+    writeln!(file, "Name {}~", mobile.name).unwrap();
+    writeln!(file, "ShortD {}~", mobile.short_description).unwrap();
+    writeln!(file, "LongD {}~", mobile.long_description).unwrap();
+    writeln!(file, "Desc {}~", mobile.description).unwrap();
+
+    let mut act = Vec::new();
+    if mobile.sentinel {
+        act.push("dont_wander");
+    }
+    if mobile.unseen {
+        act.push("unseen");
+    }
+    act.extend(mobile.unused_act_flags.iter().map(String::as_str));
+    write_flags(file, "Act", &act);
+    write_flags(file, "AffBy", &mobile.affected_by);
+
+    write_text(file, "StartP", &mobile.start_position);
+    write_text(file, "DefPos", &mobile.default_position);
+    let sex = match mobile.gender {
+        Gender::Male => "male",
+        Gender::Female => "female",
+        Gender::Neutral => "neutral",
+        // Only players choose these, and the format has nothing like them
+        Gender::Plural | Gender::Custom(_) => "neutral",
+    };
+    writeln!(file, "Sex {}~", sex).unwrap();
+    if mobile.wealth != 0 {
+        writeln!(file, "Wealth {}", mobile.wealth).unwrap();
+    }
+    write_unused_keys(file, &mobile.unused_keys);
+
+    write_mobprog_triggers(file, &mobile.mobprog_triggers);
+    write_text(file, "Script", mobile.script.as_deref().unwrap_or_default());
+    file.push_str("END\n\n");
+}
+
+fn write_object(file: &mut String, object: &Object) {
+    writeln!(file, "#{}", object.vnum.0).unwrap();
+    writeln!(file, "Name {}~", object.name).unwrap();
+    writeln!(file, "Short {}~", object.short_description).unwrap();
+    writeln!(file, "Desc {}~", object.description).unwrap();
+    writeln!(file, "ItemType {}~", object.item_type).unwrap();
+    writeln!(file, "Cost {}", object.cost).unwrap();
+    writeln!(file, "Condition {}", object.condition).unwrap();
+    write_flags(file, "Wear", &object.wear_flags);
+    write_flags(file, "Extra", &object.extra_flags);
+
+    // The values of item types without behavior are kept as they were,
+    // unless the object has become something else since
+    let kept_values = matches!(object.flags, ObjectFlags::Unknown)
+        && object
+            .unused_keys
+            .iter()
+            .any(|key| key.starts_with("Values "));
+    if !kept_values {
+        writeln!(file, "Values {}", item_values(&object.flags)).unwrap();
+    }
+    for key in &object.unused_keys {
+        if kept_values || !key.starts_with("Values ") {
+            writeln!(file, "{}", key).unwrap();
+        }
+    }
+
+    write_extra_descriptions(file, &object.extra_descriptions);
+    write_mobprog_triggers(file, &object.mobprog_triggers);
+    write_text(file, "Script", object.script.as_deref().unwrap_or_default());
+    file.push_str("End\n\n");
+}
+
+/// The opposite of `load::parse_item_values`; values it doesn't decode are 0.
+fn item_values(flags: &ObjectFlags) -> String {
+    let quote = |value: &str| format!("'{}'", value);
+
+    let values = match flags {
+        ObjectFlags::Unknown => vec![],
+        ObjectFlags::Container {
+            closable,
+            closed,
+            locked,
+            pickproof,
+            key,
+        } => {
+            let flags = [
+                (*closable, 'A'),
+                (*pickproof, 'B'),
+                (*closed, 'C'),
+                (*locked, 'D'),
+            ];
+            let letters: String = flags
+                .iter()
+                .filter(|(set, _)| *set)
+                .map(|(_, letter)| letter)
+                .collect();
+            let key = key.map_or("-1".to_string(), |key| key.0.to_string());
+
+            vec!["0".to_string(), letters, key]
+        }
+        ObjectFlags::DrinkContainer {
+            capacity,
+            amount,
+            liquid,
+            fountain: _,
+        } => vec![capacity.to_string(), amount.to_string(), quote(liquid)],
+        ObjectFlags::Light { hours } => {
+            let hours = hours.map_or("-1".to_string(), |hours| hours.to_string());
+            vec!["0".to_string(), "0".to_string(), hours]
+        }
+        ObjectFlags::Furniture { capacity, flags } => {
+            vec![capacity.to_string(), "0".to_string(), flags.join("|")]
+        }
+        ObjectFlags::Spells { level, spells } => std::iter::once(level.to_string())
+            .chain(spells.iter().map(|spell| quote(spell)))
+            .collect(),
+        ObjectFlags::Portal {
+            charges,
+            destination,
+        } => vec![
+            charges.unwrap_or(0).to_string(),
+            "0".to_string(),
+            "0".to_string(),
+            destination.0.to_string(),
+        ],
+    };
+
+    // Always at least five values, like in the stock areas
+    let mut values: Vec<String> = values
+        .into_iter()
+        .map(|value| if value.is_empty() { "0".into() } else { value })
+        .collect();
+    values.resize(values.len().max(5), "0".to_string());
+    values.join(" ")
+}
+
+fn write_room(file: &mut String, room: &Room) {
+    writeln!(file, "#{}", room.vnum.0).unwrap();
+    writeln!(file, "Name {}~", room.name).unwrap();
+    writeln!(file, "Desc {}~", room.description).unwrap();
+    write_text(file, "Sector", &room.sector);
+
+    let mut room_flags = Vec::new();
+    if room.dark {
+        room_flags.push("dark");
+    }
+    if room.bank {
+        room_flags.push("bank");
+    }
+    room_flags.extend(room.unused_room_flags.iter().map(String::as_str));
+    write_flags(file, "RoomFlags", &room_flags);
+    write_unused_keys(file, &room.unused_keys);
+
+    write_extra_descriptions(file, &room.extra_descriptions);
+    for exit in &room.exits {
+        write_exit(file, exit);
+    }
+    write_mobprog_triggers(file, &room.mobprog_triggers);
+    write_text(file, "Script", room.script.as_deref().unwrap_or_default());
+    file.push_str("End\n\n");
+}
+
+fn write_exit(file: &mut String, exit: &Exit) {
+    writeln!(file, "Exit {} {}", exit.name, exit.vnum.0).unwrap();
+    if let Some(description) = &exit.description {
+        writeln!(file, "EDesc {}~", description).unwrap();
+    }
+    if let Some(keywords) = &exit.extra_keywords {
+        writeln!(file, "EKeywords {}~", keywords).unwrap();
+    }
+
+    let flags = [
+        (exit.has_door, "door"),
+        (exit.is_closed, "closed"),
+        (exit.is_locked, "locked"),
+        (exit.is_pickproof, "pickproof"),
+        (exit.is_bashproof, "bashproof"),
+    ];
+    let flags: Vec<&str> = flags
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, flag)| *flag)
+        .collect();
+    write_flags(file, "EFlags", &flags);
+
+    if let Some(key) = exit.key {
+        writeln!(file, "EKeyvnum {}", key.0).unwrap();
+    }
+}
+
+fn write_reset(file: &mut String, reset: &ResetCommand) {
+    match reset {
+        ResetCommand::Mob {
+            m_num,
+            global_limit,
+            r_num,
+            room_limit,
+        } => writeln!(
+            file,
+            "M 0 {} {} {} {}",
+            m_num.0, global_limit, r_num.0, room_limit
+        ),
+        ResetCommand::Object {
+            o_num,
+            global_limit,
+            r_num,
+        } => writeln!(file, "O 0 {} {} {}", o_num.0, global_limit, r_num.0),
+        ResetCommand::Give {
+            o_num,
+            global_limit,
+        } => writeln!(file, "G 0 {} {}", o_num.0, global_limit),
+        ResetCommand::Equip {
+            o_num,
+            global_limit,
+            location,
+        } => writeln!(file, "E 0 {} {} {}~", o_num.0, global_limit, location),
+        ResetCommand::Put {
+            o_num,
+            global_limit,
+            c_num,
+            container_limit,
+        } => writeln!(
+            file,
+            "P 0 {} {} {} {}",
+            o_num.0, global_limit, c_num.0, container_limit
+        ),
+        // Dawn of Time keeps the state of doors on their exits instead, and
+        // the ROM loader already copied it there
+        ResetCommand::Door { .. } => Ok(()),
+    }
+    .unwrap();
+}
+
+fn write_shop(file: &mut String, shop: &Shop) {
+    writeln!(file, "#{}", shop.vnum.0).unwrap();
+    for buy_type in &shop.buy_types {
+        writeln!(file, "buy_type {}~", buy_type).unwrap();
+    }
+    for sell_type in &shop.sell_types {
+        writeln!(file, "sell_type {}~", sell_type).unwrap();
+    }
+    writeln!(file, "profit_buy {}", shop.profit_buy).unwrap();
+    writeln!(file, "profit_sell {}", shop.profit_sell).unwrap();
+    writeln!(file, "open_hour {}", shop.open_hour).unwrap();
+    writeln!(file, "close_hour {}", shop.close_hour).unwrap();
+    file.push_str("END\n\n");
+}
+
+fn write_mobprog(file: &mut String, mobprog: &MobProg) {
+    writeln!(file, "#{}", mobprog.vnum.0).unwrap();
+    writeln!(file, "title {}~", mobprog.title).unwrap();
+    writeln!(file, "code {}~", mobprog.code).unwrap();
+    writeln!(file, "disabled {}", mobprog.disabled).unwrap();
+    file.push_str("END\n\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{load::load_area, world::Area};
+
+    /// Every record in the sections that are written back, sorted: the area
+    /// data, `#SECTION #vnum` for the ones with a vnum, and resets and
+    /// specials as they're written, without comments.
+    fn records(contents: &str) -> Vec<String> {
+        let mut records = Vec::new();
+        let mut section = "";
+
+        for line in contents.lines() {
+            let line = line.trim_end();
+
+            if let Some(name) = line.strip_prefix('#') {
+                if name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '$') {
+                    section = name;
+                    if section == "DAWNAREADATA" {
+                        records.push(line.to_string());
+                    }
+                    continue;
+                }
+            }
+
+            match section {
+                "MOBILES" | "OBJECTS" | "ROOMS" | "SHOPS" | "MOBPROGS" => {
+                    let vnum = line.strip_prefix('#').and_then(|vnum| vnum.parse().ok());
+                    if let Some(vnum) = vnum.filter(|vnum: &usize| *vnum != 0) {
+                        records.push(format!("#{} #{}", section, vnum));
+                    }
+                }
+                "RESETS2" if line.len() > 1 && !line.starts_with(['S', '*']) => {
+                    let reset: Vec<&str> = line
+                        .split_whitespace()
+                        .take_while(|word| !word.starts_with('*'))
+                        .collect();
+                    records.push(format!("#{} {}", section, reset.join(" ")));
+                }
+                "SPECIALS" if !line.is_empty() && line != "S" => {
+                    records.push(format!("#{} {}", section, line))
+                }
+                _ => (),
+            }
+        }
+
+        records.sort();
+        records
+    }
+
+    /// Loads an area, saves it and loads it again, and checks that both
+    /// loads are the same, comparing them through their serde form, and that
+    /// the saved file has all of the records of the original one.
+    fn assert_round_trip(contents: &str) {
+        let mut diagnostics = Vec::new();
+        let area = load_area(contents, "area.are", &mut diagnostics)
+            .unwrap_or_else(|error| panic!("{}", error));
+        assert!(diagnostics.is_empty(), "{}", diagnostics[0]);
+
+        let saved = super::save_area(&area);
+        let reloaded = load_area(&saved, "area.are", &mut diagnostics)
+            .unwrap_or_else(|error| panic!("{}\n{}", error, saved));
+        assert!(diagnostics.is_empty(), "{}", diagnostics[0]);
+
+        let serialize = |area: &Area| toml::to_string(area).unwrap();
+        assert_eq!(serialize(&area), serialize(&reloaded));
+
+        assert!(!records(contents).is_empty());
+        assert_eq!(records(contents), records(&saved));
+
+        // And saving again doesn't change anything either
+        assert_eq!(saved, super::save_area(&reloaded));
+    }
+
+    #[test]
+    fn basic_area_round_trip() {
+        assert_round_trip(include_str!("../../data/basic_area.txt"));
+    }
+
+    #[test]
+    fn objects_exits_and_shops_round_trip() {
+        assert_round_trip(
+            "#DAWNAREADATA
+Name        Test~
+ShortName   Test~
+VNUMs       100 199
+Credits     Someone~
+End
+
+#MOBILES
+#100
+Name shopkeeper~
+ShortD the shopkeeper~
+LongD A shopkeeper waits here.~
+Desc Patient.
+~
+AffBy detect_invis sanctuary~
+StartP sit~
+DefPos sit~
+Sex female~
+Wealth 200
+MProg GIVE 100 101~
+MProg BRIBE 100 50~
+END
+
+#0
+
+#OBJECTS
+#100
+Name chest~
+Short a chest~
+Desc A chest is here.~
+ItemType container~
+Cost 120
+Condition 75
+Wear take~
+Extra magic glow~
+Values 50 ACD 101 0 0
+ExtraDesc lid~
+It's carved.
+~
+End
+
+#101
+Name key~
+Short a key~
+Desc A key is here.~
+ItemType key~
+Values 0 0 0 0 0
+End
+
+#102
+Name barrel~
+Short a barrel~
+Desc A barrel stands here.~
+ItemType fountain~
+Values 100 100 'red wine' 0 0
+End
+
+#103
+Name torch~
+Short a torch~
+Desc A torch.~
+ItemType light~
+Values 0 0 -1 0 0
+End
+
+#104
+Name bench~
+Short a bench~
+Desc A bench.~
+ItemType furniture~
+Values 3 0 sit_on|rest_on 0 0
+End
+
+#105
+Name scroll~
+Short a scroll~
+Desc A scroll.~
+ItemType scroll~
+Values 10 'cure light' 'armor' 0 0
+End
+
+#106
+Name portal~
+Short a portal~
+Desc A portal.~
+ItemType portal~
+Values 3 0 0 101 0
+End
+
+#0
+
+#ROOMS
+#100
+Name A shop~
+Desc A small shop.
+~
+Sector inside~
+RoomFlags dark bank~
+ExtraDesc counter~
+Wooden.
+~
+Exit north 101
+EDesc A door.
+~
+EKeywords door~
+EFlags door closed locked pickproof~
+EKeyvnum 101
+MProg GREET 100 100~
+End
+
+#101
+Name Outside~
+Desc Outside.
+~
+Exit south 100
+EFlags door closed locked~
+EKeyvnum 101
+End
+
+#0
+
+#RESETS2
+M 0 100 1 100 1
+G 0 101 5
+E 0 103 1 light~
+O 0 100 1 100
+P 0 101 1 100 1
+S
+
+#SHOPS
+#100
+buy_type light~
+sell_type container~
+profit_buy 120
+profit_sell 80
+open_hour 6
+close_hour 20
+END
+
+#0
+
+#MOBPROGS
+#100
+title test~
+code say hi
+~
+disabled true
+END
+
+#0
+
+#$
+",
+        );
+    }
+
+    #[test]
+    fn stock_area_keeps_the_keys_it_does_not_use() {
+        let stock = "#DAWNAREADATA
+Version     11
+Name        Stock~
+ShortName   Stock~
+Builders    Someone~
+VNUMs       200 299
+LRange      1 10
+Security    9
+End
+
+#MOBILES
+#200
+Name guard~
+ShortD a guard~
+LongD A guard stands here.~
+Desc Watchful.
+~
+Race human~
+Act dont_wander sentinel~
+Level 10
+Hitroll 2
+HitDice 2d8+100
+DamDice 1d6+2
+Sex male~
+Wealth 50
+END
+
+#0
+
+#OBJECTS
+#200
+Name sword~
+Short a sword~
+Desc A sword lies here.~
+ItemType weapon~
+Level 5
+Weight 8
+Cost 100
+Wear take wield~
+Values sword 2 6 slash 0
+End
+
+#0
+
+#ROOMS
+#200
+Name A gate~
+Desc A gate.
+~
+Sector city~
+RoomFlags law~
+Mana 100
+Exit north 200
+End
+
+#0
+
+#SPECIALS
+M 200 spec_guard
+S
+
+#RESETS2
+M 0 200 1 200 1    * the guard
+E 0 200 1 wield~
+S
+
+#$
+";
+        assert_round_trip(stock);
+
+        let mut diagnostics = Vec::new();
+        let area = load_area(stock, "stock.are", &mut diagnostics)
+            .unwrap_or_else(|error| panic!("{}", error));
+        let saved = super::save_area(&area);
+        for unused in [
+            "Version 11",
+            "Builders Someone~",
+            "Race human~",
+            "Act dont_wander sentinel~",
+            "Level 10",
+            "Hitroll 2",
+            "HitDice 2d8+100",
+            "DamDice 1d6+2",
+            "Level 5",
+            "Weight 8",
+            "Values sword 2 6 slash 0",
+            "RoomFlags law~",
+            "Mana 100",
+            "#SPECIALS\nM 200 spec_guard\nS",
+        ] {
+            assert!(saved.contains(unused), "{} wasn't written", unused);
+        }
+    }
+}
@@ -0,0 +1,652 @@
+//! Rhai scripts attached to entities with the `Script` component.
+//!
+//! Scripts live in `data/scripts/`, listed in `scriptlist.txt` the same way
+//! areas are listed in `arealist.txt`. A script can define handlers named
+//! after the mobprog actions, which are called when that action happens near
+//! an entity using that script:
+//!
+//! ```ignore
+//! fn on_speech(ctx, actor, message) {
+//!     if message.contains("hello") {
+//!         ctx.say(`Hello, ${actor.name}!`);
+//!     }
+//! }
+//! ```
+//!
+//! Scripts never touch the world directly. They get a snapshot of the
+//! entities around them, and queue effects (echoes, commands, moves) on the
+//! context, which are applied through an `EntityAgent` once the handler
+//! returns. Variables set with `ctx.set()` are stored in the entity's
+//! `Variables` component, shared with mobprogs' `mob var` commands.
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use rhai::{module_resolvers::DummyModuleResolver, Dynamic, Engine, EvalAltResult, AST};
+
+use crate::{
+    agent::EntityAgent,
+    colors::EscapeColors,
+    commands::process_agent_command,
+    components::{Script, VariableValue, Variables},
+    echo,
+    entity::{EntityId, EntityInfo, Found},
+    mobprogs::Action,
+    world::Vnum,
+    Files,
+};
+
+pub(crate) struct Scripts {
+    engine: Engine,
+    scripts: BTreeMap<String, AST>,
+    errors: Vec<String>,
+}
+
+impl Scripts {
+    pub fn load(files: &dyn Files) -> Scripts {
+        let mut scripts = Scripts {
+            engine: make_engine(),
+            scripts: BTreeMap::new(),
+            errors: Vec::new(),
+        };
+
+        scripts.reload(files);
+        scripts
+    }
+
+    /// Read and compile all scripts again.
+    ///
+    /// A script that fails to load keeps its previous version, if it had one.
+    pub fn reload(&mut self, files: &dyn Files) {
+        self.errors.clear();
+
+        let script_list = match files.read_file("data/scripts/scriptlist.txt") {
+            Ok(script_list) => script_list,
+            Err(_) => return,
+        };
+
+        for name in script_list
+            .split_whitespace()
+            .take_while(|name| *name != "$")
+        {
+            let path = format!("data/scripts/{}.rhai", name);
+
+            let source = match files.read_file(&path) {
+                Ok(source) => source,
+                Err(err) => {
+                    self.errors.push(format!("{}: {}", path, err));
+                    continue;
+                }
+            };
+
+            match self.engine.compile(&source) {
+                Ok(ast) => {
+                    self.scripts.insert(name.to_string(), ast);
+                }
+                Err(err) => self.errors.push(format!("{}: {}", path, err)),
+            }
+        }
+    }
+}
+
+fn make_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    // Scripts are written by builders, not players, but a typo in a loop
+    // should still not be able to hang the server.
+    engine.set_max_operations(50_000);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(4096);
+    engine.set_max_array_size(256);
+    engine.set_max_map_size(256);
+
+    // No access to the filesystem or the server's stdout.
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.on_print(|_| ());
+    engine.on_debug(|_, _, _| ());
+
+    engine
+        .register_type_with_name::<ScriptEntity>("Entity")
+        .register_get("name", |entity: &mut ScriptEntity| entity.name.clone())
+        .register_get("keyword", |entity: &mut ScriptEntity| {
+            entity.keyword.clone()
+        })
+        .register_get("kind", |entity: &mut ScriptEntity| entity.kind.to_string())
+        .register_get("vnum", |entity: &mut ScriptEntity| entity.vnum)
+        .register_fn("to_string", |entity: &mut ScriptEntity| entity.name.clone());
+
+    engine
+        .register_type_with_name::<ScriptContext>("Context")
+        .register_get("me", ScriptContext::me)
+        .register_get("room_vnum", ScriptContext::room_vnum)
+        .register_fn("find", ScriptContext::find)
+        .register_fn("echo", ScriptContext::echo)
+        .register_fn("echo_to", ScriptContext::echo_to)
+        .register_fn("say", ScriptContext::say)
+        .register_fn("command", ScriptContext::command)
+        .register_fn("queue", ScriptContext::queue)
+        .register_fn("transfer", ScriptContext::transfer)
+        .register_fn("get", ScriptContext::get)
+        .register_fn("set", ScriptContext::set)
+        .register_fn("set", ScriptContext::set_for);
+
+    engine
+}
+
+/// What a script sees of an entity near it.
+#[derive(Clone)]
+struct ScriptEntity {
+    index: usize,
+    name: String,
+    keyword: String,
+    kind: &'static str,
+    vnum: i64,
+}
+
+/// Things a script asked to do, applied after it returns.
+enum Effect {
+    Echo(String),
+    EchoTo(usize, String),
+    Command(String),
+    Queue(u16, String),
+    Transfer(usize, usize),
+}
+
+struct ScriptRun {
+    entities: Vec<(EntityId, ScriptEntity)>,
+    room_vnum: i64,
+    current_tick: u64,
+    variables: Variables,
+    effects: Vec<Effect>,
+}
+
+impl ScriptRun {
+    /// Add an entity to the snapshot, returning what the script sees of it.
+    fn add_entity(&mut self, entity: &EntityInfo) -> ScriptEntity {
+        let entity_id = entity.entity_id();
+
+        if let Some((_, known)) = self.entities.iter().find(|(id, _)| *id == entity_id) {
+            return known.clone();
+        }
+
+        let kind = if entity.is_player() {
+            "player"
+        } else if entity.is_mobile() {
+            "mobile"
+        } else if entity.is_object() {
+            "object"
+        } else if entity.is_room() {
+            "room"
+        } else {
+            "other"
+        };
+
+        let script_entity = ScriptEntity {
+            index: self.entities.len(),
+            name: entity.component_info().short_description().to_string(),
+            keyword: entity.component_info().keyword().to_string(),
+            kind,
+            vnum: entity.components().general.vnum.0 as i64,
+        };
+
+        self.entities.push((entity_id, script_entity.clone()));
+        script_entity
+    }
+}
+
+#[derive(Clone)]
+struct ScriptContext(Rc<RefCell<ScriptRun>>);
+
+impl ScriptContext {
+    fn me(&mut self) -> ScriptEntity {
+        self.0.borrow().entities[0].1.clone()
+    }
+
+    fn room_vnum(&mut self) -> i64 {
+        self.0.borrow().room_vnum
+    }
+
+    /// Find a nearby entity by keyword, or `()` if there is none.
+    fn find(&mut self, keyword: &str) -> Dynamic {
+        let run = self.0.borrow();
+
+        let found = run.entities.iter().skip(1).find(|(_, entity)| {
+            entity
+                .keyword
+                .split_whitespace()
+                .any(|word| word.eq_ignore_ascii_case(keyword))
+        });
+
+        match found {
+            Some((_, entity)) => Dynamic::from(entity.clone()),
+            None => Dynamic::UNIT,
+        }
+    }
+
+    fn echo(&mut self, message: &str) {
+        let effect = Effect::Echo(message.to_string());
+        self.0.borrow_mut().effects.push(effect);
+    }
+
+    fn echo_to(&mut self, target: ScriptEntity, message: &str) {
+        let effect = Effect::EchoTo(target.index, message.to_string());
+        self.0.borrow_mut().effects.push(effect);
+    }
+
+    fn say(&mut self, message: &str) {
+        let effect = Effect::Command(format!("say {}", message));
+        self.0.borrow_mut().effects.push(effect);
+    }
+
+    fn command(&mut self, command: &str) {
+        let effect = Effect::Command(command.to_string());
+        self.0.borrow_mut().effects.push(effect);
+    }
+
+    fn queue(&mut self, ticks: i64, command: &str) -> Result<(), Box<EvalAltResult>> {
+        let ticks = match ticks {
+            1..=600 => ticks as u16,
+            _ => return Err("Commands can only be queued for 1 to 600 ticks".into()),
+        };
+
+        let effect = Effect::Queue(ticks, command.to_string());
+        self.0.borrow_mut().effects.push(effect);
+        Ok(())
+    }
+
+    fn transfer(&mut self, target: ScriptEntity, room_vnum: i64) -> Result<(), Box<EvalAltResult>> {
+        if room_vnum < 0 {
+            return Err("Room vnums can't be negative".into());
+        }
+
+        let effect = Effect::Transfer(target.index, room_vnum as usize);
+        self.0.borrow_mut().effects.push(effect);
+        Ok(())
+    }
+
+    fn get(&mut self, name: &str) -> Dynamic {
+        let run = self.0.borrow();
+
+        match run.variables.get(name) {
+            Some(VariableValue::Int(number)) => Dynamic::from(*number),
+            Some(VariableValue::Bool(boolean)) => Dynamic::from(*boolean),
+            Some(VariableValue::Text(text)) => Dynamic::from(text.clone()),
+            None => Dynamic::UNIT,
+        }
+    }
+
+    fn set(&mut self, name: &str, value: Dynamic) -> Result<(), Box<EvalAltResult>> {
+        self.set_variable(name, value, None)
+    }
+
+    /// Set a variable that disappears after a number of ticks.
+    fn set_for(
+        &mut self,
+        name: &str,
+        value: Dynamic,
+        ticks: i64,
+    ) -> Result<(), Box<EvalAltResult>> {
+        if ticks < 1 {
+            return Err("Variables can only expire after at least 1 tick".into());
+        }
+
+        self.set_variable(name, value, Some(ticks as u64))
+    }
+
+    fn set_variable(
+        &mut self,
+        name: &str,
+        value: Dynamic,
+        ticks: Option<u64>,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let value = if value.is_unit() {
+            None
+        } else if value.is_int() {
+            value.as_int().ok().map(VariableValue::Int)
+        } else if value.is_bool() {
+            value.as_bool().ok().map(VariableValue::Bool)
+        } else if value.is_string() || value.is_char() {
+            Some(VariableValue::Text(value.to_string()))
+        } else {
+            return Err(format!(
+                "Variable '{}' can only hold numbers, booleans and strings, not {}",
+                name,
+                value.type_name()
+            )
+            .into());
+        };
+
+        let mut run = self.0.borrow_mut();
+        match value {
+            Some(value) => {
+                let expires = ticks.map(|ticks| run.current_tick + ticks);
+                run.variables.set(name, value, expires);
+            }
+            None => {
+                run.variables.remove(name);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The handler function called for an action, and the arguments it gets
+/// after `ctx` and `actor`.
+fn handler_name(action: &Action<'_>) -> &'static str {
+    match action {
+        Action::Speech { .. } => "on_speech",
+        Action::Greet => "on_greet",
+        Action::Exit { .. } => "on_exit",
+        Action::Entry => "on_entry",
+        Action::Login => "on_login",
+        Action::Give { .. } => "on_give",
+        Action::Bribe { .. } => "on_bribe",
+        Action::Get => "on_get",
+        Action::Drop => "on_drop",
+        Action::Wear => "on_wear",
+        Action::Use => "on_use",
+        Action::Random => "on_random",
+        Action::Kill { .. } => "on_kill",
+    }
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    /// Call the handler for the action in the scripts of all the holders,
+    /// with the current entity as the actor.
+    pub fn run_scripts(&mut self, action: &Action<'_>, holders: &[EntityId]) {
+        for holder_id in holders {
+            self.run_script(action, *holder_id);
+        }
+    }
+
+    fn run_script(&mut self, action: &Action<'_>, holder_id: EntityId) {
+        let holder = self.entity_world.entity_info(holder_id);
+        if !holder.is_awake() {
+            return;
+        }
+        let script = match &holder.components().script {
+            Some(script) => script,
+            None => return,
+        };
+        let ast = match self.scripts.scripts.get(&script.name) {
+            Some(ast) => ast,
+            None => return,
+        };
+
+        let handler = handler_name(action);
+        let room = holder.enclosing_room();
+
+        let mut run = ScriptRun {
+            entities: Vec::new(),
+            room_vnum: room.components().general.vnum.0 as i64,
+            current_tick: self.entity_world.current_tick(),
+            variables: holder.components().variables.clone(),
+            effects: Vec::new(),
+        };
+
+        run.add_entity(&holder);
+        let actor = run.add_entity(&self.entity_world.entity_info(self.entity_id));
+
+        let mut arguments = vec![Dynamic::from(actor)];
+        match action {
+            Action::Speech { message } => arguments.push(Dynamic::from(message.to_string())),
+            Action::Exit { direction } => arguments.push(Dynamic::from(direction.to_string())),
+            Action::Give { object_id } => {
+                let object = run.add_entity(&self.entity_world.entity_info(*object_id));
+                arguments.push(Dynamic::from(object));
+            }
+            Action::Bribe { value } => arguments.push(Dynamic::from(*value as i64)),
+            Action::Kill { victim_id } => {
+                let victim = run.add_entity(&self.entity_world.entity_info(*victim_id));
+                arguments.push(Dynamic::from(victim));
+            }
+            _ => (),
+        }
+
+        let has_handler = ast.iter_functions().any(|function| {
+            function.name == handler && function.params.len() == arguments.len() + 1
+        });
+
+        if !has_handler {
+            return;
+        }
+
+        let nearby = Some(room.clone())
+            .into_iter()
+            .chain(room.contained_entities())
+            .chain(holder.contained_entities());
+
+        for entity in nearby {
+            if entity.is_player() || entity.is_mobile() || entity.is_object() || entity.is_room() {
+                run.add_entity(&entity);
+            }
+        }
+
+        let script_name = script.name.clone();
+        let context = ScriptContext(Rc::new(RefCell::new(run)));
+        arguments.insert(0, Dynamic::from(context.clone()));
+
+        let result = self.scripts.engine.call_fn::<Dynamic>(
+            &mut rhai::Scope::new(),
+            ast,
+            handler,
+            arguments,
+        );
+
+        let run = match Rc::try_unwrap(context.0) {
+            Ok(run) => run.into_inner(),
+            // The script kept a reference to the context somewhere
+            Err(context) => context.replace(ScriptRun {
+                entities: Vec::new(),
+                room_vnum: 0,
+                current_tick: 0,
+                variables: Variables::default(),
+                effects: Vec::new(),
+            }),
+        };
+
+        let mut agent = self.switch_agent(holder_id);
+
+        if let Err(err) = result {
+            agent.trace(format_args!(
+                "Script '{}' failed in {}: {}",
+                script_name,
+                handler,
+                EscapeColors(&err.to_string())
+            ));
+            return;
+        }
+
+        agent.trace(format_args!("Script '{}' ran {}.", script_name, handler));
+        agent.apply_script_run(run);
+    }
+
+    fn apply_script_run(&mut self, run: ScriptRun) {
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        myself.components().variables = run.variables;
+
+        for effect in run.effects {
+            match effect {
+                Effect::Echo(message) => self.do_mob_echo(&message),
+                Effect::EchoTo(index, message) => {
+                    let target_id = run.entities[index].0;
+                    let target = self.entity_world.entity_info(target_id);
+                    echo!(self.players.info(&target), "{}\r\n", message);
+                }
+                Effect::Command(command) => {
+                    let words: Vec<_> = command.split_whitespace().collect();
+                    process_agent_command(self, &words);
+                }
+                Effect::Queue(ticks, command) => {
+                    let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+                    let queue = &mut myself.components().general.command_queue;
+                    queue.push((ticks, command));
+                }
+                Effect::Transfer(index, room_vnum) => {
+                    let target_id = run.entities[index].0;
+                    self.script_transfer(target_id, room_vnum);
+                }
+            }
+        }
+    }
+
+    fn script_transfer(&mut self, target_id: EntityId, room_vnum: usize) {
+        let room = self
+            .vnum_templates
+            .vnum_to_room_entity
+            .get(&Vnum(room_vnum))
+            .and_then(|permanent_id| self.entity_world.old_entity(permanent_id));
+
+        let room_id = match room {
+            Some(room) => room.entity_id(),
+            None => {
+                self.trace(format_args!(
+                    "Script tried to transfer to missing room {}.",
+                    room_vnum
+                ));
+                return;
+            }
+        };
+
+        let target = self.entity_world.entity_info(target_id);
+        let mut act = self.players.act_alone(&target);
+        echo!(act.others(), "$^$n vanishes.\r\n");
+
+        self.entity_world.move_entity(target_id, room_id);
+
+        let target = self.entity_world.entity_info(target_id);
+        let mut act = self.players.act_alone(&target);
+        echo!(act.others(), "$^$n appears out of thin air.\r\n");
+
+        let mut agent = self.switch_agent(target_id);
+        agent.do_look();
+        agent.check_triggers_others(Action::Greet);
+    }
+}
+
+// Immortal commands for managing scripts
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    pub fn do_scripts(&mut self, words: &[&str]) {
+        match *words {
+            [] | ["list"] => self.do_scripts_list(),
+            ["reload"] => {
+                self.scripts.reload(self.files);
+                self.do_scripts_list();
+            }
+            ["show", target] => self.do_scripts_show(target),
+            ["attach", target, name] => self.do_scripts_attach(target, Some(name)),
+            ["detach", target] => self.do_scripts_attach(target, None),
+            _ => {
+                echo!(
+                    self.info(),
+                    "Syntax: `Wscripts [list|reload|show <target>|attach <target> <script>|detach <target>]`^\r\n"
+                );
+            }
+        }
+    }
+
+    fn do_scripts_list(&mut self) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let mut info = self.players.info(&myself);
+
+        echo!(info, "Loaded scripts:\r\n");
+        for name in self.scripts.scripts.keys() {
+            echo!(info, "  `W{}`^\r\n", name);
+        }
+        if self.scripts.scripts.is_empty() {
+            echo!(info, "  None.\r\n");
+        }
+
+        for error in &self.scripts.errors {
+            echo!(info, "`RError:`^ {}\r\n", EscapeColors(error));
+        }
+    }
+
+    /// Find a target for script commands; "here" is the current room.
+    fn find_script_target(&mut self, target: &str) -> Option<EntityId> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        match myself.find_entity(target, |entity| !entity.is_extra_description()) {
+            Found::Nothing if target == "here" => Some(myself.room().entity_id()),
+            Found::Myself | Found::WrongSelf => Some(myself.entity_id()),
+            Found::Other(other) | Found::WrongOther(other) => Some(other.entity_id()),
+            Found::Nothing => {
+                echo!(self.info(), "You don't see anything here by that name.\r\n");
+                None
+            }
+        }
+    }
+
+    fn do_scripts_show(&mut self, target: &str) {
+        let target_id = match self.find_script_target(target) {
+            Some(target_id) => target_id,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = self.entity_world.entity_info(target_id);
+        let mut act = self.players.act_with(&myself, &target);
+
+        let script = match &target.components().script {
+            Some(script) => script,
+            None => {
+                echo!(act.myself(), "$^$N has no script attached.\r\n");
+                return;
+            }
+        };
+
+        echo!(act.myself(), "$^$N uses script '`W{}`^'", script.name);
+        if !self.scripts.scripts.contains_key(&script.name) {
+            echo!(act.myself(), " (`Rnot loaded`^)");
+        }
+        echo!(act.myself(), ".\r\n");
+
+        self.show_variables(target_id);
+    }
+
+    fn do_scripts_attach(&mut self, target: &str, name: Option<&str>) {
+        let target_id = match self.find_script_target(target) {
+            Some(target_id) => target_id,
+            None => return,
+        };
+
+        if let Some(name) = name {
+            // Scripts issue commands as their holder, which players must
+            // never be made to do
+            let target = self.entity_world.entity_info(target_id);
+            if target.is_player() {
+                let myself = self.entity_world.entity_info(self.entity_id);
+                let mut act = self.players.act_with(&myself, &target);
+                echo!(
+                    act.myself(),
+                    "Scripts can't be attached to players like $N.\r\n"
+                );
+                return;
+            }
+
+            if !self.scripts.scripts.contains_key(name) {
+                echo!(
+                    self.info(),
+                    "There is no script named '{}' loaded.\r\n",
+                    name
+                );
+                return;
+            }
+        }
+
+        let mut target = self.entity_world.entity_info_mut(target_id);
+        target.components().script = name.map(|name| Script {
+            name: name.to_string(),
+        });
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let target = self.entity_world.entity_info(target_id);
+        let mut act = self.players.act_with(&myself, &target);
+
+        match name {
+            Some(name) => echo!(act.myself(), "Attached script '{}' to $N.\r\n", name),
+            None => echo!(act.myself(), "Detached the script from $N.\r\n"),
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! Shopkeepers, and the wares they buy, sell and restock.
+//!
+//! A shopkeeper sells the objects it carries, except for the ones it wears,
+//! at their cost plus its `profit_buy` margin. It only buys the item types it
+//! deals in, paying their cost with its `profit_sell` margin, and less for
+//! objects in poor condition; whatever it buys is then for sale.
+//!
+//! Stock is finite: what players buy is gone until the shopkeeper restocks
+//! the objects its `G` resets gave it, every few minutes.
+
+use crate::{
+    agent::EntityAgent,
+    components::Object,
+    echo,
+    entity::{EntityId, EntityInfo},
+    find_entities::EntityIterator,
+    world::Shop,
+};
+
+impl Shop {
+    /// The item types in a shop are lists of words, e.g. "weapon armor".
+    fn deals_in(types: &[String], object: &Object) -> bool {
+        types
+            .iter()
+            .flat_map(|types| types.split_whitespace())
+            .any(|item_type| item_type == object.item_type)
+    }
+
+    /// Shopkeepers with no `sell_type` sell whatever they have.
+    fn sells(&self, object: &Object) -> bool {
+        self.sell_types.is_empty() || Shop::deals_in(&self.sell_types, object)
+    }
+
+    fn buys(&self, object: &Object) -> bool {
+        Shop::deals_in(&self.buy_types, object)
+    }
+
+    /// What players pay for an object.
+    fn selling_price(&self, object: &Object) -> usize {
+        object.cost.max(0) as usize * self.profit_buy as usize / 100
+    }
+
+    /// What players are paid for an object.
+    fn buying_price(&self, object: &Object) -> usize {
+        object.cost.max(0) as usize * self.profit_sell as usize / 100 * object.condition as usize
+            / 100
+    }
+}
+
+fn describe_condition(condition: u8) -> &'static str {
+    match condition {
+        100..=u8::MAX => "perfect",
+        75..=99 => "good",
+        50..=74 => "worn",
+        25..=49 => "damaged",
+        _ => "nearly broken",
+    }
+}
+
+/// The objects a shopkeeper has for sale.
+fn wares<'e>(
+    shopkeeper: &EntityInfo<'e>,
+    shop: &'e Shop,
+) -> impl Iterator<Item = (EntityInfo<'e>, &'e Object)> {
+    shopkeeper
+        .objects()
+        .filter(|item| item.equipped().is_none())
+        .filter_map(|item| {
+            item.components()
+                .object
+                .as_ref()
+                .map(|object| (item, object))
+        })
+        .filter(move |(_item, object)| shop.sells(object))
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    fn find_shopkeeper(&mut self) -> Option<EntityId> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let found = myself
+            .room()
+            .contained_entities()
+            .with_component::<Shop>()
+            .filter_or(
+                |e| *e != myself,
+                "The only shopkeeper here is yourself.\r\n",
+            )
+            .find_one_with_component_or("You don't see any shopkeepers here.");
+
+        match found {
+            Ok((shopkeeper, _shop)) => Some(shopkeeper.entity_id()),
+            Err(error) => {
+                self.echo_error(error);
+                None
+            }
+        }
+    }
+
+    /// Find an object the agent carries and could sell, or get a value for.
+    fn find_sellable(&mut self, item_name: &str) -> Option<EntityId> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let found = myself
+            .objects()
+            .filter_by_keyword(&myself, item_name)
+            .with_component_or::<Object>("$^$N is not an object you can sell.")
+            .filter_or(
+                |item| item.equipped().is_none(),
+                "You'll need to remove $N first.",
+            )
+            .find_one_with_component_or("You don't own anything named like that to sell.");
+
+        match found {
+            Ok((item, _object)) => Some(item.entity_id()),
+            Err(error) => {
+                self.echo_error(error);
+                None
+            }
+        }
+    }
+
+    pub fn do_buy(&mut self, item_name: &str) {
+        let shopkeeper_id = match self.find_shopkeeper() {
+            Some(shopkeeper_id) => shopkeeper_id,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let shopkeeper = self.entity_world.entity_info(shopkeeper_id);
+        let shop = match &shopkeeper.components().mobile {
+            Some(mobile) => mobile.shopkeeper.as_ref().expect("Found as a shopkeeper"),
+            None => return,
+        };
+
+        let found = shopkeeper
+            .objects()
+            .filter_by_keyword(&myself, item_name)
+            .with_component::<Object>()
+            .filter_or(|item| item.equipped().is_none(), "$^$N is not for sale.")
+            .filter_or(
+                |item| {
+                    item.components()
+                        .object
+                        .as_ref()
+                        .is_some_and(|object| shop.sells(object))
+                },
+                "$^$N is not for sale.",
+            )
+            .find_one_with_component_or("You don't see anything named like that to buy.");
+
+        let (item, object) = match found {
+            Ok(item) => item,
+            Err(error) => return self.echo_error(error),
+        };
+
+        let item_id = item.entity_id();
+        let cost = shop.selling_price(object);
+
+        if !self.remove_silver(cost, self.entity_id) {
+            echo!(
+                self.info(),
+                "You don't have the {} silver to pay for it!\r\n",
+                cost
+            );
+            return;
+        }
+
+        self.entity_world.move_entity(item_id, self.entity_id);
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let shopkeeper = self.entity_world.entity_info(shopkeeper_id);
+        let item = self.entity_world.entity_info(item_id);
+        let mut act = self.players.act_with(&myself, &shopkeeper);
+        echo!(
+            act.myself(),
+            "You buy {} from $N for {} silver.\r\n",
+            item,
+            cost
+        );
+        echo!(
+            act.target(),
+            "$^$n buys {} from you for {} silver.\r\n",
+            item,
+            cost
+        );
+        echo!(act.others(), "$^$n buys {} from $N.\r\n", item);
+    }
+
+    pub fn do_sell(&mut self, item_name: &str) {
+        let shopkeeper_id = match self.find_shopkeeper() {
+            Some(shopkeeper_id) => shopkeeper_id,
+            None => return,
+        };
+        let item_id = match self.find_sellable(item_name) {
+            Some(item_id) => item_id,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let shopkeeper = self.entity_world.entity_info(shopkeeper_id);
+        let item = self.entity_world.entity_info(item_id);
+
+        let shop = shopkeeper.components().mobile.as_ref();
+        let shop = shop.and_then(|mobile| mobile.shopkeeper.as_ref());
+        let object = item.components().object.as_ref();
+
+        let cost = match (shop, object) {
+            (Some(shop), Some(object)) if shop.buys(object) => shop.buying_price(object),
+            _ => {
+                let mut act = self.players.act_with(&myself, &shopkeeper);
+                echo!(act.myself(), "$^$N doesn't deal in that kind of thing.\r\n");
+                return;
+            }
+        };
+
+        if cost == 0 {
+            let mut act = self.players.act_with(&myself, &shopkeeper);
+            echo!(act.myself(), "$^$N looks uninterested in {}.\r\n", item);
+            return;
+        }
+
+        let mut act = self.players.act_with(&myself, &shopkeeper);
+        echo!(
+            act.myself(),
+            "You sell {} to $N for {} silver.\r\n",
+            item,
+            cost
+        );
+        echo!(
+            act.target(),
+            "$^$n sells {} to you for {} silver.\r\n",
+            item,
+            cost
+        );
+        echo!(act.others(), "$^$n sells {} to $N.\r\n", item);
+
+        self.add_silver(cost, self.entity_id);
+        self.entity_world.move_entity(item_id, shopkeeper_id);
+    }
+
+    pub fn do_value(&mut self, item_name: &str) {
+        let shopkeeper_id = match self.find_shopkeeper() {
+            Some(shopkeeper_id) => shopkeeper_id,
+            None => return,
+        };
+        let item_id = match self.find_sellable(item_name) {
+            Some(item_id) => item_id,
+            None => return,
+        };
+
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let shopkeeper = self.entity_world.entity_info(shopkeeper_id);
+        let item = self.entity_world.entity_info(item_id);
+
+        let shop = shopkeeper.components().mobile.as_ref();
+        let shop = shop.and_then(|mobile| mobile.shopkeeper.as_ref());
+        let object = item.components().object.as_ref();
+
+        let mut act = self.players.act_with(&myself, &shopkeeper);
+        match (shop, object) {
+            (Some(shop), Some(object)) if shop.buys(object) => echo!(
+                act.myself(),
+                "$^$N would give you {} silver for {}.\r\n",
+                shop.buying_price(object),
+                item
+            ),
+            _ => echo!(act.myself(), "$^$N doesn't deal in that kind of thing.\r\n"),
+        }
+        echo!(act.others(), "$^$n asks $N what {} is worth.\r\n", item);
+    }
+
+    pub fn do_appraise(&mut self, item_name: &str) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let found = myself
+            .contained_entities()
+            .filter_by_keyword(&myself, item_name)
+            .with_component_or::<Object>("$^$N is not something you can appraise.")
+            .find_one_with_component_or("You aren't holding anything named like that.");
+
+        let (item, object) = match found {
+            Ok(found) => found,
+            Err(error) => return self.echo_error(error),
+        };
+
+        let mut act = self.players.act_with(&myself, &item);
+        echo!(
+            act.myself(),
+            "$^$N is in {} condition, and is worth about {} silver coins.\r\n",
+            describe_condition(object.condition),
+            object.cost.max(0)
+        );
+        echo!(act.others(), "$^$n looks $N over carefully.\r\n");
+    }
+
+    pub fn do_list(&mut self) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        let found = myself
+            .room()
+            .contained_entities()
+            .filter_or(
+                |e| *e != myself,
+                "The only shopkeeper here is yourself.\r\n",
+            )
+            .with_component::<Shop>()
+            .find_one_with_component_or("You don't see any shopkeepers here.");
+
+        let (shopkeeper, shop) = match found {
+            Ok(found) => found,
+            Err(error) => return self.echo_error(error),
+        };
+
+        // Identical objects are listed once, with how many are left.
+        let mut lines: Vec<(String, usize, usize)> = Vec::new();
+        for (item, object) in wares(&shopkeeper, shop) {
+            let short_description = item.component_info().short_description().to_string();
+            let price = shop.selling_price(object);
+
+            match lines
+                .iter_mut()
+                .find(|(line, line_price, _)| *line == short_description && *line_price == price)
+            {
+                Some((_, _, count)) => *count += 1,
+                None => lines.push((short_description, price, 1)),
+            }
+        }
+
+        let mut act = self.players.act_with(&myself, &shopkeeper);
+        echo!(
+            act.target(),
+            "$^$n asks you about your wares, and you show $m what you have.\r\n"
+        );
+        echo!(act.others(), "$^$n asks $N about $S wares.\r\n");
+
+        if lines.is_empty() {
+            echo!(act.myself(), "$^$N has nothing for sale right now.\r\n");
+            return;
+        }
+
+        echo!(act.myself(), "$^$N shows you $S wares:\r\n");
+
+        let mut info = self.players.info(&myself);
+        for (short_description, price, count) in lines {
+            echo!(
+                info,
+                "  [`g{:>2}`^] {}: `W{}`^ silver coins\r\n",
+                count,
+                short_description,
+                price
+            );
+        }
+    }
+
+    /// Bring the shopkeeper's stock back to what its resets gave it.
+    pub fn restock_shop(&mut self) {
+        let myself = self.entity_world.entity_info(self.entity_id);
+        let restock = match myself.components().mobile.as_ref() {
+            Some(mobile) => &mobile.restock,
+            None => return,
+        };
+
+        let mut missing = Vec::new();
+        for (index, vnum) in restock.iter().enumerate() {
+            // The same object may be given more than once
+            let wanted = restock[..=index]
+                .iter()
+                .filter(|other| *other == vnum)
+                .count();
+            let stocked = myself
+                .objects()
+                .filter(|item| item.equipped().is_none())
+                .filter(|item| item.components().general.vnum == *vnum)
+                .count();
+
+            if stocked < wanted {
+                missing.push(*vnum);
+            }
+        }
+
+        for vnum in missing {
+            self.create_object(vnum, self.entity_id);
+        }
+    }
+}
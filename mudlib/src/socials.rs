@@ -1,6 +1,9 @@
 use std::collections::BTreeMap;
 
-use crate::{file_parser::FileParser, files::Files};
+use crate::{
+    file_parser::{Diagnostic, FileParser},
+    files::Files,
+};
 
 pub(crate) struct Socials {
     socials: BTreeMap<String, Social>,
@@ -33,45 +36,72 @@ impl Socials {
     }
 }
 
-pub(crate) fn load_socials(files: &dyn Files, path: &str) -> Socials {
-    let contents = files.read_file(path).unwrap();
+/// Loads the socials file, keeping the socials read before any error.
+pub(crate) fn load_socials(
+    files: &dyn Files,
+    path: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Socials {
+    let mut socials = BTreeMap::new();
+
+    let contents = match files.read_file(path) {
+        Ok(contents) => contents,
+        Err(error) => {
+            diagnostics.push(Diagnostic::unreadable(path, &error));
+            return Socials { socials };
+        }
+    };
+
     let mut parser = FileParser::new(&contents, path);
+    if let Err(error) = read_socials(&mut parser, &mut socials) {
+        diagnostics.push(error);
+    }
+    diagnostics.append(&mut parser.warnings);
 
-    let mut socials = BTreeMap::new();
+    Socials { socials }
+}
 
-    let mut current_social = None;
+fn read_socials(
+    parser: &mut FileParser,
+    socials: &mut BTreeMap<String, Social>,
+) -> Result<(), Diagnostic> {
+    let mut current_social: Option<Social> = None;
 
     loop {
         if current_social.is_none() {
-            let section = parser.read_section();
+            let section = parser.read_section()?;
 
             if section == "END" {
                 break;
             }
 
-            assert_eq!(section, "SOCIAL");
+            if section != "SOCIAL" {
+                return Err(parser.error(&format!("Expected '#SOCIAL', got '#{}'", section)));
+            }
         }
 
-        let key = parser.read_word();
+        let key = parser.read_word()?;
 
         if key == "End" {
-            let social: Social = current_social.take().unwrap();
-            socials.insert(social.name.clone(), social);
+            if let Some(social) = current_social.take() {
+                socials.insert(social.name.clone(), social);
+            }
             continue;
         }
 
-        parser.skip_one_space();
+        parser.skip_one_space()?;
 
-        if current_social.is_none() {
-            assert_eq!(key, "Name");
-            current_social = Some(Social {
-                name: parser.read_until_tilde().trim_start().to_string(),
-                ..Default::default()
-            });
-            continue;
-        }
-
-        let social = current_social.as_mut().unwrap();
+        let social = match current_social.as_mut() {
+            Some(social) => social,
+            None if key == "Name" => {
+                current_social = Some(Social {
+                    name: parser.read_until_tilde()?.trim_start().to_string(),
+                    ..Default::default()
+                });
+                continue;
+            }
+            None => return Err(parser.error(&format!("Expected 'Name', got '{}'", key))),
+        };
 
         let attribute = match key {
             "CharNoArg" => &mut social.untargetted_self,
@@ -82,36 +112,109 @@ pub(crate) fn load_socials(files: &dyn Files, path: &str) -> Socials {
             "OthersFound" => &mut social.targetted_others,
             "VictFound" => &mut social.targetted_target,
             "acts[7]" => {
-                parser.read_until_tilde();
+                parser.read_until_tilde()?;
+                continue;
+            }
+            key => {
+                parser.warn(&format!("Unrecognized key '{}' in socials file", key));
+                parser.read_until_tilde()?;
                 continue;
             }
-            key => parser.panic_on_line(&format!("Unrecognized key '{}' in socials file", key)),
         };
 
-        let message = parser.read_until_tilde().trim_start();
+        let message = mark_verbs(parser.read_until_tilde()?.trim_start());
 
         if message.starts_with('$') {
-            *attribute = String::from("$^") + message;
+            *attribute = format!("$^{}", message);
         } else {
-            *attribute = message.to_string();
+            *attribute = message;
         }
     }
 
-    Socials { socials }
+    Ok(())
+}
+
+/// Words ending in "s" that can follow a pronoun without being a verb.
+const NOT_VERBS: &[&str] = &["always", "perhaps", "sometimes", "nevertheless", "thus"];
+
+/// What can join two verbs of the same pronoun, as in "$e nods and smiles".
+const VERB_JOINERS: &[&str] = &[", and then ", " and then ", ", and ", " and ", " or ", ", "];
+
+/// Socials are written for actors that are "he", "she" or "it", as in "$n
+/// nods, and $e smiles." Add a `$v` or `$V` before each verb of a subjective
+/// pronoun, so that it agrees with actors that are "they" too. Adverbs like
+/// "always" or "slowly" are skipped, and verbs joined right after the first
+/// one, like "$e nods and smiles", are marked too.
+fn mark_verbs(message: &str) -> String {
+    let mut marked = String::with_capacity(message.len());
+    let mut rest = message;
+
+    while let Some(index) = rest.find('$') {
+        let (before, after) = rest.split_at(index);
+        marked.push_str(before);
+
+        let marker = match after.get(1..3) {
+            Some("e ") => "$v",
+            Some("E ") => "$V",
+            _ => {
+                marked.push_str(&after[..1]);
+                rest = &after[1..];
+                continue;
+            }
+        };
+        marked.push_str(&after[..3]);
+        rest = &after[3..];
+
+        loop {
+            let end = rest
+                .find(|c: char| !c.is_ascii_lowercase() && c != '\'')
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+
+            if NOT_VERBS.contains(&word) || word.ends_with("ly") {
+                if rest[end..].starts_with(' ') {
+                    marked.push_str(&rest[..end + 1]);
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                break;
+            }
+
+            let is_verb = (word.ends_with('s') || word.ends_with("n't")) && !word.contains("'s");
+            if !is_verb {
+                break;
+            }
+
+            marked.push_str(marker);
+            marked.push_str(word);
+            rest = &rest[end..];
+
+            match VERB_JOINERS.iter().find(|joiner| rest.starts_with(*joiner)) {
+                Some(joiner) => {
+                    marked.push_str(joiner);
+                    rest = &rest[joiner.len()..];
+                }
+                None => break,
+            }
+        }
+    }
+
+    marked.push_str(rest);
+    marked
 }
 
 // Dawn-format socials; currently using Ultra-Envy socials instead
 #[allow(dead_code)]
-fn load_old_socials(files: &dyn Files, path: &str) -> Socials {
+fn load_old_socials(files: &dyn Files, path: &str) -> Result<Socials, Diagnostic> {
     let contents = files.read_file(path).unwrap();
     let mut parser = FileParser::new(&contents, path);
 
     let mut socials = BTreeMap::new();
 
-    let mut current_social = None;
+    let mut current_social: Option<Social> = None;
 
     loop {
-        let key = parser.read_word();
+        let key = parser.read_word()?;
 
         if key == "EOF~" {
             assert!(current_social.is_none());
@@ -124,12 +227,12 @@ fn load_old_socials(files: &dyn Files, path: &str) -> Socials {
             continue;
         }
 
-        parser.skip_one_space();
+        parser.skip_one_space()?;
 
         if current_social.is_none() {
             assert_eq!(key, "name");
             current_social = Some(Social {
-                name: parser.read_until_tilde().to_string(),
+                name: parser.read_until_tilde()?.to_string(),
                 ..Default::default()
             });
             continue;
@@ -148,14 +251,17 @@ fn load_old_socials(files: &dyn Files, path: &str) -> Socials {
             "acts[5]" => &mut social.targetted_target,
             "acts[6]" => &mut social.targetted_others,
             "acts[7]" => {
-                parser.read_until_tilde();
+                parser.read_until_tilde()?;
                 continue;
             }
-            key => parser.panic_on_line(&format!("Unrecognized key '{}' in socials file", key)),
+            key => {
+                let message = format!("Unrecognized key '{}' in socials file", key);
+                return Err(parser.error(&message));
+            }
         };
 
-        *attribute = parser.read_until_tilde().to_string();
+        *attribute = parser.read_until_tilde()?.to_string();
     }
 
-    Socials { socials }
+    Ok(Socials { socials })
 }
@@ -16,11 +16,17 @@ use crate::{
     acting::{PlayerEcho, Players},
     agent::EntityAgent,
     echo,
+    editor::Editor,
     entity::EntityWorld,
+    file_parser::{diagnostics_report, Diagnostic},
     import::{import_from_world, VnumTemplates},
     mobprogs::Action,
+    pfiles::load_player_file,
+    quests::{load_quests, Quests},
+    scripts::Scripts,
     socials::Socials,
     tick::update_entity_world,
+    who::ConnectionInfo,
     world::Vnum,
     world::World,
     Files,
@@ -29,11 +35,23 @@ use crate::{
 pub struct WorldState {
     pub(crate) socials: Socials,
     pub(crate) entity_world: EntityWorld,
+    /// Templates that the world was imported from, which builders can edit
+    pub(crate) world: World,
     pub(crate) vnum_templates: VnumTemplates,
     pub(crate) areas: Vec<Area>,
+    pub(crate) quests: Quests,
+    pub(crate) scripts: Scripts,
+    pub(crate) files: Box<dyn Files>,
 
     pub(crate) players: Players,
+    pub(crate) immortals: Vec<String>,
+    /// Problems found in the data files when the world was loaded
+    pub(crate) diagnostics: Vec<Diagnostic>,
     pub(crate) wander_ticks: u8,
+    pub(crate) random_ticks: u8,
+    pub(crate) save_ticks: u8,
+    pub(crate) restock_ticks: u16,
+    pub(crate) light_ticks: u8,
 }
 
 // Note: this should probably become an entity that contains all of its rooms
@@ -43,29 +61,75 @@ pub(crate) struct Area {
     pub credits: String,
 }
 
-pub(super) fn create_state(world: World, socials: Socials) -> WorldState {
+pub(super) fn create_state(
+    mut world: World,
+    socials: Socials,
+    files: Box<dyn Files>,
+) -> WorldState {
     let players = Players {
         player_echoes: Default::default(),
+        mobprog_traces: Default::default(),
+        channel_history: Default::default(),
     };
 
     let mut entity_world = EntityWorld::new();
     let (vnum_templates, areas) = import_from_world(&mut entity_world, &world);
+    let scripts = Scripts::load(&*files);
+    let mut diagnostics = std::mem::take(&mut world.diagnostics);
+    let quests = load_quests(&*files, "data/quests.toml", &mut diagnostics);
 
     WorldState {
         entity_world,
+        world,
         vnum_templates,
         areas,
+        quests,
         socials,
+        scripts,
+        files,
         players,
+        immortals: Vec::new(),
+        diagnostics,
         wander_ticks: 0,
+        random_ticks: 0,
+        save_ticks: 0,
+        restock_ticks: 0,
+        light_ticks: 0,
     }
 }
 
+/// Read the names of players that are allowed to use immortal commands.
+///
+/// The file has the same format as `arealist.txt`: whitespace-separated names
+/// ending with a `$`. A missing file simply means there are no immortals.
+pub(super) fn load_immortals(files: &dyn Files, path: &str) -> Vec<String> {
+    let immortals = match files.read_file(path) {
+        Ok(immortals) => immortals,
+        Err(_) => return Vec::new(),
+    };
+
+    immortals
+        .split_whitespace()
+        .take_while(|name| *name != "$")
+        .map(|name| name.to_string())
+        .collect()
+}
+
 impl WorldState {
-    pub fn from_files(files: &dyn Files) -> WorldState {
-        let world = crate::world::load_world(files, "data/area");
-        let socials = crate::socials::load_socials(files, "data/socials.txt");
-        create_state(world, socials)
+    pub fn from_files(files: Box<dyn Files>) -> WorldState {
+        let mut world = crate::world::load_world(&*files, "data/area");
+        let socials =
+            crate::socials::load_socials(&*files, "data/socials.txt", &mut world.diagnostics);
+        let immortals = load_immortals(&*files, "data/immortals.txt");
+        let mut world_state = create_state(world, socials, files);
+        world_state.immortals = immortals;
+        world_state
+    }
+
+    /// Everything that went wrong while loading the data files, or `None` if
+    /// they loaded cleanly.
+    pub fn load_report(&self) -> Option<String> {
+        diagnostics_report(&self.diagnostics)
     }
 
     pub fn update_world(&mut self) {
@@ -73,7 +137,12 @@ impl WorldState {
     }
 
     pub fn add_player(&mut self, name: &str) {
-        let player_components = self.entity_world.make_player_components(name);
+        let new_player = self.entity_world.player_entity_id(name).is_none();
+        let immortal = self
+            .immortals
+            .iter()
+            .any(|immortal| immortal.eq_ignore_ascii_case(name));
+        let player_components = self.entity_world.make_player_components(name, immortal);
 
         let player_id = self.entity_world.add_player(name, player_components);
         let starting_location = self
@@ -82,24 +151,102 @@ impl WorldState {
             .expect("Starting location should exist");
         self.entity_world.move_entity(player_id, starting_location);
 
+        let mut player_echo = PlayerEcho::default();
+        player_echo.last_command_tick = self.entity_world.current_tick();
         self.players
             .player_echoes
-            .insert(name.to_string(), PlayerEcho::default());
+            .insert(name.to_string(), player_echo);
 
         let player = self.entity_world.entity_info(player_id);
         let mut act = self.players.act_alone(&player);
         echo!(act.others(), "$^$n materializes from thin air.\r\n");
 
         let mut agent = EntityAgent::new(self, player_id);
-        agent.add_silver(200, player_id);
+        if new_player {
+            match load_player_file(agent.files, name) {
+                Ok(Some(player_file)) => agent.restore_player(player_file),
+                Ok(None) => {
+                    // Only new characters get money to start with; the
+                    // others get back what they had
+                    agent.add_silver(200, player_id);
+                    agent.ask_pronouns();
+                }
+                Err(err) => echo!(
+                    agent.info(),
+                    "Your player file could not be loaded: {}\r\n",
+                    err
+                ),
+            }
+        }
         agent.do_look();
         agent.check_triggers_others(Action::Login);
     }
 
+    /// Write a player's file, e.g. when they disconnect.
+    pub fn save_player(&mut self, name: &str) -> Result<(), String> {
+        let player_id = match self.entity_world.player_entity_id(name) {
+            Some(player_id) => player_id,
+            None => return Err(format!("No player named '{}'", name)),
+        };
+
+        EntityAgent::new(self, player_id).save_player()
+    }
+
+    /// Write all players' files, returning the errors of those that failed.
+    pub fn save_players(&mut self) -> Vec<String> {
+        let players: Vec<_> = self.players.player_echoes.keys().cloned().collect();
+
+        players
+            .iter()
+            .filter_map(|player| self.save_player(player).err())
+            .collect()
+    }
+
     pub fn process_player_command(&mut self, player: &str, words: &[&str]) {
         crate::commands::process_player_command(self, player, words);
     }
 
+    /// Names of the players in the game, except for invisible ones.
+    pub fn player_names(&self) -> Vec<String> {
+        self.players
+            .player_echoes
+            .keys()
+            .filter(|name| match self.entity_world.player_entity_id(name) {
+                Some(player_id) => {
+                    let player = self.entity_world.entity_info(player_id);
+                    !player.components().visibility.invisible
+                }
+                None => false,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Tell the game where a player is connected from, for immortals' `users`
+    /// command. Players without it are shown as local.
+    pub fn set_connection_info(&mut self, player: &str, connection: ConnectionInfo) {
+        if let Some(player_echo) = self.players.player_echoes.get_mut(player) {
+            player_echo.connection = Some(connection);
+        }
+    }
+
+    /// Take the editor that a player's last command opened, if any. Until the
+    /// editor is saved or cancelled, the player's input should go to
+    /// `Editor::input` instead of `process_player_command`.
+    pub fn take_editor(&mut self, player: &str) -> Option<Editor> {
+        self.players
+            .player_echoes
+            .get_mut(player)
+            .and_then(|player_echo| player_echo.editor.take())
+    }
+
+    /// Give the text of a saved editor to the command that opened it.
+    pub fn finish_editor(&mut self, player: &str, editor: Editor) {
+        if let Some(player_id) = self.entity_world.player_entity_id(player) {
+            EntityAgent::new(self, player_id).finish_editor(editor);
+        }
+    }
+
     /// Get a mutable reference to a player's output echo buffer.
     ///
     /// Return None if the player doesn't exist, or a mutable buffer with text
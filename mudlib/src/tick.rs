@@ -1,10 +1,32 @@
 use string_interner::StringInterner;
 
-use crate::{agent::EntityAgent, commands::process_agent_command, WorldState};
+use crate::{
+    agent::EntityAgent, commands::process_agent_command, components::Position, mobprogs::Action,
+    WorldState,
+};
 
 pub(super) fn update_entity_world(world_state: &mut WorldState) {
+    update_variables(world_state);
     update_wander(world_state);
+    update_random_triggers(world_state);
     update_command_queue(world_state);
+    update_autosave(world_state);
+    update_restock(world_state);
+    update_lights(world_state);
+}
+
+pub(super) fn update_variables(world_state: &mut WorldState) {
+    let entity_world = &mut world_state.entity_world;
+    entity_world.advance_tick();
+    let current_tick = entity_world.current_tick();
+
+    for mut entity in entity_world.all_entities_mut() {
+        let variables = &mut entity.components().variables;
+
+        if !variables.is_empty() {
+            variables.remove_expired(current_tick);
+        }
+    }
 }
 
 pub(super) fn update_wander(world_state: &mut WorldState) {
@@ -24,7 +46,7 @@ pub(super) fn update_wander(world_state: &mut WorldState) {
 
     for entity in entity_world.all_entities() {
         let wander = match &entity.components().mobile {
-            Some(mobile) => mobile.wander,
+            Some(mobile) => mobile.wander && mobile.position == Position::Standing,
             None => continue,
         };
 
@@ -49,8 +71,13 @@ pub(super) fn update_wander(world_state: &mut WorldState) {
         let mut agent = EntityAgent {
             entity_world,
             socials: &world_state.socials,
-            vnum_templates: &world_state.vnum_templates,
+            world: &mut world_state.world,
+            vnum_templates: &mut world_state.vnum_templates,
             areas: &world_state.areas,
+            diagnostics: &world_state.diagnostics,
+            quests: &world_state.quests,
+            scripts: &mut world_state.scripts,
+            files: &*world_state.files,
             players: &mut world_state.players,
             entity_id: wanderer_id,
         };
@@ -62,6 +89,55 @@ pub(super) fn update_wander(world_state: &mut WorldState) {
     }
 }
 
+pub(super) fn update_random_triggers(world_state: &mut WorldState) {
+    world_state.random_ticks += 1;
+
+    // Check RANDOM mobprogs every 4 seconds. Only rooms react to these.
+    if world_state.random_ticks < 4 {
+        return;
+    }
+
+    world_state.random_ticks = 0;
+
+    let entity_world = &mut world_state.entity_world;
+
+    // Only rooms with players in them are checked, once per room, with the
+    // first player found there as the target.
+    let mut rooms = Vec::new();
+    let mut players = Vec::new();
+
+    for entity in entity_world.all_entities() {
+        if !entity.is_player() {
+            continue;
+        }
+
+        let room_id = entity_world.room_of(entity.entity_id());
+
+        if !rooms.contains(&room_id) {
+            rooms.push(room_id);
+            players.push(entity.entity_id());
+        }
+    }
+
+    for player_id in players {
+        let mut agent = EntityAgent {
+            entity_world,
+            socials: &world_state.socials,
+            world: &mut world_state.world,
+            vnum_templates: &mut world_state.vnum_templates,
+            areas: &world_state.areas,
+            diagnostics: &world_state.diagnostics,
+            quests: &world_state.quests,
+            scripts: &mut world_state.scripts,
+            files: &*world_state.files,
+            players: &mut world_state.players,
+            entity_id: player_id,
+        };
+
+        agent.check_triggers_others(Action::Random);
+    }
+}
+
 pub(super) fn update_command_queue(world_state: &mut WorldState) {
     let entity_world = &mut world_state.entity_world;
     let mut commands = Vec::new();
@@ -94,8 +170,13 @@ pub(super) fn update_command_queue(world_state: &mut WorldState) {
         let mut agent = EntityAgent {
             entity_world,
             socials: &world_state.socials,
-            vnum_templates: &world_state.vnum_templates,
+            world: &mut world_state.world,
+            vnum_templates: &mut world_state.vnum_templates,
             areas: &world_state.areas,
+            diagnostics: &world_state.diagnostics,
+            quests: &world_state.quests,
+            scripts: &mut world_state.scripts,
+            files: &*world_state.files,
             players: &mut world_state.players,
             entity_id,
         };
@@ -105,6 +186,131 @@ pub(super) fn update_command_queue(world_state: &mut WorldState) {
     }
 }
 
+pub(super) fn update_autosave(world_state: &mut WorldState) {
+    world_state.save_ticks += 1;
+
+    // Save players every minute, so that a crash doesn't lose much.
+    if world_state.save_ticks < 60 {
+        return;
+    }
+
+    world_state.save_ticks = 0;
+
+    // Failures are reported when saving explicitly; not all frontends can
+    // write files at all.
+    world_state.save_players();
+}
+
+pub(super) fn update_restock(world_state: &mut WorldState) {
+    world_state.restock_ticks += 1;
+
+    // Restock shops every 5 minutes.
+    if world_state.restock_ticks < 5 * 60 {
+        return;
+    }
+
+    world_state.restock_ticks = 0;
+
+    let entity_world = &mut world_state.entity_world;
+
+    let shopkeepers: Vec<_> = entity_world
+        .all_entities()
+        .filter(|entity| {
+            entity
+                .components()
+                .mobile
+                .as_ref()
+                .is_some_and(|mobile| mobile.shopkeeper.is_some())
+        })
+        .map(|entity| entity.entity_id())
+        .collect();
+
+    for shopkeeper_id in shopkeepers {
+        let mut agent = EntityAgent {
+            entity_world,
+            socials: &world_state.socials,
+            world: &mut world_state.world,
+            vnum_templates: &mut world_state.vnum_templates,
+            areas: &world_state.areas,
+            diagnostics: &world_state.diagnostics,
+            quests: &world_state.quests,
+            scripts: &mut world_state.scripts,
+            files: &*world_state.files,
+            players: &mut world_state.players,
+            entity_id: shopkeeper_id,
+        };
+
+        agent.restock_shop();
+    }
+}
+
 fn random_bits(bits: u8) -> bool {
     (rand::random::<u32>() >> 7) & ((1u32 << bits) - 1) == 0
 }
+
+pub(super) fn update_lights(world_state: &mut WorldState) {
+    world_state.light_ticks += 1;
+
+    // Carried lights burn for an hour every minute.
+    if world_state.light_ticks < 60 {
+        return;
+    }
+
+    world_state.light_ticks = 0;
+
+    let entity_world = &mut world_state.entity_world;
+    let mut burnt_out = Vec::new();
+
+    // Only lights that someone carries burn, so that those left lit in rooms
+    // keep lighting them up.
+    let carried: Vec<_> = entity_world
+        .all_entities()
+        .filter(|entity| entity.is_object())
+        .filter(|entity| {
+            let holder = entity.room();
+            holder.is_mobile() || holder.is_player()
+        })
+        .map(|entity| entity.entity_id())
+        .collect();
+
+    for entity_id in carried {
+        let mut entity = entity_world.entity_info_mut(entity_id);
+        let light = entity
+            .components()
+            .object
+            .as_mut()
+            .and_then(|object| object.light.as_mut());
+
+        let light = match light {
+            Some(light) if light.lit => light,
+            _ => continue,
+        };
+
+        if let Some(hours) = &mut light.hours {
+            *hours = hours.saturating_sub(1);
+
+            if *hours == 0 {
+                light.lit = false;
+                burnt_out.push(entity_id);
+            }
+        }
+    }
+
+    for light_id in burnt_out {
+        let mut agent = EntityAgent {
+            entity_world,
+            socials: &world_state.socials,
+            world: &mut world_state.world,
+            vnum_templates: &mut world_state.vnum_templates,
+            areas: &world_state.areas,
+            diagnostics: &world_state.diagnostics,
+            quests: &world_state.quests,
+            scripts: &mut world_state.scripts,
+            files: &*world_state.files,
+            players: &mut world_state.players,
+            entity_id: light_id,
+        };
+
+        agent.burn_out();
+    }
+}
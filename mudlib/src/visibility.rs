@@ -0,0 +1,188 @@
+//! Who can see what.
+//!
+//! A room flagged as dark hides everything in it, except for what you carry,
+//! unless something there gives off light; a lit light object lights up the room
+//! when it's on the floor or carried by anyone in it. Invisible and hidden
+//! entities can only be seen by those who can detect them, and mobiles flagged
+//! as `unseen` can't be seen at all. Immortals with `holylight` on see
+//! everything.
+//!
+//! Only players are limited by this when looking for something; mobiles,
+//! rooms and objects always find their targets, so that their programs keep
+//! working in the dark. Mobprogs can still check with `if isvisible`.
+//!
+//! Actors that can't be seen are shown as "someone" in messages.
+
+use crate::{
+    agent::EntityAgent,
+    echo,
+    entity::{EntityId, EntityInfo},
+};
+
+/// What an entity can see, worked out once so that it can be checked against
+/// many other entities.
+#[derive(Clone, Copy)]
+pub(crate) struct Sight {
+    viewer_id: EntityId,
+    sees_all: bool,
+    detect_invisible: bool,
+    detect_hidden: bool,
+    in_darkness: bool,
+}
+
+impl Sight {
+    pub fn in_darkness(&self) -> bool {
+        self.in_darkness && !self.sees_all
+    }
+
+    pub fn can_see(&self, target: &EntityInfo) -> bool {
+        if self.sees_all || target.entity_id() == self.viewer_id {
+            return true;
+        }
+
+        let visibility = &target.components().visibility;
+
+        if visibility.unseen
+            || (visibility.invisible && !self.detect_invisible)
+            || (visibility.hidden && !self.detect_hidden)
+        {
+            return false;
+        }
+
+        // Exits can still be found by feeling around in the dark, and so can
+        // the things you carry.
+        let carried = target.room().entity_id() == self.viewer_id;
+        !self.in_darkness || carried || target.is_room() || target.is_exit()
+    }
+}
+
+impl<'e> EntityInfo<'e> {
+    /// What this entity would see with its own eyes.
+    pub fn sight(&self) -> Sight {
+        let visibility = &self.components().visibility;
+        let holylight = self
+            .components()
+            .player
+            .as_ref()
+            .is_some_and(|player| player.immortal && player.holylight);
+
+        Sight {
+            viewer_id: self.entity_id(),
+            sees_all: holylight,
+            detect_invisible: visibility.detect_invisible,
+            detect_hidden: visibility.detect_hidden,
+            in_darkness: !self.is_room() && self.room().is_dark(),
+        }
+    }
+
+    /// What this entity can find when looking for targets; only players are
+    /// limited by what they can see.
+    pub fn search_sight(&self) -> Sight {
+        let mut sight = self.sight();
+        sight.sees_all |= !self.is_player();
+        sight
+    }
+
+    pub fn can_see(&self, target: &EntityInfo) -> bool {
+        self.sight().can_see(target)
+    }
+
+    /// Check if a room is too dark to see anything in.
+    pub fn is_dark(&self) -> bool {
+        if !self.components().visibility.dark {
+            return false;
+        }
+
+        let is_light = |entity: &EntityInfo| {
+            entity
+                .components()
+                .object
+                .as_ref()
+                .and_then(|object| object.light.as_ref())
+                .is_some_and(|light| light.lit)
+        };
+
+        let lit = self.contained_entities().any(|entity| {
+            is_light(&entity)
+                || (!entity.is_object() && entity.contained_entities().any(|item| is_light(&item)))
+        });
+
+        !lit
+    }
+
+    /// Check if the player with this name can see this entity.
+    pub fn visible_to_player(&self, player_name: &str) -> bool {
+        match self.player_entity(player_name) {
+            Some(player) => player.can_see(self),
+            None => true,
+        }
+    }
+}
+
+// Commands to hide from others, and to see everything
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    pub fn do_hide(&mut self) {
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        myself.components().visibility.hidden = true;
+
+        echo!(self.info(), "You hide yourself as well as you can.\r\n");
+    }
+
+    pub fn do_sneak(&mut self) {
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        let visibility = &mut myself.components().visibility;
+        visibility.sneaking = !visibility.sneaking;
+
+        if visibility.sneaking {
+            echo!(self.info(), "You start moving silently.\r\n");
+        } else {
+            echo!(self.info(), "You stop sneaking around.\r\n");
+        }
+    }
+
+    pub fn do_visible(&mut self) {
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        let visibility = &mut myself.components().visibility;
+        visibility.invisible = false;
+        visibility.hidden = false;
+        visibility.sneaking = false;
+
+        echo!(self.info(), "You step out into the open.\r\n");
+    }
+
+    pub fn do_invis(&mut self) {
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        let visibility = &mut myself.components().visibility;
+        visibility.invisible = !visibility.invisible;
+
+        if visibility.invisible {
+            echo!(self.info(), "You slowly fade out of existence.\r\n");
+        } else {
+            echo!(self.info(), "You slowly fade back into existence.\r\n");
+        }
+    }
+
+    pub fn do_holylight(&mut self) {
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        let player = match &mut myself.components().player {
+            Some(player) => player,
+            None => return,
+        };
+        player.holylight = !player.holylight;
+
+        if player.holylight {
+            echo!(
+                self.info(),
+                "Holy light mode on; you now see everything.\r\n"
+            );
+        } else {
+            echo!(self.info(), "Holy light mode off.\r\n");
+        }
+    }
+
+    /// Moving around reveals anyone who was hiding.
+    pub fn reveal_hidden(&mut self) {
+        let mut myself = self.entity_world.entity_info_mut(self.entity_id);
+        myself.components().visibility.hidden = false;
+    }
+}
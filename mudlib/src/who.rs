@@ -0,0 +1,207 @@
+//! Listings of who is playing: `who`, `whois`/`finger` and `users`.
+//!
+//! Players are listed from the entity world, so this works the same with or
+//! without a network. The network layer can additionally tell the game where
+//! each player is connected from, which only immortals get to see.
+
+use crate::{agent::EntityAgent, echo, entity::EntityInfo, pfiles::load_player_file};
+
+/// Where a player is connected from, as registered by the network layer.
+#[derive(Clone)]
+pub struct ConnectionInfo {
+    pub socket: usize,
+    pub address: String,
+}
+
+/// Turn a number of ticks (one per second) into e.g. "5m".
+fn format_idle(ticks: u64) -> String {
+    if ticks < 60 {
+        format!("{}s", ticks)
+    } else if ticks < 60 * 60 {
+        format!("{}m", ticks / 60)
+    } else {
+        format!("{}h", ticks / (60 * 60))
+    }
+}
+
+impl<'e, 'p> EntityAgent<'e, 'p> {
+    fn idle_ticks(&self, player_name: &str) -> u64 {
+        let last_command_tick = self
+            .players
+            .player_echoes
+            .get(player_name)
+            .map(|player_echo| player_echo.last_command_tick)
+            .unwrap_or_default();
+
+        self.entity_world
+            .current_tick()
+            .saturating_sub(last_command_tick)
+    }
+
+    /// Invisible players are left out of listings, except for immortals.
+    fn listed_players(&self) -> Vec<EntityInfo<'_>> {
+        let myself = self.entity_world.entity_info(self.entity_id);
+
+        self.players
+            .player_echoes
+            .keys()
+            .filter_map(|name| self.entity_world.player_entity_id(name))
+            .map(|player_id| self.entity_world.entity_info(player_id))
+            .filter(|player| {
+                myself.is_immortal()
+                    || player == &myself
+                    || !player.components().visibility.invisible
+            })
+            .collect()
+    }
+
+    pub fn do_who(&mut self) {
+        let mut lines = Vec::new();
+
+        for player in self.listed_players() {
+            let name = player.player_name().unwrap_or_default();
+            let title = match &player.components().player {
+                Some(player) => player.title.as_str(),
+                None => "",
+            };
+            let rank = if player.is_immortal() {
+                "`YImmortal`^"
+            } else {
+                " Player "
+            };
+
+            lines.push(format!(
+                "[{}] `M{}`^ {}  `s({}, idle {})`^\r\n",
+                rank,
+                name,
+                title,
+                player.room().components().general.area,
+                format_idle(self.idle_ticks(name)),
+            ));
+        }
+
+        echo!(self.info(), "Players in the realm:\r\n");
+        for line in &lines {
+            echo!(self.info(), "{}", line);
+        }
+        match lines.len() {
+            1 => echo!(self.info(), "1 player is playing.\r\n"),
+            count => echo!(self.info(), "{} players are playing.\r\n", count),
+        }
+    }
+
+    pub fn do_whois(&mut self, name: &str) {
+        let online = self
+            .listed_players()
+            .into_iter()
+            .find(|player| {
+                player
+                    .player_name()
+                    .is_some_and(|player_name| player_name.eq_ignore_ascii_case(name))
+            })
+            .map(|player| player.entity_id());
+
+        let player_id = match online {
+            Some(player_id) => player_id,
+            None => return self.do_finger(name),
+        };
+
+        let player = self.entity_world.entity_info(player_id);
+        let name = player.player_name().unwrap_or_default();
+        let idle = format_idle(self.idle_ticks(name));
+        let (title, position, completed_quests) = match &player.components().player {
+            Some(player) => (
+                player.title.as_str(),
+                player.position.describe(),
+                player.quests.completed.len(),
+            ),
+            None => ("", "standing", 0),
+        };
+        let area = &player.room().components().general.area;
+
+        let mut info = self
+            .players
+            .info(&self.entity_world.entity_info(self.entity_id));
+        echo!(info, "`M{}`^ {}\r\n", name, title);
+        if player.is_immortal() {
+            echo!(info, "  {} is one of the realm's `Yimmortals`^.\r\n", name);
+        }
+        echo!(
+            info,
+            "  Currently {} in {}, idle for {}.\r\n",
+            position,
+            area,
+            idle
+        );
+        echo!(info, "  Completed quests: {}\r\n", completed_quests);
+    }
+
+    /// Show what is known about a player from their file, whether they're
+    /// playing or not. Invisible players look like they aren't playing.
+    pub fn do_finger(&mut self, name: &str) {
+        let player_file = match load_player_file(self.files, name) {
+            Ok(Some(player_file)) => player_file,
+            Ok(None) => {
+                echo!(self.info(), "There is no player named '{}'.\r\n", name);
+                return;
+            }
+            Err(err) => {
+                echo!(
+                    self.info(),
+                    "That player's file could not be read: {}\r\n",
+                    err
+                );
+                return;
+            }
+        };
+
+        // Players the viewer can't see are shown as offline
+        let playing = self.listed_players().iter().any(|player| {
+            player
+                .player_name()
+                .is_some_and(|player_name| player_name.eq_ignore_ascii_case(&player_file.name))
+        });
+
+        echo!(
+            self.info(),
+            "`M{}`^ {}\r\n",
+            player_file.name,
+            player_file.title.as_deref().unwrap_or_default()
+        );
+        if playing {
+            echo!(self.info(), "  Playing right now.\r\n");
+        } else {
+            echo!(self.info(), "  Not playing right now.\r\n");
+        }
+        echo!(
+            self.info(),
+            "  Completed quests: {}\r\n",
+            player_file.quests.completed.len()
+        );
+    }
+
+    pub fn do_users(&mut self) {
+        let current_tick = self.entity_world.current_tick();
+        let mut lines = Vec::new();
+
+        for (name, player_echo) in &self.players.player_echoes {
+            let (socket, address) = match &player_echo.connection {
+                Some(connection) => (connection.socket.to_string(), connection.address.as_str()),
+                None => ("-".to_string(), "local"),
+            };
+
+            lines.push(format!(
+                "`g{:>4}`^ `M{:<12}`^ {:<24} idle {}\r\n",
+                socket,
+                name,
+                address,
+                format_idle(current_tick.saturating_sub(player_echo.last_command_tick)),
+            ));
+        }
+
+        echo!(self.info(), "Sock Player       Address\r\n");
+        for line in lines {
+            echo!(self.info(), "{}", line);
+        }
+    }
+}
@@ -6,25 +6,62 @@
 //! Not everything is loaded from area files yet; a lot of properties are
 //! missing because they were not yet needed.
 
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
 use serde::{Deserialize, Serialize};
 
-use crate::files::Files;
+use crate::{
+    file_parser::{Diagnostic, Severity},
+    files::Files,
+};
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
 #[serde(transparent)]
 pub(super) struct Vnum(pub(super) usize);
 
+impl FromStr for Vnum {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(vnum: &str) -> Result<Self, Self::Err> {
+        vnum.parse().map(Vnum)
+    }
+}
+
+impl Display for Vnum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub(super) struct Room {
     pub(super) vnum: Vnum,
     pub(super) name: String,
     pub(super) description: String,
     pub(super) sector: String,
+    #[serde(default)]
+    pub(super) dark: bool,
+    #[serde(default)]
+    pub(super) bank: bool,
 
     #[serde(default)]
     pub(super) exits: Vec<Exit>,
     #[serde(default)]
     pub(super) extra_descriptions: Vec<ExtraDescription>,
+    #[serde(default)]
+    pub(super) mobprog_triggers: Vec<(MobProgTrigger, Vnum)>,
+    #[serde(default)]
+    pub(super) script: Option<String>,
+    /// `RoomFlags` that don't do anything here, like `law`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) unused_room_flags: Vec<String>,
+    /// Keys that don't do anything here, like `Mana 100`, as they were written
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) unused_keys: Vec<String>,
 
     #[serde(skip)]
     pub(super) area: String,
@@ -37,10 +74,18 @@ pub(super) struct Exit {
     pub(super) description: Option<String>,
     pub(super) extra_keywords: Option<String>,
 
+    #[serde(default)]
     pub(super) has_door: bool,
+    #[serde(default)]
     pub(super) is_closed: bool,
+    #[serde(default)]
     pub(super) is_locked: bool,
+    #[serde(default)]
     pub(super) key: Option<Vnum>,
+    #[serde(default)]
+    pub(super) is_pickproof: bool,
+    #[serde(default)]
+    pub(super) is_bashproof: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -49,11 +94,15 @@ pub(super) struct ExtraDescription {
     pub(super) description: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy)]
+#[derive(Serialize, Deserialize, Clone)]
 pub(super) enum Gender {
     Male,
     Female,
     Neutral,
+    /// "They", which takes verbs in plural
+    Plural,
+    /// Pronouns chosen by a player
+    Custom(Pronouns),
 }
 
 impl Default for Gender {
@@ -62,6 +111,36 @@ impl Default for Gender {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct Pronouns {
+    pub subjective: String,
+    pub objective: String,
+    pub possessive: String,
+}
+
+impl Gender {
+    /// The subjective, objective and possessive pronouns, e.g. "he", "him"
+    /// and "his".
+    pub fn pronouns(&self) -> (&str, &str, &str) {
+        match self {
+            Gender::Male => ("he", "him", "his"),
+            Gender::Female => ("she", "her", "her"),
+            Gender::Neutral => ("it", "it", "its"),
+            Gender::Plural => ("they", "them", "their"),
+            Gender::Custom(pronouns) => (
+                &pronouns.subjective,
+                &pronouns.objective,
+                &pronouns.possessive,
+            ),
+        }
+    }
+
+    /// Whether verbs about this gender are in plural, as in "they flex".
+    pub fn is_plural(&self) -> bool {
+        matches!(self, Gender::Plural)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub(super) struct Mobile {
     pub(super) vnum: Vnum,
@@ -70,11 +149,33 @@ pub(super) struct Mobile {
     pub(super) long_description: String,
     pub(super) description: String,
 
+    #[serde(default)]
     pub(super) mobprog_triggers: Vec<(MobProgTrigger, Vnum)>,
+    #[serde(default)]
+    pub(super) script: Option<String>,
+    #[serde(default)]
     pub(super) gender: Gender,
+    #[serde(skip)]
     pub(super) area: String,
+    #[serde(default)]
     pub(super) sentinel: bool,
+    #[serde(default)]
     pub(super) unseen: bool,
+    #[serde(default)]
+    pub(super) affected_by: Vec<String>,
+    #[serde(default)]
+    pub(super) start_position: String,
+    #[serde(default)]
+    pub(super) default_position: String,
+    /// Roughly how many silver coins the mobile carries
+    #[serde(default)]
+    pub(super) wealth: usize,
+    /// `Act` flags that don't do anything here, like `aggressive`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) unused_act_flags: Vec<String>,
+    /// Keys that don't do anything here, like `Level 10`, as they were written
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) unused_keys: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -83,13 +184,31 @@ pub(super) struct Object {
     pub(super) name: String,
     pub(super) short_description: String,
     pub(super) description: String,
+    #[serde(skip)]
     pub(super) area: String,
+    #[serde(default)]
     pub(super) cost: i32,
+    /// Out of 100, where 100 is in perfect condition
+    #[serde(default = "perfect_condition")]
+    pub(super) condition: u8,
     pub(super) item_type: String,
+    #[serde(default)]
     pub(super) flags: ObjectFlags,
+    #[serde(default)]
+    pub(super) wear_flags: Vec<String>,
+    #[serde(default)]
+    pub(super) extra_flags: Vec<String>,
 
     #[serde(default)]
     pub(super) extra_descriptions: Vec<ExtraDescription>,
+    #[serde(default)]
+    pub(super) mobprog_triggers: Vec<(MobProgTrigger, Vnum)>,
+    #[serde(default)]
+    pub(super) script: Option<String>,
+    /// Keys that don't do anything here, like `Weight 8`, as they were
+    /// written, with the `Values` of item types that have no behavior
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) unused_keys: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -102,6 +221,33 @@ pub(super) enum ObjectFlags {
         closable: bool,
         closed: bool,
         locked: bool,
+        #[serde(default)]
+        pickproof: bool,
+        #[serde(default)]
+        key: Option<Vnum>,
+    },
+
+    /// Object holds a liquid to drink; fountains never run out
+    DrinkContainer {
+        capacity: u16,
+        amount: u16,
+        liquid: String,
+        fountain: bool,
+    },
+
+    /// Object gives light for some hours, or forever
+    Light { hours: Option<u16> },
+
+    /// Object can be used to sit, rest or sleep on, with flags like `sit_on`
+    Furniture { capacity: usize, flags: Vec<String> },
+
+    /// Object holds spells, like a scroll or a potion
+    Spells { level: u16, spells: Vec<String> },
+
+    /// Object leads to another room, maybe only a few times
+    Portal {
+        charges: Option<u16>,
+        destination: Vnum,
     },
 }
 
@@ -111,17 +257,30 @@ impl Default for ObjectFlags {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub(super) struct AreaData {
     pub(super) name: String,
     pub(super) short_name: String,
+    /// Where the area was loaded from
+    #[serde(skip)]
+    pub(super) file_name: String,
 
+    #[serde(default)]
     pub(super) vnums: (Vnum, Vnum),
+    #[serde(default)]
     pub(super) credits: String,
+    #[serde(default)]
     pub(super) continent: String,
+    /// Keys that don't do anything here, like `Security 9`, as they were
+    /// written
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) unused_keys: Vec<String>,
+    /// The lines of the `#SPECIALS` section, like `M 3011 spec_guard`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) specials: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub(super) enum ResetCommand {
     Mob {
@@ -157,6 +316,10 @@ pub(super) enum ResetCommand {
     },
 }
 
+fn perfect_condition() -> u8 {
+    100
+}
+
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub(super) struct Shop {
     pub vnum: Vnum,
@@ -174,6 +337,8 @@ pub(super) struct MobProg {
     pub title: String,
     pub code: String,
     pub disabled: bool,
+    #[serde(skip)]
+    pub area: String,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -190,6 +355,10 @@ pub(super) enum MobProgTrigger {
     Death { chance: u8 },
     Hour { hour: u8 },
     LoginRoom,
+    Get { chance: u8 },
+    Drop { chance: u8 },
+    Wear { chance: u8 },
+    Use { chance: u8 },
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -198,6 +367,35 @@ pub(super) enum VnumOrKeyword {
     Keyword(String),
 }
 
+/// Formats the trigger as written in area files, without the mobprog vnum.
+impl Display for MobProgTrigger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MobProgTrigger::Random { chance } => write!(f, "RANDOM {}", chance),
+            MobProgTrigger::Greet { chance } => write!(f, "GREET {}", chance),
+            MobProgTrigger::Entry { chance } => write!(f, "ENTRY {}", chance),
+            MobProgTrigger::Speech { pattern } => write!(f, "SPEECH {}", pattern),
+            MobProgTrigger::Act { pattern } => write!(f, "ACT {}", pattern),
+            MobProgTrigger::Exit { direction } => write!(f, "EXIT {}", direction),
+            MobProgTrigger::Bribe { amount } => write!(f, "BRIBE {}", amount),
+            MobProgTrigger::Give {
+                item_vnum: VnumOrKeyword::Vnum(vnum),
+            } => write!(f, "GIVE {}", vnum.0),
+            MobProgTrigger::Give {
+                item_vnum: VnumOrKeyword::Keyword(keyword),
+            } => write!(f, "GIVE {}", keyword),
+            MobProgTrigger::Kill { chance } => write!(f, "KILL {}", chance),
+            MobProgTrigger::Death { chance } => write!(f, "DEATH {}", chance),
+            MobProgTrigger::Hour { hour } => write!(f, "HOUR {}", hour),
+            MobProgTrigger::LoginRoom => write!(f, "LOGINROOM"),
+            MobProgTrigger::Get { chance } => write!(f, "GET {}", chance),
+            MobProgTrigger::Drop { chance } => write!(f, "DROP {}", chance),
+            MobProgTrigger::Wear { chance } => write!(f, "WEAR {}", chance),
+            MobProgTrigger::Use { chance } => write!(f, "USE {}", chance),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub(super) struct Area {
     pub(super) area_data: AreaData,
@@ -214,11 +412,15 @@ pub(super) struct Area {
 pub(super) struct World {
     pub(super) areas: Vec<(AreaData, Vec<ResetCommand>)>,
 
-    pub(super) rooms: Vec<Room>,
-    pub(super) objects: Vec<Object>,
-    pub(super) mobiles: Vec<Mobile>,
-    pub(super) shops: Vec<Shop>,
-    pub(super) mobprogs: Vec<MobProg>,
+    pub(super) rooms: BTreeMap<Vnum, Room>,
+    pub(super) objects: BTreeMap<Vnum, Object>,
+    pub(super) mobiles: BTreeMap<Vnum, Mobile>,
+    /// Shops by the vnum of their shopkeeper
+    pub(super) shops: BTreeMap<Vnum, Shop>,
+    pub(super) mobprogs: BTreeMap<Vnum, MobProg>,
+
+    /// Problems found while loading, including areas that were skipped
+    pub(super) diagnostics: Vec<Diagnostic>,
 }
 
 pub(super) fn load_world(files: &dyn Files, path: &str) -> World {
@@ -227,7 +429,15 @@ pub(super) fn load_world(files: &dyn Files, path: &str) -> World {
     // Note: not using &Path because paths are abstracted in the Files trait,
     // and may not correspond to the current OS's paths.
     let arealist_path = format!("{}/arealist.txt", path);
-    let area_names = files.read_file(&arealist_path).unwrap();
+    let area_names = match files.read_file(&arealist_path) {
+        Ok(area_names) => area_names,
+        Err(error) => {
+            world
+                .diagnostics
+                .push(Diagnostic::unreadable(&arealist_path, &error));
+            return world;
+        }
+    };
 
     let area_names: Vec<&str> = area_names
         .split_whitespace()
@@ -236,55 +446,159 @@ pub(super) fn load_world(files: &dyn Files, path: &str) -> World {
 
     for file_name in area_names {
         let data_file_name = format!("{}/{}", path, file_name);
-        let contents = files.read_file(&data_file_name).unwrap();
-        let area = crate::load::load_area(&contents, &data_file_name);
+        let contents = match files.read_file(&data_file_name) {
+            Ok(contents) => contents,
+            Err(error) => {
+                world
+                    .diagnostics
+                    .push(Diagnostic::unreadable(&data_file_name, &error));
+                continue;
+            }
+        };
+
+        let area = crate::load::load_area(&contents, &data_file_name, &mut world.diagnostics);
+        let area = match area {
+            Ok(area) => area,
+            Err(error) => {
+                world.diagnostics.push(error);
+                continue;
+            }
+        };
 
+        let file_name = area.area_data.file_name.clone();
+        let range = area.area_data.vnums;
         world.areas.push((area.area_data, area.resets));
 
         for room in area.rooms {
-            let vnum = room.vnum.0;
-            if world.rooms.len() <= vnum {
-                world.rooms.resize(vnum + 1, Room::default());
-            }
-            world.rooms[vnum] = room;
+            let vnum = room.vnum;
+            let defined = world.rooms.insert(vnum, room).is_some();
+            world.check_vnum(&file_name, range, "Room", vnum, defined);
         }
 
         for object in area.objects {
-            let vnum = object.vnum.0;
-            if world.objects.len() <= vnum {
-                world.objects.resize(vnum + 1, Object::default());
-            }
-            world.objects[vnum] = object;
+            let vnum = object.vnum;
+            let defined = world.objects.insert(vnum, object).is_some();
+            world.check_vnum(&file_name, range, "Object", vnum, defined);
         }
 
         for mobile in area.mobiles {
-            let vnum = mobile.vnum.0;
-            if world.mobiles.len() <= vnum {
-                world.mobiles.resize(vnum + 1, Mobile::default());
-            }
-            world.mobiles[vnum] = mobile;
+            let vnum = mobile.vnum;
+            let defined = world.mobiles.insert(vnum, mobile).is_some();
+            world.check_vnum(&file_name, range, "Mobile", vnum, defined);
         }
 
         for shop in area.shops {
-            let vnum = shop.vnum.0;
-            if world.shops.len() <= vnum {
-                world.shops.resize(vnum + 1, Shop::default());
-            }
-            world.shops[vnum] = shop;
+            let vnum = shop.vnum;
+            let defined = world.shops.insert(vnum, shop).is_some();
+            world.check_vnum(&file_name, range, "Shop", vnum, defined);
         }
 
         for mobprog in area.mobprogs {
-            let vnum = mobprog.vnum.0;
-            if world.mobprogs.len() <= vnum {
-                world.mobprogs.resize(vnum + 1, MobProg::default());
-            }
-            world.mobprogs[vnum] = mobprog;
+            let vnum = mobprog.vnum;
+            let defined = world.mobprogs.insert(vnum, mobprog).is_some();
+            world.check_vnum(&file_name, range, "Mobprog", vnum, defined);
         }
     }
 
     world
 }
 
+impl World {
+    /// Gather an area's templates back into an `Area`, e.g. to save it after
+    /// builders changed it. Shops go with the area of their shopkeeper.
+    pub(super) fn area(&self, short_name: &str) -> Option<Area> {
+        let (area_data, resets) = self
+            .areas
+            .iter()
+            .find(|(area_data, _)| area_data.short_name == short_name)?;
+
+        let in_area = |vnum: &Vnum| {
+            self.mobiles
+                .get(vnum)
+                .is_some_and(|mobile| mobile.area == short_name)
+        };
+
+        Some(Area {
+            area_data: area_data.clone(),
+            rooms: self
+                .rooms
+                .values()
+                .filter(|room| room.area == short_name)
+                .cloned()
+                .collect(),
+            objects: self
+                .objects
+                .values()
+                .filter(|object| object.area == short_name)
+                .cloned()
+                .collect(),
+            mobiles: self
+                .mobiles
+                .values()
+                .filter(|mobile| mobile.area == short_name)
+                .cloned()
+                .collect(),
+            resets: resets.clone(),
+            shops: self
+                .shops
+                .values()
+                .filter(|shop| in_area(&shop.vnum))
+                .cloned()
+                .collect(),
+            mobprogs: self
+                .mobprogs
+                .values()
+                .filter(|mobprog| mobprog.area == short_name)
+                .cloned()
+                .collect(),
+        })
+    }
+
+    /// The area whose `VNUMs` range includes a vnum, where new templates with
+    /// that vnum belong.
+    pub(super) fn area_for_vnum(&self, Vnum(vnum): Vnum) -> Option<&AreaData> {
+        self.areas
+            .iter()
+            .map(|(area_data, _)| area_data)
+            .find(|area_data| {
+                let (Vnum(low), Vnum(high)) = area_data.vnums;
+                (low, high) != (0, 0) && (low..=high).contains(&vnum)
+            })
+    }
+
+    /// Report a vnum that replaces another area's as an error, and warn
+    /// about one that is outside of its own area's `VNUMs` range (if it has
+    /// one).
+    fn check_vnum(
+        &mut self,
+        file_name: &str,
+        range: (Vnum, Vnum),
+        kind: &str,
+        Vnum(vnum): Vnum,
+        defined: bool,
+    ) {
+        if defined {
+            self.diagnostics.push(Diagnostic::about_file(
+                Severity::Error,
+                file_name,
+                format!("{} #{} was already defined, and is replaced", kind, vnum),
+            ));
+        }
+
+        let (Vnum(low), Vnum(high)) = range;
+        if (low, high) != (0, 0) && !(low..=high).contains(&vnum) {
+            self.diagnostics.push(Diagnostic::about_file(
+                Severity::Warning,
+                file_name,
+                format!(
+                    "{} #{} is outside of the area's VNUMs range {}-{}",
+                    kind, vnum, low, high
+                ),
+            ));
+        }
+    }
+}
+
 pub(crate) fn long_direction(direction: &str) -> &str {
     match direction {
         "n" => "north",